@@ -0,0 +1,225 @@
+//! Parsing and expansion of `graph!`
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{braced, bracketed, parenthesized, Expr, Ident, LitStr, Path, Token};
+
+/// `chain; chain; ...`
+pub struct Topology {
+    chains: Vec<Vec<Vec<NodeRef>>>,
+}
+
+/// One occurrence of a node in a chain, with the options given there
+struct NodeRef {
+    function: Path,
+    name: Ident,
+    label: Option<LitStr>,
+    output: Option<LitStr>,
+    config: Vec<(Ident, Expr)>,
+}
+
+impl Parse for Topology {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut chains = Vec::new();
+        while !input.is_empty() {
+            let mut chain = vec![parse_stage(input)?];
+            while input.peek(Token![->]) {
+                input.parse::<Token![->]>()?;
+                chain.push(parse_stage(input)?);
+            }
+            chains.push(chain);
+            if !input.is_empty() {
+                input.parse::<Token![;]>()?;
+            }
+        }
+        Ok(Self { chains })
+    }
+}
+
+/// `node` or `[node, node, ...]`
+fn parse_stage(input: ParseStream) -> syn::Result<Vec<NodeRef>> {
+    if input.peek(syn::token::Bracket) {
+        let content;
+        bracketed!(content in input);
+        let nodes = Punctuated::<NodeRef, Token![,]>::parse_terminated(&content)?;
+        if nodes.is_empty() {
+            return Err(content.error("expected at least one node"));
+        }
+        Ok(nodes.into_iter().collect())
+    } else {
+        Ok(vec![input.parse()?])
+    }
+}
+
+impl Parse for NodeRef {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let function: Path = input.parse()?;
+        let name = function
+            .segments
+            .last()
+            .map(|segment| segment.ident.clone())
+            .ok_or_else(|| input.error("expected a node function"))?;
+        let mut node = NodeRef {
+            function,
+            name,
+            label: None,
+            output: None,
+            config: Vec::new(),
+        };
+        if input.peek(syn::token::Brace) {
+            let content;
+            braced!(content in input);
+            while !content.is_empty() {
+                let option: Ident = content.parse()?;
+                match option.to_string().as_str() {
+                    "label" => {
+                        content.parse::<Token![=]>()?;
+                        node.label = Some(content.parse()?);
+                    }
+                    "output" => {
+                        content.parse::<Token![=]>()?;
+                        node.output = Some(content.parse()?);
+                    }
+                    "config" => {
+                        let entries;
+                        parenthesized!(entries in content);
+                        while !entries.is_empty() {
+                            let key: Ident = entries.parse()?;
+                            entries.parse::<Token![=]>()?;
+                            node.config.push((key, entries.parse()?));
+                            if !entries.is_empty() {
+                                entries.parse::<Token![,]>()?;
+                            }
+                        }
+                    }
+                    _ => {
+                        return Err(syn::Error::new(
+                            option.span(),
+                            "expected `label`, `output`, or `config`",
+                        ))
+                    }
+                }
+                if !content.is_empty() {
+                    content.parse::<Token![,]>()?;
+                }
+            }
+        }
+        Ok(node)
+    }
+}
+
+/// A node of the graph, with the options of all its occurrences combined
+struct NodeDecl {
+    function: Path,
+    name: Ident,
+    label: Option<LitStr>,
+    output: Option<LitStr>,
+    config: Vec<(Ident, Expr)>,
+    upstream: Vec<usize>,
+}
+
+pub fn expand(topology: Topology) -> syn::Result<TokenStream> {
+    // Collect nodes in order of first appearance and the edges between them
+    let mut nodes: Vec<NodeDecl> = Vec::new();
+    for chain in topology.chains {
+        let mut previous: Vec<usize> = Vec::new();
+        for stage in chain {
+            let mut current = Vec::new();
+            for occurrence in stage {
+                let index = match nodes.iter().position(|n| n.name == occurrence.name) {
+                    Some(index) => index,
+                    None => {
+                        nodes.push(NodeDecl {
+                            function: occurrence.function.clone(),
+                            name: occurrence.name.clone(),
+                            label: None,
+                            output: None,
+                            config: Vec::new(),
+                            upstream: Vec::new(),
+                        });
+                        nodes.len() - 1
+                    }
+                };
+                let node = &mut nodes[index];
+                if occurrence.label.is_some() {
+                    node.label = occurrence.label;
+                }
+                if occurrence.output.is_some() {
+                    node.output = occurrence.output;
+                }
+                node.config.extend(occurrence.config);
+                for &from in &previous {
+                    if !node.upstream.contains(&from) {
+                        node.upstream.push(from);
+                    }
+                }
+                current.push(index);
+            }
+            previous = current;
+        }
+    }
+
+    let order = topological_order(&nodes)?;
+    let adds = order.into_iter().map(|index| {
+        let node = &nodes[index];
+        let function = &node.function;
+        let name = node.name.to_string();
+        let label = node
+            .label
+            .as_ref()
+            .map_or_else(|| name.clone(), LitStr::value);
+        let output = node.output.as_ref().map_or_else(|| name.clone(), LitStr::value);
+        let inputs = if node.upstream.is_empty() {
+            quote! { ::std::option::Option::None }
+        } else {
+            let names = node.upstream.iter().map(|&i| nodes[i].name.to_string());
+            quote! { ::std::option::Option::Some(vec![#((#names, #names)),*]) }
+        };
+        let config = node.config.iter().map(|(key, value)| {
+            let key = key.to_string();
+            quote! { .with_config(#key, ::dagex::GraphData::from(#value)) }
+        });
+        quote! {
+            __graph
+                .push(
+                    #function,
+                    ::std::option::Option::Some(#label),
+                    #inputs,
+                    ::std::option::Option::Some(vec![(#output, #name)]),
+                )
+                #(#config)*;
+        }
+    });
+
+    Ok(quote! {
+        {
+            let mut __graph = ::dagex::Graph::new();
+            #(#adds)*
+            __graph
+        }
+    })
+}
+
+/// Node indices ordered so that every node comes after its upstream nodes
+fn topological_order(nodes: &[NodeDecl]) -> syn::Result<Vec<usize>> {
+    let mut order = Vec::with_capacity(nodes.len());
+    let mut placed = vec![false; nodes.len()];
+    while order.len() < nodes.len() {
+        let next = (0..nodes.len())
+            .find(|&i| !placed[i] && nodes[i].upstream.iter().all(|&u| placed[u]));
+        let Some(next) = next else {
+            let stuck = nodes
+                .iter()
+                .zip(&placed)
+                .find(|(_, &placed)| !placed)
+                .map(|(node, _)| node);
+            let span = stuck.map_or_else(proc_macro2::Span::call_site, |n| n.name.span());
+            return Err(syn::Error::new(span, "graph! topology contains a cycle"));
+        };
+        placed[next] = true;
+        order.push(next);
+    }
+    Ok(order)
+}
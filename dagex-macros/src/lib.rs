@@ -0,0 +1,225 @@
+//! Procedural macros for dagex
+//!
+//! Enabled through the `macros` feature of `dagex`, which re-exports them
+//! (`dagex::node`).  The generated code refers to `::dagex`, so the crate using
+//! the macros must depend on `dagex` under that name.
+
+mod graph;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, FnArg, Ident, ItemFn, LitStr, Pat, ReturnType, Type};
+
+/// Build a `Graph` from a topology expression.
+///
+/// ```ignore
+/// let graph = graph! {
+///     source -> [stats, model { label = "Fit", config(order = 2) }] -> combine;
+/// };
+/// ```
+///
+/// Each name is a node function (usually a `#[node]` function).  A node's result is
+/// stored under the node's own name, and a node receives the result of each of its
+/// upstream nodes under that node's name: above, `combine` reads its inputs
+/// `stats` and `model`.  `a -> [b, c]` fans out, `[b, c] -> d` fans in, and several
+/// `;`-separated chains can describe the same graph.
+///
+/// Options, in braces after any occurrence of a name:
+///
+/// - `label = "Text"` — node label (defaults to the name)
+/// - `output = "port"` — output port holding the result, if the function does not
+///   name it after itself
+/// - `config(key = value, ..)` — static configuration (see `Graph::with_config()`)
+///
+/// The expansion is a block of `Graph::add()` calls evaluating to the `Graph`, so
+/// it can be extended further before `build()`.
+#[proc_macro]
+pub fn graph(input: TokenStream) -> TokenStream {
+    let topology = parse_macro_input!(input as graph::Topology);
+    graph::expand(topology)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Turn a plain typed function into a dagex node function.
+///
+/// ```ignore
+/// #[node(inputs(x), params(factor), output = "scaled")]
+/// fn scale(x: f64, factor: f64) -> f64 {
+///     x * factor
+/// }
+///
+/// graph.add(scale, Some("Scale"), Some(vec![("signal", "x")]), Some(vec![("scaled", "y")]))
+///     .with_config("factor", GraphData::float(2.0));
+/// ```
+///
+/// The annotated function is replaced by one with the node signature
+/// (`&HashMap<String, Arc<GraphData>>` → `HashMap<String, GraphData>`) that reads each
+/// argument from the input of the same name, calls the original body, and stores
+/// the result.
+///
+/// - `inputs(..)` — arguments fed by upstream nodes (mapped with `Graph::add()`)
+/// - `params(..)` — arguments supplied by variant parameters or `with_config()`
+/// - `output = "name"` — port the return value is stored under (defaults to the
+///   function name); use `outputs("a", "b")` for a function returning a tuple
+///
+/// Every argument must be listed in `inputs` or `params`; if neither is given, all
+/// arguments are inputs.  Arguments are converted with `dagex::FromGraphData` and
+/// return values with `Into<GraphData>`.  If an argument is missing or cannot be
+/// converted, the node produces no outputs.
+#[proc_macro_attribute]
+pub fn node(args: TokenStream, item: TokenStream) -> TokenStream {
+    let mut spec = NodeSpec::default();
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("inputs") {
+            meta.parse_nested_meta(|m| {
+                spec.inputs.push(ident_of(&m.path)?);
+                Ok(())
+            })
+        } else if meta.path.is_ident("params") {
+            meta.parse_nested_meta(|m| {
+                spec.params.push(ident_of(&m.path)?);
+                Ok(())
+            })
+        } else if meta.path.is_ident("output") {
+            spec.outputs.push(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("outputs") {
+            let content;
+            syn::parenthesized!(content in meta.input);
+            let names = content.parse_terminated(|input| input.parse::<LitStr>(), syn::Token![,])?;
+            spec.outputs.extend(names);
+            Ok(())
+        } else {
+            Err(meta.error("expected `inputs(..)`, `params(..)`, `output = \"..\"`, or `outputs(..)`"))
+        }
+    });
+    parse_macro_input!(args with parser);
+    let function = parse_macro_input!(item as ItemFn);
+
+    expand(spec, function)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct NodeSpec {
+    inputs: Vec<Ident>,
+    params: Vec<Ident>,
+    outputs: Vec<LitStr>,
+}
+
+fn ident_of(path: &syn::Path) -> syn::Result<Ident> {
+    path.get_ident()
+        .cloned()
+        .ok_or_else(|| syn::Error::new(path.span(), "expected an argument name"))
+}
+
+fn expand(spec: NodeSpec, function: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let sig = &function.sig;
+    if !sig.generics.params.is_empty() || sig.asyncness.is_some() {
+        return Err(syn::Error::new(
+            sig.span(),
+            "#[node] functions cannot be generic or async",
+        ));
+    }
+
+    // Arguments, in declaration order
+    let mut args: Vec<(Ident, Type)> = Vec::new();
+    for arg in &sig.inputs {
+        let FnArg::Typed(arg) = arg else {
+            return Err(syn::Error::new(arg.span(), "#[node] functions cannot take self"));
+        };
+        let Pat::Ident(pat) = arg.pat.as_ref() else {
+            return Err(syn::Error::new(arg.pat.span(), "expected a plain argument name"));
+        };
+        args.push((pat.ident.clone(), (*arg.ty).clone()));
+    }
+
+    let declared: Vec<&Ident> = spec.inputs.iter().chain(&spec.params).collect();
+    if !declared.is_empty() {
+        for (name, _) in &args {
+            if !declared.contains(&name) {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("argument `{}` is not listed in `inputs(..)` or `params(..)`", name),
+                ));
+            }
+        }
+        for name in declared {
+            if !args.iter().any(|(arg, _)| arg == name) {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("`{}` is not an argument of this function", name),
+                ));
+            }
+        }
+    }
+
+    let outputs = if spec.outputs.is_empty() {
+        vec![LitStr::new(&sig.ident.to_string(), sig.ident.span())]
+    } else {
+        spec.outputs
+    };
+    let store = match (&sig.output, outputs.len()) {
+        (ReturnType::Default, _) => quote! {},
+        (_, 1) => {
+            let name = &outputs[0];
+            quote! {
+                __outputs.insert(#name.to_string(), ::dagex::GraphData::from(__result));
+            }
+        }
+        (_, n) => {
+            let parts: Vec<Ident> = (0..n).map(|i| format_ident!("__output_{}", i)).collect();
+            quote! {
+                let (#(#parts,)*) = __result;
+                #(__outputs.insert(#outputs.to_string(), ::dagex::GraphData::from(#parts));)*
+            }
+        }
+    };
+
+    let attrs = &function.attrs;
+    let vis = &function.vis;
+    let name = &sig.ident;
+    let names: Vec<&Ident> = args.iter().map(|(name, _)| name).collect();
+    let keys: Vec<LitStr> = names
+        .iter()
+        .map(|name| LitStr::new(&name.to_string(), Span::call_site()))
+        .collect();
+    let types: Vec<&Type> = args.iter().map(|(_, ty)| ty).collect();
+
+    // The original function, nested inside the wrapper under the same name
+    let mut inner = function.clone();
+    inner.attrs.clear();
+    inner.vis = syn::Visibility::Inherited;
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis fn #name(
+            __inputs: &::std::collections::HashMap<
+                ::std::string::String,
+                ::std::sync::Arc<::dagex::GraphData>,
+            >,
+        ) -> ::std::collections::HashMap<::std::string::String, ::dagex::GraphData> {
+            #inner
+
+            #[allow(unused_mut)]
+            let mut __outputs = ::std::collections::HashMap::new();
+            #(
+                let ::std::option::Option::Some(#names) = __inputs
+                    .get(#keys)
+                    .map(::std::sync::Arc::as_ref)
+                    .and_then(<#types as ::dagex::FromGraphData>::from_graph_data)
+                else {
+                    return __outputs;
+                };
+            )*
+            #[allow(clippy::let_unit_value)]
+            let __result = #name(#(#names),*);
+            #store
+            __outputs
+        }
+    })
+}
@@ -0,0 +1,62 @@
+//! Tests for the graph! macro
+
+use dagex::GraphData;
+use dagex_macros::{graph, node};
+
+#[node]
+fn source() -> Vec<f64> {
+    vec![1.0, 2.0, 3.0, 6.0]
+}
+
+#[node]
+fn stats(source: Vec<f64>) -> f64 {
+    source.iter().sum::<f64>() / source.len() as f64
+}
+
+#[node(inputs(source), params(order), output = "fit")]
+fn model(source: Vec<f64>, order: i64) -> f64 {
+    source.iter().take(order as usize).sum()
+}
+
+#[node]
+fn combine(stats: f64, model: f64) -> f64 {
+    stats + model
+}
+
+#[test]
+fn test_graph_macro_fan_out_and_in() {
+    let graph = graph! {
+        source -> [stats, model { label = "Fit", output = "fit", config(order = 2) }] -> combine
+    };
+    let dag = graph.build();
+    assert_eq!(dag.nodes().len(), 4);
+    assert!(dag.nodes().iter().any(|n| n.label.as_deref() == Some("Fit")));
+
+    let context = dag.execute(false, None);
+    assert_eq!(context.get_f64("stats"), Some(3.0));
+    assert_eq!(context.get_f64("model"), Some(3.0));
+    assert_eq!(context.get_f64("combine"), Some(6.0));
+}
+
+#[test]
+fn test_graph_macro_chains_share_nodes() {
+    let mut graph = graph! {
+        source -> stats;
+        source -> model { output = "fit", config(order = 4) };
+        [stats, model] -> combine;
+    };
+    // The result is an ordinary builder
+    graph.add(
+        |inputs: &std::collections::HashMap<String, std::sync::Arc<GraphData>>| {
+            let mut out = std::collections::HashMap::new();
+            out.insert("done".to_string(), GraphData::clone(&inputs["total"]));
+            out
+        },
+        Some("Sink"),
+        Some(vec![("combine", "total")]),
+        Some(vec![("done", "done")]),
+    );
+
+    let context = graph.build().execute(false, None);
+    assert_eq!(context.get_f64("done"), Some(15.0));
+}
@@ -0,0 +1,71 @@
+//! Tests for the #[node] attribute
+
+use dagex::{Graph, GraphData};
+use dagex_macros::node;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Multiply the signal by a configured factor
+#[node(inputs(x), params(factor), output = "scaled")]
+fn scale(x: f64, factor: f64) -> f64 {
+    x * factor
+}
+
+#[node(outputs("min", "max"))]
+fn bounds(values: Vec<f64>) -> (f64, f64) {
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    (min, max)
+}
+
+#[node]
+fn answer() -> i64 {
+    42
+}
+
+#[test]
+fn test_node_unpacks_inputs_and_packs_output() {
+    let mut inputs = HashMap::new();
+    inputs.insert("x".to_string(), Arc::new(GraphData::float(1.5)));
+    inputs.insert("factor".to_string(), Arc::new(GraphData::int(2)));
+    let outputs = scale(&inputs);
+    assert_eq!(outputs["scaled"].as_float(), Some(3.0));
+
+    // A missing argument yields no outputs
+    inputs.remove("factor");
+    assert!(scale(&inputs).is_empty());
+
+    assert_eq!(answer(&HashMap::new())["answer"].as_int(), Some(42));
+}
+
+#[test]
+fn test_node_functions_in_graph() {
+    let mut graph = Graph::new();
+    graph.add(
+        |_: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("v".to_string(), GraphData::float_vec(vec![3.0, -1.0, 2.0]));
+            out
+        },
+        Some("Source"),
+        None,
+        Some(vec![("v", "signal")]),
+    );
+    graph.add(
+        bounds,
+        Some("Bounds"),
+        Some(vec![("signal", "values")]),
+        Some(vec![("max", "peak")]),
+    );
+    graph
+        .push(
+            scale,
+            Some("Scale"),
+            Some(vec![("peak", "x")]),
+            Some(vec![("scaled", "result")]),
+        )
+        .with_config("factor", GraphData::float(10.0));
+
+    let context = graph.build().execute(false, None);
+    assert_eq!(context.get_f64("result"), Some(30.0));
+}
@@ -0,0 +1,71 @@
+/*
+ * Branch and merge from C
+ *
+ *     cargo build --release --features ffi
+ *     cc examples/c/branch_and_merge.c -Iinclude -Ltarget/release -ldagex -o branch_and_merge
+ *     LD_LIBRARY_PATH=target/release ./branch_and_merge
+ */
+
+#include <stdio.h>
+
+#include "graph_sp.h"
+
+static int source(const dagex_map *in, dagex_map *out, void *user_data) {
+    (void)in;
+    (void)user_data;
+    return dagex_map_set_int(out, "value", 50);
+}
+
+/* Adds the int64_t pointed to by user_data */
+static int add_offset(const dagex_map *in, dagex_map *out, void *user_data) {
+    int64_t x;
+    if (!dagex_map_get_int(in, "x", &x)) return 1;
+    return dagex_map_set_int(out, "result", x + *(const int64_t *)user_data);
+}
+
+static int combine(const dagex_map *in, dagex_map *out, void *user_data) {
+    int64_t a, b;
+    (void)user_data;
+    if (!dagex_map_get_int(in, "from_a", &a) || !dagex_map_get_int(in, "from_b", &b)) return 1;
+    return dagex_map_set_int(out, "combined", a + b);
+}
+
+int main(void) {
+    static const int64_t ten = 10, twenty = 20;
+    const char *source_out[] = {"value", "x", NULL};
+    const char *path_in[] = {"x", "x", NULL};
+    const char *path_out[] = {"result", "result", NULL};
+
+    dagex_graph *graph = dagex_graph_new();
+    dagex_graph_add(graph, source, NULL, "Source", NULL, source_out);
+
+    dagex_graph *branch_a = dagex_graph_new();
+    dagex_graph_add(branch_a, add_offset, (void *)&ten, "PathA (+10)", path_in, path_out);
+    size_t a = dagex_graph_branch(graph, branch_a);
+
+    dagex_graph *branch_b = dagex_graph_new();
+    dagex_graph_add(branch_b, add_offset, (void *)&twenty, "PathB (+20)", path_in, path_out);
+    size_t b = dagex_graph_branch(graph, branch_b);
+
+    const size_t branches[] = {a, b};
+    const char *merge_in[] = {"result", "from_a", "result", "from_b", NULL};
+    const char *merge_out[] = {"combined", "final", NULL};
+    dagex_graph_merge(graph, combine, NULL, "Merge", branches, merge_in, merge_out);
+
+    dagex_dag *dag = dagex_graph_build(graph);
+    char *mermaid = dagex_dag_to_mermaid(dag);
+    printf("%s\n", mermaid);
+    dagex_string_free(mermaid);
+
+    dagex_map *context = dagex_dag_execute(dag, NULL, 1, 0);
+    int64_t final_value = 0;
+    if (!context || !dagex_map_get_int(context, "final", &final_value)) {
+        fprintf(stderr, "execution failed\n");
+        return 1;
+    }
+    printf("final = %lld\n", (long long)final_value);
+
+    dagex_map_free(context);
+    dagex_dag_free(dag);
+    return final_value == 130 ? 0 : 1;
+}
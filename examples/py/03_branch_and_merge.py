@@ -1,7 +1,6 @@
 """Example 03: Branch and Merge
 
-Demonstrates fan-out (branching) patterns. Note: Python bindings don't currently
-expose the merge API, so this example shows branching without explicit merging.
+Demonstrates fan-out (branching) and fan-in (merging) patterns.
 """
 
 import sys
@@ -39,11 +38,10 @@ def path_b(inputs):
     return {"result": value + 20}
 
 
-def combine(inputs):
-    """Combine results from branches."""
-    # In absence of merge API, we combine sequentially
-    a = inputs.get("a", 0)
-    b = inputs.get("b", 0)
+def merge(inputs):
+    """Combine results from both branches."""
+    a = inputs.get("from_a", 0)
+    b = inputs.get("from_b", 0)
     return {"combined": a + b}
 
 
@@ -52,8 +50,7 @@ def main():
     
     print("📖 Story:")
     print("   Fan-out (branch): Create independent subgraphs that run in parallel.")
-    print("   This example demonstrates branching. Note: Python bindings don't")
-    print("   currently expose a direct merge API, so we combine results manually.\n")
+    print("   Fan-in (merge): Combine branch-specific outputs safely.\n")
     
     print_section("Building the Graph")
     
@@ -73,7 +70,7 @@ def main():
         path_a,
         label="PathA (+10)",
         inputs=[("x", "x")],
-        outputs=[("result", "a")]
+        outputs=[("result", "result")]
     )
     branch_a_id = graph.branch(branch_a)
     
@@ -83,15 +80,18 @@ def main():
         path_b,
         label="PathB (+20)",
         inputs=[("x", "x")],
-        outputs=[("result", "b")]
+        outputs=[("result", "result")]
     )
     branch_b_id = graph.branch(branch_b)
     
-    # Add combine node
-    graph.add(
-        combine,
-        label="Combine",
-        inputs=[("a", "a"), ("b", "b")],
+    # Merge branches
+    graph.merge(
+        merge,
+        label="Merge",
+        inputs=[
+            (branch_a_id, "result", "from_a"),
+            (branch_b_id, "result", "from_b"),
+        ],
         outputs=[("combined", "final")]
     )
     
@@ -103,7 +103,7 @@ def main():
     print_section("ASCII Visualization")
     print("          PathA (+10) ──┐")
     print("         /                \\")
-    print("  Source                   Combine")
+    print("  Source                   Merge")
     print("         \\                /")
     print("          PathB (+20) ──┘")
     
@@ -129,7 +129,7 @@ def main():
     print("   Source: 50")
     print("   PathA: 50 + 10 = 60")
     print("   PathB: 50 + 20 = 70")
-    print("   Combine: 60 + 70 = 130")
+    print("   Merge: 60 + 70 = 130")
     
     print("\nSequential execution:")
     output_seq = context_seq.get("final")
@@ -5,17 +5,18 @@ mod benchmark_utils;
 
 use dagex::{Graph, GraphData};
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 use benchmark_utils::{Benchmark, print_header, print_section};
 
-fn generate(_inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn generate(_inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let mut outputs = HashMap::new();
     outputs.insert("number".to_string(), GraphData::int(10));
     outputs
 }
 
-fn double(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn double(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let value = inputs.get("x").and_then(|d| d.as_int()).unwrap_or(0);
     
     // Simulate I/O-bound work (file read, network call, database query, etc.)
@@ -27,7 +28,7 @@ fn double(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
     outputs
 }
 
-fn add_five(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn add_five(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let value = inputs.get("y").and_then(|d| d.as_int()).unwrap_or(0);
     
     // Simulate I/O-bound work (file read, network call, database query, etc.)
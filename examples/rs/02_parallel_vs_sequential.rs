@@ -5,17 +5,18 @@ mod benchmark_utils;
 
 use dagex::{Graph, GraphData};
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 use benchmark_utils::{Benchmark, print_header, print_section};
 
-fn source(_inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn source(_inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let mut outputs = HashMap::new();
     outputs.insert("value".to_string(), GraphData::int(100));
     outputs
 }
 
-fn task_a(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn task_a(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let value = inputs.get("input").and_then(|d| d.as_int()).unwrap_or(0);
     // Simulate some work
     thread::sleep(Duration::from_millis(150));
@@ -24,7 +25,7 @@ fn task_a(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
     outputs
 }
 
-fn task_b(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn task_b(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let value = inputs.get("input").and_then(|d| d.as_int()).unwrap_or(0);
     // Simulate some work
     thread::sleep(Duration::from_millis(150));
@@ -33,7 +34,7 @@ fn task_b(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
     outputs
 }
 
-fn task_c(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn task_c(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let value = inputs.get("input").and_then(|d| d.as_int()).unwrap_or(0);
     // Simulate some work
     thread::sleep(Duration::from_millis(150));
@@ -106,19 +107,24 @@ fn main() {
     print_section("Results");
     
     println!("Sequential results:");
-    println!("  TaskA: {}", context_seq.get("a").and_then(|d| d.as_int()).unwrap_or(0));
-    println!("  TaskB: {}", context_seq.get("b").and_then(|d| d.as_int()).unwrap_or(0));
-    println!("  TaskC: {}", context_seq.get("c").and_then(|d| d.as_int()).unwrap_or(0));
+    println!("  TaskA: {}", context_seq.get_int("a").unwrap_or(0));
+    println!("  TaskB: {}", context_seq.get_int("b").unwrap_or(0));
+    println!("  TaskC: {}", context_seq.get_int("c").unwrap_or(0));
     println!("  Time: {:.3}ms", result_seq.duration_ms);
     
     println!("\nParallel results:");
-    println!("  TaskA: {}", context_par.get("a").and_then(|d| d.as_int()).unwrap_or(0));
-    println!("  TaskB: {}", context_par.get("b").and_then(|d| d.as_int()).unwrap_or(0));
-    println!("  TaskC: {}", context_par.get("c").and_then(|d| d.as_int()).unwrap_or(0));
+    println!("  TaskA: {}", context_par.get_int("a").unwrap_or(0));
+    println!("  TaskB: {}", context_par.get_int("b").unwrap_or(0));
+    println!("  TaskC: {}", context_par.get_int("c").unwrap_or(0));
     println!("  Time: {:.3}ms", result_par.duration_ms);
     
     let speedup = result_seq.duration_ms / result_par.duration_ms;
     println!("\n⚡ Speedup: {:.2}x faster with parallel execution!", speedup);
+
+    print_section("Execution Timeline (Mermaid Gantt)");
+
+    let report = dag.execute_timed(true, Some(4));
+    println!("{}", report.to_mermaid_gantt());
     
     println!();
 }
@@ -5,17 +5,18 @@ mod benchmark_utils;
 
 use dagex::{Graph, GraphData};
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 use benchmark_utils::{Benchmark, print_header, print_section};
 
-fn source(_inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn source(_inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let mut outputs = HashMap::new();
     outputs.insert("data".to_string(), GraphData::int(50));
     outputs
 }
 
-fn path_a(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn path_a(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let value = inputs.get("x").and_then(|d| d.as_int()).unwrap_or(0);
     
     // Simulate I/O-bound work (file read, network call, database query, etc.)
@@ -27,7 +28,7 @@ fn path_a(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
     outputs
 }
 
-fn path_b(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn path_b(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let value = inputs.get("x").and_then(|d| d.as_int()).unwrap_or(0);
     
     // Simulate I/O-bound work (file read, network call, database query, etc.)
@@ -39,7 +40,7 @@ fn path_b(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
     outputs
 }
 
-fn merge(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn merge(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let a = inputs.get("from_a").and_then(|d| d.as_int()).unwrap_or(0);
     let b = inputs.get("from_b").and_then(|d| d.as_int()).unwrap_or(0);
     let mut outputs = HashMap::new();
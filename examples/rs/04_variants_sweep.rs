@@ -5,19 +5,20 @@ mod benchmark_utils;
 
 use dagex::{Graph, GraphData};
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 use benchmark_utils::{Benchmark, print_header, print_section};
 
-fn data_source(_inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn data_source(_inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let mut outputs = HashMap::new();
     outputs.insert("base".to_string(), GraphData::int(10));
     outputs
 }
 
 // Factory function to create multiplier variants
-fn make_multiplier(factor: i64) -> impl Fn(&HashMap<String, GraphData>) -> HashMap<String, GraphData> + Send + Sync + 'static {
-    move |inputs: &HashMap<String, GraphData>| -> HashMap<String, GraphData> {
+fn make_multiplier(factor: i64) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> + Send + Sync + 'static {
+    move |inputs: &HashMap<String, Arc<GraphData>>| -> HashMap<String, GraphData> {
         let value = inputs.get("x").and_then(|d| d.as_int()).unwrap_or(0);
         
         // Simulate I/O-bound work (file read, network call, database query, etc.)
@@ -52,7 +53,7 @@ fn main() {
     );
     
     // Add variants with different multipliers
-    let factors = vec![2, 3, 5, 7];
+    let factors = [2, 3, 5, 7];
     let variant_nodes: Vec<_> = factors
         .iter()
         .map(|&f| make_multiplier(f))
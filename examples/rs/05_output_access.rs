@@ -5,17 +5,18 @@ mod benchmark_utils;
 
 use dagex::{Graph, GraphData};
 use std::collections::HashMap;
+use std::sync::Arc;
 use std::thread;
 use std::time::Duration;
 use benchmark_utils::{Benchmark, print_header, print_section};
 
-fn source(_inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn source(_inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let mut outputs = HashMap::new();
     outputs.insert("data".to_string(), GraphData::int(100));
     outputs
 }
 
-fn processor_a(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn processor_a(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let value = inputs.get("input").and_then(|d| d.as_int()).unwrap_or(0);
     
     // Simulate I/O-bound work (file read, network call, database query, etc.)
@@ -27,7 +28,7 @@ fn processor_a(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData
     outputs
 }
 
-fn processor_b(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn processor_b(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let value = inputs.get("input").and_then(|d| d.as_int()).unwrap_or(0);
     
     // Simulate I/O-bound work (file read, network call, database query, etc.)
@@ -82,7 +83,7 @@ fn main() {
     
     // Merge branches
     graph.merge(
-        |inputs: &HashMap<String, GraphData>| -> HashMap<String, GraphData> {
+        |inputs: &HashMap<String, Arc<GraphData>>| -> HashMap<String, GraphData> {
             let a = inputs.get("from_a").and_then(|d| d.as_int()).unwrap_or(0);
             let b = inputs.get("from_b").and_then(|d| d.as_int()).unwrap_or(0);
             let mut outputs = HashMap::new();
@@ -5,9 +5,10 @@ mod benchmark_utils;
 
 use dagex::{Graph, GraphData};
 use std::collections::HashMap;
+use std::sync::Arc;
 use benchmark_utils::{Benchmark, print_header, print_section};
 
-fn create_large_data(_inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn create_large_data(_inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     // Create a large vector (simulating a large dataset)
     let large_vec: Vec<i64> = (0..1_000_000).collect();
     
@@ -17,7 +18,7 @@ fn create_large_data(_inputs: &HashMap<String, GraphData>) -> HashMap<String, Gr
     outputs
 }
 
-fn consumer_a(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn consumer_a(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let mut outputs = HashMap::new();
     
     if let Some(data) = inputs.get("data") {
@@ -31,7 +32,7 @@ fn consumer_a(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData>
     outputs
 }
 
-fn consumer_b(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn consumer_b(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let mut outputs = HashMap::new();
     
     if let Some(data) = inputs.get("data") {
@@ -45,7 +46,7 @@ fn consumer_b(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData>
     outputs
 }
 
-fn consumer_c(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn consumer_c(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let mut outputs = HashMap::new();
     
     if let Some(data) = inputs.get("data") {
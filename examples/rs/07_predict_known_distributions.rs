@@ -33,21 +33,21 @@ use dagex::{DistTransferFn, Distribution, Graph, GraphData, PredictTarget};
 
 // ── Node functions ────────────────────────────────────────────────────────────
 
-fn amplify(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn amplify(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let x = inputs.get("x").and_then(|d| d.as_float()).unwrap_or(0.0);
     let mut out = HashMap::new();
     out.insert("y".to_string(), GraphData::float(3.0 * x + 5.0));
     out
 }
 
-fn attenuate(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn attenuate(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let y = inputs.get("y").and_then(|d| d.as_float()).unwrap_or(0.0);
     let mut out = HashMap::new();
     out.insert("z".to_string(), GraphData::float(0.5 * y));
     out
 }
 
-fn add_noise(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn add_noise(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     // In production this would actually draw from a noise distribution;
     // here we just pass through (the distribution transfer handles propagation).
     let z = inputs.get("z").and_then(|d| d.as_float()).unwrap_or(0.0);
@@ -56,7 +56,7 @@ fn add_noise(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData>
     out
 }
 
-fn clip(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn clip(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let w = inputs.get("w").and_then(|d| d.as_float()).unwrap_or(0.0);
     let mut out = HashMap::new();
     out.insert("out".to_string(), GraphData::float(w.clamp(0.0, 100.0)));
@@ -32,7 +32,7 @@ use dagex::{DistContext, DistTransferFn, Distribution, Graph, GraphData, Predict
 
 // ── Node functions ────────────────────────────────────────────────────────────
 
-fn non_linear(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn non_linear(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let x = inputs.get("x").and_then(|d| d.as_float()).unwrap_or(0.0);
     let y = x.sin() * (-(x * x) / 4.0).exp();
     let mut out = HashMap::new();
@@ -40,21 +40,21 @@ fn non_linear(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData>
     out
 }
 
-fn rectify(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn rectify(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let y = inputs.get("y").and_then(|d| d.as_float()).unwrap_or(0.0);
     let mut out = HashMap::new();
     out.insert("z".to_string(), GraphData::float(y.max(0.0)));
     out
 }
 
-fn scale(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn scale(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let z = inputs.get("z").and_then(|d| d.as_float()).unwrap_or(0.0);
     let mut out = HashMap::new();
     out.insert("w".to_string(), GraphData::float(z * 3.0 + 1.0));
     out
 }
 
-fn square(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn square(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let w = inputs.get("w").and_then(|d| d.as_float()).unwrap_or(0.0);
     let mut out = HashMap::new();
     out.insert("out".to_string(), GraphData::float(w * w));
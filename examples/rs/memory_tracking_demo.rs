@@ -7,7 +7,7 @@ use std::sync::Arc;
 // #[global_allocator]
 // static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
-fn create_large_data(_: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn create_large_data(_: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let mut result = HashMap::new();
     // Create 10MB of data
     let large_vec: Vec<f64> = (0..1_250_000).map(|i| i as f64).collect();
@@ -18,7 +18,7 @@ fn create_large_data(_: &HashMap<String, GraphData>) -> HashMap<String, GraphDat
     result
 }
 
-fn use_subset(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+fn use_subset(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
     let mut result = HashMap::new();
     if let Some(data) = inputs.get("data").and_then(|d| d.as_float_vec()) {
         println!("Received vector with {} elements", data.len());
@@ -0,0 +1,138 @@
+/*
+ * graph_sp.h - C interface to the dagex DAG executor
+ *
+ * Build the library with `cargo build --release --features ffi` and link
+ * against target/release/libdagex.so (libdagex.dylib / dagex.dll).
+ *
+ * Graphs, DAGs and maps are opaque handles.  Every pointer argument must be
+ * NULL or valid: handles must come from this library and not have been freed
+ * or consumed, strings must be NUL-terminated UTF-8, and arrays must hold the
+ * stated number of elements.
+ *
+ * In parallel mode node callbacks run on several threads at once, so their
+ * user_data must be safe to share between threads.
+ */
+
+#ifndef GRAPH_SP_H
+#define GRAPH_SP_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct DagexGraph dagex_graph;
+typedef struct DagexDag dagex_dag;
+typedef struct DagexMap dagex_map;
+
+/*
+ * Node callback.  Reads its inputs (by impl_var name) from `inputs` and writes
+ * its outputs (by impl_var name) to `outputs`; neither map may be freed.
+ * Returns 0 on success; any other value discards the outputs.
+ */
+typedef int (*dagex_node_fn)(const dagex_map *inputs, dagex_map *outputs, void *user_data);
+
+/* ─── Graph construction ─────────────────────────────────────────────────── */
+
+/* Create an empty graph; free it with dagex_graph_free() unless it is built. */
+dagex_graph *dagex_graph_new(void);
+
+/* Free a graph that was not built or branched. */
+void dagex_graph_free(dagex_graph *graph);
+
+/*
+ * Add a node calling `function`.  `inputs` lists broadcast_var, impl_var pairs
+ * and `outputs` lists impl_var, broadcast_var pairs, each as a flat
+ * NULL-terminated array (or NULL for none):
+ *
+ *     const char *inputs[] = {"value", "x", NULL};
+ *
+ * Returns 0, or -1 for invalid arguments.
+ */
+int dagex_graph_add(dagex_graph *graph, dagex_node_fn function, void *user_data,
+                    const char *label, const char *const *inputs,
+                    const char *const *outputs);
+
+/*
+ * Add `subgraph` as a branch and return its branch ID (0 for invalid
+ * arguments).  Takes ownership of `subgraph`.
+ */
+size_t dagex_graph_branch(dagex_graph *graph, dagex_graph *subgraph);
+
+/*
+ * Add a node merging branch outputs.  Input i reads inputs[2i] from branch
+ * branch_ids[i] and passes it to the callback as inputs[2i + 1]; `inputs` is
+ * NULL-terminated and holds one pair per branch ID.  `outputs` is as in
+ * dagex_graph_add().  Returns 0, or -1 for invalid arguments.
+ */
+int dagex_graph_merge(dagex_graph *graph, dagex_node_fn function, void *user_data,
+                      const char *label, const size_t *branch_ids,
+                      const char *const *inputs, const char *const *outputs);
+
+/*
+ * Build the graph into a DAG, taking ownership of `graph`.  Returns NULL on
+ * failure.  Free the DAG with dagex_dag_free().
+ */
+dagex_dag *dagex_graph_build(dagex_graph *graph);
+
+/* ─── Execution ──────────────────────────────────────────────────────────── */
+
+/* Free a DAG. */
+void dagex_dag_free(dagex_dag *dag);
+
+/*
+ * Execute the DAG and return its context, or NULL on failure.  `inputs` (may
+ * be NULL) seeds the context.  `max_threads` of 0 means unlimited.  Free the
+ * result with dagex_map_free().
+ */
+dagex_map *dagex_dag_execute(const dagex_dag *dag, const dagex_map *inputs,
+                             int parallel, size_t max_threads);
+
+/* Mermaid diagram of the DAG; free it with dagex_string_free(). */
+char *dagex_dag_to_mermaid(const dagex_dag *dag);
+
+/* Free a string returned by this library. */
+void dagex_string_free(char *s);
+
+/* ─── Value maps ─────────────────────────────────────────────────────────── */
+
+/* Create an empty map, e.g. for dagex_dag_execute() inputs. */
+dagex_map *dagex_map_new(void);
+
+/*
+ * Free a map from dagex_map_new() or dagex_dag_execute().  Maps passed to
+ * node callbacks are owned by the library.
+ */
+void dagex_map_free(dagex_map *map);
+
+/* Whether the map holds `key` (1) or not (0). */
+int dagex_map_contains(const dagex_map *map, const char *key);
+
+/* Read a value into `out`; return 1 if `key` holds one of that type, else 0.
+ * dagex_map_get_float() also accepts integers. */
+int dagex_map_get_int(const dagex_map *map, const char *key, int64_t *out);
+int dagex_map_get_float(const dagex_map *map, const char *key, double *out);
+
+/*
+ * Borrow a string or array value, writing its length to `len`; return NULL if
+ * `key` does not hold one.  Strings are not NUL-terminated.  The pointer stays
+ * valid while the map is alive and `key` is not overwritten.
+ */
+const char *dagex_map_get_string(const dagex_map *map, const char *key, size_t *len);
+const double *dagex_map_get_float_array(const dagex_map *map, const char *key, size_t *len);
+const int64_t *dagex_map_get_int_array(const dagex_map *map, const char *key, size_t *len);
+
+/* Store a copy of a value; return 0, or -1 for invalid arguments. */
+int dagex_map_set_int(dagex_map *map, const char *key, int64_t value);
+int dagex_map_set_float(dagex_map *map, const char *key, double value);
+int dagex_map_set_string(dagex_map *map, const char *key, const char *value);
+int dagex_map_set_float_array(dagex_map *map, const char *key, const double *values, size_t len);
+int dagex_map_set_int_array(dagex_map *map, const char *key, const int64_t *values, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GRAPH_SP_H */
@@ -0,0 +1,163 @@
+"""Smoke test for the Python bindings (build them first, e.g. `maturin develop`)."""
+import sys, os
+sys.path.insert(0, os.path.dirname(os.path.dirname(__file__)))
+
+import json
+import threading
+import time
+from concurrent.futures import ThreadPoolExecutor
+import dagex
+
+
+def where(name):
+    """Node function reporting the thread it ran on"""
+    def run(inputs):
+        time.sleep(0.005)  # releases the GIL so concurrent runs overlap
+        return {name: threading.current_thread().name}
+    return run
+
+
+g = dagex.Graph()
+g.add(where("a"), label="A", inputs=[], outputs=[("a", "a")])
+g.add(where("b"), label="B", inputs=[("a", "a")], outputs=[("b", "b")])
+g.add(where("c"), label="C", inputs=[("b", "b")], outputs=[("c", "c")])
+dag = g.build()
+
+# ── 1. Node functions are submitted to the executor of the run ───────────────
+with ThreadPoolExecutor(2, thread_name_prefix="pool") as pool:
+    ctx = dag.execute(executor=pool)
+assert all(ctx[k].startswith("pool") for k in "abc"), f"not run on the executor: {ctx}"
+ctx = dag.execute()
+assert not any(ctx[k].startswith("pool") for k in "abc"), f"executor outlived its run: {ctx}"
+print(f"  1. executor path: {ctx['a']} without, pool_* with  ✓")
+
+# ── 2. Concurrent execute() calls keep their own executor ────────────────────
+failures = []
+
+
+def runs(prefix, parallel):
+    with ThreadPoolExecutor(2, thread_name_prefix=prefix) as pool:
+        for _ in range(20):
+            ctx = dag.execute(parallel=parallel, executor=pool)
+            failures.extend((prefix, ctx[k]) for k in "abc" if not ctx[k].startswith(prefix))
+
+
+threads = [threading.Thread(target=runs, args=(prefix, parallel))
+           for prefix, parallel in [("left", False), ("right", True)]]
+for t in threads:
+    t.start()
+for t in threads:
+    t.join()
+assert not failures, f"nodes ran on another run's executor: {failures[:5]}"
+print("  2. concurrent runs: every node on its own run's executor  ✓")
+
+# ── 3. Values keep their types on the way through Rust ───────────────────────
+g = dagex.Graph()
+g.add(lambda _: {"i": 3, "f": 2.0, "iv": [1, 2, 3], "fv": [1, 2.5], "t": (1, "x")},
+      label="Values", inputs=[], outputs=[(k, k) for k in ["i", "f", "iv", "fv", "t"]])
+ctx = g.build().execute()
+assert type(ctx["i"]) is int and type(ctx["f"]) is float, f"scalars changed type: {ctx}"
+assert ctx["iv"] == [1, 2, 3] and all(type(x) is int for x in ctx["iv"])
+assert ctx["fv"] == [1.0, 2.5] and all(type(x) is float for x in ctx["fv"])
+assert ctx["t"] == [1, "x"]
+try:
+    import numpy as np
+except ImportError:
+    np = None
+if np is not None:
+    g = dagex.Graph()
+    g.add(lambda _: {"ia": np.array([1, 2]), "fa": np.array([0.5, 1.5])},
+          label="Arrays", inputs=[], outputs=[("ia", "ia"), ("fa", "fa")])
+    ctx = g.build().execute()
+    assert list(ctx["ia"]) == [1, 2] and list(ctx["fa"]) == [0.5, 1.5], f"arrays changed: {ctx}"
+print(f"  3. conversions: ints, floats, lists{', numpy arrays' if np else ''} keep their types  ✓")
+
+# ── 4. Branches fan out, merge fans them back in ─────────────────────────────
+def source(_inputs):
+    return {"data": 50}
+
+
+def plus_10(inputs):
+    return {"result": inputs["x"] + 10}
+
+
+def plus_20(inputs):
+    return {"result": inputs["x"] + 20}
+
+
+def combine(inputs):
+    return {"combined": inputs["from_a"] + inputs["from_b"]}
+
+
+def fan_in():
+    graph = dagex.Graph()
+    graph.add(source, label="Source", inputs=[], outputs=[("data", "x")])
+    ids = []
+    for label, function in [("A", plus_10), ("B", plus_20)]:
+        branch = dagex.Graph()
+        branch.add(function, label=label, inputs=[("x", "x")], outputs=[("result", "result")])
+        ids.append(graph.branch(branch))
+    graph.merge(combine, label="Merge",
+                inputs=[(ids[0], "result", "from_a"), (ids[1], "result", "from_b")],
+                outputs=[("combined", "final")])
+    return graph
+
+
+ctx = fan_in().build().execute(parallel=True)
+assert ctx["final"] == 60 + 70, f"merge saw the wrong branch outputs: {ctx}"
+print(f"  4. merge: final = {ctx['final']}  ✓")
+
+# ── 5. Introspection: execution levels and stats ─────────────────────────────
+dag = fan_in().build()
+levels = dag.execution_levels()
+stats = dag.stats()
+assert sorted(sum(levels, [])) == list(range(dag.node_count())), f"levels miss nodes: {levels}"
+assert levels[0] == [0] and (stats.depth, stats.node_count) == (len(levels), dag.node_count())
+assert stats.max_parallelism == max(map(len, levels)) and stats.branch_count == 2
+assert stats.critical_path[0] == 0 and stats.critical_path[-1] in levels[-1]
+slow = stats.critical_path[1]
+weighted = dag.stats(costs={slow: 5.0})
+assert weighted.critical_path_cost == 7.0 and slow in weighted.critical_path, f"costs ignored: {weighted}"
+assert "Critical Path" in str(stats)
+print(f"  5. introspection: levels={levels}  speedup={stats.speedup:.2f}x  ✓")
+
+# ── 6. Graph definitions round-trip through JSON ─────────────────────────────
+for function in [source, plus_10, plus_20, combine]:
+    dagex.register(function)
+spec = json.loads(json.dumps(fan_in().to_dict()))
+ctx = dagex.Graph.from_dict(spec).build().execute()
+assert ctx["final"] == 130, f"rebuilt graph differs: {ctx}"
+ctx = dagex.Graph.from_dict(spec, functions={"plus_10": lambda inputs: {"result": 0}}).build().execute()
+assert ctx["final"] == 70, f"functions= did not override the registry: {ctx}"
+try:
+    dagex.Graph.from_dict({**spec, "steps": [{**spec["steps"][0], "function": "missing"}]})
+except ValueError as e:
+    print(f"  6. to_dict/from_dict round trip; unknown function: {e!r}  ✓")
+else:
+    raise AssertionError("from_dict accepted an unknown function")
+
+# ── 7. Node exceptions surface as NodeError ──────────────────────────────────
+def boom(_inputs):
+    raise ValueError("bad input")
+
+
+g = dagex.Graph()
+g.add(source, label="Source", inputs=[], outputs=[("data", "x")])
+g.add(boom, label="Boom", inputs=[("x", "x")], outputs=[("y", "y")])
+dag = g.build()
+for executor in [None, ThreadPoolExecutor(1)]:
+    try:
+        dag.execute(executor=executor)
+    except dagex.NodeError as e:
+        assert (e.node_id, e.label) == (1, "Boom"), f"wrong node: {e.node_id} {e.label}"
+        assert isinstance(e.__cause__, ValueError) and str(e.__cause__) == "bad input"
+    else:
+        raise AssertionError("execute() swallowed the node's exception")
+    if executor is not None:
+        executor.shutdown()
+ctx = dag.execute(raise_on_error=False)
+assert ctx["x"] == 50 and "y" not in ctx, f"lenient run: {ctx}"
+print("  7. NodeError names the failing node, with or without an executor  ✓")
+
+print("\n  ══════════════════════════════════════════════")
+print("  All smoke tests passed  ✓")
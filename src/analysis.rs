@@ -0,0 +1,198 @@
+//! Data-flow analysis: unused outputs, dead nodes, and structural inspection
+//!
+//! A DAG accumulates cruft as it evolves — outputs nobody reads any more, whole
+//! chains of nodes computing values that no longer feed a result.  These helpers
+//! find them and optionally remove them.  `Dag::inspect()` summarises the shape
+//! of a DAG (sources, sinks, width, depth) and lists what could be improved.
+
+use crate::dag::Dag;
+use crate::node::{Node, NodeId};
+use crate::payload::Payload;
+use std::collections::HashSet;
+
+/// A broadcast variable that a node produces but no node consumes.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct UnusedOutput {
+    /// Node producing the variable
+    pub node_id: NodeId,
+    /// Display name of that node
+    pub label: String,
+    /// The unread broadcast variable
+    pub broadcast_var: String,
+}
+
+impl<T: Payload> Dag<T> {
+    /// List outputs that no node reads
+    ///
+    /// The outputs of sink nodes — usually the results of the pipeline — are
+    /// included, so this is a list to review rather than a list of mistakes.
+    /// Sorted by node ID, then variable name.
+    pub fn unused_outputs(&self) -> Vec<UnusedOutput> {
+        let consumed: HashSet<&str> = self
+            .nodes()
+            .iter()
+            .flat_map(|n| n.input_mapping.keys())
+            .map(|key| key.split_once(':').map_or(key.as_str(), |(_, var)| var))
+            .collect();
+
+        let mut unused: Vec<UnusedOutput> = self
+            .nodes()
+            .iter()
+            .flat_map(|node| {
+                node.output_mapping
+                    .values()
+                    .filter(|var| !consumed.contains(var.as_str()))
+                    .map(move |var| UnusedOutput {
+                        node_id: node.id,
+                        label: node.display_name(),
+                        broadcast_var: var.clone(),
+                    })
+            })
+            .collect();
+        unused.sort();
+        unused.dedup();
+        unused
+    }
+
+    /// Find nodes that do not contribute to any of the `keep` variables
+    ///
+    /// A node contributes if it produces one of `keep`, or if a contributing node
+    /// depends on it.  Every other node does work whose result nobody uses.
+    /// Returns node IDs in ascending order.
+    pub fn dead_nodes(&self, keep: &[&str]) -> Vec<NodeId> {
+        let live = live_nodes(self.nodes(), keep);
+        let mut dead: Vec<NodeId> = self
+            .nodes()
+            .iter()
+            .map(|n| n.id)
+            .filter(|id| !live.contains(id))
+            .collect();
+        dead.sort_unstable();
+        dead
+    }
+
+    /// Remove the nodes reported by `dead_nodes(keep)` and return their IDs
+    ///
+    /// Remaining nodes keep their IDs; execution order and levels are recomputed.
+    pub fn prune_dead_nodes(&mut self, keep: &[&str]) -> Vec<NodeId> {
+        let dead = self.dead_nodes(keep);
+        if !dead.is_empty() {
+            let dead_set: HashSet<NodeId> = dead.iter().copied().collect();
+            *self = self.retain_nodes(|id| !dead_set.contains(&id));
+        }
+        dead
+    }
+}
+
+/// Structure of a DAG, returned by `Dag::inspect()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inspection {
+    /// Nodes without dependencies, in ascending order
+    pub sources: Vec<NodeId>,
+    /// Nodes no other node depends on, in ascending order
+    pub sinks: Vec<NodeId>,
+    /// Nodes with neither dependencies nor dependents (only reported when the DAG
+    /// has more than one node), in ascending order
+    pub isolated: Vec<NodeId>,
+    /// Number of execution levels
+    pub depth: usize,
+    /// Size of the widest execution level: the most nodes that can run at once
+    pub width: usize,
+    /// Human-readable suggestions, such as isolated nodes, dangling inputs, or
+    /// outputs of intermediate nodes that nothing reads
+    pub suggestions: Vec<String>,
+}
+
+impl<T: Payload> Dag<T> {
+    /// Inspect the structure of the DAG and suggest improvements
+    ///
+    /// ```ignore
+    /// let inspection = dag.inspect();
+    /// println!("{} sources, {} sinks, width {}", inspection.sources.len(),
+    ///          inspection.sinks.len(), inspection.width);
+    /// for suggestion in &inspection.suggestions {
+    ///     println!("- {}", suggestion);
+    /// }
+    /// ```
+    pub fn inspect(&self) -> Inspection {
+        let nodes = self.nodes();
+        let depended_on: HashSet<NodeId> = nodes
+            .iter()
+            .flat_map(|n| n.dependencies.iter().copied())
+            .collect();
+        let sorted = |mut ids: Vec<NodeId>| {
+            ids.sort_unstable();
+            ids
+        };
+        let sources = sorted(nodes.iter().filter(|n| n.dependencies.is_empty()).map(|n| n.id).collect());
+        let sinks = sorted(nodes.iter().filter(|n| !depended_on.contains(&n.id)).map(|n| n.id).collect());
+        let isolated: Vec<NodeId> = match nodes.len() {
+            0 | 1 => Vec::new(),
+            _ => sources.iter().copied().filter(|id| sinks.contains(id)).collect(),
+        };
+        let depth = self.execution_levels().len();
+        let width = self.execution_levels().iter().map(Vec::len).max().unwrap_or(0);
+
+        let mut suggestions = Vec::new();
+        let label = |id: NodeId| {
+            nodes
+                .iter()
+                .find(|n| n.id == id)
+                .map(|n| n.display_name())
+                .unwrap_or_default()
+        };
+        for &id in &isolated {
+            suggestions.push(format!(
+                "node {} ({}) is isolated: it depends on nothing and nothing depends on it",
+                id,
+                label(id)
+            ));
+        }
+        for warning in self.build_warnings() {
+            suggestions.push(warning.to_string());
+        }
+        for unused in self.unused_outputs() {
+            if !sinks.contains(&unused.node_id) {
+                suggestions.push(format!(
+                    "node {} ({}) writes '{}', which no node reads",
+                    unused.node_id, unused.label, unused.broadcast_var
+                ));
+            }
+        }
+        if width == 1 && nodes.len() > 1 {
+            suggestions.push(
+                "every level holds a single node, so parallel execution cannot speed this DAG up"
+                    .to_string(),
+            );
+        }
+
+        Inspection {
+            sources,
+            sinks,
+            isolated,
+            depth,
+            width,
+            suggestions,
+        }
+    }
+}
+
+/// Producers of `keep` and everything they (transitively) depend on
+pub(crate) fn live_nodes<T>(nodes: &[Node<T>], keep: &[&str]) -> HashSet<NodeId> {
+    let mut live: HashSet<NodeId> = HashSet::new();
+    let mut stack: Vec<NodeId> = nodes
+        .iter()
+        .filter(|n| n.output_mapping.values().any(|v| keep.contains(&v.as_str())))
+        .map(|n| n.id)
+        .collect();
+
+    while let Some(id) = stack.pop() {
+        if !live.insert(id) {
+            continue;
+        }
+        if let Some(node) = nodes.iter().find(|n| n.id == id) {
+            stack.extend(node.dependencies.iter().copied());
+        }
+    }
+    live
+}
@@ -1,16 +1,32 @@
 //! Graph builder with implicit connections API
 
+use crate::conflict::{collected_inputs, conflicting_outputs, ConflictPolicy};
+use crate::context::ExecutionContext;
 use crate::dag::Dag;
 use crate::distribution::DistTransferFn;
-use crate::graph_data::GraphData;
-use crate::node::{Node, NodeId};
-use std::collections::{HashMap, HashSet};
+use crate::graph_data::{GraphData, GraphDataKind};
+use crate::node::{unshare, Guard, Node, NodeFunction, NodeHandle, NodeId, Validator};
+use crate::payload::Payload;
+use crate::optimizer::{Optimizer, OptimizerConfig};
+use crate::plan_cache::ExecutionPlan;
+use crate::storage::StorageBackend;
+use crate::sweep::{AdaptiveSweep, IntoVariantValues, ParamGrid, SweepStep, Trial};
+use crate::units::check_port_units;
+use crate::validation::{apply_schema, check_port_types, BuildError};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
+/// One variant to create: (function, label, variant_params)
+type VariantSpec<T> = (NodeFunction<T>, Option<String>, HashMap<String, T>);
+
 /// Graph builder for constructing graphs with implicit node connections
-pub struct Graph {
+pub struct Graph<T = GraphData> {
     /// All nodes in the graph
-    nodes: Vec<Node>,
+    nodes: Vec<Node<T>>,
     /// Counter for generating unique node IDs
     next_id: NodeId,
     /// Current frontier node IDs (active attach points)
@@ -18,7 +34,7 @@ pub struct Graph {
     /// Track the last branch points for sequential `.branch()` calls (copies of `frontier`)
     last_branch_point: Option<Vec<NodeId>>,
     /// Subgraph builders for branches with their IDs
-    branches: Vec<(usize, Graph)>,
+    branches: Vec<(usize, Graph<T>)>,
     /// Next branch ID counter
     next_branch_id: usize,
     /// Track nodes that should be merged together
@@ -26,23 +42,32 @@ pub struct Graph {
     /// Pending dist_transfers to be applied to nodes by label at `build()` time.
     /// label -> DistTransferFn
     dist_transfers: HashMap<String, DistTransferFn>,
+    /// What to do with variables written by several nodes
+    conflict_policy: ConflictPolicy,
+    /// Whether branches write only to their own namespaces (see `isolate_branches()`)
+    isolate_branches: bool,
+    /// External parameters and their defaults (see `input()`)
+    declared_inputs: Vec<(String, T)>,
+    /// Result variables (see `output()`)
+    declared_outputs: Vec<String>,
+    /// Expected broadcast variables and their kinds (see `schema()`)
+    schema: BTreeMap<String, GraphDataKind>,
+    /// Whether node ports are type-checked at run time (see `enforce_schema()`)
+    enforce_schema: bool,
+    /// Whether inputs are converted between compatible units (see `auto_scale_units()`)
+    auto_scale_units: bool,
 }
 
 impl Graph {
-    /// Create a new graph
+    /// Create a new graph carrying `GraphData` values
+    ///
+    /// Use `Graph::<T>::default()` for a graph over another `Payload` type.
     pub fn new() -> Self {
-        Self {
-            nodes: Vec::new(),
-            next_id: 0,
-            frontier: Vec::new(),
-            last_branch_point: None,
-            branches: Vec::new(),
-            next_branch_id: 1,
-            merge_targets: Vec::new(),
-            dist_transfers: HashMap::new(),
-        }
+        Self::default()
     }
+}
 
+impl<T: Payload> Graph<T> {
     /// Get a unique branch ID for tracking branches
     fn get_branch_id(&mut self) -> usize {
         let id = self.next_branch_id;
@@ -50,6 +75,24 @@ impl Graph {
         id
     }
 
+    /// The nodes of the current frontier
+    ///
+    /// Frontier nodes are the most recently added ones, so the search starts at
+    /// the end and stops as soon as all of them are found.
+    fn frontier_nodes_mut(&mut self) -> impl Iterator<Item = &mut Node<T>> {
+        let mut remaining: HashSet<NodeId> = self.frontier.iter().copied().collect();
+        self.nodes
+            .iter_mut()
+            .rev()
+            .map_while(move |node| {
+                if remaining.is_empty() {
+                    return None;
+                }
+                Some(remaining.remove(&node.id).then_some(node))
+            })
+            .flatten()
+    }
+
     /// Add a node to the graph with implicit connections
     ///
     /// # Arguments
@@ -68,16 +111,22 @@ impl Graph {
     /// # Function Signature
     ///
     /// Functions receive a single parameter:
-    /// - `inputs: &HashMap<String, GraphData>` - Mapped input variables (impl_var names)
+    /// - `inputs: &HashMap<String, Arc<GraphData>>` - Mapped input variables (impl_var names)
     ///
     /// Functions return outputs using impl_var names, which get mapped to broadcast_var names.
     ///
+    /// # Returns
+    ///
+    /// A `NodeHandle` for `connect()` and for looking up the node's outputs after
+    /// execution.  If the node was replicated over several frontier nodes, the handle
+    /// refers to the first copy.  Use `push()` to keep chaining builder calls instead.
+    ///
     /// # Example
     ///
     /// ```ignore
     /// // Function sees "input_data", context has "data"
     /// // Function returns "output_value", gets stored as "result" in context
-    /// graph.add(
+    /// let process = graph.add(
     ///     process_fn,
     ///     Some("Process"),
     ///     Some(vec![("data", "input_data")]),     // (broadcast, impl)
@@ -90,13 +139,33 @@ impl Graph {
         label: Option<&str>,
         inputs: Option<Vec<(&str, &str)>>,
         outputs: Option<Vec<(&str, &str)>>,
-    ) -> &mut Self
+    ) -> NodeHandle
     where
-        F: Fn(&HashMap<String, GraphData>) -> HashMap<String, GraphData>
+        F: Fn(&HashMap<String, Arc<T>>) -> HashMap<String, T>
             + Send
             + Sync
             + 'static,
-    {   
+    {
+        self.add_shared(Arc::new(function), label, inputs, outputs)
+    }
+
+    /// Add a node whose function is already a shared `NodeFunction`
+    ///
+    /// Same as `add()`, for functions built elsewhere, such as the constructors
+    /// in `std_nodes`, or one function used by several nodes or graphs.
+    ///
+    /// ```ignore
+    /// graph.add_shared(std_nodes::multiply(), Some("Gain"),
+    ///                  Some(vec![("signal", "a"), ("gain", "b")]),
+    ///                  Some(vec![("result", "amplified")]));
+    /// ```
+    pub fn add_shared(
+        &mut self,
+        function: NodeFunction<T>,
+        label: Option<&str>,
+        inputs: Option<Vec<(&str, &str)>>,
+        outputs: Option<Vec<(&str, &str)>>,
+    ) -> NodeHandle {
         // Build input_mapping: broadcast_var -> impl_var
         let input_mapping: HashMap<String, String> = inputs
             .unwrap_or_default()
@@ -120,15 +189,13 @@ impl Graph {
 
         let mut created_ids: Vec<NodeId> = Vec::new();
 
-        // Automatically wrap the function in Arc for thread-safe sharing
-        let func_arc: crate::node::NodeFunction = Arc::new(function);
         for _parent in parents {
             let id = self.next_id;
             self.next_id += 1;
 
             let mut node = Node::new(
                 id,
-                Arc::clone(&func_arc),
+                Arc::clone(&function),
                 label.map(|s| s.to_string()),
                 input_mapping.clone(),
                 output_mapping.clone(),
@@ -147,15 +214,64 @@ impl Graph {
             created_ids.push(id);
         }
 
+        let handle = NodeHandle::new(created_ids[0]);
+
         // Update frontier to the newly created node(s)
         self.frontier = created_ids;
 
         // Reset branch point after adding a regular node
         self.last_branch_point = None;
 
+        handle
+    }
+
+    /// Add a node like `add()`, returning the builder for chaining
+    ///
+    /// ```ignore
+    /// graph
+    ///     .push(fft, Some("FFT"), Some(vec![("signal", "x")]), Some(vec![("y", "spectrum")]))
+    ///     .with_config("size", GraphData::int(1024));
+    /// ```
+    pub fn push<F>(
+        &mut self,
+        function: F,
+        label: Option<&str>,
+        inputs: Option<Vec<(&str, &str)>>,
+        outputs: Option<Vec<(&str, &str)>>,
+    ) -> &mut Self
+    where
+        F: Fn(&HashMap<String, Arc<T>>) -> HashMap<String, T>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.add(function, label, inputs, outputs);
         self
     }
 
+    /// Add a source node that writes a fixed value to `name`
+    ///
+    /// Sugar for the closure that ignores its inputs and returns one value; the
+    /// node is labelled with the variable name.
+    ///
+    /// ```ignore
+    /// graph.constant("data", GraphData::int(100));
+    /// graph.add(process, Some("Process"), Some(vec![("data", "x")]), Some(vec![("y", "result")]));
+    /// ```
+    pub fn constant<V: Into<T>>(&mut self, name: &str, value: V) -> NodeHandle {
+        let value = value.into();
+        self.add(
+            move |_: &HashMap<String, Arc<T>>| {
+                let mut outputs = HashMap::new();
+                outputs.insert("value".to_string(), value.clone());
+                outputs
+            },
+            Some(name),
+            None,
+            Some(vec![("value", name)]),
+        )
+    }
+
     /// Insert a branching subgraph
     ///
     /// # Implicit Branching Behavior
@@ -171,7 +287,7 @@ impl Graph {
     /// # Returns
     ///
     /// Returns the branch ID for use in merge operations
-    pub fn branch(&mut self, mut subgraph: Graph) -> usize {
+    pub fn branch(&mut self, mut subgraph: Graph<T>) -> usize {
         // Assign a branch ID to this subgraph (shared for all replicates)
         let branch_id = self.get_branch_id();
 
@@ -199,6 +315,16 @@ impl Graph {
             return branch_id;
         };
 
+        // Branches nested in the subgraph get IDs of their own in this graph, so they
+        // stay distinguishable from the enclosing branch; their paths record the nesting
+        let mut nested_ids: Vec<usize> = subgraph.nodes.iter().filter_map(|n| n.branch_id).collect();
+        nested_ids.sort_unstable();
+        nested_ids.dedup();
+        let nested_ids: HashMap<usize, usize> = nested_ids
+            .into_iter()
+            .map(|inner| (inner, self.get_branch_id()))
+            .collect();
+
         // For each branch point, append a cloned copy of the subgraph and attach to the branch point
         for bp in branch_points.iter() {
             // Map old node ids to new ids
@@ -209,23 +335,16 @@ impl Graph {
                 id_map.insert(node.id, new_id);
             }
 
-            // Clone nodes with remapped ids and dependencies
+            // Clone nodes with remapped ids and dependencies; every other setting
+            // (validators, units, variant parameters, ...) carries over
             for node in &subgraph.nodes {
-                let new_id = *id_map.get(&node.id).unwrap();
-                let mut new_node = Node::new(
-                    new_id,
-                    node.function.clone(),
-                    node.label.clone(),
-                    node.input_mapping.clone(),
-                    node.output_mapping.clone(),
-                );
+                let mut new_node = node.clone();
+                new_node.id = *id_map.get(&node.id).unwrap();
+                // Merge inputs of the subgraph address its nested branches by their new IDs
+                new_node.input_mapping = remap_merge_inputs(&node.input_mapping, &nested_ids);
 
                 // Remap dependencies: if dependency is internal to subgraph, map it; otherwise, attach to branch point
-                for &dep in &node.dependencies {
-                    if let Some(&mapped) = id_map.get(&dep) {
-                        new_node.dependencies.push(mapped);
-                    }
-                }
+                new_node.dependencies = node.dependencies.iter().filter_map(|dep| id_map.get(dep).copied()).collect();
 
                 // Ensure first node attaches to the branch point
                 if node.dependencies.is_empty() {
@@ -233,26 +352,63 @@ impl Graph {
                 }
 
                 new_node.is_branch = true;
-                new_node.branch_id = Some(branch_id);
-
-                // Preserve the dist_transfer from the source node
-                new_node.dist_transfer = node.dist_transfer.clone();
+                new_node.branch_id = Some(
+                    node.branch_id
+                        .and_then(|inner| nested_ids.get(&inner).copied())
+                        .unwrap_or(branch_id),
+                );
+                new_node.branch_path = std::iter::once(branch_id)
+                    .chain(node.branch_path.iter().copied())
+                    .collect();
 
                 self.nodes.push(new_node);
             }
         }
 
+        // The stored subgraph is merged in again at build time; its nested branches
+        // must keep the same IDs there
+        for node in &mut subgraph.nodes {
+            if let Some(&nested) = node.branch_id.and_then(|inner| nested_ids.get(&inner)) {
+                node.branch_id = Some(nested);
+                node.branch_path.insert(0, branch_id);
+            }
+            node.input_mapping = remap_merge_inputs(&node.input_mapping, &nested_ids);
+        }
+
         // Store original subgraph as metadata under the branch ID for reference
         self.branches.push((branch_id, subgraph));
 
         branch_id
     }
 
+    /// Flat branch ID of a nested branch, for use in `merge()` inputs
+    ///
+    /// A subgraph that itself contains branches keeps them distinct when it is
+    /// added with `branch()`: branch 1 of a subgraph added as branch 2 gets the
+    /// hierarchical ID `[2, 1]` (shown as `"2.1"`) and a flat ID of its own, which
+    /// this method returns.  `nested_branch(&[2])` is simply `Some(2)`.
+    ///
+    /// ```ignore
+    /// let outer = graph.branch(subgraph_with_branches);
+    /// let inner = graph.nested_branch(&[outer, 1]).unwrap();
+    /// graph.merge(join, Some("Join"), vec![(outer, "a", "a"), (inner, "b", "b")], None);
+    /// ```
+    pub fn nested_branch(&self, path: &[usize]) -> Option<usize> {
+        self.nodes
+            .iter()
+            .find(|n| n.branch_path == path)
+            .and_then(|n| n.branch_id)
+    }
+
     /// Create variant nodes from a vector of closures
     ///
     /// Takes a vector of closures, each representing a variant of the computation.
     /// Functions are automatically wrapped in Arc for thread-safe sharing.
     ///
+    /// Chained after another sweep, every new variant is replicated under every
+    /// upstream variant (the cartesian product, see `variants_product()`); use
+    /// `variants_zip()` to pair them one to one instead.
+    ///
     /// # Arguments
     ///
     /// * `functions` - Vector of node functions (closures) - automatically wrapped in Arc
@@ -266,7 +422,7 @@ impl Graph {
     /// let factors = vec![2.0, 3.0, 5.0];
     /// graph.variants(
     ///     factors.iter().map(|&factor| {
-    ///         move |inputs: &HashMap<String, GraphData>| {
+    ///         move |inputs: &HashMap<String, Arc<GraphData>>| {
     ///             let mut outputs = HashMap::new();
     ///             if let Some(val) = inputs.get("x").and_then(|d| d.as_float()) {
     ///                 outputs.insert("scaled".to_string(), GraphData::float(val * factor));
@@ -287,164 +443,867 @@ impl Graph {
         outputs: Option<Vec<(&str, &str)>>,
     ) -> &mut Self
     where
-        F: Fn(&HashMap<String, GraphData>) -> HashMap<String, GraphData>
+        F: Fn(&HashMap<String, Arc<T>>) -> HashMap<String, T>
             + Send
             + Sync
             + 'static,
     {
-        // Determine parent attach points (frontier). If frontier is empty, treat as a single None parent
-        let parents: Vec<Option<NodeId>> = if self.frontier.is_empty() {
-            vec![None]
-        } else {
-            self.frontier.iter().map(|&id| Some(id)).collect()
-        };
-
-        // Remember previous frontier as branch point for sequential .branch() calls
-        let previous_frontier = if self.frontier.is_empty() {
-            None
-        } else {
-            Some(self.frontier.clone())
-        };
-
-        // Prepare mappings
-        let input_mapping: HashMap<String, String> = inputs
-            .unwrap_or_default()
-            .iter()
-            .map(|(broadcast, impl_var)| (broadcast.to_string(), impl_var.to_string()))
-            .collect();
-
-        let output_mapping: HashMap<String, String> = outputs
-            .unwrap_or_default()
-            .iter()
-            .map(|(impl_var, broadcast)| (impl_var.to_string(), broadcast.to_string()))
-            .collect();
+        self.variants_product(functions, label, inputs, outputs)
+    }
 
-        let mut created_ids: Vec<NodeId> = Vec::new();
+    /// Create variant nodes, replicating every variant under every frontier node
+    ///
+    /// This is what `variants()` does.  Chained after another sweep of `n`
+    /// variants, `m` functions give `n × m` nodes: each upstream variant feeds its
+    /// own copy of every downstream variant, so all combinations are evaluated.
+    ///
+    /// ```ignore
+    /// graph.variant("lr", vec![0.1, 0.01], train, Some("Train"), None, Some(vec![("model", "model")]));
+    /// graph.variants_product(evaluators, Some("Eval"), Some(vec![("model", "m")]), None);   // 2 × evaluators
+    /// ```
+    pub fn variants_product<F>(
+        &mut self,
+        functions: Vec<F>,
+        label: Option<&str>,
+        inputs: Option<Vec<(&str, &str)>>,
+        outputs: Option<Vec<(&str, &str)>>,
+    ) -> &mut Self
+    where
+        F: Fn(&HashMap<String, Arc<T>>) -> HashMap<String, T>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let variants = Self::variant_specs(functions, label);
+        self.add_variant_nodes(variants, inputs, outputs, VariantPairing::Product)
+    }
 
-        for (idx, node_fn) in functions.into_iter().enumerate() {
-            // Automatically wrap each function in Arc and cast to trait object
-            let node_fn_arc: crate::node::NodeFunction = Arc::new(node_fn);
-            for parent in &parents {
-                let id = self.next_id;
-                self.next_id += 1;
+    /// Create variant nodes, pairing the `i`-th function with the `i`-th frontier node
+    ///
+    /// Chained after another sweep, `n` functions give `n` nodes: the first upstream
+    /// variant feeds the first function, the second the second, and so on — useful
+    /// when two sweeps move in lockstep (e.g. a model size and its matching batch
+    /// size).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the frontier holds more than one node and the number of functions
+    /// differs from it.
+    pub fn variants_zip<F>(
+        &mut self,
+        functions: Vec<F>,
+        label: Option<&str>,
+        inputs: Option<Vec<(&str, &str)>>,
+        outputs: Option<Vec<(&str, &str)>>,
+    ) -> &mut Self
+    where
+        F: Fn(&HashMap<String, Arc<T>>) -> HashMap<String, T>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let variants = Self::variant_specs(functions, label);
+        self.add_variant_nodes(variants, inputs, outputs, VariantPairing::Zip)
+    }
 
-                let mut node = Node::new(
-                    id,
-                    Arc::clone(&node_fn_arc),
+    /// One `VariantSpec` per function, labelled `"label (v{idx})"`
+    fn variant_specs<F>(functions: Vec<F>, label: Option<&str>) -> Vec<VariantSpec<T>>
+    where
+        F: Fn(&HashMap<String, Arc<T>>) -> HashMap<String, T>
+            + Send
+            + Sync
+            + 'static,
+    {
+        functions
+            .into_iter()
+            .enumerate()
+            .map(|(idx, node_fn)| {
+                // Automatically wrap each function in Arc and cast to trait object
+                let node_fn_arc: NodeFunction<T> = Arc::new(node_fn);
+                (
+                    node_fn_arc,
                     label.map(|s| format!("{} (v{})", s, idx)),
-                    input_mapping.clone(),
-                    output_mapping.clone(),
-                );
-
-                node.variant_index = Some(idx);
-
-                if !self.merge_targets.is_empty() {
-                    node.dependencies.extend(self.merge_targets.iter().copied());
-                    self.merge_targets.clear();
-                } else if let Some(pid) = *parent {
-                    node.dependencies.push(pid);
-                    node.is_branch = true;
-                }
-
-                self.nodes.push(node);
-                created_ids.push(id);
-            }
-        }
-
-        // New frontier is the set of created nodes
-        self.frontier = created_ids;
-
-        // Set last_branch_point to previous frontier (if any) for sequential .branch() calls
-        self.last_branch_point = previous_frontier;
-
-        self
+                    HashMap::new(),
+                )
+            })
+            .collect()
     }
 
-    /// Merge multiple branches back together with a merge function
-    ///
-    /// After branching, use `.merge()` to bring parallel paths back to a single point.
-    /// The merge function receives outputs from all specified branches and combines them.
+}
+
+impl Graph {
+    /// Create variant nodes from a single function and a sweep of parameter values
     ///
-    /// Branch nodes write their outputs with branch-prefixed keys in the global context
-    /// to avoid conflicts, allowing merge nodes to correctly retrieve branch-specific values.
+    /// One node is created per value.  Each node sees the value in its inputs under
+    /// `param` (alongside its mapped inputs), and the value is recorded in the node's
+    /// `variant_params`.  Values can be plain vectors or generators such as `Linspace`
+    /// and `Logspace`, whose rounding keeps labels readable.
     ///
     /// # Arguments
     ///
-    /// * `merge_fn` - Function that combines outputs from all branches
-    /// * `label` - Optional label for visualization
-    /// * `inputs` - List of (branch_id, broadcast_var, impl_var) tuples specifying which branch outputs to merge
+    /// * `param` - Name under which the swept value is passed to the function
+    /// * `values` - Values to sweep over (anything implementing `IntoVariantValues`)
+    /// * `function` - The function shared by all variants
+    /// * `label` - Optional label; each variant is labelled `"label (param=value)"`
+    /// * `inputs` - Optional list of (broadcast_var, impl_var) tuples for inputs
     /// * `outputs` - Optional list of (impl_var, broadcast_var) tuples for outputs
     ///
     /// # Example
     ///
     /// ```ignore
-    /// graph.add(source_fn, Some("Source"), None, Some(vec![("src_out", "data")]));
-    ///
-    /// let mut branch_a = Graph::new();
-    /// branch_a.add(process_a, Some("Process A"), Some(vec![("data", "input")]), Some(vec![("output", "result")]));
-    ///
-    /// let mut branch_b = Graph::new();
-    /// branch_b.add(process_b, Some("Process B"), Some(vec![("data", "input")]), Some(vec![("output", "result")]));
-    ///
-    /// let branch_a_id = graph.branch(branch_a);
-    /// let branch_b_id = graph.branch(branch_b);
-    ///
-    /// // Merge function combines results from both branches
-    /// // Branches can use same output name "result", merge maps them distinctly
-    /// graph.merge(
-    ///     combine_fn,
-    ///     Some("Combine"),
-    ///     vec![
-    ///         (branch_a_id, "result", "a_result"),    // (branch, broadcast, impl)
-    ///         (branch_b_id, "result", "b_result")
-    ///     ],
-    ///     Some(vec![("combined", "final")])            // (impl, broadcast)
+    /// graph.variant(
+    ///     "factor",
+    ///     Linspace::new(0.5, 2.0, 4),
+    ///     |inputs: &HashMap<String, Arc<GraphData>>| {
+    ///         let x = inputs.get("x").and_then(|d| d.as_float()).unwrap_or(0.0);
+    ///         let factor = inputs.get("factor").and_then(|d| d.as_float()).unwrap_or(1.0);
+    ///         let mut outputs = HashMap::new();
+    ///         outputs.insert("scaled".to_string(), GraphData::float(x * factor));
+    ///         outputs
+    ///     },
+    ///     Some("Scale"),
+    ///     Some(vec![("data", "x")]),
+    ///     Some(vec![("scaled", "result")])
     /// );
     /// ```
-    pub fn merge<F>(
+    pub fn variant<F, V>(
         &mut self,
-        merge_fn: F,
+        param: &str,
+        values: V,
+        function: F,
         label: Option<&str>,
-        inputs: Vec<(usize, &str, &str)>,
+        inputs: Option<Vec<(&str, &str)>>,
         outputs: Option<Vec<(&str, &str)>>,
     ) -> &mut Self
     where
-        F: Fn(&HashMap<String, GraphData>) -> HashMap<String, GraphData>
+        F: Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData>
             + Send
             + Sync
             + 'static,
+        V: IntoVariantValues,
     {
-        // First, integrate all pending branches into the main graph
-        let branches = std::mem::take(&mut self.branches);
-        let mut branch_terminals = Vec::new();
-
-        for (_branch_id, branch) in branches {
-            let terminals = self.merge_branch(branch);
-            branch_terminals.extend(terminals);
-        }
+        let func_arc: NodeFunction = Arc::new(function);
+        let variants = values
+            .into_variant_values()
+            .into_iter()
+            .map(|(value_label, value)| {
+                let mut params = HashMap::new();
+                params.insert(param.to_string(), value);
+                (
+                    Arc::clone(&func_arc),
+                    label.map(|s| format!("{} ({}={})", s, param, value_label)),
+                    params,
+                )
+            })
+            .collect();
 
-        // Create the merge node
-        let id = self.next_id;
-        self.next_id += 1;
+        self.add_variant_nodes(variants, inputs, outputs, VariantPairing::Product)
+    }
 
-        // Build input_mapping with branch-specific resolution
-        // For merge, we need special handling: (branch_id, broadcast_var) -> impl_var
-        // This will be handled in execution by looking at branch_id field of dependency nodes
-        let input_mapping: HashMap<String, String> = inputs
-            .iter()
-            .map(|(branch_id, broadcast_var, impl_var)| {
-                // Store as "branch_id:broadcast_var" -> impl_var for unique identification
+    /// Create variant nodes from a single function and every combination in a `ParamGrid`
+    ///
+    /// Like `variant()`, but with several swept parameters: one node is created per
+    /// combination, each seeing all of the grid's parameters in its inputs and in its
+    /// `variant_params`.  Each variant is labelled `"label (lr=0.01, batch=32)"`.
+    ///
+    /// ```ignore
+    /// let grid = ParamGrid::new()
+    ///     .add("lr", Logspace::new(-3.0, -1.0, 3))
+    ///     .add("batch", vec![32i64, 64]);
+    /// graph.variant_grid(grid, train, Some("Train"), Some(vec![("data", "x")]), Some(vec![("loss", "loss")]));
+    /// ```
+    pub fn variant_grid<F>(
+        &mut self,
+        grid: ParamGrid,
+        function: F,
+        label: Option<&str>,
+        inputs: Option<Vec<(&str, &str)>>,
+        outputs: Option<Vec<(&str, &str)>>,
+    ) -> &mut Self
+    where
+        F: Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let func_arc: NodeFunction = Arc::new(function);
+        let variants = grid
+            .combinations()
+            .into_iter()
+            .map(|(combo_label, params)| {
                 (
-                    format!("{}:{}", branch_id, broadcast_var),
-                    impl_var.to_string(),
+                    Arc::clone(&func_arc),
+                    label.map(|s| format!("{} ({})", s, combo_label)),
+                    params,
                 )
             })
             .collect();
 
-        // Build output_mapping: impl_var -> broadcast_var
-        let output_mapping: HashMap<String, String> = outputs
-            .unwrap_or_default()
-            .iter()
+        self.add_variant_nodes(variants, inputs, outputs, VariantPairing::Product)
+    }
+
+    /// Add a node that applies `subgraph` to each element of a list variable
+    ///
+    /// Unlike variants, the number of elements is only known at run time.  Each
+    /// element of the broadcast variable `items` (a `List`, or an int or float
+    /// vector) runs through its own execution of `subgraph`, seeded with the element
+    /// under the inner variable `item`.  `outputs` maps inner variables to outer
+    /// broadcast variables, each of which receives a `List` with one entry per
+    /// element, in order (`None` where an execution did not produce the variable).
+    /// If `items` is missing or not a list, the node produces no outputs.
+    ///
+    /// The executions run in parallel, on at most one thread per available core;
+    /// each thread takes the next pending element until none are left.
+    ///
+    /// ```ignore
+    /// // detect_graph reads "pulse", writes "peak"
+    /// graph.map_over("pulses", "pulse", detect_graph(), Some("Detect"), Some(vec![("peak", "peaks")]));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `subgraph` cannot be built (see `Graph::try_build()`).
+    pub fn map_over(
+        &mut self,
+        items: &str,
+        item: &str,
+        subgraph: Graph,
+        label: Option<&str>,
+        outputs: Option<Vec<(&str, &str)>>,
+    ) -> NodeHandle {
+        let dag = Arc::new(subgraph.build());
+        let item = item.to_string();
+        let collected: Vec<String> = outputs
+            .iter()
+            .flatten()
+            .map(|(inner, _)| inner.to_string())
+            .collect();
+        self.add(
+            move |inputs: &HashMap<String, Arc<GraphData>>| {
+                let mut results: HashMap<String, Vec<GraphData>> = collected
+                    .iter()
+                    .map(|var| (var.clone(), Vec::new()))
+                    .collect();
+                let Some(elements) = inputs.get("items").map(Arc::as_ref).and_then(list_elements) else {
+                    return HashMap::new();
+                };
+
+                let contexts = run_bounded(&elements, |element| {
+                    let mut seed = HashMap::new();
+                    seed.insert(item.clone(), element.clone());
+                    dag.execute_with_inputs(seed, false, None)
+                });
+                for mut context in contexts {
+                    for (var, values) in &mut results {
+                        values.push(context.remove(var).map_or_else(GraphData::none, Arc::unwrap_or_clone));
+                    }
+                }
+                results
+                    .into_iter()
+                    .map(|(var, values)| (var, GraphData::list(values)))
+                    .collect()
+            },
+            label,
+            Some(vec![(items, "items")]),
+            outputs,
+        )
+    }
+
+    /// Add a node that folds the elements of a list variable into a single value
+    ///
+    /// The complement of `map_over()`: starting from `init`, `fold` is called with
+    /// the accumulator and each element of `items` (a `List`, or an int or float
+    /// vector) in order, and the final accumulator is written to `output`.  If
+    /// `items` is missing or not a list, the node produces no outputs.
+    ///
+    /// ```ignore
+    /// graph.reduce("squares", 0i64, |total, x| GraphData::int(total.as_int().unwrap() + x.as_int().unwrap()), Some("Sum"), "sum");
+    /// ```
+    pub fn reduce<V, F>(
+        &mut self,
+        items: &str,
+        init: V,
+        fold: F,
+        label: Option<&str>,
+        output: &str,
+    ) -> NodeHandle
+    where
+        V: Into<GraphData>,
+        F: Fn(GraphData, &GraphData) -> GraphData + Send + Sync + 'static,
+    {
+        let init = init.into();
+        self.add(
+            move |inputs: &HashMap<String, Arc<GraphData>>| {
+                let Some(elements) = inputs.get("items").map(Arc::as_ref).and_then(list_elements) else {
+                    return HashMap::new();
+                };
+                let total = elements.iter().fold(init.clone(), &fold);
+                let mut outputs = HashMap::new();
+                outputs.insert("result".to_string(), total);
+                outputs
+            },
+            label,
+            Some(vec![(items, "items")]),
+            Some(vec![("result", output)]),
+        )
+    }
+
+    /// Add a node that splits an array into chunks, processes them in parallel, and
+    /// joins the results in order
+    ///
+    /// The int or float vector in the broadcast variable `input.0` is cut into
+    /// `chunks` contiguous, zero-copy slices of near-equal length (see
+    /// `GraphData::slice()`).  Each slice is fed to its own execution of `subgraph`
+    /// under the inner variable `input.1`; as in `map_over()`, the executions share
+    /// at most one thread per available core.  The inner variable
+    /// `output.0` of every execution is gathered, in chunk order, into the broadcast
+    /// variable `output.1`: concatenated when every chunk produced an int (or every
+    /// chunk a float) array, otherwise as a `List` with one entry per chunk.
+    ///
+    /// ```ignore
+    /// // normalise_graph reads "block", writes "normalised"
+    /// graph.scatter_gather(8, normalise_graph(), Some("Normalise"), ("samples", "block"), ("normalised", "clean"));
+    /// ```
+    ///
+    /// If the input is missing or not an array, the node produces no outputs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `subgraph` cannot be built (see `Graph::try_build()`).
+    pub fn scatter_gather(
+        &mut self,
+        chunks: usize,
+        subgraph: Graph,
+        label: Option<&str>,
+        input: (&str, &str),
+        output: (&str, &str),
+    ) -> NodeHandle {
+        let dag = Arc::new(subgraph.build());
+        let chunks = chunks.max(1);
+        let (inner_input, inner_output) = (input.1.to_string(), output.0.to_string());
+        self.add(
+            move |inputs: &HashMap<String, Arc<GraphData>>| {
+                let Some(array) = inputs.get("array") else {
+                    return HashMap::new();
+                };
+                let Some(len) = array
+                    .as_float_slice()
+                    .map(<[f64]>::len)
+                    .or_else(|| array.as_int_slice().map(<[i64]>::len))
+                else {
+                    return HashMap::new();
+                };
+                let parts: Vec<GraphData> = (0..chunks)
+                    .filter_map(|i| array.slice(i * len / chunks..(i + 1) * len / chunks))
+                    .collect();
+
+                let results = run_bounded(&parts, |part| {
+                    let mut seed = HashMap::new();
+                    seed.insert(inner_input.clone(), part.clone());
+                    dag.execute_with_inputs(seed, false, None)
+                        .remove(&inner_output)
+                        .map_or_else(GraphData::none, Arc::unwrap_or_clone)
+                });
+
+                let mut outputs = HashMap::new();
+                outputs.insert("gathered".to_string(), gather(results));
+                outputs
+            },
+            label,
+            Some(vec![(input.0, "array")]),
+            Some(vec![("gathered", output.1)]),
+        )
+    }
+
+    /// Add a node that tunes a parameter of `subgraph` with an `AdaptiveSweep`
+    ///
+    /// Each round runs `subgraph` once per remaining candidate, in parallel on at
+    /// most one thread per available core (as in `map_over()`), seeded
+    /// with the node's inputs (mapped as in `add_subgraph()`), the candidate under
+    /// the sweep's parameter name and, if configured, the round's budget.  The
+    /// sweep's scoring callback scores each run and its pruning callback picks the
+    /// candidates for the next round, until it stops, no candidates remain, or the
+    /// round limit is reached.  The inner context of the best-scoring run of the
+    /// last round, which includes the winning parameter value, is mapped through
+    /// `outputs`; runs with a non-finite score (e.g. NaN from a diverged run) never
+    /// win, and if no run of that round has a finite score the node produces no
+    /// outputs.
+    ///
+    /// ```ignore
+    /// let sweep = AdaptiveSweep::new("lr", Logspace::new(-4.0, -1.0, 8))
+    ///     .score(|ctx| -ctx["loss"].as_float().unwrap_or(f64::INFINITY))
+    ///     .budget("epochs", 1, 2);
+    /// graph.adaptive_sweep(sweep, train_graph(), Some("Tune"), Some(vec![("data", "x")]),
+    ///     Some(vec![("lr", "best_lr"), ("loss", "best_loss")]));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `subgraph` cannot be built (see `Graph::try_build()`).
+    pub fn adaptive_sweep(
+        &mut self,
+        sweep: AdaptiveSweep,
+        subgraph: Graph,
+        label: Option<&str>,
+        inputs: Option<Vec<(&str, &str)>>,
+        outputs: Option<Vec<(&str, &str)>>,
+    ) -> NodeHandle {
+        let dag = Arc::new(subgraph.build());
+        self.add(
+            move |inputs: &HashMap<String, Arc<GraphData>>| {
+                let mut candidates = sweep.values.clone();
+                let mut best: Option<Trial> = None;
+                for round in 0..sweep.max_rounds {
+                    if candidates.is_empty() {
+                        break;
+                    }
+                    let budget = sweep.budget_for(round);
+                    let trials = run_bounded(&candidates, |(label, value)| {
+                        let mut seed = inputs.clone();
+                        seed.insert(sweep.param.clone(), Arc::new(value.clone()));
+                        seed.extend(budget.clone().map(|(name, value)| (name, Arc::new(value))));
+                        let outputs = dag.execute_with_shared_inputs(seed, false, None).into_inner();
+                        Trial {
+                            label: label.clone(),
+                            value: value.clone(),
+                            round,
+                            score: (sweep.score)(&outputs),
+                            outputs,
+                        }
+                    });
+
+                    best = trials
+                        .iter()
+                        .filter(|t| t.score.is_finite())
+                        .max_by(|a, b| a.score.total_cmp(&b.score))
+                        .cloned();
+                    candidates = match (sweep.prune)(&trials) {
+                        SweepStep::Keep(kept) => kept
+                            .into_iter()
+                            .filter_map(|i| trials.get(i))
+                            .map(|t| (t.label.clone(), t.value.clone()))
+                            .collect(),
+                        SweepStep::Refine(values) => values,
+                        SweepStep::Stop => break,
+                    };
+                }
+                best.map(|t| unshare(t.outputs)).unwrap_or_default()
+            },
+            label,
+            inputs,
+            outputs,
+        )
+    }
+
+    /// Add a node gathering every variant's value of `broadcast_var` into `output`
+    ///
+    /// Covers the variant nodes added so far that write `broadcast_var`.  After
+    /// the node runs, `output` holds a `List` of their values in variant order and
+    /// `"{output}_params"` a `List` of `Map`s with the matching variant parameters,
+    /// so downstream nodes can aggregate a sweep without merge plumbing.  Variants
+    /// that were skipped (see `when()`) are left out of both lists.
+    ///
+    /// ```ignore
+    /// graph.variant("lr", Logspace::new(1e-4, 1e-1, 4), train, Some("Train"),
+    ///               Some(vec![("data", "x")]), Some(vec![("loss", "loss")]));
+    /// graph.collect_variants("loss", "losses");
+    /// graph.add(pick_best, Some("Best"), Some(vec![("losses", "l"), ("losses_params", "p")]), None);
+    /// ```
+    pub fn collect_variants(&mut self, broadcast_var: &str, output: &str) -> &mut Self {
+        let mut variants: Vec<&Node> = self
+            .nodes
+            .iter()
+            .filter(|n| n.branch_id.is_none() && n.output_mapping.values().any(|v| v == broadcast_var))
+            .filter(|n| !n.variant_path.is_empty())
+            .collect();
+        variants.sort_by(|a, b| a.variant_path.cmp(&b.variant_path));
+
+        // Each variant's namespaced output arrives under its own key
+        let keys: Vec<(String, String)> = variants
+            .iter()
+            .filter_map(|n| n.variant_key(broadcast_var))
+            .enumerate()
+            .map(|(i, key)| (key, format!("variant_{}", i)))
+            .collect();
+        let params: Vec<GraphData> = variants
+            .iter()
+            .map(|n| GraphData::map(n.variant_params.clone()))
+            .collect();
+
+        let params_output = format!("{}_params", output);
+        let impl_vars: Vec<String> = keys.iter().map(|(_, impl_var)| impl_var.clone()).collect();
+        // A single node, not one per frontier node
+        self.frontier.clear();
+        self.add(
+            move |inputs: &HashMap<String, Arc<GraphData>>| {
+                let (values, params): (Vec<GraphData>, Vec<GraphData>) = impl_vars
+                    .iter()
+                    .zip(&params)
+                    .filter_map(|(impl_var, params)| Some((GraphData::clone(inputs.get(impl_var)?), params.clone())))
+                    .unzip();
+                let mut outputs = HashMap::new();
+                outputs.insert("values".to_string(), GraphData::list(values));
+                outputs.insert("params".to_string(), GraphData::list(params));
+                outputs
+            },
+            Some(&format!("Collect {}", broadcast_var)),
+            Some(keys.iter().map(|(key, impl_var)| (key.as_str(), impl_var.as_str())).collect()),
+            Some(vec![("values", output), ("params", params_output.as_str())]),
+        );
+        if let Some(node) = self.nodes.last_mut() {
+            node.partial_inputs = true;
+        }
+        self
+    }
+
+    /// Add a node selecting the `k` best variants from the output of `collect_variants()`
+    ///
+    /// Reads the `collected` list and its `"{collected}_params"` companion.  Each
+    /// value is scored by its `score_key` entry when it is a `Map` (e.g. a variant
+    /// returning several metrics), or by the value itself when `score_key` is
+    /// `None`.  Higher scores rank first unless `minimize` is set.  Values that
+    /// cannot be scored are left out.  The node writes the best-first scores (a
+    /// float vector) to `output`, the matching parameters (a `List` of `Map`s) to
+    /// `"{output}_params"`, and their positions in `collected` to
+    /// `"{output}_index"`.
+    ///
+    /// ```ignore
+    /// graph.collect_variants("metrics", "all_metrics");
+    /// graph.top_k("all_metrics", 3, Some("accuracy"), false, "best");
+    /// ```
+    pub fn top_k(
+        &mut self,
+        collected: &str,
+        k: usize,
+        score_key: Option<&str>,
+        minimize: bool,
+        output: &str,
+    ) -> NodeHandle {
+        let score_key = score_key.map(str::to_string);
+        let collected_params = format!("{}_params", collected);
+        let params_output = format!("{}_params", output);
+        let index_output = format!("{}_index", output);
+        // A single node, not one per frontier node
+        self.frontier.clear();
+        self.add(
+            move |inputs: &HashMap<String, Arc<GraphData>>| {
+                let values = inputs.get("values").and_then(|v| v.as_list()).cloned().unwrap_or_default();
+                let params = inputs.get("params").and_then(|v| v.as_list()).cloned().unwrap_or_default();
+                let mut ranked: Vec<(usize, f64)> = values
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, value)| {
+                        let score = match &score_key {
+                            Some(key) => value.as_map()?.get(key)?.as_f64_lossy()?,
+                            None => value.as_f64_lossy()?,
+                        };
+                        Some((i, score))
+                    })
+                    .collect();
+                // Stable sort: ties keep variant order
+                ranked.sort_by(|a, b| match minimize {
+                    true => a.1.total_cmp(&b.1),
+                    false => b.1.total_cmp(&a.1),
+                });
+                ranked.truncate(k);
+
+                let mut outputs = HashMap::new();
+                outputs.insert(
+                    "scores".to_string(),
+                    GraphData::float_vec(ranked.iter().map(|&(_, score)| score).collect()),
+                );
+                outputs.insert(
+                    "params".to_string(),
+                    GraphData::list(
+                        ranked
+                            .iter()
+                            .map(|&(i, _)| params.get(i).cloned().unwrap_or_else(GraphData::none))
+                            .collect(),
+                    ),
+                );
+                outputs.insert(
+                    "index".to_string(),
+                    GraphData::int_vec(ranked.iter().map(|&(i, _)| i as i64).collect()),
+                );
+                outputs
+            },
+            Some(&format!("Top {} {}", k, collected)),
+            Some(vec![(collected, "values"), (collected_params.as_str(), "params")]),
+            Some(vec![
+                ("scores", output),
+                ("params", params_output.as_str()),
+                ("index", index_output.as_str()),
+            ]),
+        )
+    }
+}
+
+/// `run` applied to each of `items`, returned in item order
+///
+/// Runs on at most as many threads as the machine runs in parallel (and no more
+/// than there are items); each thread takes the next pending item.  A panic in
+/// `run` is resumed on the calling thread.
+fn run_bounded<I: Sync, R: Send>(items: &[I], run: impl Fn(&I) -> R + Sync) -> Vec<R> {
+    let threads = std::thread::available_parallelism().map_or(1, usize::from).min(items.len());
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<Option<R>> = items.iter().map(|_| None).collect();
+    std::thread::scope(|s| {
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let (run, next) = (&run, &next);
+                s.spawn(move || {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            return done;
+                        };
+                        done.push((index, run(item)));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            let done = handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            for (index, result) in done {
+                results[index] = Some(result);
+            }
+        }
+    });
+    results.into_iter().map(|result| result.expect("every item is run once")).collect()
+}
+
+/// Concatenate per-chunk int or float arrays, or list the results otherwise
+fn gather(results: Vec<GraphData>) -> GraphData {
+    if let Some(ints) = results.iter().map(GraphData::as_int_slice).collect::<Option<Vec<_>>>() {
+        return GraphData::int_vec(ints.concat());
+    }
+    if let Some(floats) = results.iter().map(GraphData::as_float_slice).collect::<Option<Vec<_>>>() {
+        return GraphData::float_vec(floats.concat());
+    }
+    GraphData::list(results)
+}
+
+/// Elements of a list-like value: a `List`, or an int or float vector
+fn list_elements(value: &GraphData) -> Option<Vec<GraphData>> {
+    if let Some(list) = value.as_list() {
+        Some(list.clone())
+    } else if let Some(ints) = value.as_int_slice() {
+        Some(ints.iter().map(|&v| GraphData::int(v)).collect())
+    } else {
+        value
+            .as_float_slice()
+            .map(|floats| floats.iter().map(|&v| GraphData::float(v)).collect())
+    }
+}
+
+impl<T: Payload> Graph<T> {
+    /// Shared implementation of `variants()` and `variant()`
+    ///
+    /// Each entry is (function, label, variant_params); one node is created per entry
+    /// and per frontier parent.
+    fn add_variant_nodes(
+        &mut self,
+        variants: Vec<VariantSpec<T>>,
+        inputs: Option<Vec<(&str, &str)>>,
+        outputs: Option<Vec<(&str, &str)>>,
+        pairing: VariantPairing,
+    ) -> &mut Self {
+        // Determine parent attach points (frontier). If frontier is empty, treat as a single None parent
+        let parents: Vec<Option<NodeId>> = if self.frontier.is_empty() {
+            vec![None]
+        } else {
+            self.frontier.iter().map(|&id| Some(id)).collect()
+        };
+
+        // Remember previous frontier as branch point for sequential .branch() calls
+        let previous_frontier = if self.frontier.is_empty() {
+            None
+        } else {
+            Some(self.frontier.clone())
+        };
+
+        // Prepare mappings
+        let input_mapping: HashMap<String, String> = inputs
+            .unwrap_or_default()
+            .iter()
+            .map(|(broadcast, impl_var)| (broadcast.to_string(), impl_var.to_string()))
+            .collect();
+
+        let output_mapping: HashMap<String, String> = outputs
+            .unwrap_or_default()
+            .iter()
+            .map(|(impl_var, broadcast)| (impl_var.to_string(), broadcast.to_string()))
+            .collect();
+
+        let mut created_ids: Vec<NodeId> = Vec::new();
+
+        // Position of each parent, found in one pass over the nodes
+        let frontier: HashSet<NodeId> = self.frontier.iter().copied().collect();
+        let parent_positions: HashMap<NodeId, usize> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| frontier.contains(&n.id))
+            .map(|(i, n)| (n.id, i))
+            .collect();
+
+        if pairing == VariantPairing::Zip && parents.len() > 1 {
+            assert_eq!(
+                variants.len(),
+                parents.len(),
+                "variants_zip() needs one function per frontier node"
+            );
+        }
+
+        for (idx, (node_fn, variant_label, params)) in variants.into_iter().enumerate() {
+            let paired = match pairing {
+                VariantPairing::Zip if parents.len() > 1 => &parents[idx..=idx],
+                _ => &parents[..],
+            };
+            for parent in paired {
+                let id = self.next_id;
+                self.next_id += 1;
+
+                let mut node = Node::new(
+                    id,
+                    Arc::clone(&node_fn),
+                    variant_label.clone(),
+                    input_mapping.clone(),
+                    output_mapping.clone(),
+                );
+
+                node.variant_index = Some(idx);
+                node.variant_params = params.clone();
+
+                // Under an upstream variant, extend its path and read its own
+                // (namespaced) outputs rather than whichever variant wrote last
+                if let Some(upstream) = parent.and_then(|pid| parent_positions.get(&pid)).map(|&i| &self.nodes[i]) {
+                    node.variant_path = upstream.variant_path.clone();
+                    let produced: HashSet<&String> = upstream.output_mapping.values().collect();
+                    node.input_mapping = node
+                        .input_mapping
+                        .into_iter()
+                        .map(|(var, impl_var)| match upstream.variant_key(&var) {
+                            Some(namespaced) if produced.contains(&var) => (namespaced, impl_var),
+                            _ => (var, impl_var),
+                        })
+                        .collect();
+                }
+                node.variant_path.push(idx);
+
+                if !self.merge_targets.is_empty() {
+                    node.dependencies.extend(self.merge_targets.iter().copied());
+                    self.merge_targets.clear();
+                } else if let Some(pid) = *parent {
+                    node.dependencies.push(pid);
+                    node.is_branch = true;
+                }
+
+                self.nodes.push(node);
+                created_ids.push(id);
+            }
+        }
+
+        // New frontier is the set of created nodes
+        self.frontier = created_ids;
+
+        // Set last_branch_point to previous frontier (if any) for sequential .branch() calls
+        self.last_branch_point = previous_frontier;
+
+        self
+    }
+
+    /// Merge multiple branches back together with a merge function
+    ///
+    /// After branching, use `.merge()` to bring parallel paths back to a single point.
+    /// The merge function receives outputs from all specified branches and combines them.
+    ///
+    /// Branch nodes write their outputs with branch-prefixed keys in the global context
+    /// to avoid conflicts, allowing merge nodes to correctly retrieve branch-specific values.
+    ///
+    /// # Arguments
+    ///
+    /// * `merge_fn` - Function that combines outputs from all branches
+    /// * `label` - Optional label for visualization
+    /// * `inputs` - List of (branch_id, broadcast_var, impl_var) tuples specifying which branch outputs to merge
+    /// * `outputs` - Optional list of (impl_var, broadcast_var) tuples for outputs
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// graph.add(source_fn, Some("Source"), None, Some(vec![("src_out", "data")]));
+    ///
+    /// let mut branch_a = Graph::new();
+    /// branch_a.add(process_a, Some("Process A"), Some(vec![("data", "input")]), Some(vec![("output", "result")]));
+    ///
+    /// let mut branch_b = Graph::new();
+    /// branch_b.add(process_b, Some("Process B"), Some(vec![("data", "input")]), Some(vec![("output", "result")]));
+    ///
+    /// let branch_a_id = graph.branch(branch_a);
+    /// let branch_b_id = graph.branch(branch_b);
+    ///
+    /// // Merge function combines results from both branches
+    /// // Branches can use same output name "result", merge maps them distinctly
+    /// graph.merge(
+    ///     combine_fn,
+    ///     Some("Combine"),
+    ///     vec![
+    ///         (branch_a_id, "result", "a_result"),    // (branch, broadcast, impl)
+    ///         (branch_b_id, "result", "b_result")
+    ///     ],
+    ///     Some(vec![("combined", "final")])            // (impl, broadcast)
+    /// );
+    /// ```
+    pub fn merge<F>(
+        &mut self,
+        merge_fn: F,
+        label: Option<&str>,
+        inputs: Vec<(usize, &str, &str)>,
+        outputs: Option<Vec<(&str, &str)>>,
+    ) -> &mut Self
+    where
+        F: Fn(&HashMap<String, Arc<T>>) -> HashMap<String, T>
+            + Send
+            + Sync
+            + 'static,
+    {
+        // First, integrate all pending branches into the main graph
+        let branches = std::mem::take(&mut self.branches);
+        let mut branch_terminals = Vec::new();
+        let mut isolated = self.isolated_terminals();
+        let mut existing_ids: HashSet<NodeId> = self.nodes.iter().map(|n| n.id).collect();
+
+        for (branch_id, branch) in branches {
+            let terminals = match isolated.remove(&branch_id) {
+                Some(terminals) => terminals,
+                None => self.merge_branch(branch, &mut existing_ids),
+            };
+            branch_terminals.extend(terminals);
+        }
+
+        // Create the merge node
+        let id = self.next_id;
+        self.next_id += 1;
+
+        // Build input_mapping with branch-specific resolution
+        // For merge, we need special handling: (branch_id, broadcast_var) -> impl_var
+        // This will be handled in execution by looking at branch_id field of dependency nodes
+        let input_mapping: HashMap<String, String> = inputs
+            .iter()
+            .map(|(branch_id, broadcast_var, impl_var)| {
+                // Store as "branch_id:broadcast_var" -> impl_var for unique identification
+                (
+                    format!("{}:{}", branch_id, broadcast_var),
+                    impl_var.to_string(),
+                )
+            })
+            .collect();
+
+        // Build output_mapping: impl_var -> broadcast_var
+        let output_mapping: HashMap<String, String> = outputs
+            .unwrap_or_default()
+            .iter()
             .map(|(impl_var, broadcast)| (impl_var.to_string(), broadcast.to_string()))
             .collect();
 
@@ -456,16 +1315,851 @@ impl Graph {
             output_mapping,
         );
 
-        // Connect to all branch terminals
-        node.dependencies.extend(branch_terminals);
+        // Connect to all branch terminals
+        node.dependencies.extend(branch_terminals);
+
+        self.nodes.push(node);
+        // Update frontier to the merge node
+        self.frontier = vec![id];
+
+        // Reset branch point
+        self.last_branch_point = None;
+
+        self
+    }
+
+    /// Merge every pending branch without listing their outputs
+    ///
+    /// Like `merge()`, but the merge function receives every output of each
+    /// pending branch's terminal nodes under `"{branch_id}:{var}"` (e.g. `"1:result"`
+    /// and `"2:result"`), so there are no `branch_id` tuples to track when the
+    /// goal is simply to join everything.
+    ///
+    /// ```ignore
+    /// graph.branch(branch_a);
+    /// graph.branch(branch_b);
+    /// graph.merge_all(
+    ///     |inputs: &HashMap<String, Arc<GraphData>>| {
+    ///         let total: i64 = inputs.values().filter_map(|v| v.as_int()).sum();
+    ///         // ...
+    ///     },
+    ///     Some("Join"),
+    ///     Some(vec![("total", "total")]),
+    /// );
+    /// ```
+    pub fn merge_all<F>(
+        &mut self,
+        merge_fn: F,
+        label: Option<&str>,
+        outputs: Option<Vec<(&str, &str)>>,
+    ) -> &mut Self
+    where
+        F: Fn(&HashMap<String, Arc<T>>) -> HashMap<String, T>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let mut inputs: Vec<(usize, String, String)> = Vec::new();
+        for (branch_id, branch) in &self.branches {
+            let deps: HashSet<NodeId> = branch
+                .nodes
+                .iter()
+                .flat_map(|n| n.dependencies.iter().copied())
+                .collect();
+            let mut vars: Vec<&String> = branch
+                .nodes
+                .iter()
+                .filter(|n| !deps.contains(&n.id))
+                .flat_map(|n| n.output_mapping.values())
+                .collect();
+            vars.sort();
+            vars.dedup();
+            inputs.extend(
+                vars.into_iter()
+                    .map(|var| (*branch_id, var.clone(), format!("{}:{}", branch_id, var))),
+            );
+        }
+        let inputs = inputs
+            .iter()
+            .map(|(branch_id, var, impl_var)| (*branch_id, var.as_str(), impl_var.as_str()))
+            .collect();
+        self.merge(merge_fn, label, inputs, outputs)
+    }
+
+    /// Add a single node that receives every value written to its inputs
+    ///
+    /// Each input is delivered as a `GraphData::List` holding the value of every
+    /// node that produces it, ordered by node ID (variant order for sweeps), instead
+    /// of whichever producer ran last.  Unlike `add()`, the node is not replicated
+    /// per frontier node, which makes it the natural way to aggregate the outputs
+    /// of a sweep.  Inputs no node produces arrive as an empty list.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// graph.variant("factor", Linspace::new(0.5, 2.0, 4), scale, Some("Scale"),
+    ///               Some(vec![("data", "x")]), Some(vec![("y", "result")]));
+    /// graph.collect(
+    ///     |inputs: &HashMap<String, Arc<GraphData>>| {
+    ///         let n = inputs["results"].as_list().map_or(0, |l| l.len());
+    ///         // ... aggregate the n values ...
+    ///     },
+    ///     Some("Summarize"),
+    ///     Some(vec![("result", "results")]),
+    ///     Some(vec![("summary", "summary")]),
+    /// );
+    /// ```
+    pub fn collect<F>(
+        &mut self,
+        function: F,
+        label: Option<&str>,
+        inputs: Option<Vec<(&str, &str)>>,
+        outputs: Option<Vec<(&str, &str)>>,
+    ) -> &mut Self
+    where
+        F: Fn(&HashMap<String, Arc<T>>) -> HashMap<String, T>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let input_mapping: HashMap<String, String> = inputs
+            .unwrap_or_default()
+            .iter()
+            .map(|(broadcast, impl_var)| (broadcast.to_string(), impl_var.to_string()))
+            .collect();
+
+        let output_mapping: HashMap<String, String> = outputs
+            .unwrap_or_default()
+            .iter()
+            .map(|(impl_var, broadcast)| (impl_var.to_string(), broadcast.to_string()))
+            .collect();
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let mut node = Node::new(
+            id,
+            Arc::new(function),
+            label.map(|s| s.to_string()),
+            input_mapping,
+            output_mapping,
+        );
+        node.collect_inputs = true;
+        node.partial_inputs = true;
+
+        if !self.merge_targets.is_empty() {
+            node.dependencies.extend(self.merge_targets.iter().copied());
+            self.merge_targets.clear();
+        }
+
+        self.nodes.push(node);
+        self.frontier = vec![id];
+        self.last_branch_point = None;
+
+        self
+    }
+
+    /// Connect output `out_var` of node `from` directly to input `in_var` of node `to`
+    ///
+    /// Both names are impl-side: what `from`'s function returns and what `to`'s
+    /// function sees.  The value travels over a private variable, so it reaches `to`
+    /// even when other nodes produce a broadcast variable of the same name, and it
+    /// takes precedence over any implicit input mapped to `in_var`.
+    ///
+    /// ```ignore
+    /// let calibrate = graph.add(calibrate_fn, Some("Calibrate"), None, Some(vec![("gain", "gain")]));
+    /// let apply = graph.add(apply_fn, Some("Apply"), Some(vec![("gain", "g")]), None);
+    /// graph.connect(calibrate, "gain", apply, "g");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or `to` is not a node of this graph.
+    pub fn connect(
+        &mut self,
+        from: NodeHandle,
+        out_var: &str,
+        to: NodeHandle,
+        in_var: &str,
+    ) -> &mut Self {
+        let (from, to) = (from.id(), to.id());
+        let edge_var = format!("__edge_{}__{}", to, in_var);
+        let producer = self
+            .nodes
+            .iter_mut()
+            .find(|n| n.id == from)
+            .unwrap_or_else(|| panic!("connect(): no node with ID {}", from));
+        producer
+            .edge_outputs
+            .push((out_var.to_string(), edge_var.clone()));
+
+        let consumer = self
+            .nodes
+            .iter_mut()
+            .find(|n| n.id == to)
+            .unwrap_or_else(|| panic!("connect(): no node with ID {}", to));
+        consumer.input_mapping.retain(|_, impl_var| impl_var != in_var);
+        consumer.input_mapping.insert(edge_var, in_var.to_string());
+        self
+    }
+
+    /// Append the nodes of another builder to this one
+    ///
+    /// Node and branch IDs of `other` are renumbered so they cannot clash with this
+    /// graph's, and its nodes connect to the existing ones through data flow as if
+    /// they had been added here.  Afterwards the frontier is `other`'s, so further
+    /// calls continue from where `other` ended.  Handles returned by `other` do not
+    /// refer to the appended nodes.  This graph's conflict policy is kept.
+    ///
+    /// ```ignore
+    /// fn preprocessing() -> Graph { /* reads "raw", writes "clean" */ }
+    ///
+    /// let mut graph = Graph::new();
+    /// graph.add(load, Some("Load"), None, Some(vec![("samples", "raw")]));
+    /// graph.extend(preprocessing());
+    /// graph.add(analyse, Some("Analyse"), Some(vec![("clean", "x")]), None);
+    /// ```
+    pub fn extend(&mut self, other: Graph<T>) -> &mut Self {
+        // IDs of `other` are all below its `next_id`, so shifting them is enough
+        let (id_offset, branch_offset) = (self.next_id, self.next_branch_id);
+        let remap_id = |id: &NodeId| id + id_offset;
+
+        // Private variables embed node IDs (`__edge_{id}__`, `__via_{id}__`) and merge
+        // inputs embed branch IDs (`{branch_id}:{var}`)
+        let remap_var = |var: &str| -> String {
+            for prefix in ["__edge_", "__via_"] {
+                if let Some((id, rest)) = var
+                    .strip_prefix(prefix)
+                    .and_then(|r| r.split_once("__"))
+                    .and_then(|(id, rest)| Some((id.parse::<NodeId>().ok()?, rest)))
+                {
+                    return format!("{}{}__{}", prefix, remap_id(&id), rest);
+                }
+            }
+            match var.split_once(':') {
+                Some((branch, rest)) => match branch.parse::<usize>() {
+                    Ok(branch) => format!("{}:{}", branch + branch_offset, rest),
+                    Err(_) => var.to_string(),
+                },
+                None => var.to_string(),
+            }
+        };
+
+        // Branch IDs of `other` are shifted too; in hierarchical paths only the
+        // outermost ID is one of `other`'s, the inner ones are local to their subgraph
+        let remap_branches = |node: &mut Node<T>| {
+            node.branch_id = node.branch_id.map(|b| b + branch_offset);
+            if let Some(outermost) = node.branch_path.first_mut() {
+                *outermost += branch_offset;
+            }
+        };
+
+        for mut node in other.nodes {
+            node.id = remap_id(&node.id);
+            node.dependencies = node.dependencies.iter().map(remap_id).collect();
+            remap_branches(&mut node);
+            node.input_mapping = node
+                .input_mapping
+                .into_iter()
+                .map(|(var, impl_var)| (remap_var(&var), impl_var))
+                .collect();
+            node.output_mapping = node
+                .output_mapping
+                .into_iter()
+                .map(|(impl_var, var)| (impl_var, remap_var(&var)))
+                .collect();
+            for (_, var) in &mut node.edge_outputs {
+                *var = remap_var(var);
+            }
+            self.nodes.push(node);
+        }
+        // Stored subgraphs name their nested branches by `other`'s IDs as well
+        self.branches.extend(other.branches.into_iter().map(|(branch_id, mut branch)| {
+            branch.nodes.iter_mut().for_each(remap_branches);
+            (branch_id + branch_offset, branch)
+        }));
+        for (label, transfer) in other.dist_transfers {
+            self.dist_transfers.entry(label).or_insert(transfer);
+        }
+
+        if !other.frontier.is_empty() {
+            self.frontier = other.frontier.iter().map(remap_id).collect();
+            self.last_branch_point = other
+                .last_branch_point
+                .map(|points| points.iter().map(remap_id).collect());
+        }
+        self.merge_targets
+            .extend(other.merge_targets.iter().map(remap_id));
+        for (name, default) in other.declared_inputs {
+            if !self.declared_inputs.iter().any(|(n, _)| *n == name) {
+                self.declared_inputs.push((name, default));
+            }
+        }
+        for name in other.declared_outputs {
+            if !self.declared_outputs.contains(&name) {
+                self.declared_outputs.push(name);
+            }
+        }
+        for (var, kind) in other.schema {
+            self.schema.entry(var).or_insert(kind);
+        }
+        self.enforce_schema |= other.enforce_schema;
+        self.auto_scale_units |= other.auto_scale_units;
+        self.next_id += other.next_id;
+        self.next_branch_id += other.next_branch_id;
+        self
+    }
+
+    /// Add a whole graph as a single node
+    ///
+    /// `subgraph` is built once; each time the node runs, it executes the inner DAG
+    /// (sequentially) starting from the node's inputs.  `inputs` maps outer broadcast
+    /// variables to the inner variables they feed, and `outputs` maps inner variables
+    /// to outer broadcast variables.  The subgraph keeps its own namespace: inner
+    /// variables that are not listed in `outputs` do not reach the outer context, so
+    /// packaged components cannot clash with the graph they are used in.
+    ///
+    /// ```ignore
+    /// let compression = range_compression_graph();   // reads "echo", writes "compressed"
+    /// graph.add_subgraph(
+    ///     compression,
+    ///     Some("Range compression"),
+    ///     Some(vec![("rx_samples", "echo")]),
+    ///     Some(vec![("compressed", "range_profile")]),
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `subgraph` cannot be built (see `Graph::try_build()`).
+    pub fn add_subgraph(
+        &mut self,
+        subgraph: Graph<T>,
+        label: Option<&str>,
+        inputs: Option<Vec<(&str, &str)>>,
+        outputs: Option<Vec<(&str, &str)>>,
+    ) -> NodeHandle {
+        let dag = Arc::new(subgraph.build());
+        self.add(
+            move |inputs: &HashMap<String, Arc<T>>| {
+                unshare(dag.execute_with_shared_inputs(inputs.clone(), false, None).into_inner())
+            },
+            label,
+            inputs,
+            outputs,
+        )
+    }
+
+    /// Add a node that executes `subgraph` repeatedly, feeding its outputs back in
+    ///
+    /// The first iteration starts from the node's inputs (mapped as in
+    /// `add_subgraph()`); each following iteration starts from the full inner context
+    /// of the previous one, so a variable the subgraph both reads and writes carries
+    /// its updated value forward.  After each iteration `until` inspects the inner
+    /// context, and the loop stops when it returns `true` or after `max_iters`
+    /// iterations, whichever comes first.  The last inner context is mapped through
+    /// `outputs`.
+    ///
+    /// ```ignore
+    /// // step reads "x", writes the next "x" and the "delta" between them
+    /// graph.repeat(
+    ///     gradient_step_graph(),
+    ///     |ctx| ctx.get("delta").and_then(|d| d.as_float()).is_some_and(|d| d.abs() < 1e-9),
+    ///     1000,
+    ///     Some("Descend"),
+    ///     Some(vec![("initial_guess", "x")]),
+    ///     Some(vec![("x", "minimum")]),
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `subgraph` cannot be built (see `Graph::try_build()`).
+    pub fn repeat<U>(
+        &mut self,
+        subgraph: Graph<T>,
+        until: U,
+        max_iters: usize,
+        label: Option<&str>,
+        inputs: Option<Vec<(&str, &str)>>,
+        outputs: Option<Vec<(&str, &str)>>,
+    ) -> NodeHandle
+    where
+        U: Fn(&HashMap<String, Arc<T>>) -> bool + Send + Sync + 'static,
+    {
+        let dag = Arc::new(subgraph.build());
+        self.add(
+            move |inputs: &HashMap<String, Arc<T>>| {
+                let mut state = inputs.clone();
+                for _ in 0..max_iters {
+                    state = dag.execute_with_shared_inputs(state, false, None).into_inner();
+                    if until(&state) {
+                        break;
+                    }
+                }
+                unshare(state)
+            },
+            label,
+            inputs,
+            outputs,
+        )
+    }
+
+    /// Splice a new node into the data flow between `upstream` and `downstream`
+    ///
+    /// Every variable that `upstream` produces and `downstream` reads (implicitly or
+    /// through `connect()`) is routed through the new node instead: the function
+    /// receives each value under the name `downstream`'s function reads it by and
+    /// must return it under the same name.  Other consumers of `upstream` are not
+    /// affected.  Useful for injecting logging, validation, or unit conversion:
+    ///
+    /// ```ignore
+    /// graph.insert_between(source, fft, |inputs: &HashMap<String, Arc<GraphData>>| {
+    ///     let x = inputs["x"].as_float_vec().unwrap();
+    ///     let mut out = HashMap::new();
+    ///     out.insert("x".to_string(), GraphData::float_vec(x.iter().map(|v| v * 1e-3).collect()));
+    ///     out
+    /// }, Some("mV → V"));
+    /// ```
+    ///
+    /// Returns `None` (and changes nothing) if either node is not in this graph or
+    /// no variable flows between them.
+    pub fn insert_between<F>(
+        &mut self,
+        upstream: NodeHandle,
+        downstream: NodeHandle,
+        function: F,
+        label: Option<&str>,
+    ) -> Option<NodeHandle>
+    where
+        F: Fn(&HashMap<String, Arc<T>>) -> HashMap<String, T>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let producer = self.nodes.iter().find(|n| n.id == upstream.id())?;
+        let produced: HashSet<&String> = producer.all_outputs().map(|(_, var)| var).collect();
+        let consumer = self.nodes.iter().find(|n| n.id == downstream.id())?;
+        let mut shared: Vec<(String, String)> = consumer
+            .input_mapping
+            .iter()
+            .filter(|(var, _)| produced.contains(var))
+            .map(|(var, impl_var)| (var.clone(), impl_var.clone()))
+            .collect();
+        if shared.is_empty() {
+            return None;
+        }
+        shared.sort();
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let routed = |var: &str| format!("__via_{}__{}", id, var);
+
+        let consumer = self.nodes.iter_mut().find(|n| n.id == downstream.id())?;
+        for (var, impl_var) in &shared {
+            consumer.input_mapping.remove(var);
+            consumer.input_mapping.insert(routed(var), impl_var.clone());
+        }
+
+        let node = Node::new(
+            id,
+            Arc::new(function),
+            label.map(|s| s.to_string()),
+            shared
+                .iter()
+                .map(|(var, impl_var)| (var.clone(), impl_var.clone()))
+                .collect(),
+            shared
+                .iter()
+                .map(|(var, impl_var)| (impl_var.clone(), routed(var)))
+                .collect(),
+        );
+        self.nodes.push(node);
+        Some(NodeHandle::new(id))
+    }
+
+    /// Remove a node before building
+    ///
+    /// Other nodes' explicit dependencies on it and connections into it are dropped
+    /// too; nodes that read its broadcast variables simply no longer receive them.
+    /// Returns `false` if the node is not in this graph.
+    pub fn remove_node(&mut self, node: NodeHandle) -> bool {
+        let id = node.id();
+        let Some(index) = self.nodes.iter().position(|n| n.id == id) else {
+            return false;
+        };
+        self.nodes.remove(index);
+
+        let edge_prefix = format!("__edge_{}__", id);
+        for other in &mut self.nodes {
+            other.dependencies.retain(|&dep| dep != id);
+            other
+                .edge_outputs
+                .retain(|(_, var)| !var.starts_with(&edge_prefix));
+        }
+        self.frontier.retain(|&n| n != id);
+        self.merge_targets.retain(|&n| n != id);
+        if let Some(branch_point) = &mut self.last_branch_point {
+            branch_point.retain(|&n| n != id);
+        }
+        true
+    }
 
-        self.nodes.push(node);
-        // Update frontier to the merge node
-        self.frontier = vec![id];
+    /// Swap the function of a node, keeping its label, port mappings, and configuration
+    ///
+    /// Returns `false` if the node is not in this graph.
+    pub fn replace_function<F>(&mut self, node: NodeHandle, function: F) -> bool
+    where
+        F: Fn(&HashMap<String, Arc<T>>) -> HashMap<String, T>
+            + Send
+            + Sync
+            + 'static,
+    {
+        match self.nodes.iter_mut().find(|n| n.id == node.id()) {
+            Some(target) => {
+                target.function = Arc::new(function);
+                true
+            }
+            None => false,
+        }
+    }
 
-        // Reset branch point
+    /// Attach a static configuration value to the most recently added node(s)
+    ///
+    /// The value is passed to the node function in its inputs under `key`, so one
+    /// function can be instantiated several times with different settings.  Mapped
+    /// inputs and variant parameters with the same name take precedence.  When the
+    /// previous call created several nodes (variants, or an `add()` replicated over
+    /// several frontier nodes) every one of them receives the value.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// graph
+    ///     .push(fft, Some("FFT 256"), Some(vec![("signal", "x")]), Some(vec![("y", "spectrum_256")]))
+    ///     .with_config("size", GraphData::int(256));
+    /// graph
+    ///     .push(fft, Some("FFT 1024"), Some(vec![("signal", "x")]), Some(vec![("y", "spectrum_1024")]))
+    ///     .with_config("size", GraphData::int(1024));
+    /// ```
+    pub fn with_config<V: Into<T>>(&mut self, key: &str, value: V) -> &mut Self {
+        let value = value.into();
+        for node in self.frontier_nodes_mut() {
+            node.config.insert(key.to_string(), value.clone());
+        }
+        self
+    }
+
+    /// Give an input port of the most recently added node(s) a default value
+    ///
+    /// `impl_var` is the name the function sees.  When no upstream node (or caller
+    /// of `Dag::execute_with_inputs()`) supplies the mapped broadcast variable, the
+    /// function receives `value` instead, so source-less parameters need neither a
+    /// dedicated constant node nor a fallback in the function body.  A defaulted
+    /// input is not reported as unsatisfied by `Dag::build_warnings()`.
+    ///
+    /// ```ignore
+    /// graph
+    ///     .push(chirp, Some("Chirp"), Some(vec![("bandwidth", "bw")]), Some(vec![("pulse", "pulse")]))
+    ///     .with_default("bw", GraphData::float(100e6));
+    /// ```
+    ///
+    /// Defaults share their storage with `with_config()`: a mapped input always
+    /// takes precedence over a configuration value of the same name.
+    pub fn with_default<V: Into<T>>(&mut self, impl_var: &str, value: V) -> &mut Self {
+        self.with_config(impl_var, value)
+    }
+
+    /// Describe what the most recently added node(s) do
+    ///
+    /// Shown as a tooltip in `Dag::to_mermaid()`.
+    pub fn with_description(&mut self, description: &str) -> &mut Self {
+        for node in self.frontier_nodes_mut() {
+            node.description = Some(description.to_string());
+        }
+        self
+    }
+
+    /// Annotate the most recently added node(s) with a key-value pair
+    ///
+    /// Metadata does not affect execution.  It is listed in Mermaid tooltips,
+    /// counted in `Dag::stats()`, and can be queried with
+    /// `Dag::nodes_with_metadata()`, so large graphs can be documented and filtered:
+    ///
+    /// ```ignore
+    /// graph
+    ///     .push(load, Some("Load"), None, Some(vec![("samples", "raw")]))
+    ///     .with_metadata("tag", "io")
+    ///     .with_metadata("owner", "team-x");
+    /// let io_nodes = graph.build().nodes_with_metadata("tag", "io");
+    /// ```
+    pub fn with_metadata(&mut self, key: &str, value: &str) -> &mut Self {
+        for node in self.frontier_nodes_mut() {
+            node.metadata.insert(key.to_string(), value.to_string());
+        }
+        self
+    }
+
+    /// Mark an input port of the most recently added node(s) as required
+    ///
+    /// `impl_var` is the name the function sees.  If the input is absent when the
+    /// node is about to run (and has no default, see `with_default()`), the function
+    /// is not called: the node and its dependents are skipped and an
+    /// `ExecutionError` naming the node, the missing variable, and the nodes that
+    /// produce it is recorded.  `Dag::try_execute()` returns that error.
+    ///
+    /// ```ignore
+    /// graph
+    ///     .push(detect, Some("Detect"), Some(vec![("spectrum", "x")]), Some(vec![("hits", "detections")]))
+    ///     .require_input("x");
+    /// ```
+    pub fn require_input(&mut self, impl_var: &str) -> &mut Self {
+        for node in self.frontier_nodes_mut() {
+            node.required_inputs.insert(impl_var.to_string());
+        }
+        self
+    }
+
+    /// Only execute the most recently added node(s) when `predicate` holds
+    ///
+    /// The predicate sees the execution context (broadcast variable names) just
+    /// before the node would run.  When it returns `false` the node is skipped: it
+    /// writes no outputs, and nodes reading them are skipped as well (unless another
+    /// producer of the same variable ran).  Skipped
+    /// nodes are listed in `ExecutionResult::skipped_nodes`.  Use `when_or()` to let
+    /// downstream nodes run with a placeholder value instead.
+    ///
+    /// ```ignore
+    /// graph
+    ///     .push(detect, Some("Detect"), Some(vec![("spectrum", "x")]), Some(vec![("hits", "detections")]))
+    ///     .when(|ctx| ctx.get("snr_db").and_then(|v| v.as_float()).is_some_and(|snr| snr > 10.0));
+    /// ```
+    ///
+    /// Guards are evaluated by the executors (`execute*()`, `poll_execute()`), not by
+    /// `predict()`.
+    pub fn when<P>(&mut self, predicate: P) -> &mut Self
+    where
+        P: Fn(&ExecutionContext<T>) -> bool + Send + Sync + 'static,
+    {
+        let guard: Guard<T> = Arc::new(predicate);
+        for node in self.frontier_nodes_mut() {
+            node.guard = Some(Arc::clone(&guard));
+        }
+        self
+    }
+
+    /// Like `when()`, but a rejected node writes `sentinel` to each of its outputs,
+    /// so the nodes depending on it still run
+    pub fn when_or<P, V>(&mut self, predicate: P, sentinel: V) -> &mut Self
+    where
+        P: Fn(&ExecutionContext<T>) -> bool + Send + Sync + 'static,
+        V: Into<T>,
+    {
+        let sentinel = sentinel.into();
+        self.when(predicate);
+        for node in self.frontier_nodes_mut() {
+            node.skip_sentinel = Some(sentinel.clone());
+        }
+        self
+    }
+
+    /// Append several alternative branches of which only one executes per run
+    ///
+    /// `selector` sees the execution context once the current frontier has run and
+    /// returns the index of the branch to execute.  Every node of the other branches
+    /// is skipped (see `when()`); an index out of range skips them all.  Branches
+    /// usually write the same variables, so downstream nodes read whichever result
+    /// was produced:
+    ///
+    /// ```ignore
+    /// let mut fast = Graph::new();
+    /// fast.add(approximate, Some("Approximate"), Some(vec![("data", "x")]), Some(vec![("y", "estimate")]));
+    /// let mut exact = Graph::new();
+    /// exact.add(solve, Some("Solve"), Some(vec![("data", "x")]), Some(vec![("y", "estimate")]));
+    ///
+    /// graph
+    ///     .switch(
+    ///         |ctx| if ctx.get("n").and_then(|n| n.as_int()).unwrap_or(0) > 10_000 { 0 } else { 1 },
+    ///         vec![fast, exact],
+    ///     )
+    ///     .push(report, Some("Report"), Some(vec![("estimate", "x")]), None);
+    /// ```
+    ///
+    /// The selector is re-evaluated before each branch node, so it should only read
+    /// variables produced upstream of the switch.  Branch nodes also depend on the
+    /// current frontier, so those variables are in place when it runs.
+    pub fn switch<S>(&mut self, selector: S, branches: Vec<Graph<T>>) -> &mut Self
+    where
+        S: Fn(&ExecutionContext<T>) -> usize + Send + Sync + 'static,
+    {
+        let selector = Arc::new(selector);
+        let upstream = std::mem::take(&mut self.frontier);
+        let mut frontier = Vec::new();
+
+        for (index, branch) in branches.into_iter().enumerate() {
+            let first = self.next_id;
+            self.frontier.clear();
+            self.extend(branch);
+
+            let selector = Arc::clone(&selector);
+            let chosen: Guard<T> = Arc::new(move |ctx| selector(ctx) == index);
+            for node in self.nodes.iter_mut().filter(|n| n.id >= first) {
+                if node.dependencies.is_empty() {
+                    node.dependencies.extend(upstream.iter().copied());
+                }
+                node.guard = Some(match node.guard.take() {
+                    Some(own) => {
+                        let chosen = Arc::clone(&chosen);
+                        Arc::new(move |ctx| chosen(ctx) && own(ctx))
+                    }
+                    None => Arc::clone(&chosen),
+                });
+            }
+            frontier.extend(self.frontier.iter().copied());
+        }
+
+        self.frontier = frontier;
         self.last_branch_point = None;
+        self
+    }
+
+    /// Declare an external parameter of the graph and its default value
+    ///
+    /// Every execution starts with `name` set to `default` unless the caller
+    /// supplies it (`Dag::execute_with_inputs()`), so nodes can read it like any
+    /// broadcast variable.  Once inputs are declared,
+    /// `Dag::try_execute_with_inputs()` rejects variables that are not among them.
+    ///
+    /// ```ignore
+    /// graph
+    ///     .input("path", "data/capture.bin")
+    ///     .input("threshold", 0.5)
+    ///     .output("detections");
+    /// ```
+    pub fn input<V: Into<T>>(&mut self, name: &str, default: V) -> &mut Self {
+        let default = default.into();
+        match self.declared_inputs.iter_mut().find(|(n, _)| n == name) {
+            Some((_, value)) => *value = default,
+            None => self.declared_inputs.push((name.to_string(), default)),
+        }
+        self
+    }
+
+    /// Declare a result variable of the graph
+    ///
+    /// Once outputs are declared, `Dag::execute()` and `Dag::execute_with_inputs()`
+    /// return only these variables instead of the whole accumulated context (as do
+    /// subgraph nodes built from this graph).  `Dag::execute_detailed()` still
+    /// exposes everything.
+    pub fn output(&mut self, name: &str) -> &mut Self {
+        if !self.declared_outputs.iter().any(|n| n == name) {
+            self.declared_outputs.push(name.to_string());
+        }
+        self
+    }
+
+    /// Declare the type of an input port of the most recently added node(s)
+    ///
+    /// `impl_var` is the name the function sees.  `build()` checks the declaration
+    /// against the declared output types of the nodes producing the variable and
+    /// fails with `BuildError::TypeMismatch` if they disagree.  Undeclared ports are
+    /// not checked.
+    ///
+    /// ```ignore
+    /// graph
+    ///     .push(source, Some("Source"), None, Some(vec![("value", "x")]))
+    ///     .with_output_type("value", GraphDataKind::Float);
+    /// graph
+    ///     .push(square, Some("Square"), Some(vec![("x", "v")]), Some(vec![("out", "y")]))
+    ///     .with_input_type("v", GraphDataKind::Float);
+    /// ```
+    pub fn with_input_type(&mut self, impl_var: &str, kind: GraphDataKind) -> &mut Self {
+        for node in self.frontier_nodes_mut() {
+            node.input_types.insert(impl_var.to_string(), kind);
+        }
+        self
+    }
+
+    /// Declare the type of an output port of the most recently added node(s)
+    ///
+    /// `impl_var` is the name under which the function returns the value.  See
+    /// `with_input_type()`.
+    pub fn with_output_type(&mut self, impl_var: &str, kind: GraphDataKind) -> &mut Self {
+        for node in self.frontier_nodes_mut() {
+            node.output_types.insert(impl_var.to_string(), kind);
+        }
+        self
+    }
+
+    /// Declare the physical unit of an input port of the most recently added node(s)
+    ///
+    /// `impl_var` is the name the function sees; `unit` is a unit string such as
+    /// `"Hz"`, `"MHz"`, `"m"` or `"ms"`.  `build()` fails with
+    /// `BuildError::UnitMismatch` if a producer of the variable declares a unit of
+    /// another dimension, or of another scale unless `auto_scale_units()` is on.
+    ///
+    /// ```ignore
+    /// graph
+    ///     .push(tune, Some("Tune"), None, Some(vec![("f", "carrier")]))
+    ///     .with_output_unit("f", "MHz");
+    /// graph
+    ///     .push(mix, Some("Mix"), Some(vec![("carrier", "freq")]), Some(vec![("out", "if")]))
+    ///     .with_input_unit("freq", "Hz");
+    /// ```
+    pub fn with_input_unit(&mut self, impl_var: &str, unit: &str) -> &mut Self {
+        for node in self.frontier_nodes_mut() {
+            node.input_units.insert(impl_var.to_string(), unit.to_string());
+        }
+        self
+    }
+
+    /// Declare the physical unit of an output port of the most recently added node(s)
+    ///
+    /// `impl_var` is the name under which the function returns the value.  See
+    /// `with_input_unit()`.
+    pub fn with_output_unit(&mut self, impl_var: &str, unit: &str) -> &mut Self {
+        for node in self.frontier_nodes_mut() {
+            node.output_units.insert(impl_var.to_string(), unit.to_string());
+        }
+        self
+    }
+
+    /// Convert inputs whose unit differs from their producers' only by a factor
+    ///
+    /// With this on, a `"MHz"` output feeding a `"Hz"` input builds, and the
+    /// consumer receives the value multiplied by 1e6 (see `Payload::scaled()`).
+    /// Units of different dimensions are still rejected.
+    pub fn auto_scale_units(&mut self, enabled: bool) -> &mut Self {
+        self.auto_scale_units = enabled;
+        self
+    }
 
+    /// Check the value of a port of the most recently added node(s) on every run
+    ///
+    /// `impl_var` is the name the function sees.  An input is checked just before
+    /// the function is called, an output just after it returns; ports absent from
+    /// a run are not checked.  When `validator` returns an error the node counts as
+    /// skipped, so its dependents are skipped as well, and an
+    /// `ExecutionError::InvalidInput` / `InvalidOutput` naming the node, the port,
+    /// and the message is recorded in `ExecutionResult::errors` (and returned by
+    /// `Dag::try_execute()`).
+    ///
+    /// ```ignore
+    /// graph.add(filter, Some("Filter"), Some(vec![("cutoff", "hz")]), Some(vec![("out", "y")]));
+    /// graph.validate("hz", |d: &GraphData| match d.as_float() {
+    ///     Some(hz) if hz > 0.0 => Ok(()),
+    ///     _ => Err("expected a positive frequency"),
+    /// });
+    /// ```
+    pub fn validate<F, E>(&mut self, impl_var: &str, validator: F) -> &mut Self
+    where
+        F: Fn(&T) -> Result<(), E> + Send + Sync + 'static,
+        E: fmt::Display,
+    {
+        let validator: Validator<T> = Arc::new(move |value| validator(value).map_err(|e| e.to_string()));
+        for node in self.frontier_nodes_mut() {
+            node.validators.push((impl_var.to_string(), Arc::clone(&validator)));
+        }
         self
     }
 
@@ -482,6 +2176,76 @@ impl Graph {
         self
     }
 
+    /// Choose how variables written by several nodes are handled
+    ///
+    /// The default, `ConflictPolicy::LastWriterWins`, keeps whichever value was
+    /// written last.  `ConflictPolicy::Error` makes such graphs fail to build, and
+    /// `ConflictPolicy::Collect` gathers all values into a `GraphData::List`:
+    ///
+    /// ```ignore
+    /// graph.on_conflict(ConflictPolicy::Collect);
+    /// graph.variant("factor", vec![1.0, 2.0, 3.0], scale, Some("Scale"),
+    ///               Some(vec![("data", "x")]), Some(vec![("y", "result")]));
+    /// // Consumers of "result" receive List([Float(..), Float(..), Float(..)])
+    /// ```
+    pub fn on_conflict(&mut self, policy: ConflictPolicy) -> &mut Self {
+        self.conflict_policy = policy;
+        self
+    }
+
+    /// Give every branch its own namespace in the execution context
+    ///
+    /// By default the nodes of a branch also publish their outputs to the shared
+    /// context, so two branches writing the same variable overwrite each other and
+    /// a node may read the other branch's value.  With isolation on, branch nodes
+    /// write only under their branch's prefix and read copy-on-write: their own
+    /// branch first, then enclosing branches, then the shared context.  Values
+    /// leave a branch only through `merge()` (or `get_from_branch()` on the
+    /// result):
+    ///
+    /// ```ignore
+    /// graph.isolate_branches(true);
+    /// let a = graph.branch(scale_by_two);   // writes "result"
+    /// let b = graph.branch(scale_by_three); // also writes "result"
+    /// graph.merge(sum, Some("Sum"), vec![(a, "result", "a"), (b, "result", "b")], None);
+    /// ```
+    pub fn isolate_branches(&mut self, isolate: bool) -> &mut Self {
+        self.isolate_branches = isolate;
+        self
+    }
+
+    /// Declare a broadcast variable of the graph's schema and the kind of its values
+    ///
+    /// Once a schema is declared, `build()` fails with `BuildError::NotInSchema` if a
+    /// node reads or writes a variable the schema does not list, and with
+    /// `BuildError::SchemaMismatch` if a port type declared with `with_input_type()` /
+    /// `with_output_type()` contradicts the schema.  Ports of schema variables without
+    /// a declared type take the schema's kind, so producers and consumers are
+    /// checked against each other as well.  Only the schema of the top-level graph
+    /// applies; branches are checked against it.
+    ///
+    /// ```ignore
+    /// graph
+    ///     .schema("samples", GraphDataKind::FloatVec)
+    ///     .schema("rate_hz", GraphDataKind::Float)
+    ///     .enforce_schema(true);
+    /// ```
+    pub fn schema(&mut self, broadcast_var: &str, kind: GraphDataKind) -> &mut Self {
+        self.schema.insert(broadcast_var.to_string(), kind);
+        self
+    }
+
+    /// Also check the kinds of values against the schema when the DAG runs
+    ///
+    /// A node whose input or output value has another kind than its port declares
+    /// is treated like one rejected by a validator (see `validate()`): it counts as
+    /// skipped and an `ExecutionError::InvalidInput` / `InvalidOutput` is recorded.
+    /// Values of a `Payload` type without `Payload::kind()` are not checked.
+    pub fn enforce_schema(&mut self, enforce: bool) -> &mut Self {
+        self.enforce_schema = enforce;
+        self
+    }
+
     /// Build the final DAG from the graph builder
     ///
     /// This performs the implicit inspection phase:
@@ -489,11 +2253,131 @@ impl Graph {
     /// - Execution path optimization
     /// - Data flow connection determination
     /// - Identification of parallelizable operations
-    pub fn build(mut self) -> Dag {
+    ///
+    /// # Panics
+    ///
+    /// Panics if the graph cannot be built (see `try_build()`).
+    pub fn build(self) -> Dag<T> {
+        self.try_build().unwrap_or_else(|e| panic!("failed to build graph: {}", e))
+    }
+
+    /// Build the final DAG, returning an error instead of panicking
+    ///
+    /// Fails with:
+    /// - `BuildError::TypeMismatch` when a producer and a consumer of a variable
+    ///   declare incompatible port types,
+    /// - `BuildError::ConflictingProducers` when the conflict policy is
+    ///   `ConflictPolicy::Error` and several nodes write the same variable,
+    /// - `BuildError::NotInSchema` when a schema is declared and a node reads or
+    ///   writes a variable it does not list,
+    /// - `BuildError::SchemaMismatch` when a declared port type contradicts the
+    ///   schema (see `schema()`),
+    /// - `BuildError::UnitMismatch` when a producer and a consumer of a variable
+    ///   declare units of different dimensions, or of different scales unless
+    ///   `auto_scale_units()` is on.
+    pub fn try_build(self) -> Result<Dag<T>, BuildError> {
+        self.build_with_optimizer(&Optimizer::new())
+    }
+
+    /// Build the final DAG and apply the optimization passes enabled in `config`
+    ///
+    /// The passes run after data dependencies have been resolved; what each one
+    /// changed is recorded in `Dag::optimization_log()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the graph cannot be built (see `try_build()`).
+    pub fn build_optimized(self, config: OptimizerConfig) -> Dag<T> {
+        self.build_with_optimizer(&Optimizer::from_config(&config))
+            .unwrap_or_else(|e| panic!("failed to build graph: {}", e))
+    }
+
+    /// Build the final DAG, reusing the execution order and levels of `plan` if it
+    /// was computed for the same topology (see `Dag::execution_plan()`)
+    ///
+    /// A plan for a different topology is ignored and the DAG is analyzed as
+    /// usual.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the graph cannot be built (see `try_build()`).
+    pub fn build_with_plan(self, plan: &ExecutionPlan) -> Dag<T> {
+        self.build_planned(&Optimizer::new(), Some(plan))
+            .unwrap_or_else(|e| panic!("failed to build graph: {}", e))
+    }
+
+    /// Build the final DAG, caching its execution plan in the file at `path`
+    ///
+    /// If the file holds the plan of a graph with the same topology, the DAG
+    /// reuses it; otherwise (no file, an unreadable file, or a changed graph) the
+    /// DAG is analyzed as usual and its plan is written to `path`.  Only writing the
+    /// plan can fail.
+    ///
+    /// ```ignore
+    /// let dag = sweep_graph().build_cached("sweep.plan")?;
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the graph cannot be built (see `try_build()`).
+    pub fn build_cached(self, path: impl AsRef<Path>) -> io::Result<Dag<T>> {
+        let cached = ExecutionPlan::load(&path).ok();
+        self.build_with_cached_plan(cached, |plan| plan.save(&path))
+    }
+
+    /// Build the final DAG, caching its execution plan under `key` in `storage`
+    ///
+    /// Same as `build_cached()`, with the plan kept in a `StorageBackend`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the graph cannot be built (see `try_build()`).
+    pub fn build_cached_in(self, storage: &dyn StorageBackend, key: &str) -> io::Result<Dag<T>> {
+        let cached = ExecutionPlan::load_from(storage, key).ok();
+        self.build_with_cached_plan(cached, |plan| plan.save_to(storage, key))
+    }
+
+    /// Shared implementation of `build_cached()` and `build_cached_in()`: build
+    /// with `cached` and `save` the plan unless it was reused
+    fn build_with_cached_plan(
+        self,
+        cached: Option<ExecutionPlan>,
+        save: impl FnOnce(&ExecutionPlan) -> io::Result<()>,
+    ) -> io::Result<Dag<T>> {
+        let dag = self
+            .build_planned(&Optimizer::new(), cached.as_ref())
+            .unwrap_or_else(|e| panic!("failed to build graph: {}", e));
+        if cached.map(|plan| plan.topology_hash()) != Some(dag.topology_hash()) {
+            save(&dag.execution_plan())?;
+        }
+        Ok(dag)
+    }
+
+    /// Build the final DAG, running a custom pipeline of optimization passes
+    pub fn build_with_optimizer(
+        self,
+        optimizer: &Optimizer<T>,
+    ) -> Result<Dag<T>, BuildError> {
+        self.build_planned(optimizer, None)
+    }
+
+    /// Shared implementation of the `build*()` methods
+    fn build_planned(
+        mut self,
+        optimizer: &Optimizer<T>,
+        plan: Option<&ExecutionPlan>,
+    ) -> Result<Dag<T>, BuildError> {
         // Merge all branch subgraphs into main node list
         let branches = std::mem::take(&mut self.branches);
-        for (_branch_id, branch) in branches {
-            self.merge_branch(branch);
+        let isolated = self.isolated_terminals();
+        let mut existing_ids: HashSet<NodeId> = self.nodes.iter().map(|n| n.id).collect();
+        for (branch_id, branch) in branches {
+            if !isolated.contains_key(&branch_id) {
+                self.merge_branch(branch, &mut existing_ids);
+            }
+        }
+        if self.isolate_branches {
+            self.assign_branch_scopes();
         }
 
         // Resolve data dependencies based on input/output mappings
@@ -513,7 +2397,38 @@ impl Graph {
             }
         }
 
-        Dag::new(self.nodes)
+        if !self.schema.is_empty() {
+            apply_schema(&mut self.nodes, &self.schema)?;
+        }
+        if self.enforce_schema {
+            for node in &mut self.nodes {
+                node.check_types = true;
+            }
+        }
+        check_port_types(&self.nodes)?;
+        check_port_units(&mut self.nodes, self.auto_scale_units)?;
+
+        let optimization_log = optimizer.run(&mut self.nodes);
+
+        let conflicts = conflicting_outputs(&self.nodes);
+        let collected = collected_inputs(&self.nodes);
+        if self.conflict_policy == ConflictPolicy::Error {
+            if let Some(conflict) = conflicts.values().next() {
+                return Err(BuildError::ConflictingProducers {
+                    broadcast_var: conflict.broadcast_var.clone(),
+                    producers: conflict.producers.clone(),
+                });
+            }
+        }
+
+        let mut dag = Dag::with_plan(self.nodes, plan);
+        dag.set_interface(self.declared_inputs, self.declared_outputs);
+        if self.conflict_policy == ConflictPolicy::Collect {
+            dag.add_collected(conflicts);
+        }
+        dag.add_collected(collected);
+        dag.set_optimization_log(optimization_log);
+        Ok(dag)
     }
 
     /// Resolve dependencies based on data flow (input/output mappings)
@@ -523,53 +2438,89 @@ impl Graph {
     fn resolve_data_dependencies(&mut self) {
         // Build a map of which nodes produce which broadcast variables
         let mut producers: HashMap<String, Vec<NodeId>> = HashMap::new();
-        
+
         for node in &self.nodes {
-            for broadcast_var in node.output_mapping.values() {
+            for (_, broadcast_var) in node.all_outputs() {
                 producers.entry(broadcast_var.clone())
                     .or_default()
                     .push(node.id);
+                if let Some(namespaced) = node.variant_key(broadcast_var) {
+                    producers.entry(namespaced).or_default().push(node.id);
+                }
             }
         }
 
         // For each node, find its dependencies based on required inputs
-        for i in 0..self.nodes.len() {
-            let node = &self.nodes[i];
-            let required_inputs: Vec<String> = node.input_mapping.keys().cloned().collect();
-            let node_id = node.id;
-            
-            let mut dependencies: HashSet<NodeId> = HashSet::new();
-            
+        for node in &mut self.nodes {
             // Keep any existing dependencies (from merge_targets or branches)
-            dependencies.extend(node.dependencies.iter().copied());
-            
+            let mut dependencies: HashSet<NodeId> = node.dependencies.drain(..).collect();
+
             // Add dependencies based on data flow
-            for input_key in &required_inputs {
-                // Handle merge node special format: "branch_id:broadcast_var"
-                let broadcast_var = if input_key.contains(':') {
-                    // For merge nodes, extract the broadcast_var part after ':'
-                    input_key.split(':').nth(1).unwrap_or(input_key.as_str())
-                } else {
-                    input_key.as_str()
-                };
-                
+            for input_key in node.input_mapping.keys() {
+                // Merge inputs ("branch_id:broadcast_var") depend on producers of the variable
+                let broadcast_var = input_key.split(':').nth(1).unwrap_or(input_key);
+
                 if let Some(producer_ids) = producers.get(broadcast_var) {
-                    for &producer_id in producer_ids {
-                        // Don't depend on ourselves
-                        if producer_id != node_id {
-                            dependencies.insert(producer_id);
-                        }
-                    }
+                    // Don't depend on ourselves
+                    dependencies.extend(producer_ids.iter().copied().filter(|&id| id != node.id));
                 }
             }
-            
+
             // Update the node's dependencies
-            self.nodes[i].dependencies = dependencies.into_iter().collect();
+            node.dependencies = dependencies.into_iter().collect();
+            node.dependencies.sort_unstable();
         }
     }
 
-    /// Merge a branch builder's nodes into this builder
-    fn merge_branch(&mut self, branch: Graph) -> Vec<NodeId> {
+    /// Terminal nodes of each isolated branch that `branch()` already copied in,
+    /// by branch ID
+    ///
+    /// Empty without isolation.  Branches missing from the map had no branch
+    /// point: they are merged in with `merge_branch()`, whose copies of the branch
+    /// nodes publish to the shared context.  Nested branches count as part of
+    /// their enclosing branch.
+    fn isolated_terminals(&self) -> HashMap<usize, Vec<NodeId>> {
+        if !self.isolate_branches {
+            return HashMap::new();
+        }
+        let mut members: HashMap<usize, Vec<&Node<T>>> = HashMap::new();
+        for node in &self.nodes {
+            if let Some(&branch_id) = node.branch_path.first() {
+                members.entry(branch_id).or_default().push(node);
+            }
+        }
+        members
+            .into_iter()
+            .map(|(branch_id, members)| {
+                let deps: HashSet<NodeId> = members
+                    .iter()
+                    .flat_map(|n| n.dependencies.iter().copied())
+                    .collect();
+                (branch_id, members.iter().map(|n| n.id).filter(|id| !deps.contains(id)).collect())
+            })
+            .collect()
+    }
+
+    /// Point every branch node at the namespaces it reads, innermost first
+    fn assign_branch_scopes(&mut self) {
+        let flat_ids: HashMap<Vec<usize>, usize> = self
+            .nodes
+            .iter()
+            .filter_map(|n| Some((n.branch_path.clone(), n.branch_id?)))
+            .collect();
+        for node in &mut self.nodes {
+            node.branch_scopes = (1..=node.branch_path.len())
+                .rev()
+                .filter_map(|len| flat_ids.get(&node.branch_path[..len]).copied())
+                .collect();
+        }
+    }
+
+    /// Merge a branch builder's nodes into this builder, returning its terminals
+    ///
+    /// `existing_ids` holds the IDs of the nodes of this builder; dependencies on
+    /// them are kept, while dependencies within the branch are renumbered.
+    fn merge_branch(&mut self, branch: Graph<T>, existing_ids: &mut HashSet<NodeId>) -> Vec<NodeId> {
         // Determine terminal nodes in the branch (nodes that are not dependencies of any other node within the branch)
         let branch_deps: HashSet<NodeId> = branch
             .nodes
@@ -586,9 +2537,6 @@ impl Graph {
         // Create a mapping from old branch IDs to new IDs
         let mut id_mapping: HashMap<NodeId, NodeId> = HashMap::new();
 
-        // Get the set of existing node IDs in the main graph (before merging)
-        let existing_ids: HashSet<NodeId> = self.nodes.iter().map(|n| n.id).collect();
-
         // Renumber all nodes from the branch
         for mut node in branch.nodes {
             let old_id = node.id;
@@ -616,6 +2564,7 @@ impl Graph {
 
             self.nodes.push(node);
         }
+        existing_ids.extend(id_mapping.values().copied());
 
         // Recursively merge nested branches and collect their terminals as well
         let mut terminals: Vec<NodeId> = terminal_old_ids
@@ -624,7 +2573,7 @@ impl Graph {
             .collect();
 
         for (_branch_id, nested_branch) in branch.branches {
-            let nested_terminals = self.merge_branch(nested_branch);
+            let nested_terminals = self.merge_branch(nested_branch, existing_ids);
             terminals.extend(nested_terminals);
         }
 
@@ -632,8 +2581,50 @@ impl Graph {
     }
 }
 
-impl Default for Graph {
+/// Rewrite merge inputs (`"branch_id:var"`) whose branch ID appears in `ids`
+fn remap_merge_inputs(
+    input_mapping: &HashMap<String, String>,
+    ids: &HashMap<usize, usize>,
+) -> HashMap<String, String> {
+    input_mapping
+        .iter()
+        .map(|(broadcast_key, impl_var)| {
+            let remapped = broadcast_key
+                .split_once(':')
+                .and_then(|(id, var)| Some((ids.get(&id.parse().ok()?)?, var)))
+                .map(|(id, var)| format!("{}:{}", id, var));
+            (remapped.unwrap_or_else(|| broadcast_key.clone()), impl_var.clone())
+        })
+        .collect()
+}
+
+/// How new variants attach to a frontier of several nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VariantPairing {
+    /// Every variant under every frontier node (`variants_product()`)
+    Product,
+    /// The `i`-th variant under the `i`-th frontier node (`variants_zip()`)
+    Zip,
+}
+
+impl<T: Payload> Default for Graph<T> {
     fn default() -> Self {
-        Self::new()
+        Self {
+            nodes: Vec::new(),
+            next_id: 0,
+            frontier: Vec::new(),
+            last_branch_point: None,
+            branches: Vec::new(),
+            next_branch_id: 1,
+            merge_targets: Vec::new(),
+            dist_transfers: HashMap::new(),
+            conflict_policy: ConflictPolicy::default(),
+            isolate_branches: false,
+            declared_inputs: Vec::new(),
+            declared_outputs: Vec::new(),
+            schema: BTreeMap::new(),
+            enforce_schema: false,
+            auto_scale_units: false,
+        }
     }
 }
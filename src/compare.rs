@@ -0,0 +1,399 @@
+//! Structural comparison and diffing of DAGs
+//!
+//! Node IDs depend on the order in which a graph was constructed, so two pipelines
+//! built in different ways (different `.add()` order, branches declared in another
+//! sequence, …) can be identical in every respect except their numbering.  The
+//! helpers here compare DAGs by what their nodes *are* — label, port mappings,
+//! variant information — and how they are wired, ignoring the IDs themselves.
+
+use crate::dag::Dag;
+use crate::node::{Node, NodeId};
+use crate::payload::Payload;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// Everything about a node that takes part in a structural comparison, rendered
+/// as a canonical string (mappings and parameters sorted by key).
+pub(crate) fn node_key<T: Payload>(node: &Node<T>) -> String {
+    let mut inputs: Vec<_> = node.input_mapping.iter().collect();
+    inputs.sort();
+    let mut outputs: Vec<_> = node.output_mapping.iter().collect();
+    outputs.sort();
+    format!(
+        "label={:?} in={:?} out={:?} variant={:?} params={:?} config={:?} collect={} branch={}",
+        node.label,
+        inputs,
+        outputs,
+        node.variant_index,
+        summarized(&node.variant_params),
+        summarized(&node.config),
+        node.collect_inputs,
+        node.is_branch
+    )
+}
+
+/// `(key, Debug)` pairs sorted by key (`GraphData`'s `Debug` is its `summary()`).
+fn summarized<T: Payload>(values: &HashMap<String, T>) -> Vec<(String, String)> {
+    let mut v: Vec<(String, String)> = values
+        .iter()
+        .map(|(k, v)| (k.clone(), format!("{:?}", v)))
+        .collect();
+    v.sort();
+    v
+}
+
+/// Name used to match nodes between two DAGs in `Dag::diff()`.
+fn diff_name<T>(node: &Node<T>) -> String {
+    node.label.clone().unwrap_or_else(|| "<unlabeled>".to_string())
+}
+
+/// Differences in the comparable attributes of two nodes with the same name.
+fn node_changes<T: Payload>(before: &Node<T>, after: &Node<T>) -> Vec<String> {
+    fn sorted<K: Ord + Clone, V: Ord + Clone>(m: &HashMap<K, V>) -> Vec<(K, V)> {
+        let mut v: Vec<(K, V)> = m.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        v.sort();
+        v
+    }
+    let mut changes = Vec::new();
+    let (bi, ai) = (sorted(&before.input_mapping), sorted(&after.input_mapping));
+    if bi != ai {
+        changes.push(format!("inputs: {:?} → {:?}", bi, ai));
+    }
+    let (bo, ao) = (sorted(&before.output_mapping), sorted(&after.output_mapping));
+    if bo != ao {
+        changes.push(format!("outputs: {:?} → {:?}", bo, ao));
+    }
+    if before.variant_index != after.variant_index {
+        changes.push(format!(
+            "variant: {:?} → {:?}",
+            before.variant_index, after.variant_index
+        ));
+    }
+    let (bp, ap) = (summarized(&before.variant_params), summarized(&after.variant_params));
+    if bp != ap {
+        changes.push(format!("variant params: {:?} → {:?}", bp, ap));
+    }
+    let (bc, ac) = (summarized(&before.config), summarized(&after.config));
+    if bc != ac {
+        changes.push(format!("config: {:?} → {:?}", bc, ac));
+    }
+    if before.is_branch != after.is_branch {
+        changes.push(format!("branch: {} → {}", before.is_branch, after.is_branch));
+    }
+    changes
+}
+
+/// Nodes of a DAG grouped by `diff_name`, each group in canonical order.
+fn nodes_by_name<T: Payload>(dag: &Dag<T>) -> HashMap<String, Vec<&Node<T>>> {
+    let mut groups: HashMap<String, Vec<&Node<T>>> = HashMap::new();
+    for node in dag.nodes() {
+        groups.entry(diff_name(node)).or_default().push(node);
+    }
+    for nodes in groups.values_mut() {
+        nodes.sort_by_cached_key(|n| node_key(n));
+    }
+    groups
+}
+
+/// All edges of a DAG as sorted `(from name, to name)` pairs (a multiset).
+fn named_edges<T: Payload>(dag: &Dag<T>) -> Vec<(String, String)> {
+    let names: HashMap<NodeId, String> =
+        dag.nodes().iter().map(|n| (n.id, diff_name(n))).collect();
+    let mut edges: Vec<(String, String)> = Vec::new();
+    for node in dag.nodes() {
+        let deps: HashSet<NodeId> = node.dependencies.iter().copied().collect();
+        for dep in deps {
+            if let Some(from) = names.get(&dep) {
+                edges.push((from.clone(), diff_name(node)));
+            }
+        }
+    }
+    edges.sort();
+    edges
+}
+
+/// Elements of sorted multiset `a` that are not matched by an element of sorted multiset `b`.
+fn multiset_minus<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut out = Vec::new();
+    let mut j = 0;
+    for item in a {
+        while j < b.len() && b[j] < *item {
+            j += 1;
+        }
+        if j < b.len() && b[j] == *item {
+            j += 1;
+        } else {
+            out.push(item.clone());
+        }
+    }
+    out
+}
+
+/// A node present in both DAGs whose mappings or variant information differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeChange {
+    /// Label shared by the two nodes
+    pub label: String,
+    /// One human-readable line per changed attribute, e.g. `inputs: [..] → [..]`
+    pub changes: Vec<String>,
+}
+
+/// Result of `Dag::diff()`: how to get from one DAG to another.
+///
+/// Nodes are matched by label (unlabeled nodes are grouped together); edges are
+/// `(from label, to label)` pairs.  All lists are sorted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DagDiff {
+    /// Labels of nodes only present in the other DAG
+    pub added_nodes: Vec<String>,
+    /// Labels of nodes only present in this DAG
+    pub removed_nodes: Vec<String>,
+    /// Nodes present in both with different mappings / variant information
+    pub changed_nodes: Vec<NodeChange>,
+    /// Edges only present in the other DAG
+    pub added_edges: Vec<(String, String)>,
+    /// Edges only present in this DAG
+    pub removed_edges: Vec<(String, String)>,
+}
+
+impl DagDiff {
+    /// `true` when the two DAGs have no differences.
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.changed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+
+    /// Format the diff as a human-readable string (`+` added, `-` removed, `~` changed).
+    pub fn summary(&self) -> String {
+        if self.is_empty() {
+            return "No differences".to_string();
+        }
+        let mut lines = Vec::new();
+        for n in &self.added_nodes {
+            lines.push(format!("+ node {}", n));
+        }
+        for n in &self.removed_nodes {
+            lines.push(format!("- node {}", n));
+        }
+        for c in &self.changed_nodes {
+            lines.push(format!("~ node {}", c.label));
+            for change in &c.changes {
+                lines.push(format!("    {}", change));
+            }
+        }
+        for (from, to) in &self.added_edges {
+            lines.push(format!("+ edge {} → {}", from, to));
+        }
+        for (from, to) in &self.removed_edges {
+            lines.push(format!("- edge {} → {}", from, to));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Adjacency and per-node signatures of a DAG, used for isomorphism checks.
+struct Structure<'a> {
+    order: &'a [NodeId],
+    deps: HashMap<NodeId, HashSet<NodeId>>,
+    signature: HashMap<NodeId, u64>,
+}
+
+impl<'a> Structure<'a> {
+    fn new<T: Payload>(dag: &'a Dag<T>) -> Self {
+        let keys: HashMap<NodeId, String> =
+            dag.nodes().iter().map(|n| (n.id, node_key(n))).collect();
+
+        let mut deps: HashMap<NodeId, HashSet<NodeId>> = HashMap::new();
+        let mut dependents: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+        for node in dag.nodes() {
+            let node_deps: HashSet<NodeId> = node.dependencies.iter().copied().collect();
+            for &dep in &node_deps {
+                dependents.entry(dep).or_default().push(node.id);
+            }
+            deps.insert(node.id, node_deps);
+        }
+
+        let order = dag.execution_order();
+
+        // Signature from the ancestors' side, computed in topological order…
+        let mut up: HashMap<NodeId, u64> = HashMap::new();
+        for &id in order {
+            let mut parent_sigs: Vec<u64> =
+                deps[&id].iter().filter_map(|d| up.get(d)).copied().collect();
+            parent_sigs.sort_unstable();
+            up.insert(id, hash_of(&(&keys[&id], parent_sigs)));
+        }
+
+        // …and from the descendants' side, in reverse topological order.
+        let mut down: HashMap<NodeId, u64> = HashMap::new();
+        for &id in order.iter().rev() {
+            let mut child_sigs: Vec<u64> = dependents
+                .get(&id)
+                .map(|c| c.iter().filter_map(|d| down.get(d)).copied().collect())
+                .unwrap_or_default();
+            child_sigs.sort_unstable();
+            down.insert(id, hash_of(&(&keys[&id], child_sigs)));
+        }
+
+        let signature = order
+            .iter()
+            .map(|&id| (id, hash_of(&(up[&id], down[&id]))))
+            .collect();
+
+        Self {
+            order,
+            deps,
+            signature,
+        }
+    }
+
+    fn edge_count(&self) -> usize {
+        self.deps.values().map(|d| d.len()).sum()
+    }
+
+    fn sorted_signatures(&self) -> Vec<u64> {
+        let mut sigs: Vec<u64> = self.signature.values().copied().collect();
+        sigs.sort_unstable();
+        sigs
+    }
+}
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Try to extend `mapping` (nodes of `a` → nodes of `b`) to cover `a.order[pos..]`.
+fn extend_mapping(
+    a: &Structure,
+    b: &Structure,
+    pos: usize,
+    mapping: &mut HashMap<NodeId, NodeId>,
+    used: &mut HashSet<NodeId>,
+) -> bool {
+    let Some(&u) = a.order.get(pos) else {
+        return true;
+    };
+
+    let sig = a.signature[&u];
+    for &v in b.order {
+        if used.contains(&v) || b.signature[&v] != sig {
+            continue;
+        }
+        // Every dependency of `u` is already mapped (topological order); its image
+        // must be a dependency of `v`.  Equal signatures imply equal dependency counts.
+        let edges_match = a.deps[&u]
+            .iter()
+            .all(|d| mapping.get(d).is_some_and(|md| b.deps[&v].contains(md)));
+        if !edges_match {
+            continue;
+        }
+
+        mapping.insert(u, v);
+        used.insert(v);
+        if extend_mapping(a, b, pos + 1, mapping, used) {
+            return true;
+        }
+        mapping.remove(&u);
+        used.remove(&v);
+    }
+    false
+}
+
+impl<T: Payload> Dag<T> {
+    /// Compare this DAG with another one and report added, removed, and changed
+    /// nodes and edges.
+    ///
+    /// Nodes are matched by label.  When several nodes share a label (variants,
+    /// replicated branches), identical nodes are paired first and the remainder are
+    /// paired in a stable order and reported as changed.  Node IDs are ignored, so
+    /// two DAGs built in a different order but with the same topology yield an
+    /// empty diff.
+    pub fn diff(&self, other: &Dag<T>) -> DagDiff {
+        let mut diff = DagDiff::default();
+
+        let mine = nodes_by_name(self);
+        let theirs = nodes_by_name(other);
+
+        let mut names: Vec<&String> = mine.keys().chain(theirs.keys()).collect();
+        names.sort();
+        names.dedup();
+
+        for name in names {
+            let mut before: Vec<&Node<T>> = mine.get(name).cloned().unwrap_or_default();
+            let mut after: Vec<&Node<T>> = theirs.get(name).cloned().unwrap_or_default();
+
+            // Pair identical nodes first
+            before.retain(|b| {
+                let key = node_key(b);
+                if let Some(pos) = after.iter().position(|a| node_key(a) == key) {
+                    after.remove(pos);
+                    false
+                } else {
+                    true
+                }
+            });
+
+            let paired = before.len().min(after.len());
+            for (b, a) in before.iter().zip(after.iter()) {
+                diff.changed_nodes.push(NodeChange {
+                    label: name.clone(),
+                    changes: node_changes(b, a),
+                });
+            }
+            for _ in paired..before.len() {
+                diff.removed_nodes.push(name.clone());
+            }
+            for _ in paired..after.len() {
+                diff.added_nodes.push(name.clone());
+            }
+        }
+
+        let (my_edges, their_edges) = (named_edges(self), named_edges(other));
+        diff.added_edges = multiset_minus(&their_edges, &my_edges);
+        diff.removed_edges = multiset_minus(&my_edges, &their_edges);
+
+        diff
+    }
+
+    /// Check whether two DAGs have the same structure, ignoring node ID numbering.
+    ///
+    /// Nodes are compared by label, input/output mappings, variant index and
+    /// parameters, and whether they belong to a branch; edges are compared through
+    /// the resulting node correspondence.  Function pointers are not compared.
+    ///
+    /// Useful for asserting that two construction styles produce the same pipeline.
+    pub fn is_isomorphic(&self, other: &Dag<T>) -> bool {
+        self.isomorphism(other).is_some()
+    }
+
+    /// Find a node correspondence (self ID → other ID) under which the two DAGs are
+    /// structurally identical, or `None` if they are not isomorphic.
+    pub fn isomorphism(&self, other: &Dag<T>) -> Option<HashMap<NodeId, NodeId>> {
+        if self.nodes().len() != other.nodes().len() {
+            return None;
+        }
+        let a = Structure::new(self);
+        let b = Structure::new(other);
+        if a.order.len() != self.nodes().len() || b.order.len() != other.nodes().len() {
+            // Cyclic input; execution_order is incomplete and no comparison is meaningful.
+            return None;
+        }
+        if a.edge_count() != b.edge_count() || a.sorted_signatures() != b.sorted_signatures() {
+            return None;
+        }
+
+        let mut mapping = HashMap::new();
+        let mut used = HashSet::new();
+        if extend_mapping(&a, &b, 0, &mut mapping, &mut used) {
+            Some(mapping)
+        } else {
+            None
+        }
+    }
+}
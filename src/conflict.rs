@@ -0,0 +1,93 @@
+//! Handling of broadcast variables written by more than one node
+//!
+//! Several nodes can declare the same output variable — most commonly the nodes of
+//! a variant sweep, which all write `"result"`.  `Graph::on_conflict()` selects what
+//! happens in that case; see `ConflictPolicy`.
+
+use crate::node::{Node, NodeId};
+use crate::streaming::context_keys;
+use std::collections::{BTreeMap, HashMap};
+
+/// What to do when several nodes write the same variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictPolicy {
+    /// Refuse to build: `Graph::try_build()` returns `BuildError::ConflictingProducers`
+    /// (and `Graph::build()` panics).
+    Error,
+    /// The value written last during execution is kept.
+    #[default]
+    LastWriterWins,
+    /// The variable holds a `GraphData::List` with the value of every producer,
+    /// ordered by node ID (variant order for sweeps).  Consumers receive the list.
+    Collect,
+}
+
+/// The nodes writing one variable of the execution context.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Producers {
+    /// Broadcast variable as declared in the output mappings
+    pub(crate) broadcast_var: String,
+    /// Writers, sorted by node ID
+    pub(crate) producers: Vec<NodeId>,
+}
+
+/// Context keys written by more than one node, with their writers.
+///
+/// Branch outputs are stored under branch-prefixed keys, so the same variable
+/// written in two different branches is not a conflict.
+pub(crate) fn conflicting_outputs<T>(nodes: &[Node<T>]) -> BTreeMap<String, Producers> {
+    let mut writers: BTreeMap<String, Producers> = BTreeMap::new();
+    for node in nodes {
+        for (var, key) in context_keys(node) {
+            writers
+                .entry(key)
+                .or_insert_with(|| Producers {
+                    broadcast_var: var,
+                    producers: Vec::new(),
+                })
+                .producers
+                .push(node.id);
+        }
+    }
+    writers.retain(|_, p| p.producers.len() > 1);
+    for p in writers.values_mut() {
+        p.producers.sort_unstable();
+    }
+    writers
+}
+
+/// Context key under which the values of all producers of `broadcast_var` are
+/// gathered for nodes added with `Graph::collect()`.
+pub(crate) fn collected_key(broadcast_var: &str) -> String {
+    format!("__collect__{}", broadcast_var)
+}
+
+/// Lists to gather for the inputs of collecting nodes, keyed by `collected_key()`.
+///
+/// Only main-graph producers are considered, since branch outputs live under
+/// branch-prefixed keys and are read through `merge()` instead.
+pub(crate) fn collected_inputs<T>(nodes: &[Node<T>]) -> BTreeMap<String, Producers> {
+    let mut producers_of: HashMap<&str, Vec<NodeId>> = HashMap::new();
+    for node in nodes.iter().filter(|n| n.branch_id.is_none()) {
+        for var in node.output_mapping.values() {
+            let producers = producers_of.entry(var).or_default();
+            if producers.last() != Some(&node.id) {
+                producers.push(node.id);
+            }
+        }
+    }
+
+    let mut gathered = BTreeMap::new();
+    for consumer in nodes.iter().filter(|n| n.collect_inputs) {
+        for broadcast_var in consumer.input_mapping.keys() {
+            gathered.insert(
+                collected_key(broadcast_var),
+                Producers {
+                    broadcast_var: broadcast_var.clone(),
+                    producers: producers_of.get(broadcast_var.as_str()).cloned().unwrap_or_default(),
+                },
+            );
+        }
+    }
+    gathered
+}
@@ -0,0 +1,352 @@
+//! `ExecutionContext` — the variables produced by an execution
+//!
+//! A map from variable names to values, keyed by interned names (`Symbol`) so
+//! that the executor stores and looks up variables without hashing strings.  It
+//! offers the familiar map methods (`get()`, `contains_key()`, `insert()`,
+//! `iter()`, indexing, ...) taking names as `&str`.  Values are shared with the
+//! nodes that produced and read them, never copied.  For `GraphData` contexts it
+//! adds typed accessors that replace the usual
+//! `context.get("x").and_then(|d| d.as_int()).unwrap_or(0)` chains:
+//!
+//! ```ignore
+//! let context = dag.execute(false, None);
+//! let total = context.get_int("total").unwrap_or(0);
+//! let gain: f64 = context.require("gain")?;   // Err names the key and the actual type
+//! ```
+
+use crate::graph_data::{GraphData, GraphDataKind};
+use crate::symbol::{Symbol, SymbolMap};
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Index;
+use std::sync::Arc;
+
+/// Execution context for storing variable values during graph execution
+///
+/// A context may be a copy-on-write view of another (see `view()`): reads fall
+/// through to the parent, writes stay in the view.
+#[derive(Clone)]
+pub struct ExecutionContext<T = GraphData> {
+    /// Variables set in this context
+    values: SymbolMap<Arc<T>>,
+    /// Context this one is a view of
+    parent: Option<Arc<ExecutionContext<T>>>,
+}
+
+impl<T> ExecutionContext<T> {
+    /// Create an empty context
+    pub fn new() -> Self {
+        Self {
+            values: SymbolMap::default(),
+            parent: None,
+        }
+    }
+
+    /// A copy-on-write view of `parent`
+    ///
+    /// Creating the view copies nothing and reading through it allocates nothing;
+    /// variables set on the view shadow the parent's without changing it.  The
+    /// parallel executor gives every concurrently running chain of nodes its own
+    /// view of the context as of the start of the level, so workers never share a
+    /// map they write to; their outputs are merged back once the level has run.
+    pub fn view(parent: Arc<ExecutionContext<T>>) -> Self {
+        Self {
+            values: SymbolMap::default(),
+            parent: Some(parent),
+        }
+    }
+
+    /// The value of `key`
+    pub fn get(&self, key: &str) -> Option<&Arc<T>> {
+        Symbol::lookup(key).and_then(|symbol| self.get_symbol(symbol))
+    }
+
+    /// The value of an interned `key`, without going through the symbol table
+    pub fn get_symbol(&self, key: Symbol) -> Option<&Arc<T>> {
+        self.values
+            .get(&key)
+            .or_else(|| self.parent.as_ref().and_then(|parent| parent.get_symbol(key)))
+    }
+
+    /// Whether `key` is set
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Set `key`, returning its previous value
+    pub fn insert(&mut self, key: impl Into<Symbol>, value: Arc<T>) -> Option<Arc<T>> {
+        let key = key.into();
+        match self.values.insert(key, value) {
+            Some(previous) => Some(previous),
+            None => self.parent.as_ref().and_then(|parent| parent.get_symbol(key).cloned()),
+        }
+    }
+
+    /// Remove `key`, returning its value
+    pub fn remove(&mut self, key: &str) -> Option<Arc<T>> {
+        self.detach();
+        Symbol::lookup(key).and_then(|symbol| self.values.remove(&symbol))
+    }
+
+    /// Keep only the variables for which `keep` returns true
+    pub fn retain(&mut self, mut keep: impl FnMut(Symbol, &Arc<T>) -> bool) {
+        self.detach();
+        self.values.retain(|&key, value| keep(key, value))
+    }
+
+    /// Number of variables
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Whether no variable is set
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Names of the variables, in arbitrary order
+    pub fn keys(&self) -> impl Iterator<Item = Symbol> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Values of the variables, in arbitrary order
+    pub fn values(&self) -> impl Iterator<Item = &Arc<T>> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    /// Variables and their values, in arbitrary order
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (Symbol, &Arc<T>)> + '_> {
+        let own = self.values.iter().map(|(&key, value)| (key, value));
+        match &self.parent {
+            None => Box::new(own),
+            Some(parent) => Box::new(
+                own.chain(
+                    parent
+                        .iter()
+                        .filter(|(key, _)| !self.values.contains_key(key)),
+                ),
+            ),
+        }
+    }
+
+    /// The variables as a map keyed by name
+    pub fn into_inner(self) -> HashMap<String, Arc<T>> {
+        self.into_iter().map(|(key, value)| (key.into(), value)).collect()
+    }
+
+    /// Copy the parent's variables into this context and stop being a view
+    fn detach(&mut self) {
+        if let Some(parent) = self.parent.take() {
+            for (key, value) in parent.iter() {
+                self.values.entry(key).or_insert_with(|| Arc::clone(value));
+            }
+        }
+    }
+}
+
+impl<T> Default for ExecutionContext<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ExecutionContext<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Index<&str> for ExecutionContext<T> {
+    type Output = T;
+
+    fn index(&self, key: &str) -> &T {
+        match self.get(key) {
+            Some(value) => value,
+            None => panic!("variable '{}' is not in the execution context", key),
+        }
+    }
+}
+
+impl<T> From<HashMap<String, Arc<T>>> for ExecutionContext<T> {
+    fn from(map: HashMap<String, Arc<T>>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<T> From<ExecutionContext<T>> for HashMap<String, Arc<T>> {
+    fn from(context: ExecutionContext<T>) -> Self {
+        context.into_inner()
+    }
+}
+
+impl<T, K: Into<Symbol>> FromIterator<(K, Arc<T>)> for ExecutionContext<T> {
+    fn from_iter<I: IntoIterator<Item = (K, Arc<T>)>>(iter: I) -> Self {
+        let mut context = Self::new();
+        context.extend(iter);
+        context
+    }
+}
+
+impl<T, K: Into<Symbol>> Extend<(K, Arc<T>)> for ExecutionContext<T> {
+    fn extend<I: IntoIterator<Item = (K, Arc<T>)>>(&mut self, iter: I) {
+        self.values
+            .extend(iter.into_iter().map(|(key, value)| (key.into(), value)))
+    }
+}
+
+impl<T> IntoIterator for ExecutionContext<T> {
+    type Item = (Symbol, Arc<T>);
+    type IntoIter = std::collections::hash_map::IntoIter<Symbol, Arc<T>>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.detach();
+        self.values.into_iter()
+    }
+}
+
+impl ExecutionContext {
+    /// `key` as an `f64` (from a Float, an Int, or a numeric String)
+    pub fn get_f64(&self, key: &str) -> Option<f64> {
+        self.require(key).ok()
+    }
+
+    /// `key` as an `i64` (from an Int or an integer String)
+    pub fn get_int(&self, key: &str) -> Option<i64> {
+        self.require(key).ok()
+    }
+
+    /// `key` as a `bool` (from a Bool or a "true"/"false" String)
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.require(key).ok()
+    }
+
+    /// `key` as a string slice
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(|value| value.as_string())
+    }
+
+    /// `key` converted to `V`, or an error naming the key and what was found
+    pub fn require<V: FromGraphData>(&self, key: &str) -> Result<V, ContextError> {
+        let value = self.get(key).ok_or_else(|| ContextError::Missing {
+            key: key.to_string(),
+        })?;
+        V::from_graph_data(value).ok_or_else(|| ContextError::WrongType {
+            key: key.to_string(),
+            expected: std::any::type_name::<V>(),
+            found: value.kind(),
+        })
+    }
+}
+
+/// Conversion from a `GraphData` value, used by `ExecutionContext::require()`.
+pub trait FromGraphData: Sized {
+    /// `None` if `value` cannot be represented as `Self`
+    fn from_graph_data(value: &GraphData) -> Option<Self>;
+}
+
+impl FromGraphData for GraphData {
+    fn from_graph_data(value: &GraphData) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+impl FromGraphData for f64 {
+    fn from_graph_data(value: &GraphData) -> Option<Self> {
+        match value.force() {
+            GraphData::Float(v) => Some(*v),
+            GraphData::Int(v) => Some(*v as f64),
+            GraphData::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromGraphData for i64 {
+    fn from_graph_data(value: &GraphData) -> Option<Self> {
+        match value.force() {
+            GraphData::Int(v) => Some(*v),
+            GraphData::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromGraphData for i32 {
+    fn from_graph_data(value: &GraphData) -> Option<Self> {
+        i64::from_graph_data(value).and_then(|v| v.try_into().ok())
+    }
+}
+
+impl FromGraphData for usize {
+    fn from_graph_data(value: &GraphData) -> Option<Self> {
+        i64::from_graph_data(value).and_then(|v| v.try_into().ok())
+    }
+}
+
+impl FromGraphData for bool {
+    fn from_graph_data(value: &GraphData) -> Option<Self> {
+        match value.force() {
+            GraphData::Bool(v) => Some(*v),
+            GraphData::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl FromGraphData for String {
+    fn from_graph_data(value: &GraphData) -> Option<Self> {
+        value.as_string().map(str::to_string)
+    }
+}
+
+impl FromGraphData for Vec<f64> {
+    fn from_graph_data(value: &GraphData) -> Option<Self> {
+        value.as_float_slice().map(<[f64]>::to_vec)
+    }
+}
+
+impl FromGraphData for Vec<i64> {
+    fn from_graph_data(value: &GraphData) -> Option<Self> {
+        value.as_int_slice().map(<[i64]>::to_vec)
+    }
+}
+
+/// Why `ExecutionContext::require()` could not produce a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContextError {
+    /// No variable with this name is in the context.
+    Missing {
+        /// Requested variable
+        key: String,
+    },
+    /// The variable exists but cannot be converted to the requested type.
+    WrongType {
+        /// Requested variable
+        key: String,
+        /// Name of the requested Rust type
+        expected: &'static str,
+        /// Variant actually stored
+        found: GraphDataKind,
+    },
+}
+
+impl fmt::Display for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContextError::Missing { key } => {
+                write!(f, "variable '{}' is not in the execution context", key)
+            }
+            ContextError::WrongType {
+                key,
+                expected,
+                found,
+            } => write!(
+                f,
+                "variable '{}' holds {:?}, which cannot be read as {}",
+                key, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ContextError {}
@@ -1,14 +1,32 @@
 //! DAG representation with execution and visualization support
 
+use crate::conflict::Producers;
+use crate::context::ExecutionContext;
 use crate::distribution::{DistContext, Distribution};
 use crate::graph_data::GraphData;
-use crate::node::{Node, NodeId};
+use crate::log_capture;
+use crate::middleware::Middleware;
+use crate::node::{Node, NodeFailure, NodeId};
+use crate::payload::{ContentHasher, Payload};
+use crate::plan::{self, NodePlan, Stage};
+use crate::plan_cache::{self, ExecutionPlan};
+use crate::optimizer::PassSummary;
+#[cfg(feature = "python")]
+use crate::progress::AroundNode;
+use crate::progress::{CancelToken, NodeProgress, RunControl};
+use crate::report::{self, ExecutionReport, NodeTiming, VariantRun};
 use crate::stat_result::StatResult;
-use std::collections::{HashMap, HashSet, VecDeque};
-use std::sync::{Arc, Mutex};
+#[cfg(feature = "signals")]
+use crate::storage::StorageBackend;
+use crate::streaming::{self, FinalOutputs};
+use crate::symbol::Symbol;
+use crate::validation::{self, BuildWarning, ExecutionError, InputError};
+use crate::workers::Workers;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
 
-/// Execution context for storing variable values during graph execution
-pub type ExecutionContext = HashMap<String, GraphData>;
 
 // ─── PredictTarget ────────────────────────────────────────────────────────────
 
@@ -32,54 +50,104 @@ pub enum PredictTarget {
     VariantIndex(usize),
 }
 
+/// What running one node produced: outputs, `None` if skipped, or why it failed
+type NodeOutcome<T> = Result<Option<HashMap<String, Arc<T>>>, NodeFailure>;
+
+/// A node run by a parallel worker: its outcome, timing, and whether the run was
+/// cancelled before it started
+type WorkerSlot<T> = (NodeOutcome<T>, NodeTiming, bool);
+
 /// Execution result that tracks outputs per node and per branch
 #[derive(Debug, Clone)]
-pub struct ExecutionResult {
+pub struct ExecutionResult<T = GraphData> {
     /// Global execution context (all variables accessible by broadcast name)
-    pub context: ExecutionContext,
+    pub context: ExecutionContext<T>,
     /// Outputs per node (node_id -> HashMap of output variables)
-    pub node_outputs: HashMap<NodeId, HashMap<String, GraphData>>,
+    pub node_outputs: HashMap<NodeId, HashMap<String, Arc<T>>>,
     /// Outputs per branch (branch_id -> HashMap of output variables)
-    pub branch_outputs: HashMap<usize, HashMap<String, GraphData>>,
+    pub branch_outputs: HashMap<usize, HashMap<String, Arc<T>>>,
+    /// Hierarchical ID of each branch in `branch_outputs` (see `Node::branch_path`)
+    pub branch_paths: HashMap<usize, Vec<usize>>,
+    /// Nodes that did not run because of a guard (see `Graph::when()`) or an error
+    pub skipped_nodes: HashSet<NodeId>,
+    /// Nodes that could not run, in execution order (see `Dag::try_execute()`)
+    pub errors: Vec<ExecutionError>,
+    /// Whether a `CancelToken` stopped the run; nodes that had not started are in
+    /// `skipped_nodes` (see `Dag::execute_with_progress()`)
+    pub cancelled: bool,
 }
 
-impl ExecutionResult {
+impl<T> ExecutionResult<T> {
     /// Create a new empty execution result
     pub fn new() -> Self {
         Self {
-            context: HashMap::new(),
+            context: ExecutionContext::new(),
             node_outputs: HashMap::new(),
             branch_outputs: HashMap::new(),
+            branch_paths: HashMap::new(),
+            skipped_nodes: HashSet::new(),
+            errors: Vec::new(),
+            cancelled: false,
         }
     }
 
     /// Get a value from the global context
-    pub fn get(&self, key: &str) -> Option<&GraphData> {
-        self.context.get(key)
+    pub fn get(&self, key: &str) -> Option<&T> {
+        self.context.get(key).map(Arc::as_ref)
     }
 
-    /// Get all outputs from a specific node
-    pub fn get_node_outputs(&self, node_id: NodeId) -> Option<&HashMap<String, GraphData>> {
-        self.node_outputs.get(&node_id)
+    /// Get all outputs from a specific node (by ID or `NodeHandle`)
+    pub fn get_node_outputs(&self, node: impl Into<NodeId>) -> Option<&HashMap<String, Arc<T>>> {
+        self.node_outputs.get(&node.into())
     }
 
     /// Get all outputs from a specific branch
-    pub fn get_branch_outputs(&self, branch_id: usize) -> Option<&HashMap<String, GraphData>> {
+    pub fn get_branch_outputs(&self, branch_id: usize) -> Option<&HashMap<String, Arc<T>>> {
         self.branch_outputs.get(&branch_id)
     }
 
-    /// Get a specific variable from a node
-    pub fn get_from_node(&self, node_id: NodeId, key: &str) -> Option<&GraphData> {
+    /// Get a specific variable from a node (by ID or `NodeHandle`)
+    pub fn get_from_node(&self, node: impl Into<NodeId>, key: &str) -> Option<&T> {
         self.node_outputs
-            .get(&node_id)
+            .get(&node.into())
             .and_then(|outputs| outputs.get(key))
+            .map(Arc::as_ref)
     }
 
     /// Get a specific variable from a branch
-    pub fn get_from_branch(&self, branch_id: usize, key: &str) -> Option<&GraphData> {
+    pub fn get_from_branch(&self, branch_id: usize, key: &str) -> Option<&T> {
         self.branch_outputs
             .get(&branch_id)
             .and_then(|outputs| outputs.get(key))
+            .map(Arc::as_ref)
+    }
+
+    /// Get a specific variable from a branch by its hierarchical ID (`&[2, 1]` for
+    /// branch 1 nested in branch 2)
+    pub fn get_from_branch_path(&self, path: &[usize], key: &str) -> Option<&T> {
+        let (branch_id, _) = self.branch_paths.iter().find(|(_, p)| p.as_slice() == path)?;
+        self.get_from_branch(*branch_id, key)
+    }
+
+    /// Every variant's value of `broadcast_var`, keyed by variant path
+    ///
+    /// Variant nodes store their outputs under namespaced keys (`"result#0"`,
+    /// `"result#1"`, or `"result#1.0"` for chained sweeps) alongside the shared
+    /// `"result"`, which holds only the last writer's value.  Paths are sorted, so
+    /// a single sweep comes back in variant order.
+    pub fn variant_outputs(&self, broadcast_var: &str) -> Vec<(Vec<usize>, &T)> {
+        let prefix = format!("{}#", broadcast_var);
+        let mut values: Vec<(Vec<usize>, &T)> = self
+            .context
+            .iter()
+            .filter_map(|(key, value)| {
+                let path = key.strip_prefix(&prefix)?;
+                let path: Option<Vec<usize>> = path.split('.').map(|i| i.parse().ok()).collect();
+                Some((path?, value.as_ref()))
+            })
+            .collect();
+        values.sort_by(|a, b| a.0.cmp(&b.0));
+        values
     }
 
     /// Check if a variable exists in global context
@@ -88,70 +156,239 @@ impl ExecutionResult {
     }
 }
 
+impl<T> Default for ExecutionResult<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Directed Acyclic Graph representing the optimized execution plan
-pub struct Dag {
+///
+/// Executing never modifies the DAG: every `execute*()` method takes `&self` and
+/// keeps all state of a run in the context it returns.  `Dag` is `Send + Sync`,
+/// so one DAG (e.g. behind an `Arc`) can be executed from several threads at
+/// once, each run with its own inputs and context.
+pub struct Dag<T = GraphData> {
     /// All nodes in the DAG
-    nodes: Vec<Node>,
+    nodes: Vec<Node<T>>,
     /// Execution order (topologically sorted)
     execution_order: Vec<NodeId>,
     /// Levels for parallel execution (nodes at same level can run in parallel)
     execution_levels: Vec<Vec<NodeId>>,
+    /// Problems found while building (see `build_warnings()`)
+    build_warnings: Vec<BuildWarning>,
+    /// Context keys holding a list of the values of all producers of a variable
+    /// (`ConflictPolicy::Collect` and inputs of `Graph::collect()` nodes)
+    collected: BTreeMap<String, Producers>,
+    /// Passes applied by `Graph::build_optimized()` (see `optimization_log()`)
+    optimization_log: Vec<PassSummary>,
+    /// External parameters and their defaults (see `Graph::input()`)
+    declared_inputs: Vec<(String, T)>,
+    /// Result variables returned by `execute()` (see `Graph::output()`)
+    declared_outputs: Vec<String>,
+    /// Execution plan of each node, aligned with `nodes`
+    plans: Vec<NodePlan>,
+    /// Position of each node in `nodes`
+    positions: HashMap<NodeId, usize>,
+    /// Chains of nodes the parallel executor runs concurrently, stage by stage
+    stages: Vec<Stage>,
+    /// Threads the chains of a parallel stage run on (see `set_workers()`)
+    workers: Workers,
+    /// Hooks wrapping every node function, outermost first (see `add_middleware()`)
+    middleware: Vec<Arc<dyn Middleware<T>>>,
+    /// Structural hash, computed on first use (see `fingerprint()`)
+    fingerprint: OnceLock<u64>,
 }
 
-impl Dag {
+// Concurrent runs share the DAG between threads
+const _: fn() = || {
+    fn shared_between_threads<D: Send + Sync>() {}
+    shared_between_threads::<Dag<GraphData>>();
+};
+
+impl<T: Payload> Dag<T> {
     /// Create a new DAG from a list of nodes
     ///
     /// Performs implicit inspection:
     /// - Validates the graph is acyclic
     /// - Determines optimal execution order
     /// - Identifies parallelizable operations
-    pub fn new(nodes: Vec<Node>) -> Self {
-        let execution_order = Self::topological_sort(&nodes);
-        let execution_levels = Self::compute_execution_levels(&nodes, &execution_order);
+    /// - Detects inputs that no node produces (see `build_warnings()`)
+    pub fn new(nodes: Vec<Node<T>>) -> Self {
+        Self::with_plan(nodes, None)
+    }
+
+    /// Create a DAG, taking its execution order and levels from `cached` if it was
+    /// computed for the same topology (see `Graph::build_with_plan()`)
+    pub(crate) fn with_plan(nodes: Vec<Node<T>>, cached: Option<&ExecutionPlan>) -> Self {
+        let positions: HashMap<NodeId, usize> =
+            nodes.iter().enumerate().map(|(i, node)| (node.id, i)).collect();
+        let (execution_order, execution_levels) =
+            match cached.filter(|plan| plan.topology_hash == plan_cache::topology_hash(&nodes)) {
+                Some(plan) => (plan.execution_order.clone(), plan.execution_levels.clone()),
+                None => {
+                    let order = Self::topological_sort(&nodes, &positions);
+                    let levels = Self::compute_execution_levels(&nodes, &positions, &order);
+                    (order, levels)
+                }
+            };
+        let stages = plan::parallel_stages(&nodes, &execution_order);
+        let build_warnings = validation::unsatisfied_inputs(&nodes);
+        let plans = nodes
+            .iter()
+            .map(|node| {
+                let deps: Vec<&Node<T>> = node
+                    .dependencies
+                    .iter()
+                    .filter_map(|id| positions.get(id).map(|&i| &nodes[i]))
+                    .collect();
+                NodePlan::new(node, &deps)
+            })
+            .collect();
 
         Self {
             nodes,
             execution_order,
             execution_levels,
+            build_warnings,
+            collected: BTreeMap::new(),
+            optimization_log: Vec::new(),
+            declared_inputs: Vec::new(),
+            declared_outputs: Vec::new(),
+            plans,
+            positions,
+            stages,
+            workers: Workers::default(),
+            middleware: Vec::new(),
+            fingerprint: OnceLock::new(),
         }
     }
 
-    /// Perform topological sort to determine execution order
-    fn topological_sort(nodes: &[Node]) -> Vec<NodeId> {
-        let mut in_degree: HashMap<NodeId, usize> = HashMap::new();
-        let mut adj_list: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+    /// Copy of this DAG containing only the nodes for which `keep` returns true
+    ///
+    /// Dependencies on removed nodes are dropped; execution order, levels, and
+    /// build warnings are recomputed.
+    pub(crate) fn retain_nodes(&self, keep: impl Fn(NodeId) -> bool) -> Dag<T> {
+        let nodes: Vec<Node<T>> = self
+            .nodes
+            .iter()
+            .filter(|n| keep(n.id))
+            .cloned()
+            .map(|mut n| {
+                n.dependencies.retain(|&dep| keep(dep));
+                n
+            })
+            .collect();
+        let mut dag = Dag::new(nodes);
+        dag.add_collected(
+            self.collected
+                .iter()
+                .map(|(key, gathered)| {
+                    let mut gathered = gathered.clone();
+                    gathered.producers.retain(|&id| keep(id));
+                    (key.clone(), gathered)
+                })
+                .collect(),
+        );
+        dag.set_interface(self.declared_inputs.clone(), self.declared_outputs.clone());
+        dag.workers = self.workers.clone();
+        dag.middleware = self.middleware.clone();
+        dag
+    }
+
+    /// Run the chains of parallel stages on `workers` instead of scoped threads
+    ///
+    /// `max_threads` still caps how many chains of a stage are handed to the
+    /// workers at once.
+    pub fn set_workers(&mut self, workers: Workers) {
+        self.workers = workers;
+    }
+
+    /// Threads the chains of parallel stages run on
+    pub fn workers(&self) -> &Workers {
+        &self.workers
+    }
+
+    /// Wrap every node function of later executions in `middleware`
+    ///
+    /// Middleware added first is the outermost layer: its `before()` runs first
+    /// and its `after()` last.
+    pub fn add_middleware(&mut self, middleware: impl Middleware<T> + 'static) {
+        self.middleware.push(Arc::new(middleware));
+    }
 
-        // Initialize in-degree and adjacency list
-        for node in nodes {
-            in_degree.entry(node.id).or_insert(0);
-            adj_list.entry(node.id).or_default();
+    pub(crate) fn middleware(&self) -> &[Arc<dyn Middleware<T>>] {
+        &self.middleware
+    }
 
-            for &dep in &node.dependencies {
-                *in_degree.entry(node.id).or_insert(0) += 1;
-                adj_list.entry(dep).or_default().push(node.id);
+    /// Record the declared inputs and outputs of the graph
+    ///
+    /// Declared inputs count as produced, so reading them is not reported as an
+    /// unsatisfied input.
+    pub(crate) fn set_interface(&mut self, inputs: Vec<(String, T)>, outputs: Vec<String>) {
+        self.build_warnings.retain(|warning| match warning {
+            BuildWarning::UnsatisfiedInput { broadcast_var, .. } => {
+                !inputs.iter().any(|(name, _)| name == broadcast_var)
             }
-        }
+        });
+        self.declared_inputs = inputs;
+        self.declared_outputs = outputs;
+        self.fingerprint = OnceLock::new();
+    }
 
-        // Kahn's algorithm for topological sort
-        let mut queue: VecDeque<NodeId> = in_degree
-            .iter()
-            .filter(|(_, &degree)| degree == 0)
-            .map(|(&id, _)| id)
-            .collect();
+    pub(crate) fn set_optimization_log(&mut self, log: Vec<PassSummary>) {
+        self.optimization_log = log;
+    }
 
-        let mut result = Vec::new();
+    /// Gather the values of all producers into lists under the given context keys
+    pub(crate) fn add_collected(&mut self, collected: BTreeMap<String, Producers>) {
+        self.collected.extend(collected);
+        for plan in &mut self.plans {
+            plan.collects_into.clear();
+        }
+        for (key, gathered) in &self.collected {
+            let key = Symbol::from(key);
+            for id in &gathered.producers {
+                let Some(&i) = self.positions.get(id) else { continue };
+                let collects_into = &mut self.plans[i].collects_into;
+                if collects_into.last() != Some(&key) {
+                    collects_into.push(key);
+                }
+            }
+        }
+    }
 
-        while let Some(node_id) = queue.pop_front() {
-            result.push(node_id);
+    /// The node with the given ID and its execution plan
+    pub(crate) fn planned(&self, id: NodeId) -> Option<(&Node<T>, &NodePlan)> {
+        self.positions.get(&id).map(|&i| (&self.nodes[i], &self.plans[i]))
+    }
 
-            if let Some(neighbors) = adj_list.get(&node_id) {
-                for &neighbor in neighbors {
-                    if let Some(degree) = in_degree.get_mut(&neighbor) {
-                        *degree -= 1;
-                        if *degree == 0 {
-                            queue.push_back(neighbor);
-                        }
-                    }
+    /// Perform topological sort to determine execution order
+    ///
+    /// Kahn's algorithm over positions in `nodes`, linear in the number of nodes
+    /// and dependencies.  Ready nodes are taken in the order they were added, so
+    /// the order is deterministic.
+    fn topological_sort(nodes: &[Node<T>], positions: &HashMap<NodeId, usize>) -> Vec<NodeId> {
+        let mut in_degree: Vec<usize> = nodes.iter().map(|node| node.dependencies.len()).collect();
+        let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+        for (i, node) in nodes.iter().enumerate() {
+            for dep in &node.dependencies {
+                if let Some(&d) = positions.get(dep) {
+                    dependents[d].push(i);
+                }
+            }
+        }
+
+        // Kahn's algorithm for topological sort
+        let mut queue: VecDeque<usize> = (0..nodes.len()).filter(|&i| in_degree[i] == 0).collect();
+        let mut result = Vec::with_capacity(nodes.len());
+
+        while let Some(i) = queue.pop_front() {
+            result.push(nodes[i].id);
+            for &dependent in &dependents[i] {
+                in_degree[dependent] -= 1;
+                if in_degree[dependent] == 0 {
+                    queue.push_back(dependent);
                 }
             }
         }
@@ -163,30 +400,30 @@ impl Dag {
     ///
     /// Nodes at the same level have no dependencies on each other and can
     /// execute in parallel.
-    fn compute_execution_levels(nodes: &[Node], execution_order: &[NodeId]) -> Vec<Vec<NodeId>> {
+    fn compute_execution_levels(
+        nodes: &[Node<T>],
+        positions: &HashMap<NodeId, usize>,
+        execution_order: &[NodeId],
+    ) -> Vec<Vec<NodeId>> {
         let mut levels: Vec<Vec<NodeId>> = Vec::new();
-        let mut node_level: HashMap<NodeId, usize> = HashMap::new();
+        let mut node_level: Vec<Option<usize>> = vec![None; nodes.len()];
 
         for &node_id in execution_order {
-            let node = nodes.iter().find(|n| n.id == node_id).unwrap();
+            let i = positions[&node_id];
 
-            // Find the maximum level of all dependencies
-            let level = if node.dependencies.is_empty() {
-                0
-            } else {
-                node.dependencies
-                    .iter()
-                    .filter_map(|dep_id| node_level.get(dep_id))
-                    .max()
-                    .map(|&max_level| max_level + 1)
-                    .unwrap_or(0)
-            };
+            // One level past the deepest dependency
+            let level = nodes[i]
+                .dependencies
+                .iter()
+                .filter_map(|dep_id| node_level[*positions.get(dep_id)?])
+                .max()
+                .map_or(0, |max_level| max_level + 1);
 
-            node_level.insert(node_id, level);
+            node_level[i] = Some(level);
 
             // Add node to its level
-            while levels.len() <= level {
-                levels.push(Vec::new());
+            if levels.len() <= level {
+                levels.resize_with(level + 1, Vec::new);
             }
             levels[level].push(node_id);
         }
@@ -197,12 +434,117 @@ impl Dag {
     /// Execute the DAG (legacy method returning just context)
     ///
     /// Runs all nodes in topological order, accumulating outputs in the execution context.
+    /// If the graph declares outputs (`Graph::output()`), only those are returned.
     ///
     /// # Arguments
     /// * `parallel` - If true, execute nodes at the same level concurrently
     /// * `max_threads` - Optional maximum number of threads to use per level (None = unlimited)
-    pub fn execute(&self, parallel: bool, max_threads: Option<usize>) -> ExecutionContext {
-        self.execute_detailed(parallel, max_threads).context
+    pub fn execute(&self, parallel: bool, max_threads: Option<usize>) -> ExecutionContext<T> {
+        self.execute_with_inputs(HashMap::new(), parallel, max_threads)
+    }
+
+    /// Execute the DAG starting from a context that already holds `inputs`
+    ///
+    /// Nodes read the given variables exactly as if an upstream node had produced
+    /// them, so a graph whose sources are missing can be fed from outside.  Declared
+    /// inputs that are not given take their defaults, and if the graph declares
+    /// outputs, only those are returned.
+    pub fn execute_with_inputs(
+        &self,
+        inputs: HashMap<String, T>,
+        parallel: bool,
+        max_threads: Option<usize>,
+    ) -> ExecutionContext<T> {
+        let inputs = inputs.into_iter().map(|(name, value)| (name, Arc::new(value))).collect();
+        self.execute_with_shared_inputs(inputs, parallel, max_threads)
+    }
+
+    /// Like `execute_with_inputs()`, but with values that are already shared
+    ///
+    /// The inputs are handed to nodes without being copied, e.g. when a node runs
+    /// a subgraph on its own inputs or the context of one DAG feeds another.
+    pub fn execute_with_shared_inputs(
+        &self,
+        inputs: HashMap<String, Arc<T>>,
+        parallel: bool,
+        max_threads: Option<usize>,
+    ) -> ExecutionContext<T> {
+        let mut context = self.run(parallel, max_threads, None, None, inputs).result.context;
+        if !self.declared_outputs.is_empty() {
+            context.retain(|key, _| self.declared_outputs.iter().any(|name| key == *name));
+        }
+        context
+    }
+
+    /// Run `execute_with_inputs()` on the blocking pool of an existing tokio runtime
+    ///
+    /// Async code awaits the returned handle instead of blocking its executor;
+    /// parallel stages run on this DAG's workers (see `set_workers()`).
+    #[cfg(feature = "tokio")]
+    pub fn spawn_on(
+        self: &Arc<Self>,
+        handle: &tokio::runtime::Handle,
+        inputs: HashMap<String, T>,
+        parallel: bool,
+        max_threads: Option<usize>,
+    ) -> tokio::task::JoinHandle<ExecutionContext<T>> {
+        let dag = Arc::clone(self);
+        handle.spawn_blocking(move || dag.execute_with_inputs(inputs, parallel, max_threads))
+    }
+
+    /// Execute the DAG, failing if a node could not run
+    ///
+    /// A node whose required input (see `Graph::require_input()`) is missing is not
+    /// called, and one whose port value a validator rejects (see `Graph::validate()`)
+    /// does not produce outputs; the other executors skip it and its dependents and
+    /// record the problem in `ExecutionResult::errors`, while this returns the first
+    /// such error.
+    ///
+    /// ```ignore
+    /// match dag.try_execute(false, None) {
+    ///     Ok(context) => println!("{:?}", context.get("result")),
+    ///     Err(e) => eprintln!("{}", e),   // names the node, the variable, and its producers
+    /// }
+    /// ```
+    pub fn try_execute(
+        &self,
+        parallel: bool,
+        max_threads: Option<usize>,
+    ) -> Result<ExecutionContext<T>, ExecutionError> {
+        let mut result = self.execute_detailed(parallel, max_threads);
+        if !result.errors.is_empty() {
+            return Err(result.errors.remove(0));
+        }
+        if !self.declared_outputs.is_empty() {
+            result.context.retain(|key, _| self.declared_outputs.iter().any(|name| key == *name));
+        }
+        Ok(result.context)
+    }
+
+    /// Like `execute_with_inputs()`, but reject inputs the graph does not declare
+    ///
+    /// Catches misspelt parameter names, which would otherwise be silently ignored
+    /// while the default is used.  Graphs without declared inputs accept anything.
+    pub fn try_execute_with_inputs(
+        &self,
+        inputs: HashMap<String, T>,
+        parallel: bool,
+        max_threads: Option<usize>,
+    ) -> Result<ExecutionContext<T>, InputError> {
+        if !self.declared_inputs.is_empty() {
+            let mut undeclared: Vec<&String> = inputs
+                .keys()
+                .filter(|key| !self.declared_inputs.iter().any(|(name, _)| name == *key))
+                .collect();
+            undeclared.sort();
+            if let Some(name) = undeclared.first() {
+                return Err(InputError::Undeclared {
+                    name: name.to_string(),
+                    declared: self.declared_inputs.iter().map(|(n, _)| n.clone()).collect(),
+                });
+            }
+        }
+        Ok(self.execute_with_inputs(inputs, parallel, max_threads))
     }
 
     /// Execute the DAG with detailed per-node and per-branch tracking
@@ -212,133 +554,495 @@ impl Dag {
     /// # Arguments
     /// * `parallel` - If true, execute nodes at the same level concurrently
     /// * `max_threads` - Optional maximum number of threads to use per level (None = unlimited)
-    pub fn execute_detailed(&self, parallel: bool, max_threads: Option<usize>) -> ExecutionResult {
+    pub fn execute_detailed(&self, parallel: bool, max_threads: Option<usize>) -> ExecutionResult<T> {
+        self.execute_timed(parallel, max_threads).result
+    }
+
+    /// Execute the DAG and record when each node started and finished
+    ///
+    /// Returns the same `ExecutionResult` as `execute_detailed()`, wrapped in an
+    /// `ExecutionReport` that also holds a `NodeTiming` per node.  Use
+    /// `ExecutionReport::to_mermaid_gantt()` to visualise the run.
+    ///
+    /// # Arguments
+    /// * `parallel` - If true, execute nodes at the same level concurrently
+    /// * `max_threads` - Optional maximum number of threads to use per level (None = unlimited)
+    pub fn execute_timed(&self, parallel: bool, max_threads: Option<usize>) -> ExecutionReport<T> {
+        self.run(parallel, max_threads, None, None, HashMap::new())
+    }
+
+    /// Execute the DAG and hash the outputs of every node
+    ///
+    /// Same as `execute_timed()`, but also fills `ExecutionReport::output_hashes`
+    /// with a hash of each node's outputs (see `Payload::content_hash()`).  Hashes
+    /// are stable across runs, so comparing two reports with
+    /// `ExecutionReport::differing_outputs()` shows which nodes produced something
+    /// different.  Nodes with an output that cannot be hashed get no entry.
+    ///
+    /// # Arguments
+    /// * `parallel` - If true, execute nodes at the same level concurrently
+    /// * `max_threads` - Optional maximum number of threads to use per level (None = unlimited)
+    pub fn execute_hashed(&self, parallel: bool, max_threads: Option<usize>) -> ExecutionReport<T> {
+        let mut report = self.execute_timed(parallel, max_threads);
+        report.output_hashes = report
+            .result
+            .node_outputs
+            .iter()
+            .filter_map(|(&id, outputs)| Some((id, report::outputs_hash(outputs)?)))
+            .collect();
+        report
+    }
+
+    /// Execute the DAG, calling `on_final` as soon as each output variable is final
+    ///
+    /// A variable is final once every node that declares it as an output has run.
+    /// The callback receives the context key (branch outputs use the
+    /// `__branch_{id}__{var}` form) and the value, so consumers can start using early
+    /// results while slower parts of the DAG are still running.  In parallel mode the
+    /// callback is invoked from worker threads as soon as the writing node finishes;
+    /// variables written by several nodes of the same level are reported once the
+    /// level is complete.  Variables that were declared but never produced are not
+    /// reported.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let result = dag.execute_streaming(true, None, |key, value| {
+    ///     println!("{} is ready: {}", key, value.summary());
+    /// });
+    /// ```
+    pub fn execute_streaming<F>(
+        &self,
+        parallel: bool,
+        max_threads: Option<usize>,
+        on_final: F,
+    ) -> ExecutionResult<T>
+    where
+        F: Fn(&str, &T) + Sync,
+    {
+        let tracker = FinalOutputs::new(&self.nodes, &on_final);
+        self.run(parallel, max_threads, Some(&tracker), None, HashMap::new()).result
+    }
+
+    /// Execute the DAG, reporting each finished node and stopping early on request
+    ///
+    /// `on_node_complete` is called once per node that was started, with the number
+    /// of nodes finished so far, e.g. to drive a progress bar; in parallel mode it is
+    /// invoked from worker threads.  Once `cancel` is cancelled no further nodes are
+    /// started: nodes already running finish, the rest are added to
+    /// `skipped_nodes`, and `ExecutionResult::cancelled` is set.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let cancel = CancelToken::new();
+    /// let result = dag.execute_with_progress(true, None, Some(&cancel), |p| {
+    ///     println!("[{}/{}] {}", p.completed, p.total, p.label);
+    /// });
+    /// ```
+    pub fn execute_with_progress<F>(
+        &self,
+        parallel: bool,
+        max_threads: Option<usize>,
+        cancel: Option<&CancelToken>,
+        on_node_complete: F,
+    ) -> ExecutionResult<T>
+    where
+        F: Fn(&NodeProgress) + Sync,
+    {
+        let control = RunControl::new(self.nodes.len(), cancel, &on_node_complete);
+        self.run(parallel, max_threads, None, Some(&control), HashMap::new()).result
+    }
+
+    /// `execute_with_progress()`, running each node inside `around_node` on the
+    /// thread that executes it (see `RunControl::around_node()`)
+    #[cfg(feature = "python")]
+    pub(crate) fn execute_around_nodes<F>(
+        &self,
+        parallel: bool,
+        max_threads: Option<usize>,
+        cancel: Option<&CancelToken>,
+        on_node_complete: F,
+        around_node: AroundNode,
+    ) -> ExecutionResult<T>
+    where
+        F: Fn(&NodeProgress) + Sync,
+    {
+        let control = RunControl::new(self.nodes.len(), cancel, &on_node_complete).around_node(around_node);
+        self.run(parallel, max_threads, None, Some(&control), HashMap::new()).result
+    }
+
+    /// Execute the DAG, stopping gracefully on Ctrl-C
+    ///
+    /// While this runs, SIGINT and SIGTERM do not kill the process: nodes that have
+    /// not started are skipped, running nodes finish, the run's manifest (see
+    /// `ExecutionReport::manifest()`) is stored under `checkpoint` in `storage`, and
+    /// the partial report is returned with `result.cancelled` set.  A second signal terminates
+    /// the process as usual.  Nothing is written when the run completes.
+    ///
+    /// ```ignore
+    /// let storage = FileStorage::new("runs");
+    /// let report = dag.execute_interruptible(true, None, &storage, "sweep.checkpoint.json")?;
+    /// if report.result.cancelled {
+    ///     eprintln!("interrupted after {} nodes", report.timings.len());
+    /// }
+    /// ```
+    #[cfg(feature = "signals")]
+    pub fn execute_interruptible(
+        &self,
+        parallel: bool,
+        max_threads: Option<usize>,
+        storage: &dyn StorageBackend,
+        checkpoint: &str,
+    ) -> std::io::Result<ExecutionReport<T>> {
+        let interrupt = crate::progress::InterruptGuard::new()?;
+        let control = RunControl::new(self.nodes.len(), Some(&interrupt.token), &|_| {});
+        let report = self.run(parallel, max_threads, None, Some(&control), HashMap::new());
+        if report.result.cancelled {
+            storage.put(checkpoint, report.manifest().as_bytes())?;
+        }
+        Ok(report)
+    }
+
+    /// Shared implementation of `execute_timed()`, `execute_streaming()`,
+    /// `execute_with_progress()` and `execute_interruptible()`
+    fn run(
+        &self,
+        parallel: bool,
+        max_threads: Option<usize>,
+        tracker: Option<&FinalOutputs<T>>,
+        control: Option<&RunControl>,
+        inputs: HashMap<String, Arc<T>>,
+    ) -> ExecutionReport<T> {
+        let run_start = Instant::now();
         let mut result = ExecutionResult::new();
+        result.context.extend(
+            self.declared_inputs
+                .iter()
+                .map(|(name, value)| (name.clone(), Arc::new(value.clone()))),
+        );
+        result.context.extend(inputs);
+        let run_inputs: BTreeMap<String, Arc<T>> =
+            result.context.iter().map(|(name, value)| (name.as_str().to_string(), value.clone())).collect();
+        let mut timings: Vec<NodeTiming> = Vec::new();
 
         if !parallel {
             // Sequential execution
             for &node_id in &self.execution_order {
-                if let Some(node) = self.nodes.iter().find(|n| n.id == node_id) {
-                    let outputs = node.execute(&result.context);
-
-                    // Store outputs in global context
-                    // For branch nodes, prefix keys with branch_id to avoid conflicts
-                    if let Some(branch_id) = node.branch_id {
-                        for (key, value) in &outputs {
-                            let prefixed_key = format!("__branch_{}__{}",  branch_id, key);
-                            result.context.insert(prefixed_key, value.clone());
-                        }
-                    } else {
-                        result.context.extend(outputs.clone());
-                    }
-
-                    // Store outputs per node (using broadcast variable names from output_mapping)
-                    result.node_outputs.insert(node_id, outputs.clone());
-
-                    // Store outputs per branch if this node belongs to a branch
-                    if let Some(branch_id) = node.branch_id {
-                        result
-                            .branch_outputs
-                            .entry(branch_id)
-                            .or_default()
-                            .extend(outputs);
-                    }
+                if let Some((node, plan)) = self.planned(node_id) {
+                    self.run_one(&mut result, &mut timings, node, plan, run_start, control);
+                    Self::emit_final(tracker, node, &result);
                 }
             }
         } else {
-            // Parallel execution
-            for level in &self.execution_levels {
-                // Execute nodes at the same level in parallel
-                if level.len() == 1 {
-                    // Single node - no need for threading overhead
-                    let node_id = level[0];
-                    if let Some(node) = self.nodes.iter().find(|n| n.id == node_id) {
-                        let outputs = node.execute(&result.context);
-
-                        // For branch nodes, prefix keys to avoid conflicts
-                        if let Some(branch_id) = node.branch_id {
-                            for (key, value) in &outputs {
-                                let prefixed_key = format!("__branch_{}__{}",  branch_id, key);
-                                result.context.insert(prefixed_key, value.clone());
-                            }
-                        } else {
-                            result.context.extend(outputs.clone());
-                        }
-                        
-                        result.node_outputs.insert(node_id, outputs.clone());
-
-                        if let Some(branch_id) = node.branch_id {
-                            result
-                                .branch_outputs
-                                .entry(branch_id)
-                                .or_default()
-                                .extend(outputs);
+            // Parallel execution, stage by stage (see `plan::parallel_stages()`)
+            for stage in &self.stages {
+                if let [chain] = stage.chains.as_slice() {
+                    // Single chain - no need for threading overhead
+                    for &node_id in chain {
+                        if let Some((node, plan)) = self.planned(node_id) {
+                            self.run_one(&mut result, &mut timings, node, plan, run_start, control);
+                            Self::emit_final(tracker, node, &result);
                         }
                     }
+                    continue;
+                }
+
+                // Several chains - each runs on a worker (see `set_workers()`), reading and writing
+                // its own copy-on-write view of the context as of the start of the
+                // stage.  Outcomes are merged into the context in execution order
+                // once the whole stage has finished, so results never depend on
+                // thread completion order.
+                let chains: Vec<Vec<(&Node<T>, &NodePlan)>> = stage
+                    .chains
+                    .iter()
+                    .map(|chain| chain.iter().filter_map(|&id| self.planned(id)).collect())
+                    .collect();
+
+                // Limit threads if max_threads is specified
+                let chunk_size = if let Some(max) = max_threads {
+                    max.max(1) // At least 1 thread
                 } else {
-                    // Multiple nodes - execute in parallel using scoped threads
-                    let context = Arc::new(result.context.clone());
-                    let nodes_to_execute: Vec<_> = level
+                    chains.len() // Unlimited - one thread per chain
+                };
+
+                // Variables with several writers in this stage are final only after the merge
+                let shared = match tracker {
+                    Some(_) => {
+                        let nodes: Vec<&Node<T>> = chains.iter().flatten().map(|&(node, _)| node).collect();
+                        streaming::shared_keys(&nodes)
+                    }
+                    None => HashSet::new(),
+                };
+                let shared = &shared;
+                let mut deferred: Vec<String> = Vec::new();
+
+                let base = Arc::new(std::mem::take(&mut result.context));
+                let skipped = &result.skipped_nodes;
+                let mut slots: Vec<Vec<Option<WorkerSlot<T>>>> = Vec::with_capacity(chains.len());
+
+                // Process chains in chunks to respect max_threads limit
+                for chunk in chains.chunks(chunk_size) {
+                    let jobs: Vec<_> = chunk
                         .iter()
-                        .filter_map(|&node_id| self.nodes.iter().find(|n| n.id == node_id))
+                        .enumerate()
+                        .map(|(worker, chain)| {
+                            let base = Arc::clone(&base);
+                            move || {
+                                let mut view = ExecutionContext::view(base);
+                                let mut chain_skipped: HashSet<NodeId> = HashSet::new();
+                                let mut chain_slots = Vec::with_capacity(chain.len());
+                                let mut later = Vec::new();
+                                for &(node, plan) in chain {
+                                    let start = run_start.elapsed();
+                                    let cancelled = control.is_some_and(RunControl::is_cancelled);
+                                    let (node_outputs, logs) = if cancelled {
+                                        (Ok(None), Vec::new())
+                                    } else {
+                                        let upstream_skipped = plan.skipped_by_dependencies(|id| {
+                                            skipped.contains(id) || chain_skipped.contains(id)
+                                        });
+                                        let middleware = &self.middleware;
+                                        RunControl::run_node(control, || {
+                                            log_capture::capture(|| {
+                                                node.execute_guarded(&plan.inputs, &view, upstream_skipped, middleware)
+                                            })
+                                        })
+                                    };
+                                    let end = run_start.elapsed();
+                                    if let Some(control) = control.filter(|_| !cancelled) {
+                                        control.finish(node, matches!(node_outputs, Ok(Some(_))));
+                                    }
+                                    match &node_outputs {
+                                        Ok(Some(outputs)) => plan.write_outputs(&mut view, outputs),
+                                        _ => {
+                                            chain_skipped.insert(node.id);
+                                        }
+                                    }
+                                    if let Some(tracker) = tracker {
+                                        for (var, key) in tracker.finish(node) {
+                                            if shared.contains(&key) {
+                                                later.push(key);
+                                            } else {
+                                                let value = node_outputs
+                                                    .as_ref()
+                                                    .ok()
+                                                    .and_then(Option::as_ref)
+                                                    .and_then(|outputs| outputs.get(&var))
+                                                    .or_else(|| view.get(&key));
+                                                tracker.emit(&key, value.map(Arc::as_ref));
+                                            }
+                                        }
+                                    }
+                                    let mut timing = NodeTiming::new(node, start, end, worker);
+                                    timing.logs = logs;
+                                    chain_slots.push(Some((node_outputs, timing, cancelled)));
+                                }
+                                (chain_slots, later)
+                            }
+                        })
                         .collect();
+                    for (chain_slots, later) in self.workers.run_all(jobs) {
+                        slots.push(chain_slots);
+                        deferred.extend(later);
+                    }
+                }
 
-                    // Limit threads if max_threads is specified
-                    let chunk_size = if let Some(max) = max_threads {
-                        max.max(1) // At least 1 thread
-                    } else {
-                        nodes_to_execute.len() // Unlimited - one thread per node
-                    };
+                // Every view has been dropped, so the context is no longer shared
+                result.context = Arc::try_unwrap(base).unwrap_or_else(|base| (*base).clone());
 
-                    let outputs = Arc::new(Mutex::new(Vec::new()));
-
-                    // Process nodes in chunks to respect max_threads limit
-                    for chunk in nodes_to_execute.chunks(chunk_size) {
-                        std::thread::scope(|s| {
-                            for node in chunk {
-                                let context = Arc::clone(&context);
-                                let outputs = Arc::clone(&outputs);
-
-                                s.spawn(move || {
-                                    let node_outputs = node.execute(&context);
-                                    outputs.lock().unwrap().push((
-                                        node.id,
-                                        node.branch_id,
-                                        node_outputs,
-                                    ));
-                                });
-                            }
-                        });
+                // Merge slots deterministically, in execution order
+                for &(chain, index) in &stage.merge_order {
+                    let (node, plan) = chains[chain][index];
+                    let Some((node_outputs, timing, cancelled)) = slots[chain][index].take() else {
+                        continue;
+                    };
+                    result.cancelled |= cancelled;
+                    if self.record_outcome(&mut result, node, plan, node_outputs) {
+                        timings.push(timing);
                     }
-
-                    // Collect outputs from all parallel executions
-                    let collected_outputs = outputs.lock().unwrap();
-                    for (node_id, branch_id, node_outputs) in collected_outputs.iter() {
-                        // For branch nodes, prefix keys to avoid conflicts
-                        if let Some(bid) = branch_id {
-                            for (key, value) in node_outputs {
-                                let prefixed_key = format!("__branch_{}__{}",  bid, key);
-                                result.context.insert(prefixed_key, value.clone());
-                            }
-                        } else {
-                            result.context.extend(node_outputs.clone());
-                        }
-                        
-                        result.node_outputs.insert(*node_id, node_outputs.clone());
-
-                        if let Some(bid) = branch_id {
-                            result
-                                .branch_outputs
-                                .entry(*bid)
-                                .or_default()
-                                .extend(node_outputs.clone());
-                        }
+                }
+                if let Some(tracker) = tracker {
+                    for key in deferred {
+                        tracker.emit(&key, result.context.get(&key).map(Arc::as_ref));
                     }
                 }
             }
         }
 
-        result
+        let total_duration = run_start.elapsed();
+        let variants = timings
+            .iter()
+            .filter_map(|t| self.planned(t.node_id))
+            .filter_map(|(node, _)| VariantRun::new(node))
+            .collect();
+        ExecutionReport {
+            result,
+            timings,
+            total_duration,
+            variants,
+            output_hashes: BTreeMap::new(),
+            graph_fingerprint: self.fingerprint(),
+            inputs: run_inputs,
+        }
+    }
+
+    /// Execute one node on the sequential paths, recording its timing and outputs
+    /// (or that it was skipped)
+    fn run_one(
+        &self,
+        result: &mut ExecutionResult<T>,
+        timings: &mut Vec<NodeTiming>,
+        node: &Node<T>,
+        plan: &NodePlan,
+        run_start: Instant,
+        control: Option<&RunControl>,
+    ) {
+        if control.is_some_and(RunControl::is_cancelled) {
+            result.cancelled = true;
+            result.skipped_nodes.insert(node.id);
+            return;
+        }
+        let start = run_start.elapsed();
+        let upstream_skipped = plan.skipped_by_dependencies(|id| result.skipped_nodes.contains(id));
+        let (outcome, logs) = RunControl::run_node(control, || {
+            log_capture::capture(|| {
+                node.execute_guarded(&plan.inputs, &result.context, upstream_skipped, &self.middleware)
+            })
+        });
+        let ran = self.record_outcome(result, node, plan, outcome);
+        if ran {
+            let mut timing = NodeTiming::new(node, start, run_start.elapsed(), 0);
+            timing.logs = logs;
+            timings.push(timing);
+        }
+        if let Some(control) = control {
+            control.finish(node, ran);
+        }
+    }
+
+    /// Store the outputs of a node that ran, or mark it skipped; returns whether it ran
+    ///
+    /// A node with a missing required input or a value rejected by a validator
+    /// counts as skipped, so that its dependents are skipped too, and the problem
+    /// is added to `result.errors`.
+    pub(crate) fn record_outcome(
+        &self,
+        result: &mut ExecutionResult<T>,
+        node: &Node<T>,
+        plan: &NodePlan,
+        outcome: NodeOutcome<T>,
+    ) -> bool {
+        match outcome {
+            Ok(Some(outputs)) => {
+                self.store_outputs(result, node, plan, outputs);
+                return true;
+            }
+            Ok(None) => {}
+            Err(NodeFailure::MissingInput(broadcast_var)) => {
+                let error = self.missing_input(node, broadcast_var);
+                result.errors.push(error);
+            }
+            Err(NodeFailure::Invalid { port, output, message }) => {
+                let (node_id, label) = (node.id, node.display_name());
+                result.errors.push(match output {
+                    false => ExecutionError::InvalidInput { node_id, label, port, message },
+                    true => ExecutionError::InvalidOutput { node_id, label, port, message },
+                });
+            }
+        }
+        result.skipped_nodes.insert(node.id);
+        false
+    }
+
+    /// Describe a missing required input, naming the nodes that produce it (or, if
+    /// none does, the producers of the closest variable name)
+    fn missing_input(&self, node: &Node<T>, broadcast_var: String) -> ExecutionError {
+        let read = broadcast_var
+            .split_once(':')
+            .map_or(broadcast_var.as_str(), |(_, var)| var);
+        let producers_of = |var: &str| -> Vec<(NodeId, String)> {
+            self.nodes
+                .iter()
+                .filter(|n| n.all_outputs().any(|(_, v)| v == var))
+                .map(|n| (n.id, n.display_name()))
+                .collect()
+        };
+        let producers = producers_of(read);
+        let suggestion = if producers.is_empty() {
+            let produced: HashSet<String> = self
+                .nodes
+                .iter()
+                .flat_map(|n| n.all_outputs().map(|(_, v)| v.clone()))
+                .collect();
+            validation::closest_name(read, &produced)
+        } else {
+            None
+        };
+        let producers = match &suggestion {
+            Some(name) => producers_of(name),
+            None => producers,
+        };
+        ExecutionError::MissingInput {
+            node_id: node.id,
+            label: node.display_name(),
+            broadcast_var,
+            suggestion,
+            producers,
+        }
+    }
+
+    /// Report the variables that became final when `node` finished (sequential paths)
+    fn emit_final(tracker: Option<&FinalOutputs<T>>, node: &Node<T>, result: &ExecutionResult<T>) {
+        if let Some(tracker) = tracker {
+            for (_, key) in tracker.finish(node) {
+                tracker.emit(&key, result.context.get(&key).map(Arc::as_ref));
+            }
+        }
+    }
+
+    /// Record a node's outputs in the global context and the per-node/per-branch maps
+    pub(crate) fn store_outputs(
+        &self,
+        result: &mut ExecutionResult<T>,
+        node: &Node<T>,
+        plan: &NodePlan,
+        outputs: HashMap<String, Arc<T>>,
+    ) {
+        // Store outputs in global context under the keys planned at build time
+        // (branch namespace, variant entry)
+        plan.write_outputs(&mut result.context, &outputs);
+
+        // Store outputs per branch if this node belongs to a branch
+        if let Some(branch_id) = node.branch_id {
+            result
+                .branch_outputs
+                .entry(branch_id)
+                .or_default()
+                .extend(outputs.clone());
+            result
+                .branch_paths
+                .entry(branch_id)
+                .or_insert_with(|| node.branch_path.clone());
+        }
+
+        // Store outputs per node (using broadcast variable names from output_mapping)
+        result.node_outputs.insert(node.id, outputs);
+
+        // Collected variables hold every producer's value, in node ID order
+        for &key in &plan.collects_into {
+            let gathered = &self.collected[key.as_str()];
+            let values: Vec<T> = gathered
+                .producers
+                .iter()
+                .filter_map(|id| result.node_outputs.get(id))
+                .filter_map(|outputs| outputs.get(&gathered.broadcast_var))
+                .map(|value| T::clone(value))
+                .collect();
+            if let Some(value) = T::gather(values) {
+                result.context.insert(key, Arc::new(value));
+            }
+        }
     }
 
     /// Generate a Mermaid diagram for visualization with port mappings
@@ -360,22 +1064,8 @@ impl Dag {
             for &dep_id in &node.dependencies {
                 let edge = (dep_id, node.id);
                 if !edges_added.contains(&edge) {
-                    // Find the dependency node to get its output mappings
-                    let dep_node = self.nodes.iter().find(|n| n.id == dep_id);
-
-                    // Build port mapping label
-                    let mut port_labels = Vec::new();
-
-                    // Show input mappings for the current node that come from this dependency
-                    for (broadcast_var, impl_var) in &node.input_mapping {
-                        // Check if this broadcast var comes from the dependency
-                        if let Some(dep) = dep_node {
-                            // Check if dependency produces this broadcast var
-                            if dep.output_mapping.values().any(|v| v == broadcast_var) {
-                                port_labels.push(format!("{} → {}", broadcast_var, impl_var));
-                            }
-                        }
-                    }
+                    // Build port mapping label from the dependency's output mappings
+                    let port_labels = self.edge_port_labels(dep_id, node);
 
                     // Format edge with port labels
                     if port_labels.is_empty() {
@@ -390,6 +1080,16 @@ impl Dag {
             }
         }
 
+        // Descriptions and metadata as tooltips
+        for node in &self.nodes {
+            let mut tooltip: Vec<String> = node.description.iter().cloned().collect();
+            tooltip.extend(node.metadata.iter().map(|(k, v)| format!("{}={}", k, v)));
+            if !tooltip.is_empty() {
+                let text = tooltip.join("; ").replace('"', "#quot;");
+                mermaid.push_str(&format!("    click {} callback \"{}\"\n", node.id, text));
+            }
+        }
+
         // Add styling for branches
         for node in &self.nodes {
             if node.is_branch {
@@ -409,6 +1109,102 @@ impl Dag {
         mermaid
     }
 
+    /// IDs of the nodes whose metadata maps `key` to `value`, in ID order
+    pub fn nodes_with_metadata(&self, key: &str, value: &str) -> Vec<NodeId> {
+        let mut ids: Vec<NodeId> = self
+            .nodes
+            .iter()
+            .filter(|n| n.metadata.get(key).is_some_and(|v| v == value))
+            .map(|n| n.id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Port mappings carried by the edge `dep_id → node`, formatted as
+    /// `"broadcast_var → impl_var"`, one per input the dependency produces.
+    fn edge_port_labels(&self, dep_id: NodeId, node: &Node<T>) -> Vec<String> {
+        let Some((dep, _)) = self.planned(dep_id) else {
+            return Vec::new();
+        };
+        let mut labels: Vec<String> = node
+            .input_mapping
+            .iter()
+            .filter(|(broadcast_var, _)| dep.all_outputs().any(|(_, v)| v == *broadcast_var))
+            .map(|(broadcast_var, impl_var)| format!("{} → {}", broadcast_var, impl_var))
+            .collect();
+        labels.sort();
+        labels
+    }
+
+    /// Render the DAG as plain text, one block per execution level
+    ///
+    /// Intended for quick inspection in a terminal where rendering Mermaid is
+    /// inconvenient.  Each node is listed under its level with its ID, label, and
+    /// branch/variant membership, followed by one `◄` line per incoming edge showing
+    /// the port mappings carried by that edge.
+    ///
+    /// ```text
+    /// Level 0
+    /// └── [0] Source
+    /// Level 1
+    /// ├── [1] Branch A (branch 1)
+    /// │     ◄ [0] data → x
+    /// └── [2] Branch B (branch 2)
+    ///       ◄ [0] data → x
+    /// ```
+    pub fn to_ascii(&self) -> String {
+        let mut out = String::new();
+
+        for (level_idx, level) in self.execution_levels.iter().enumerate() {
+            out.push_str(&format!("Level {}\n", level_idx));
+
+            let mut level_nodes: Vec<&Node<T>> = level
+                .iter()
+                .filter_map(|&id| self.planned(id).map(|(node, _)| node))
+                .collect();
+            level_nodes.sort_by_key(|n| n.id);
+
+            for (i, node) in level_nodes.iter().enumerate() {
+                let last = i + 1 == level_nodes.len();
+                let (connector, continuation) = if last {
+                    ("└── ", "      ")
+                } else {
+                    ("├── ", "│     ")
+                };
+
+                let mut line = format!("{}[{}] {}", connector, node.id, node.display_name());
+                if let Some(bid) = node.branch_id {
+                    line.push_str(&format!(" (branch {})", bid));
+                }
+                if let Some(vi) = node.variant_index {
+                    line.push_str(&format!(" (variant {})", vi));
+                }
+                out.push_str(&line);
+                out.push('\n');
+
+                let mut deps = node.dependencies.clone();
+                deps.sort_unstable();
+                deps.dedup();
+                for dep_id in deps {
+                    let ports = self.edge_port_labels(dep_id, node);
+                    if ports.is_empty() {
+                        out.push_str(&format!("{}◄ [{}]\n", continuation, dep_id));
+                    } else {
+                        out.push_str(&format!(
+                            "{}◄ [{}] {}\n",
+                            continuation,
+                            dep_id,
+                            ports.join(", ")
+                        ));
+                    }
+                }
+            }
+        }
+
+        out
+    }
+
     /// Get the execution order
     pub fn execution_order(&self) -> &[NodeId] {
         &self.execution_order
@@ -419,11 +1215,119 @@ impl Dag {
         &self.execution_levels
     }
 
+    /// Hash of the node IDs and dependencies, which determine the execution order
+    /// and levels
+    pub fn topology_hash(&self) -> u64 {
+        plan_cache::topology_hash(&self.nodes)
+    }
+
+    /// Stable hash of the structure of the DAG
+    ///
+    /// Covers what decides which data flows where: node IDs, dependencies, port
+    /// mappings, branch and variant placement (including variant parameters and
+    /// node config), guards' presence, required inputs, port types, and the
+    /// declared inputs and outputs.  Node functions, labels, descriptions, and
+    /// metadata are not included, so the fingerprint changes exactly when the
+    /// wiring of a pipeline does.  The hash is the same in every run and build of
+    /// the program and is computed once per DAG.
+    ///
+    /// Values that have no `Payload::content_hash()` contribute only their presence.
+    pub fn fingerprint(&self) -> u64 {
+        *self.fingerprint.get_or_init(|| {
+            fn sorted<K: Ord + Hash, V>(
+                hasher: &mut ContentHasher,
+                map: impl Iterator<Item = (K, V)>,
+                mut value: impl FnMut(&mut ContentHasher, V),
+            ) {
+                let mut entries: Vec<(K, V)> = map.collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                hasher.write_usize(entries.len());
+                for (key, v) in entries {
+                    key.hash(hasher);
+                    value(hasher, v);
+                }
+            }
+            fn payload<T: Payload>(hasher: &mut ContentHasher, value: &T) {
+                value.content_hash().hash(hasher);
+            }
+
+            let mut hasher = ContentHasher::default();
+            hasher.write_u64(self.topology_hash());
+            for node in &self.nodes {
+                node.id.hash(&mut hasher);
+                sorted(&mut hasher, node.input_mapping.iter(), |h, v| v.hash(h));
+                sorted(&mut hasher, node.output_mapping.iter(), |h, v| v.hash(h));
+                node.edge_outputs.hash(&mut hasher);
+                node.branch_id.hash(&mut hasher);
+                node.branch_path.hash(&mut hasher);
+                node.branch_scopes.hash(&mut hasher);
+                node.is_branch.hash(&mut hasher);
+                node.variant_index.hash(&mut hasher);
+                node.variant_path.hash(&mut hasher);
+                sorted(&mut hasher, node.variant_params.iter(), payload);
+                sorted(&mut hasher, node.config.iter(), payload);
+                node.collect_inputs.hash(&mut hasher);
+                node.partial_inputs.hash(&mut hasher);
+                node.guard.is_some().hash(&mut hasher);
+                node.skip_sentinel.as_ref().map(T::content_hash).hash(&mut hasher);
+                sorted(&mut hasher, node.required_inputs.iter().map(|var| (var, ())), |_, _| {});
+                sorted(&mut hasher, node.input_types.iter(), |h, kind| kind.hash(h));
+                sorted(&mut hasher, node.output_types.iter(), |h, kind| kind.hash(h));
+            }
+            hasher.write_usize(self.declared_inputs.len());
+            for (name, default) in &self.declared_inputs {
+                name.hash(&mut hasher);
+                payload(&mut hasher, default);
+            }
+            self.declared_outputs.hash(&mut hasher);
+            hasher.finish()
+        })
+    }
+
+    /// Execution order and levels, to be saved and reused by a later build of the
+    /// same graph (see `Graph::build_with_plan()`)
+    pub fn execution_plan(&self) -> ExecutionPlan {
+        ExecutionPlan {
+            topology_hash: self.topology_hash(),
+            execution_order: self.execution_order.clone(),
+            execution_levels: self.execution_levels.clone(),
+        }
+    }
+
     /// Get all nodes
-    pub fn nodes(&self) -> &[Node] {
+    pub fn nodes(&self) -> &[Node<T>] {
         &self.nodes
     }
 
+    /// Get the problems detected while building the DAG
+    ///
+    /// Currently reports inputs that no node produces, usually a typo in a
+    /// broadcast variable name.  Variables intentionally supplied from outside
+    /// (e.g. `predict()` input distributions) are reported as well.
+    pub fn build_warnings(&self) -> &[BuildWarning] {
+        &self.build_warnings
+    }
+
+    /// External parameters declared with `Graph::input()`, with their defaults
+    pub fn declared_inputs(&self) -> &[(String, T)] {
+        &self.declared_inputs
+    }
+
+    /// Result variables declared with `Graph::output()`
+    pub fn declared_outputs(&self) -> &[String] {
+        &self.declared_outputs
+    }
+
+    /// Get the optimization passes applied while building, in order
+    ///
+    /// Empty unless the DAG was built with `Graph::build_optimized()` or
+    /// `Graph::build_with_optimizer()`.
+    pub fn optimization_log(&self) -> &[PassSummary] {
+        &self.optimization_log
+    }
+}
+
+impl Dag {
     // ── Statistical forward pass ──────────────────────────────────────────────
 
     /// Forward-propagate distributions through the DAG, optionally stopping early
@@ -475,9 +1379,8 @@ impl Dag {
         let mut satisfied: HashSet<NodeId> = HashSet::new();
 
         for &node_id in &self.execution_order {
-            let node = match self.nodes.iter().find(|n| n.id == node_id) {
-                Some(n) => n,
-                None => continue,
+            let Some((node, _)) = self.planned(node_id) else {
+                continue;
             };
 
             // ── 1. Gather input distributions keyed by impl_var (for DistTransfer) ──
@@ -492,8 +1395,7 @@ impl Dag {
             // ── 3. Produce output distributions (broadcast-var keyed) ──────────
             let output_broadcast: DistContext = if let Some(impl_dists) = maybe_analytical {
                 // Map impl_var -> broadcast_var
-                node.output_mapping
-                    .iter()
+                node.all_outputs()
                     .filter_map(|(impl_var, broadcast_var)| {
                         impl_dists
                             .get(impl_var)
@@ -606,7 +1508,7 @@ impl Dag {
         // (Only used to detect deterministic-only inputs for no-stochastic nodes.)
         let mut dist_ctx: DistContext = {
             let mut ctx: DistContext = HashMap::new();
-            for (var, _) in &input_dists {
+            for var in input_dists.keys() {
                 let samples: Vec<f64> = particles
                     .iter()
                     .map(|p| p.get(var).copied().unwrap_or(f64::NAN))
@@ -619,9 +1521,8 @@ impl Dag {
         let mut stat = StatResult::new();
 
         for &node_id in &self.execution_order {
-            let node = match self.nodes.iter().find(|n| n.id == node_id) {
-                Some(n) => n,
-                None => continue,
+            let Some((node, _)) = self.planned(node_id) else {
+                continue;
             };
 
             // NOTE: dist_transfer is intentionally ignored in predict().
@@ -652,20 +1553,20 @@ impl Dag {
                 }
             } else {
                 // Stochastic node — run the node function once per particle.
-                for i in 0..n_samples {
+                for particle in particles.iter().take(n_samples) {
                     // Build mini context: scalars from particles, or reconstruct
                     // FloatVec/IntVec from indexed `key[j]` + `__veclen__key` markers.
-                    let mut mini: ExecutionContext = HashMap::new();
-                    for (broadcast_key, _) in &node.input_mapping {
+                    let mut mini = ExecutionContext::new();
+                    for broadcast_key in node.input_mapping.keys() {
                         let lookup = broadcast_to_lookup_key(broadcast_key);
-                        if let Some(&val) = particles[i].get(&lookup) {
-                            mini.insert(lookup, GraphData::Float(val));
-                        } else if let Some(&len_f) = particles[i].get(&format!("__veclen__{}", lookup)) {
+                        if let Some(&val) = particle.get(&lookup) {
+                            mini.insert(lookup, Arc::new(GraphData::Float(val)));
+                        } else if let Some(&len_f) = particle.get(&format!("__veclen__{}", lookup)) {
                             let len = len_f as usize;
                             let vec: Vec<f64> = (0..len)
-                                .map(|k| particles[i].get(&format!("{}[{}]", lookup, k)).copied().unwrap_or(f64::NAN))
+                                .map(|k| particle.get(&format!("{}[{}]", lookup, k)).copied().unwrap_or(f64::NAN))
                                 .collect();
-                            mini.insert(lookup, GraphData::FloatVec(Arc::new(vec)));
+                            mini.insert(lookup, Arc::new(GraphData::FloatVec(Arc::new(vec))));
                         }
                     }
 
@@ -792,14 +1693,79 @@ impl Dag {
                         Some(r) => dist.sample_n_with_rng(1, r)[0],
                         None => dist.mean(), // deterministic path
                     };
-                    (lookup, GraphData::Float(val))
+                    (lookup, Arc::new(GraphData::Float(val)))
                 })
             })
             .collect()
     }
+}
 
+impl<T: Payload> Dag<T> {
     /// Get statistics about the DAG
+    ///
+    /// Every node is assumed to cost 1.0, so the cost figures count nodes; use
+    /// `stats_with_costs()` to plan with real per-node estimates.
     pub fn stats(&self) -> DagStats {
+        self.stats_with_costs(&HashMap::new())
+    }
+
+    /// Get statistics about the DAG using per-node cost hints
+    ///
+    /// `costs` maps node IDs to an estimated cost in any unit (e.g. milliseconds);
+    /// nodes without a hint cost 1.0.  The critical path is the most expensive chain
+    /// of dependencies, which bounds the run time with unlimited workers.
+    pub fn stats_with_costs(&self, costs: &HashMap<NodeId, f64>) -> DagStats {
+        let cost_of = |id: NodeId| costs.get(&id).copied().unwrap_or(1.0);
+
+        // Longest (most expensive) path ending at each node, in topological order
+        let mut path_cost: HashMap<NodeId, f64> = HashMap::new();
+        let mut predecessor: HashMap<NodeId, NodeId> = HashMap::new();
+        for &node_id in &self.execution_order {
+            let mut best: Option<(NodeId, f64)> = None;
+            if let Some((node, _)) = self.planned(node_id) {
+                for &dep in &node.dependencies {
+                    if let Some(&c) = path_cost.get(&dep) {
+                        if best.is_none_or(|(b, bc)| c > bc || (c == bc && dep < b)) {
+                            best = Some((dep, c));
+                        }
+                    }
+                }
+            }
+            if let Some((dep, _)) = best {
+                predecessor.insert(node_id, dep);
+            }
+            path_cost.insert(node_id, best.map_or(0.0, |(_, c)| c) + cost_of(node_id));
+        }
+
+        let mut critical_path = Vec::new();
+        let mut critical_path_cost = 0.0;
+        let mut end: Option<NodeId> = None;
+        for &node_id in &self.execution_order {
+            let c = path_cost[&node_id];
+            if end.is_none() || c > critical_path_cost {
+                end = Some(node_id);
+                critical_path_cost = c;
+            }
+        }
+        while let Some(node_id) = end {
+            critical_path.push(node_id);
+            end = predecessor.get(&node_id).copied();
+        }
+        critical_path.reverse();
+
+        let level_costs: Vec<f64> = self
+            .execution_levels
+            .iter()
+            .map(|level| level.iter().map(|&id| cost_of(id)).fold(0.0, f64::max))
+            .collect();
+        let serial_cost: f64 = self.execution_order.iter().map(|&id| cost_of(id)).sum();
+        let parallel_cost: f64 = level_costs.iter().sum();
+        let speedup = if parallel_cost > 0.0 {
+            serial_cost / parallel_cost
+        } else {
+            1.0
+        };
+
         DagStats {
             node_count: self.nodes.len(),
             depth: self.execution_levels.len(),
@@ -817,6 +1783,18 @@ impl Dag {
                 .max()
                 .map(|max| max + 1)
                 .unwrap_or(0),
+            metadata_counts: self.nodes.iter().fold(BTreeMap::new(), |mut counts, node| {
+                for (key, value) in &node.metadata {
+                    *counts.entry(format!("{}={}", key, value)).or_insert(0) += 1;
+                }
+                counts
+            }),
+            critical_path,
+            critical_path_cost,
+            level_costs,
+            serial_cost,
+            parallel_cost,
+            speedup,
         }
     }
 }
@@ -942,23 +1920,54 @@ pub struct DagStats {
     pub branch_count: usize,
     /// Number of variants
     pub variant_count: usize,
+    /// Number of nodes carrying each metadata pair, keyed `"key=value"`
+    pub metadata_counts: BTreeMap<String, usize>,
+    /// Node IDs of the most expensive dependency chain, from source to sink
+    pub critical_path: Vec<NodeId>,
+    /// Total cost of the critical path (lower bound with unlimited workers)
+    pub critical_path_cost: f64,
+    /// Cost of each execution level: its most expensive node
+    pub level_costs: Vec<f64>,
+    /// Sum of all node costs (sequential execution)
+    pub serial_cost: f64,
+    /// Sum of `level_costs` (level-by-level parallel execution)
+    pub parallel_cost: f64,
+    /// Theoretical speedup of parallel over sequential execution
+    pub speedup: f64,
 }
 
 impl DagStats {
     /// Format stats as a human-readable string
     pub fn summary(&self) -> String {
-        format!(
+        let mut summary = format!(
             "DAG Statistics:\n\
              - Nodes: {}\n\
              - Depth: {} levels\n\
              - Max Parallelism: {} nodes\n\
              - Branches: {}\n\
-             - Variants: {}",
+             - Variants: {}\n\
+             - Critical Path: {} (cost {})\n\
+             - Serial Cost: {}\n\
+             - Parallel Cost: {}\n\
+             - Theoretical Speedup: {:.2}x",
             self.node_count,
             self.depth,
             self.max_parallelism,
             self.branch_count,
-            self.variant_count
-        )
+            self.variant_count,
+            self.critical_path
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(" → "),
+            self.critical_path_cost,
+            self.serial_cost,
+            self.parallel_cost,
+            self.speedup
+        );
+        for (pair, count) in &self.metadata_counts {
+            summary.push_str(&format!("\n - Metadata {}: {} nodes", pair, count));
+        }
+        summary
     }
 }
@@ -380,6 +380,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_empirical_empty() {
         let d = Distribution::empirical(vec![]);
         assert!(d.mean().is_nan());
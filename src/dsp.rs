@@ -0,0 +1,247 @@
+//! Signal-processing nodes (`radar_examples` feature)
+//!
+//! The steps of a pulse-Doppler radar chain, as node functions on complex
+//! arrays.  Like `std_nodes`, each constructor returns a `NodeFunction` to add
+//! with `Graph::add_shared()`:
+//!
+//! ```ignore
+//! graph.add_shared(dsp::lfm_chirp(128, 5e6, 20e6), Some("Pulse"), None,
+//!                  Some(vec![("result", "pulse")]));
+//! graph.add_shared(dsp::range_doppler(Window::Hann), Some("RangeDoppler"),
+//!                  Some(vec![("echoes", "pulses"), ("pulse", "reference")]),
+//!                  Some(vec![("result", "rd_map")]));
+//! ```
+//!
+//! | Constructor                                  | Inputs                | Outputs  |
+//! |----------------------------------------------|-----------------------|----------|
+//! | `lfm_chirp(samples, bandwidth, sample_rate)` | none                  | `result` |
+//! | `window(kind)`                               | `x`                   | `result` |
+//! | `fft()`, `ifft()`                            | `x`                   | `result` |
+//! | `fft_node(size, direction)`                  | `x`                   | `result` |
+//! | `matched_filter()`                           | `signal`, `reference` | `result` |
+//! | `range_doppler(slow_time)`                   | `pulses`, `reference` | `result` |
+//! | `magnitude_db()`                             | `x`                   | `result` |
+//!
+//! Signals are `ComplexArray`s; real float vectors and arrays are accepted
+//! wherever a signal is read, as complex values with a zero imaginary part.
+//! Pulse trains are 2-D `ComplexArrayND`s (or `FloatArrayND`s) with one row per
+//! pulse.  Failing nodes output only an `error` string.
+
+use crate::graph_data::GraphData;
+use crate::node::NodeFunction;
+use crate::std_nodes::{input, node, result, Inputs};
+use ndarray::{Array1, Array2, ArrayD, Axis, Ix2};
+use num_complex::Complex;
+use rustfft::FftPlanner;
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+pub use rustfft::FftDirection;
+
+/// Window function tapering a pulse or a slow-time series
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Window {
+    /// No tapering
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+impl Window {
+    /// The `len` coefficients of the (symmetric) window
+    pub fn coefficients(self, len: usize) -> Vec<f64> {
+        if len < 2 {
+            return vec![1.0; len];
+        }
+        let span = (len - 1) as f64;
+        (0..len)
+            .map(|n| {
+                let x = 2.0 * PI * n as f64 / span;
+                match self {
+                    Window::Rectangular => 1.0,
+                    Window::Hann => 0.5 - 0.5 * x.cos(),
+                    Window::Hamming => 0.54 - 0.46 * x.cos(),
+                    Window::Blackman => 0.42 - 0.5 * x.cos() + 0.08 * (2.0 * x).cos(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// The complex samples of input `name`
+fn samples(inputs: &Inputs, name: &str) -> Result<Vec<Complex<f64>>, String> {
+    let value = input(inputs, name)?;
+    if let Some(array) = value.as_complex_array() {
+        return Ok(array.to_vec());
+    }
+    if let Some(values) = value.as_complex_slice() {
+        return Ok(values.to_vec());
+    }
+    let reals: Option<Vec<f64>> =
+        value.as_float_slice().map(<[f64]>::to_vec).or_else(|| value.as_float_array().map(Array1::to_vec));
+    reals
+        .map(|reals| reals.into_iter().map(|re| Complex::new(re, 0.0)).collect())
+        .ok_or_else(|| format!("input '{}' is {}, not a signal", name, value.kind()))
+}
+
+/// The pulse train in input `name`, one row per pulse
+fn pulses(inputs: &Inputs, name: &str) -> Result<Array2<Complex<f64>>, String> {
+    let value = input(inputs, name)?;
+    let array: ArrayD<Complex<f64>> = match (value.as_complex_array_nd(), value.as_array()) {
+        (Some(array), _) => array.clone(),
+        (_, Some(array)) => array.mapv(|re| Complex::new(re, 0.0)),
+        _ => return Err(format!("input '{}' is {}, not a pulse train", name, value.kind())),
+    };
+    let shape = array.shape().to_vec();
+    array
+        .into_dimensionality::<Ix2>()
+        .map_err(|_| format!("input '{}' has shape {:?}, not [pulses, samples]", name, shape))
+}
+
+/// Transform `buffer` in place; the inverse transform is scaled by `1 / len`
+fn transform(planner: &mut FftPlanner<f64>, buffer: &mut [Complex<f64>], direction: FftDirection) {
+    planner.plan_fft(buffer.len(), direction).process(buffer);
+    normalize(buffer, direction);
+}
+
+/// Scale the output of an inverse transform by `1 / len`
+fn normalize(buffer: &mut [Complex<f64>], direction: FftDirection) {
+    if direction == FftDirection::Inverse && !buffer.is_empty() {
+        let scale = 1.0 / buffer.len() as f64;
+        buffer.iter_mut().for_each(|x| *x *= scale);
+    }
+}
+
+/// Cross-correlation of `signal` with `reference` at lags `0..signal.len()`,
+/// through zero-padded FFTs
+fn correlate(
+    planner: &mut FftPlanner<f64>,
+    signal: &[Complex<f64>],
+    reference: &[Complex<f64>],
+) -> Vec<Complex<f64>> {
+    let len = signal.len() + reference.len().saturating_sub(1);
+    let zero = Complex::new(0.0, 0.0);
+    let mut x: Vec<_> = signal.iter().copied().chain(std::iter::repeat(zero)).take(len).collect();
+    let mut h: Vec<_> = reference.iter().copied().chain(std::iter::repeat(zero)).take(len).collect();
+    transform(planner, &mut x, FftDirection::Forward);
+    transform(planner, &mut h, FftDirection::Forward);
+    x.iter_mut().zip(&h).for_each(|(x, h)| *x *= h.conj());
+    transform(planner, &mut x, FftDirection::Inverse);
+    x.truncate(signal.len());
+    x
+}
+
+/// `result` is a linear-frequency-modulated pulse of `samples` samples at
+/// `sample_rate`, sweeping `bandwidth` (in the same unit) across baseband
+pub fn lfm_chirp(samples: usize, bandwidth: f64, sample_rate: f64) -> NodeFunction {
+    let duration = samples as f64 / sample_rate;
+    let rate = bandwidth / duration;
+    let pulse: Array1<Complex<f64>> = (0..samples)
+        .map(|n| {
+            let t = n as f64 / sample_rate - duration / 2.0;
+            Complex::from_polar(1.0, PI * rate * t * t)
+        })
+        .collect();
+    let pulse = GraphData::complex_array(pulse);
+    Arc::new(move |_| result(pulse.clone()))
+}
+
+/// `result` is the signal `x` multiplied by the window `kind`
+pub fn window(kind: Window) -> NodeFunction {
+    node(move |inputs| {
+        let mut x = samples(inputs, "x")?;
+        let taper = kind.coefficients(x.len());
+        x.iter_mut().zip(taper).for_each(|(x, w)| *x *= w);
+        Ok(result(GraphData::complex_array(x.into())))
+    })
+}
+
+/// Node function transforming `x` in the given direction
+fn fft_in(direction: FftDirection) -> NodeFunction {
+    node(move |inputs| {
+        let mut x = samples(inputs, "x")?;
+        transform(&mut FftPlanner::new(), &mut x, direction);
+        Ok(result(GraphData::complex_array(x.into())))
+    })
+}
+
+/// `result` is the discrete Fourier transform of `x`
+///
+/// Plans the transform for the length of each input; for a fixed length, use
+/// `fft_node()`.
+pub fn fft() -> NodeFunction {
+    fft_in(FftDirection::Forward)
+}
+
+/// `result` is the inverse discrete Fourier transform of `x`, scaled so that
+/// `ifft()` undoes `fft()`
+pub fn ifft() -> NodeFunction {
+    fft_in(FftDirection::Inverse)
+}
+
+/// `result` is the transform of `x` in `direction`, with the plan for `size`
+/// samples made once, when the node is created
+///
+/// `x` is zero-padded or truncated to `size` samples.  The inverse transform is
+/// scaled by `1 / size`, like `ifft()`.
+pub fn fft_node(size: usize, direction: FftDirection) -> NodeFunction {
+    let plan = FftPlanner::new().plan_fft(size, direction);
+    node(move |inputs| {
+        let mut x = samples(inputs, "x")?;
+        x.resize(size, Complex::new(0.0, 0.0));
+        plan.process(&mut x);
+        normalize(&mut x, direction);
+        Ok(result(GraphData::complex_array(x.into())))
+    })
+}
+
+/// `result` is the correlation of `signal` with `reference`: one sample per
+/// delay of the reference into the signal, the length of `signal`
+pub fn matched_filter() -> NodeFunction {
+    node(|inputs| {
+        let (signal, reference) = (samples(inputs, "signal")?, samples(inputs, "reference")?);
+        let filtered = correlate(&mut FftPlanner::new(), &signal, &reference);
+        Ok(result(GraphData::complex_array(filtered.into())))
+    })
+}
+
+/// `result` is the range-Doppler map of the pulse train `pulses`, shaped
+/// `[doppler bins, range bins]`
+///
+/// Each pulse is matched-filtered with `reference` (range), then every range
+/// bin is tapered with `slow_time` across pulses and transformed (Doppler).
+/// Doppler bins are centered: zero Doppler is row `pulses / 2`.
+pub fn range_doppler(slow_time: Window) -> NodeFunction {
+    node(move |inputs| {
+        let (train, reference) = (pulses(inputs, "pulses")?, samples(inputs, "reference")?);
+        let (count, length) = train.dim();
+        let mut planner = FftPlanner::new();
+        let mut map = Array2::zeros((count, length));
+        for (pulse, mut row) in train.outer_iter().zip(map.outer_iter_mut()) {
+            let range = correlate(&mut planner, &pulse.to_vec(), &reference);
+            row.assign(&Array1::from(range));
+        }
+        let taper = slow_time.coefficients(count);
+        for mut column in map.axis_iter_mut(Axis(1)) {
+            let mut bins: Vec<_> = column.iter().zip(&taper).map(|(x, w)| x * w).collect();
+            transform(&mut planner, &mut bins, FftDirection::Forward);
+            for (k, bin) in bins.into_iter().enumerate() {
+                column[(k + count / 2) % count] = bin;
+            }
+        }
+        Ok(result(GraphData::complex_array_nd(map.into_dyn())))
+    })
+}
+
+/// `result` is `20 log10 |x|` of a signal or pulse train, keeping its shape
+pub fn magnitude_db() -> NodeFunction {
+    let db = |x: &Complex<f64>| 20.0 * x.norm().log10();
+    node(move |inputs| {
+        let x = input(inputs, "x")?;
+        match x.as_complex_array_nd() {
+            Some(array) => Ok(result(GraphData::array(array.map(db)))),
+            None => Ok(result(GraphData::float_array(samples(inputs, "x")?.iter().map(db).collect()))),
+        }
+    })
+}
@@ -0,0 +1,446 @@
+//! C interface for embedding dagex in C/C++ programs
+//!
+//! Built into the `cdylib` when the `ffi` feature is enabled; the declarations
+//! live in `include/graph_sp.h`.  Graphs, DAGs and value maps are opaque handles
+//! created and freed through this interface.  Node functions are C callbacks
+//! that read their inputs from one map and write their outputs to another:
+//!
+//! ```c
+//! static int square(const dagex_map *in, dagex_map *out, void *user_data) {
+//!     double x;
+//!     if (!dagex_map_get_float(in, "x", &x)) return 1;
+//!     dagex_map_set_float(out, "y", x * x);
+//!     return 0;
+//! }
+//!
+//! const char *inputs[] = {"value", "x", NULL};
+//! const char *outputs[] = {"y", "squared", NULL};
+//! dagex_graph_add(graph, square, NULL, "Square", inputs, outputs);
+//! ```
+//!
+//! In parallel mode callbacks are invoked from several threads at once, so
+//! `user_data` must be safe to share between threads.
+//!
+//! # Safety
+//!
+//! Every pointer argument must be NULL or valid: handles must come from this
+//! library and not have been freed or consumed, strings must be NUL-terminated,
+//! and arrays must hold the stated number of elements.  The functions check for
+//! NULL but cannot check anything else, so they are all `unsafe` with this one
+//! contract instead of per-function sections.
+#![allow(clippy::missing_safety_doc)]
+
+use crate::builder::Graph;
+use crate::dag::Dag;
+use crate::graph_data::GraphData;
+use crate::node::unshare;
+use std::collections::HashMap;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::sync::Arc;
+
+/// Node callback: returns 0 on success; any other value discards its outputs
+pub type DagexNodeFn =
+    unsafe extern "C" fn(inputs: *const DagexMap, outputs: *mut DagexMap, user_data: *mut c_void) -> c_int;
+
+/// Graph under construction (`dagex_graph` in C)
+pub struct DagexGraph(Graph);
+
+/// Built, executable DAG (`dagex_dag` in C)
+pub struct DagexDag(Dag);
+
+/// Map from variable names to values (`dagex_map` in C)
+///
+/// Transparent so a node's `&HashMap` inputs can be handed to C without copying.
+#[repr(transparent)]
+pub struct DagexMap(HashMap<String, Arc<GraphData>>);
+
+impl DagexMap {
+    fn from_ref(map: &HashMap<String, Arc<GraphData>>) -> &DagexMap {
+        // SAFETY: `DagexMap` is a transparent wrapper around the map
+        unsafe { &*(map as *const HashMap<String, Arc<GraphData>> as *const DagexMap) }
+    }
+}
+
+/// C callback and its user data, shared by the threads running the node
+struct Callback {
+    function: DagexNodeFn,
+    user_data: *mut c_void,
+}
+
+// SAFETY: the caller promises `user_data` may be used from any thread (see module docs)
+unsafe impl Send for Callback {}
+unsafe impl Sync for Callback {}
+
+impl Callback {
+    fn call(&self, inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
+        let mut outputs = DagexMap(HashMap::new());
+        let status = unsafe { (self.function)(DagexMap::from_ref(inputs), &mut outputs, self.user_data) };
+        if status == 0 {
+            unshare(outputs.0)
+        } else {
+            HashMap::new()
+        }
+    }
+
+    fn node_function(self) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> + Send + Sync {
+        move |inputs| self.call(inputs)
+    }
+}
+
+/// Borrow a C string, or `None` for NULL or invalid UTF-8
+unsafe fn str_arg<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        None
+    } else {
+        CStr::from_ptr(s).to_str().ok()
+    }
+}
+
+/// Read a NULL-terminated list of name pairs; `None` if malformed
+unsafe fn pairs_arg<'a>(list: *const *const c_char) -> Option<Vec<(&'a str, &'a str)>> {
+    let mut names = Vec::new();
+    if !list.is_null() {
+        let mut i = 0;
+        while !(*list.add(i)).is_null() {
+            names.push(str_arg(*list.add(i))?);
+            i += 1;
+        }
+    }
+    if names.len() % 2 != 0 {
+        return None;
+    }
+    Some(names.chunks(2).map(|pair| (pair[0], pair[1])).collect())
+}
+
+fn non_empty<T>(v: Vec<T>) -> Option<Vec<T>> {
+    (!v.is_empty()).then_some(v)
+}
+
+// ─── Graph construction ──────────────────────────────────────────────────────
+
+/// Create an empty graph; free it with `dagex_graph_free()` unless it is built
+#[no_mangle]
+pub extern "C" fn dagex_graph_new() -> *mut DagexGraph {
+    Box::into_raw(Box::new(DagexGraph(Graph::new())))
+}
+
+/// Free a graph that was not built or branched
+#[no_mangle]
+pub unsafe extern "C" fn dagex_graph_free(graph: *mut DagexGraph) {
+    if !graph.is_null() {
+        drop(Box::from_raw(graph));
+    }
+}
+
+/// Add a node calling `function`; returns 0, or -1 for invalid arguments
+///
+/// `inputs` lists `broadcast_var, impl_var` pairs and `outputs` lists
+/// `impl_var, broadcast_var` pairs, each as a flat NULL-terminated array (or NULL).
+#[no_mangle]
+pub unsafe extern "C" fn dagex_graph_add(
+    graph: *mut DagexGraph,
+    function: Option<DagexNodeFn>,
+    user_data: *mut c_void,
+    label: *const c_char,
+    inputs: *const *const c_char,
+    outputs: *const *const c_char,
+) -> c_int {
+    let (Some(graph), Some(function)) = (graph.as_mut(), function) else {
+        return -1;
+    };
+    let (Some(inputs), Some(outputs)) = (pairs_arg(inputs), pairs_arg(outputs)) else {
+        return -1;
+    };
+    let callback = Callback { function, user_data };
+    graph.0.add(
+        callback.node_function(),
+        str_arg(label),
+        non_empty(inputs),
+        non_empty(outputs),
+    );
+    0
+}
+
+/// Add `subgraph` as a branch and return its branch ID, or 0 for invalid arguments
+///
+/// Takes ownership of `subgraph`, which must not be used or freed afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn dagex_graph_branch(graph: *mut DagexGraph, subgraph: *mut DagexGraph) -> usize {
+    let Some(graph) = graph.as_mut() else {
+        return 0;
+    };
+    if subgraph.is_null() {
+        return 0;
+    }
+    graph.0.branch(Box::from_raw(subgraph).0)
+}
+
+/// Add a node merging branch outputs; returns 0, or -1 for invalid arguments
+///
+/// Input `i` reads `inputs[2i]` from branch `branch_ids[i]` and passes it to the
+/// callback as `inputs[2i + 1]`; `inputs` is NULL-terminated and must hold one
+/// pair per branch ID.  `outputs` is as in `dagex_graph_add()`.
+#[no_mangle]
+pub unsafe extern "C" fn dagex_graph_merge(
+    graph: *mut DagexGraph,
+    function: Option<DagexNodeFn>,
+    user_data: *mut c_void,
+    label: *const c_char,
+    branch_ids: *const usize,
+    inputs: *const *const c_char,
+    outputs: *const *const c_char,
+) -> c_int {
+    let (Some(graph), Some(function)) = (graph.as_mut(), function) else {
+        return -1;
+    };
+    let (Some(inputs), Some(outputs)) = (pairs_arg(inputs), pairs_arg(outputs)) else {
+        return -1;
+    };
+    if !inputs.is_empty() && branch_ids.is_null() {
+        return -1;
+    }
+    let merge_inputs = inputs
+        .iter()
+        .enumerate()
+        .map(|(i, &(var, impl_var))| (*branch_ids.add(i), var, impl_var))
+        .collect();
+    let callback = Callback { function, user_data };
+    graph.0.merge(callback.node_function(), str_arg(label), merge_inputs, non_empty(outputs));
+    0
+}
+
+/// Build the graph into a DAG; takes ownership of `graph`
+///
+/// Returns NULL if `graph` is NULL.  Free the DAG with `dagex_dag_free()`.
+#[no_mangle]
+pub unsafe extern "C" fn dagex_graph_build(graph: *mut DagexGraph) -> *mut DagexDag {
+    if graph.is_null() {
+        return ptr::null_mut();
+    }
+    let graph = Box::from_raw(graph);
+    match catch_unwind(AssertUnwindSafe(|| graph.0.build())) {
+        Ok(dag) => Box::into_raw(Box::new(DagexDag(dag))),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+// ─── Execution ───────────────────────────────────────────────────────────────
+
+/// Free a DAG
+#[no_mangle]
+pub unsafe extern "C" fn dagex_dag_free(dag: *mut DagexDag) {
+    if !dag.is_null() {
+        drop(Box::from_raw(dag));
+    }
+}
+
+/// Execute the DAG and return its context, or NULL on failure
+///
+/// `inputs` (may be NULL) seeds the context as in `Dag::execute_with_inputs()`.
+/// `max_threads` of 0 means unlimited.  Free the result with `dagex_map_free()`.
+#[no_mangle]
+pub unsafe extern "C" fn dagex_dag_execute(
+    dag: *const DagexDag,
+    inputs: *const DagexMap,
+    parallel: c_int,
+    max_threads: usize,
+) -> *mut DagexMap {
+    let Some(dag) = dag.as_ref() else {
+        return ptr::null_mut();
+    };
+    let inputs = inputs.as_ref().map(|m| m.0.clone()).unwrap_or_default();
+    let max_threads = (max_threads > 0).then_some(max_threads);
+    match catch_unwind(AssertUnwindSafe(|| {
+        dag.0.execute_with_shared_inputs(inputs, parallel != 0, max_threads)
+    })) {
+        Ok(context) => Box::into_raw(Box::new(DagexMap(context.into_inner()))),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Mermaid diagram of the DAG; free it with `dagex_string_free()`
+#[no_mangle]
+pub unsafe extern "C" fn dagex_dag_to_mermaid(dag: *const DagexDag) -> *mut c_char {
+    match dag.as_ref() {
+        Some(dag) => CString::new(dag.0.to_mermaid()).map_or(ptr::null_mut(), CString::into_raw),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Free a string returned by this library
+#[no_mangle]
+pub unsafe extern "C" fn dagex_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+// ─── Value maps ──────────────────────────────────────────────────────────────
+
+/// Create an empty map, e.g. for `dagex_dag_execute()` inputs
+#[no_mangle]
+pub extern "C" fn dagex_map_new() -> *mut DagexMap {
+    Box::into_raw(Box::new(DagexMap(HashMap::new())))
+}
+
+/// Free a map created by `dagex_map_new()` or returned by `dagex_dag_execute()`
+///
+/// Maps passed to node callbacks are owned by the library and must not be freed.
+#[no_mangle]
+pub unsafe extern "C" fn dagex_map_free(map: *mut DagexMap) {
+    if !map.is_null() {
+        drop(Box::from_raw(map));
+    }
+}
+
+/// Whether the map holds `key`
+#[no_mangle]
+pub unsafe extern "C" fn dagex_map_contains(map: *const DagexMap, key: *const c_char) -> c_int {
+    lookup(map, key).is_some() as c_int
+}
+
+unsafe fn lookup<'a>(map: *const DagexMap, key: *const c_char) -> Option<&'a GraphData> {
+    map.as_ref()?.0.get(str_arg(key)?).map(Arc::as_ref)
+}
+
+unsafe fn insert(map: *mut DagexMap, key: *const c_char, value: GraphData) -> c_int {
+    match (map.as_mut(), str_arg(key)) {
+        (Some(map), Some(key)) => {
+            map.0.insert(key.to_string(), Arc::new(value));
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// Read an integer into `out`; returns 1 if `key` holds one, else 0
+#[no_mangle]
+pub unsafe extern "C" fn dagex_map_get_int(map: *const DagexMap, key: *const c_char, out: *mut i64) -> c_int {
+    match (lookup(map, key).and_then(GraphData::as_int), out.as_mut()) {
+        (Some(value), Some(out)) => {
+            *out = value;
+            1
+        }
+        _ => 0,
+    }
+}
+
+/// Read a number (float or integer) into `out`; returns 1 if `key` holds one, else 0
+#[no_mangle]
+pub unsafe extern "C" fn dagex_map_get_float(map: *const DagexMap, key: *const c_char, out: *mut f64) -> c_int {
+    match (lookup(map, key).and_then(GraphData::as_float), out.as_mut()) {
+        (Some(value), Some(out)) => {
+            *out = value;
+            1
+        }
+        _ => 0,
+    }
+}
+
+/// Borrow a string value; returns NULL if `key` does not hold one
+///
+/// The bytes are not NUL-terminated: their length is written to `len`.  The
+/// pointer is valid while the map is alive and `key` is not overwritten.
+#[no_mangle]
+pub unsafe extern "C" fn dagex_map_get_string(
+    map: *const DagexMap,
+    key: *const c_char,
+    len: *mut usize,
+) -> *const c_char {
+    match (lookup(map, key).and_then(GraphData::as_string), len.as_mut()) {
+        (Some(s), Some(len)) => {
+            *len = s.len();
+            s.as_ptr() as *const c_char
+        }
+        _ => ptr::null(),
+    }
+}
+
+/// Borrow a float array; returns NULL if `key` does not hold one
+///
+/// Its length is written to `len`; validity is as for `dagex_map_get_string()`.
+#[no_mangle]
+pub unsafe extern "C" fn dagex_map_get_float_array(
+    map: *const DagexMap,
+    key: *const c_char,
+    len: *mut usize,
+) -> *const f64 {
+    match (lookup(map, key).and_then(GraphData::as_float_slice), len.as_mut()) {
+        (Some(values), Some(len)) => {
+            *len = values.len();
+            values.as_ptr()
+        }
+        _ => ptr::null(),
+    }
+}
+
+/// Borrow an integer array; returns NULL if `key` does not hold one
+///
+/// Its length is written to `len`; validity is as for `dagex_map_get_string()`.
+#[no_mangle]
+pub unsafe extern "C" fn dagex_map_get_int_array(
+    map: *const DagexMap,
+    key: *const c_char,
+    len: *mut usize,
+) -> *const i64 {
+    match (lookup(map, key).and_then(GraphData::as_int_slice), len.as_mut()) {
+        (Some(values), Some(len)) => {
+            *len = values.len();
+            values.as_ptr()
+        }
+        _ => ptr::null(),
+    }
+}
+
+/// Store an integer; returns 0, or -1 for invalid arguments
+#[no_mangle]
+pub unsafe extern "C" fn dagex_map_set_int(map: *mut DagexMap, key: *const c_char, value: i64) -> c_int {
+    insert(map, key, GraphData::int(value))
+}
+
+/// Store a float; returns 0, or -1 for invalid arguments
+#[no_mangle]
+pub unsafe extern "C" fn dagex_map_set_float(map: *mut DagexMap, key: *const c_char, value: f64) -> c_int {
+    insert(map, key, GraphData::float(value))
+}
+
+/// Copy a NUL-terminated UTF-8 string in; returns 0, or -1 for invalid arguments
+#[no_mangle]
+pub unsafe extern "C" fn dagex_map_set_string(map: *mut DagexMap, key: *const c_char, value: *const c_char) -> c_int {
+    match str_arg(value) {
+        Some(value) => insert(map, key, GraphData::string(value)),
+        None => -1,
+    }
+}
+
+/// Copy `len` floats in; returns 0, or -1 for invalid arguments
+#[no_mangle]
+pub unsafe extern "C" fn dagex_map_set_float_array(
+    map: *mut DagexMap,
+    key: *const c_char,
+    values: *const f64,
+    len: usize,
+) -> c_int {
+    if values.is_null() && len > 0 {
+        return -1;
+    }
+    let values = if len == 0 { Vec::new() } else { std::slice::from_raw_parts(values, len).to_vec() };
+    insert(map, key, GraphData::float_vec(values))
+}
+
+/// Copy `len` integers in; returns 0, or -1 for invalid arguments
+#[no_mangle]
+pub unsafe extern "C" fn dagex_map_set_int_array(
+    map: *mut DagexMap,
+    key: *const c_char,
+    values: *const i64,
+    len: usize,
+) -> c_int {
+    if values.is_null() && len > 0 {
+        return -1;
+    }
+    let values = if len == 0 { Vec::new() } else { std::slice::from_raw_parts(values, len).to_vec() };
+    insert(map, key, GraphData::int_vec(values))
+}
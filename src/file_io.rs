@@ -0,0 +1,256 @@
+//! File reading and writing nodes
+//!
+//! Every pipeline starts by loading data and ends by saving results.  These
+//! constructors build the node functions for common formats, taking the file
+//! path from the `path` input so it can come from the context:
+//!
+//! ```ignore
+//! graph.constant("samples_file", GraphData::string("capture.csv"));
+//! graph.add(read_csv(), Some("Load"), Some(vec![("samples_file", "path")]),
+//!           Some(vec![("time", "t"), ("voltage", "v")]));
+//! ```
+//!
+//! | Constructor     | Inputs                  | Outputs                          |
+//! |-----------------|-------------------------|----------------------------------|
+//! | `read_csv()`    | `path`                  | one port per column              |
+//! | `write_csv(..)` | `path`, the columns     | `path`                           |
+//! | `read_floats()` | `path`                  | `data` (float vector)            |
+//! | `write_floats()`| `path`, `data`          | `path`                           |
+//! | `read_json()`   | `path`                  | `data` (JSON, `json` feature)    |
+//! | `write_json()`  | `path`, `data`          | `path` (`json` feature)          |
+//!
+//! CSV files have a header row naming the columns and one record per line;
+//! fields may be quoted (`"a, b"`, with `""` for a quote).  A column whose
+//! fields all parse as numbers is read as a float vector (empty fields are NaN),
+//! any other as a list of strings.  Float files are flat arrays of native-endian
+//! `f64` values, the layout `GraphData::mmap()` maps.
+//!
+//! Writers output the path they wrote, so nodes reading the file can depend on
+//! them.  A node that fails (missing file, malformed content, wrong input type)
+//! outputs only an `error` string, like the HTTP nodes.
+
+use crate::graph_data::GraphData;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Node function reading a CSV file into one output per column (see the module
+/// documentation)
+pub fn read_csv(
+) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> + Send + Sync + 'static {
+    fallible(|inputs| {
+        let path = path(inputs)?;
+        let text = std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+        parse_csv(&text)
+    })
+}
+
+/// Node function writing the inputs named `columns`, in that order, as the
+/// columns of a CSV file (see the module documentation)
+///
+/// Columns may be float or integer vectors or lists; shorter columns are padded
+/// with empty fields.
+pub fn write_csv(
+    columns: &[&str],
+) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> + Send + Sync + 'static {
+    let columns: Vec<String> = columns.iter().map(|c| c.to_string()).collect();
+    fallible(move |inputs| {
+        let path = path(inputs)?;
+        let mut cells = Vec::with_capacity(columns.len());
+        for column in &columns {
+            let value = inputs.get(column).ok_or_else(|| format!("missing column input '{}'", column))?;
+            cells.push(column_cells(value).ok_or_else(|| format!("column '{}' is not a vector or list", column))?);
+        }
+        let rows = cells.iter().map(Vec::len).max().unwrap_or(0);
+        let mut text = record(columns.iter().map(String::as_str));
+        for row in 0..rows {
+            text += &record(cells.iter().map(|column| column.get(row).map_or("", String::as_str)));
+        }
+        std::fs::write(path, text).map_err(|e| format!("cannot write {}: {}", path, e))?;
+        Ok(HashMap::from([("path".to_string(), GraphData::string(path))]))
+    })
+}
+
+/// Node function reading a file of native-endian `f64` values into `data`
+pub fn read_floats(
+) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> + Send + Sync + 'static {
+    fallible(|inputs| {
+        let path = path(inputs)?;
+        let bytes = std::fs::read(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+        if bytes.len() % 8 != 0 {
+            return Err(format!("{} holds {} bytes, not a whole number of f64 values", path, bytes.len()));
+        }
+        let data = bytes.chunks_exact(8).map(|b| f64::from_ne_bytes(b.try_into().unwrap())).collect();
+        Ok(HashMap::from([("data".to_string(), GraphData::float_vec(data))]))
+    })
+}
+
+/// Node function writing the float vector `data` as native-endian `f64` values
+pub fn write_floats(
+) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> + Send + Sync + 'static {
+    fallible(|inputs| {
+        let path = path(inputs)?;
+        let data = inputs
+            .get("data")
+            .and_then(|data| data.as_float_slice())
+            .ok_or("missing float vector input 'data'")?;
+        let bytes: Vec<u8> = data.iter().flat_map(|v| v.to_ne_bytes()).collect();
+        std::fs::write(path, bytes).map_err(|e| format!("cannot write {}: {}", path, e))?;
+        Ok(HashMap::from([("path".to_string(), GraphData::string(path))]))
+    })
+}
+
+/// Node function parsing a JSON file into `data`
+#[cfg(feature = "json")]
+pub fn read_json(
+) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> + Send + Sync + 'static {
+    fallible(|inputs| {
+        let path = path(inputs)?;
+        let text = std::fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path, e))?;
+        let data = serde_json::from_str(&text).map_err(|e| format!("{} is not JSON: {}", path, e))?;
+        Ok(HashMap::from([("data".to_string(), GraphData::json(data))]))
+    })
+}
+
+/// Node function writing the JSON document `data`, pretty-printed
+#[cfg(feature = "json")]
+pub fn write_json(
+) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> + Send + Sync + 'static {
+    fallible(|inputs| {
+        let path = path(inputs)?;
+        let data = inputs.get("data").and_then(|data| data.as_json()).ok_or("missing JSON input 'data'")?;
+        let text = serde_json::to_string_pretty(data).map_err(|e| e.to_string())?;
+        std::fs::write(path, text + "\n").map_err(|e| format!("cannot write {}: {}", path, e))?;
+        Ok(HashMap::from([("path".to_string(), GraphData::string(path))]))
+    })
+}
+
+/// Node function running `f`, whose error becomes the only output, `error`
+pub(crate) fn fallible<F>(
+    f: F,
+) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> + Send + Sync + 'static
+where
+    F: Fn(&HashMap<String, Arc<GraphData>>) -> Result<HashMap<String, GraphData>, String>
+        + Send
+        + Sync
+        + 'static,
+{
+    move |inputs| {
+        f(inputs).unwrap_or_else(|message| HashMap::from([("error".to_string(), GraphData::string(message))]))
+    }
+}
+
+/// The `path` input
+fn path(inputs: &HashMap<String, Arc<GraphData>>) -> Result<&str, String> {
+    inputs
+        .get("path")
+        .and_then(|path| path.as_string())
+        .ok_or_else(|| "missing string input 'path'".to_string())
+}
+
+/// Columns of a CSV text, keyed by header
+fn parse_csv(text: &str) -> Result<HashMap<String, GraphData>, String> {
+    let mut records = records(text).into_iter();
+    let header = records.next().map(|(_, fields)| fields).ok_or("empty CSV file")?;
+    let mut columns: Vec<Vec<String>> = vec![Vec::new(); header.len()];
+    for (line, record) in records {
+        if record.len() != header.len() {
+            return Err(format!("line {} has {} fields, expected {}", line, record.len(), header.len()));
+        }
+        for (column, field) in columns.iter_mut().zip(record) {
+            column.push(field);
+        }
+    }
+    Ok(header
+        .into_iter()
+        .zip(columns)
+        .map(|(name, cells)| {
+            let numbers: Option<Vec<f64>> = cells
+                .iter()
+                .map(|cell| match cell.trim() {
+                    "" => Some(f64::NAN),
+                    cell => cell.parse().ok(),
+                })
+                .collect();
+            let value = match numbers {
+                Some(numbers) => GraphData::float_vec(numbers),
+                None => GraphData::list(cells.into_iter().map(GraphData::string).collect()),
+            };
+            (name, value)
+        })
+        .collect())
+}
+
+/// Split a CSV text into records, with the line each starts on, unquoting quoted
+/// fields
+///
+/// Quoted fields may contain line breaks.  Blank lines are skipped, and so is the
+/// `'\r'` of a `"\r\n"` line ending outside quotes.
+fn records(text: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut fields = vec![String::new()];
+    let (mut quoted, mut blank) = (false, true);
+    let (mut line, mut start) = (1, 1);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().unwrap();
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', _) => (quoted, blank) = (!quoted, false),
+            (',', false) => {
+                fields.push(String::new());
+                blank = false;
+            }
+            ('\r', false) if chars.peek() == Some(&'\n') => {}
+            ('\n', false) => {
+                let record = std::mem::replace(&mut fields, vec![String::new()]);
+                if !blank {
+                    records.push((start, record));
+                }
+                line += 1;
+                (start, blank) = (line, true);
+            }
+            (c, _) => {
+                line += usize::from(c == '\n');
+                blank &= c.is_whitespace();
+                field.push(c);
+            }
+        }
+    }
+    if !blank {
+        records.push((start, fields));
+    }
+    records
+}
+
+/// One CSV line of `cells`, quoting those that need it
+fn record<'a>(cells: impl Iterator<Item = &'a str>) -> String {
+    let cells: Vec<String> = cells
+        .map(|cell| {
+            if cell.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.to_string()
+            }
+        })
+        .collect();
+    cells.join(",") + "\n"
+}
+
+/// Fields of a column input
+fn column_cells(value: &GraphData) -> Option<Vec<String>> {
+    if let Some(values) = value.as_float_slice() {
+        return Some(values.iter().map(|v| if v.is_nan() { String::new() } else { v.to_string() }).collect());
+    }
+    if let Some(values) = value.as_int_slice() {
+        return Some(values.iter().map(i64::to_string).collect());
+    }
+    value.as_list().map(|items| {
+        items
+            .iter()
+            .map(|item| item.as_string().map_or_else(|| item.to_string_repr(), str::to_string))
+            .collect()
+    })
+}
@@ -1,18 +1,40 @@
 //! GraphData container for passing typed data between nodes
 //!
 //! This module provides a generic container that can hold various data types
-//! (numbers, arrays, complex arrays, strings, etc.) and be passed through graph nodes.
+//! (numbers, flags, byte buffers, arrays, complex arrays, strings, JSON documents, etc.)
+//! and be passed through graph nodes.
 //!
 //! Large data types (Vec, Array) are wrapped in Arc for efficient cloning across nodes.
+//!
+//! With the `serde` feature, `GraphData` implements `Serialize`/`Deserialize` (externally
+//! tagged, e.g. `{"Int": 42}`).  Byte buffers are written as byte strings and arrays as
+//! flat sequences plus shape, so compact formats such as bincode store them as raw
+//! numbers.  `Custom`, `DataFrame`, `Mapped`, and `PyObject` values cannot be
+//! serialized and produce an error.
 
+use crate::lazy::LazyValue;
+use crate::payload::ContentHasher;
+use crate::shared_slice::SharedSlice;
+use std::any::Any;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::RangeBounds;
 use std::sync::Arc;
 
 #[cfg(feature = "radar_examples")]
-use ndarray::Array1;
+use ndarray::{Array1, ArrayD};
 #[cfg(feature = "radar_examples")]
 use num_complex::Complex;
 
+#[cfg(feature = "json")]
+use serde_json::Value as JsonValue;
+
+#[cfg(feature = "polars")]
+use polars::prelude::DataFrame;
+
+#[cfg(feature = "mmap")]
+use crate::mapped::MappedFile;
+
 #[cfg(feature = "python")]
 use pyo3::PyObject;
 
@@ -20,18 +42,30 @@ use pyo3::PyObject;
 /// 
 /// Large data types (Vec, Array) are wrapped in Arc for efficient sharing between nodes.
 /// Small types (Int, Float, String) remain unwrapped as they're cheap to clone.
-#[derive(Clone, Debug)]
+///
+/// `Debug` output is bounded: see `GraphData::summary()`.
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GraphData {
     /// 64-bit integer (small, no Arc needed)
     Int(i64),
     /// 64-bit floating point (small, no Arc needed)
     Float(f64),
+    /// Boolean flag (small, no Arc needed)
+    Bool(bool),
     /// UTF-8 string (already uses internal Arc-like optimization)
     String(String),
     /// Vector of floats (Arc-wrapped for efficient cloning)
     FloatVec(Arc<Vec<f64>>),
     /// Vector of integers (Arc-wrapped for efficient cloning)
     IntVec(Arc<Vec<i64>>),
+    /// Segment of a float vector, sharing its buffer (see `GraphData::slice()`)
+    FloatSlice(SharedSlice<f64>),
+    /// Segment of an integer vector, sharing its buffer (see `GraphData::slice()`)
+    IntSlice(SharedSlice<i64>),
+    /// Binary blob (Arc-wrapped for efficient cloning)
+    #[cfg_attr(feature = "serde", serde(with = "serde_bytes_arc"))]
+    Bytes(Arc<Vec<u8>>),
     /// Complex number (small, no Arc needed)
     #[cfg(feature = "radar_examples")]
     Complex(Complex<f64>),
@@ -41,16 +75,254 @@ pub enum GraphData {
     /// 1D array of complex numbers (Arc-wrapped for efficient cloning)
     #[cfg(feature = "radar_examples")]
     ComplexArray(Arc<Array1<Complex<f64>>>),
+    /// N-dimensional array of floats, carrying its own shape (Arc-wrapped)
+    #[cfg(feature = "radar_examples")]
+    FloatArrayND(Arc<ArrayD<f64>>),
+    /// N-dimensional array of complex numbers, carrying its own shape (Arc-wrapped)
+    #[cfg(feature = "radar_examples")]
+    ComplexArrayND(Arc<ArrayD<Complex<f64>>>),
     /// Nested map of GraphData (for structured data)
     Map(HashMap<String, GraphData>),
+    /// Ordered list of GraphData (e.g. values gathered from several producers)
+    List(Vec<GraphData>),
+    /// Structured JSON document (e.g. a configuration object)
+    #[cfg(feature = "json")]
+    Json(JsonValue),
+    /// Polars DataFrame (columns are reference-counted, so cloning is cheap)
+    #[cfg(feature = "polars")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    DataFrame(DataFrame),
+    /// Memory-mapped file holding a flat array (see `GraphData::mmap()`)
+    #[cfg(feature = "mmap")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Mapped(MappedFile),
+    /// Value computed on first access (see `GraphData::lazy()`)
+    Lazy(LazyValue),
+    /// Application-defined value (opaque; read it back with `downcast_ref()`)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Arc<dyn Any + Send + Sync>),
     /// Python object (opaque, no conversion)
     #[cfg(feature = "python")]
+    #[cfg_attr(feature = "serde", serde(skip))]
     PyObject(PyObject),
     /// Empty/null value
+    #[default]
+    None,
+}
+
+/// The variant of a `GraphData` value, without its payload.
+///
+/// Used to declare the types of node ports (`Graph::with_input_type()` /
+/// `Graph::with_output_type()`) so that `Graph::build()` can check that producers
+/// and consumers of a variable agree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GraphDataKind {
+    Int,
+    Float,
+    Bool,
+    String,
+    FloatVec,
+    IntVec,
+    FloatSlice,
+    IntSlice,
+    Bytes,
+    #[cfg(feature = "radar_examples")]
+    Complex,
+    #[cfg(feature = "radar_examples")]
+    FloatArray,
+    #[cfg(feature = "radar_examples")]
+    ComplexArray,
+    #[cfg(feature = "radar_examples")]
+    FloatArrayND,
+    #[cfg(feature = "radar_examples")]
+    ComplexArrayND,
+    Map,
+    List,
+    #[cfg(feature = "json")]
+    Json,
+    #[cfg(feature = "polars")]
+    DataFrame,
+    #[cfg(feature = "mmap")]
+    Mapped,
+    Custom,
+    #[cfg(feature = "python")]
+    PyObject,
     None,
 }
 
+impl GraphDataKind {
+    /// Whether a port declared as `self` can receive values of kind `produced`.
+    ///
+    /// Kinds must match, except that a `Float` port also accepts `Int` values
+    /// (as `GraphData::as_float()` does).
+    pub fn accepts(self, produced: GraphDataKind) -> bool {
+        self == produced || (self == GraphDataKind::Float && produced == GraphDataKind::Int)
+    }
+}
+
+impl std::fmt::Display for GraphDataKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
 impl GraphData {
+    /// The variant of this value
+    pub fn kind(&self) -> GraphDataKind {
+        match self.force() {
+            GraphData::Int(_) => GraphDataKind::Int,
+            GraphData::Float(_) => GraphDataKind::Float,
+            GraphData::Bool(_) => GraphDataKind::Bool,
+            GraphData::String(_) => GraphDataKind::String,
+            GraphData::FloatVec(_) => GraphDataKind::FloatVec,
+            GraphData::IntVec(_) => GraphDataKind::IntVec,
+            GraphData::FloatSlice(_) => GraphDataKind::FloatSlice,
+            GraphData::IntSlice(_) => GraphDataKind::IntSlice,
+            GraphData::Bytes(_) => GraphDataKind::Bytes,
+            #[cfg(feature = "radar_examples")]
+            GraphData::Complex(_) => GraphDataKind::Complex,
+            #[cfg(feature = "radar_examples")]
+            GraphData::FloatArray(_) => GraphDataKind::FloatArray,
+            #[cfg(feature = "radar_examples")]
+            GraphData::ComplexArray(_) => GraphDataKind::ComplexArray,
+            #[cfg(feature = "radar_examples")]
+            GraphData::FloatArrayND(_) => GraphDataKind::FloatArrayND,
+            #[cfg(feature = "radar_examples")]
+            GraphData::ComplexArrayND(_) => GraphDataKind::ComplexArrayND,
+            GraphData::Map(_) => GraphDataKind::Map,
+            GraphData::List(_) => GraphDataKind::List,
+            #[cfg(feature = "json")]
+            GraphData::Json(_) => GraphDataKind::Json,
+            #[cfg(feature = "polars")]
+            GraphData::DataFrame(_) => GraphDataKind::DataFrame,
+            #[cfg(feature = "mmap")]
+            GraphData::Mapped(_) => GraphDataKind::Mapped,
+            GraphData::Custom(_) => GraphDataKind::Custom,
+            GraphData::Lazy(_) => unreachable!("forced above"),
+            #[cfg(feature = "python")]
+            GraphData::PyObject(_) => GraphDataKind::PyObject,
+            GraphData::None => GraphDataKind::None,
+        }
+    }
+
+    /// Hash of the value's content, equal for equal values in every run
+    ///
+    /// Floats are hashed by their bits and maps in key order.  Returns `None` for
+    /// values whose content is opaque (`Custom`, `DataFrame`, `PyObject`) or that
+    /// contain such a value.
+    pub fn content_hash(&self) -> Option<u64> {
+        let mut hasher = ContentHasher::default();
+        self.hash_content(&mut hasher).then(|| hasher.finish())
+    }
+
+    /// The value multiplied by `factor`, for numeric values
+    ///
+    /// Floats, float vectors and arrays, and complex values are scaled in place;
+    /// ints become floats and float slices become float vectors.  Lists are scaled
+    /// element by element.  Returns `None` for other values.
+    pub fn scaled(&self, factor: f64) -> Option<GraphData> {
+        Some(match self.force() {
+            GraphData::Int(v) => GraphData::Float(*v as f64 * factor),
+            GraphData::Float(v) => GraphData::Float(v * factor),
+            GraphData::FloatVec(v) => GraphData::float_vec(v.iter().map(|x| x * factor).collect()),
+            GraphData::FloatSlice(s) => GraphData::float_vec(s.iter().map(|x| x * factor).collect()),
+            GraphData::IntVec(v) => GraphData::float_vec(v.iter().map(|&x| x as f64 * factor).collect()),
+            GraphData::IntSlice(s) => GraphData::float_vec(s.iter().map(|&x| x as f64 * factor).collect()),
+            #[cfg(feature = "radar_examples")]
+            GraphData::Complex(c) => GraphData::Complex(c * factor),
+            #[cfg(feature = "radar_examples")]
+            GraphData::FloatArray(a) => GraphData::FloatArray(Arc::new(a.as_ref() * factor)),
+            #[cfg(feature = "radar_examples")]
+            GraphData::ComplexArray(a) => GraphData::ComplexArray(Arc::new(a.mapv(|c| c * factor))),
+            #[cfg(feature = "radar_examples")]
+            GraphData::FloatArrayND(a) => GraphData::FloatArrayND(Arc::new(a.as_ref() * factor)),
+            #[cfg(feature = "radar_examples")]
+            GraphData::ComplexArrayND(a) => GraphData::ComplexArrayND(Arc::new(a.mapv(|c| c * factor))),
+            GraphData::List(items) => {
+                GraphData::List(items.iter().map(|item| item.scaled(factor)).collect::<Option<_>>()?)
+            }
+            _ => return None,
+        })
+    }
+
+    /// Feed the kind and content of the value to `hasher`; false if it is opaque
+    fn hash_content(&self, hasher: &mut ContentHasher) -> bool {
+        fn floats<'a>(hasher: &mut ContentHasher, values: impl ExactSizeIterator<Item = &'a f64>) {
+            hasher.write_usize(values.len());
+            for value in values {
+                hasher.write_u64(value.to_bits());
+            }
+        }
+        #[cfg(feature = "radar_examples")]
+        fn complexes<'a>(hasher: &mut ContentHasher, values: impl ExactSizeIterator<Item = &'a Complex<f64>>) {
+            hasher.write_usize(values.len());
+            for value in values {
+                hasher.write_u64(value.re.to_bits());
+                hasher.write_u64(value.im.to_bits());
+            }
+        }
+
+        let value = self.force();
+        value.kind().hash(hasher);
+        match value {
+            GraphData::Int(v) => v.hash(hasher),
+            GraphData::Float(v) => hasher.write_u64(v.to_bits()),
+            GraphData::Bool(v) => v.hash(hasher),
+            GraphData::String(v) => v.hash(hasher),
+            GraphData::FloatVec(v) => floats(hasher, v.iter()),
+            GraphData::IntVec(v) => v.hash(hasher),
+            GraphData::FloatSlice(v) => floats(hasher, v.iter()),
+            GraphData::IntSlice(v) => v[..].hash(hasher),
+            GraphData::Bytes(v) => v.hash(hasher),
+            #[cfg(feature = "radar_examples")]
+            GraphData::Complex(v) => complexes(hasher, std::iter::once(v)),
+            #[cfg(feature = "radar_examples")]
+            GraphData::FloatArray(v) => floats(hasher, v.iter()),
+            #[cfg(feature = "radar_examples")]
+            GraphData::ComplexArray(v) => complexes(hasher, v.iter()),
+            #[cfg(feature = "radar_examples")]
+            GraphData::FloatArrayND(v) => {
+                v.shape().hash(hasher);
+                floats(hasher, v.iter());
+            }
+            #[cfg(feature = "radar_examples")]
+            GraphData::ComplexArrayND(v) => {
+                v.shape().hash(hasher);
+                complexes(hasher, v.iter());
+            }
+            GraphData::Map(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                hasher.write_usize(keys.len());
+                for key in keys {
+                    key.hash(hasher);
+                    if !map[key].hash_content(hasher) {
+                        return false;
+                    }
+                }
+            }
+            GraphData::List(items) => {
+                hasher.write_usize(items.len());
+                if !items.iter().all(|item| item.hash_content(hasher)) {
+                    return false;
+                }
+            }
+            #[cfg(feature = "json")]
+            GraphData::Json(v) => v.to_string().hash(hasher),
+            #[cfg(feature = "polars")]
+            GraphData::DataFrame(_) => return false,
+            #[cfg(feature = "mmap")]
+            GraphData::Mapped(v) => v.bytes().hash(hasher),
+            GraphData::Custom(_) => return false,
+            GraphData::Lazy(_) => unreachable!("forced above"),
+            #[cfg(feature = "python")]
+            GraphData::PyObject(_) => return false,
+            GraphData::None => {}
+        }
+        true
+    }
+
     /// Create an Int variant
     pub fn int(value: i64) -> Self {
         GraphData::Int(value)
@@ -61,6 +333,11 @@ impl GraphData {
         GraphData::Float(value)
     }
 
+    /// Create a Bool variant
+    pub fn bool(value: bool) -> Self {
+        GraphData::Bool(value)
+    }
+
     /// Create a String variant
     pub fn string(value: impl Into<String>) -> Self {
         GraphData::String(value.into())
@@ -76,16 +353,105 @@ impl GraphData {
         GraphData::IntVec(Arc::new(value))
     }
 
+    /// Zero-copy view of part of a float or integer vector
+    ///
+    /// Works on `FloatVec`/`IntVec` and on existing slices (the range is then relative
+    /// to the slice).  The result shares the original buffer, so handing each of
+    /// several nodes its own segment of a large array costs no copies.  Returns
+    /// `None` for other variants or when the range is out of bounds.
+    ///
+    /// ```ignore
+    /// let half = data.len() / 2;
+    /// outputs.insert("first".to_string(), data.slice(..half).unwrap());
+    /// outputs.insert("second".to_string(), data.slice(half..).unwrap());
+    /// ```
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Option<Self> {
+        match self.force() {
+            GraphData::FloatVec(v) => SharedSlice::new(Arc::clone(v), range).map(GraphData::FloatSlice),
+            GraphData::IntVec(v) => SharedSlice::new(Arc::clone(v), range).map(GraphData::IntSlice),
+            GraphData::FloatSlice(s) => s.slice(range).map(GraphData::FloatSlice),
+            GraphData::IntSlice(s) => s.slice(range).map(GraphData::IntSlice),
+            _ => None,
+        }
+    }
+
+    /// Create a Bytes variant (wraps in Arc)
+    pub fn bytes(value: Vec<u8>) -> Self {
+        GraphData::Bytes(Arc::new(value))
+    }
+
     /// Create a Map variant
     pub fn map(value: HashMap<String, GraphData>) -> Self {
         GraphData::Map(value)
     }
 
+    /// Create a List variant
+    pub fn list(values: Vec<GraphData>) -> Self {
+        GraphData::List(values)
+    }
+
+    #[cfg(feature = "polars")]
+    /// Create a DataFrame variant
+    pub fn data_frame(value: DataFrame) -> Self {
+        GraphData::DataFrame(value)
+    }
+
+    #[cfg(feature = "mmap")]
+    /// Memory-map the file at `path` as a flat array of native-endian `f64` values
+    ///
+    /// Nothing is read up front; pages are loaded as nodes access the data through
+    /// `as_float_slice()` (or `as_complex_slice()` for interleaved complex samples).
+    /// The file must not be modified while any clone of the value is alive.
+    pub fn mmap(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        MappedFile::open(path).map(GraphData::Mapped)
+    }
+
+    /// Create a Lazy variant, computed by `init` the first time the value is read
+    ///
+    /// Reading means calling an accessor (`as_float_vec()`, `kind()`, ...) or
+    /// `force()`; passing the value through the context does not evaluate it.
+    ///
+    /// ```ignore
+    /// outputs.insert(
+    ///     "spectrogram".to_string(),
+    ///     GraphData::lazy(move || GraphData::float_vec(compute_spectrogram(&signal))),
+    /// );
+    /// ```
+    pub fn lazy(init: impl FnOnce() -> GraphData + Send + 'static) -> Self {
+        GraphData::Lazy(LazyValue::new(init))
+    }
+
+    /// The value itself, with lazy values evaluated (computing them if needed)
+    pub fn force(&self) -> &GraphData {
+        let mut value = self;
+        while let GraphData::Lazy(lazy) = value {
+            value = lazy.get();
+        }
+        value
+    }
+
+    /// Create a Custom variant holding any application type (wraps in Arc)
+    ///
+    /// ```ignore
+    /// outputs.insert("model".to_string(), GraphData::custom(TrainedModel::new()));
+    /// // ... in a downstream node:
+    /// let model = inputs["model"].downcast_ref::<TrainedModel>().unwrap();
+    /// ```
+    pub fn custom<T: Any + Send + Sync>(value: T) -> Self {
+        GraphData::Custom(Arc::new(value))
+    }
+
     /// Create a None variant
     pub fn none() -> Self {
         GraphData::None
     }
 
+    #[cfg(feature = "json")]
+    /// Create a Json variant
+    pub fn json(value: JsonValue) -> Self {
+        GraphData::Json(value)
+    }
+
     #[cfg(feature = "radar_examples")]
     /// Create a Complex variant
     pub fn complex(value: Complex<f64>) -> Self {
@@ -104,6 +470,18 @@ impl GraphData {
         GraphData::ComplexArray(Arc::new(value))
     }
 
+    #[cfg(feature = "radar_examples")]
+    /// Create a FloatArrayND variant (wraps in Arc)
+    pub fn array(value: ArrayD<f64>) -> Self {
+        GraphData::FloatArrayND(Arc::new(value))
+    }
+
+    #[cfg(feature = "radar_examples")]
+    /// Create a ComplexArrayND variant (wraps in Arc)
+    pub fn complex_array_nd(value: ArrayD<Complex<f64>>) -> Self {
+        GraphData::ComplexArrayND(Arc::new(value))
+    }
+
     #[cfg(feature = "python")]
     /// Create a PyObject variant (stores Python object without conversion)
     pub fn py_object(value: PyObject) -> Self {
@@ -112,7 +490,7 @@ impl GraphData {
 
     /// Try to extract as i64
     pub fn as_int(&self) -> Option<i64> {
-        match self {
+        match self.force() {
             GraphData::Int(v) => Some(*v),
             _ => None,
         }
@@ -120,7 +498,7 @@ impl GraphData {
 
     /// Try to extract as f64
     pub fn as_float(&self) -> Option<f64> {
-        match self {
+        match self.force() {
             GraphData::Float(v) => Some(*v),
             GraphData::Int(v) => Some(*v as f64),
             _ => None,
@@ -131,7 +509,7 @@ impl GraphData {
     /// `python` feature is enabled.  Used by `Dag::predict()` to collect
     /// Monte Carlo samples from the outputs of Python node functions.
     pub fn as_f64_lossy(&self) -> Option<f64> {
-        match self {
+        match self.force() {
             GraphData::Float(v) => Some(*v),
             GraphData::Int(v) => Some(*v as f64),
             #[cfg(feature = "python")]
@@ -146,9 +524,17 @@ impl GraphData {
         }
     }
 
+    /// Try to extract as bool
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.force() {
+            GraphData::Bool(v) => Some(*v),
+            _ => None,
+        }
+    }
+
     /// Try to extract as String reference
     pub fn as_string(&self) -> Option<&str> {
-        match self {
+        match self.force() {
             GraphData::String(s) => Some(s.as_str()),
             _ => None,
         }
@@ -156,7 +542,7 @@ impl GraphData {
 
     /// Try to extract as `Vec<f64>` reference (dereferences Arc)
     pub fn as_float_vec(&self) -> Option<&Vec<f64>> {
-        match self {
+        match self.force() {
             GraphData::FloatVec(v) => Some(v.as_ref()),
             _ => None,
         }
@@ -164,24 +550,80 @@ impl GraphData {
 
     /// Try to extract as `Vec<i64>` reference (dereferences Arc)
     pub fn as_int_vec(&self) -> Option<&Vec<i64>> {
-        match self {
+        match self.force() {
             GraphData::IntVec(v) => Some(v.as_ref()),
             _ => None,
         }
     }
 
+    /// Try to extract as `&[f64]` from a `FloatVec` or a `FloatSlice`
+    pub fn as_float_slice(&self) -> Option<&[f64]> {
+        match self.force() {
+            GraphData::FloatVec(v) => Some(v.as_slice()),
+            GraphData::FloatSlice(s) => Some(s),
+            #[cfg(feature = "mmap")]
+            GraphData::Mapped(m) => m.as_floats(),
+            _ => None,
+        }
+    }
+
+    /// Try to extract as `&[i64]` from an `IntVec` or an `IntSlice`
+    pub fn as_int_slice(&self) -> Option<&[i64]> {
+        match self.force() {
+            GraphData::IntVec(v) => Some(v.as_slice()),
+            GraphData::IntSlice(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Try to extract as byte slice (dereferences Arc)
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self.force() {
+            GraphData::Bytes(b) => Some(b.as_slice()),
+            #[cfg(feature = "mmap")]
+            GraphData::Mapped(m) => Some(m.bytes()),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "mmap")]
+    /// Try to extract as a memory-mapped file
+    pub fn as_mapped(&self) -> Option<&MappedFile> {
+        match self.force() {
+            GraphData::Mapped(m) => Some(m),
+            _ => None,
+        }
+    }
+
     /// Try to extract as HashMap reference
     pub fn as_map(&self) -> Option<&HashMap<String, GraphData>> {
-        match self {
+        match self.force() {
             GraphData::Map(m) => Some(m),
             _ => None,
         }
     }
 
+    /// Try to extract as list reference
+    pub fn as_list(&self) -> Option<&Vec<GraphData>> {
+        match self.force() {
+            GraphData::List(l) => Some(l),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "json")]
+    /// Try to extract as JSON value reference
+    pub fn as_json(&self) -> Option<&JsonValue> {
+        match self.force() {
+            GraphData::Json(v) => Some(v),
+            _ => None,
+        }
+    }
+
     #[cfg(feature = "radar_examples")]
     /// Try to extract as Complex<f64>
     pub fn as_complex(&self) -> Option<Complex<f64>> {
-        match self {
+        match self.force() {
             GraphData::Complex(c) => Some(*c),
             _ => None,
         }
@@ -190,7 +632,7 @@ impl GraphData {
     #[cfg(feature = "radar_examples")]
     /// Try to extract as Array1<f64> reference (dereferences Arc)
     pub fn as_float_array(&self) -> Option<&Array1<f64>> {
-        match self {
+        match self.force() {
             GraphData::FloatArray(a) => Some(a.as_ref()),
             _ => None,
         }
@@ -199,47 +641,229 @@ impl GraphData {
     #[cfg(feature = "radar_examples")]
     /// Try to extract as Array1<Complex<f64>> reference (dereferences Arc)
     pub fn as_complex_array(&self) -> Option<&Array1<Complex<f64>>> {
-        match self {
+        match self.force() {
             GraphData::ComplexArray(a) => Some(a.as_ref()),
             _ => None,
         }
     }
 
+    #[cfg(feature = "radar_examples")]
+    /// Try to extract as `&[Complex<f64>]` from a `ComplexArray` (in standard layout)
+    /// or a memory-mapped file
+    pub fn as_complex_slice(&self) -> Option<&[Complex<f64>]> {
+        match self.force() {
+            GraphData::ComplexArray(a) => a.as_slice(),
+            #[cfg(feature = "mmap")]
+            GraphData::Mapped(m) => m.as_complex(),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "radar_examples")]
+    /// Try to extract as ArrayD<f64> reference (dereferences Arc)
+    pub fn as_array(&self) -> Option<&ArrayD<f64>> {
+        match self.force() {
+            GraphData::FloatArrayND(a) => Some(a.as_ref()),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "radar_examples")]
+    /// Try to extract as ArrayD<Complex<f64>> reference (dereferences Arc)
+    pub fn as_complex_array_nd(&self) -> Option<&ArrayD<Complex<f64>>> {
+        match self.force() {
+            GraphData::ComplexArrayND(a) => Some(a.as_ref()),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "radar_examples")]
+    /// Shape of an array value (`[len]` for 1-D arrays), or `None` for other variants
+    pub fn shape(&self) -> Option<&[usize]> {
+        match self.force() {
+            GraphData::FloatArray(a) => Some(a.shape()),
+            GraphData::ComplexArray(a) => Some(a.shape()),
+            GraphData::FloatArrayND(a) => Some(a.shape()),
+            GraphData::ComplexArrayND(a) => Some(a.shape()),
+            _ => None,
+        }
+    }
+
     #[cfg(feature = "python")]
     /// Try to extract as PyObject reference
     pub fn as_py_object(&self) -> Option<&PyObject> {
-        match self {
+        match self.force() {
             GraphData::PyObject(obj) => Some(obj),
             _ => None,
         }
     }
 
+    #[cfg(feature = "polars")]
+    /// Try to extract as DataFrame reference
+    pub fn as_data_frame(&self) -> Option<&DataFrame> {
+        match self.force() {
+            GraphData::DataFrame(df) => Some(df),
+            _ => None,
+        }
+    }
+
+    /// Try to extract a Custom value as a reference to `T`
+    ///
+    /// Returns `None` for other variants and for Custom values of another type.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        match self.force() {
+            GraphData::Custom(v) => v.downcast_ref::<T>(),
+            _ => None,
+        }
+    }
+
     /// Check if this is None
     pub fn is_none(&self) -> bool {
-        matches!(self, GraphData::None)
+        matches!(self.force(), GraphData::None)
     }
 
     /// Convert GraphData to a string representation (for compatibility)
     pub fn to_string_repr(&self) -> String {
-        match self {
+        match self.force() {
             GraphData::Int(v) => v.to_string(),
             GraphData::Float(v) => v.to_string(),
+            GraphData::Bool(v) => v.to_string(),
             GraphData::String(s) => s.clone(),
             GraphData::FloatVec(v) => format!("{:?}", v),
             GraphData::IntVec(v) => format!("{:?}", v),
+            GraphData::FloatSlice(s) => format!("{:?}", s),
+            GraphData::IntSlice(s) => format!("{:?}", s),
+            GraphData::Bytes(b) => format!("{:?}", b),
             #[cfg(feature = "radar_examples")]
             GraphData::Complex(c) => format!("{:?}", c),
             #[cfg(feature = "radar_examples")]
             GraphData::FloatArray(a) => format!("{:?}", a),
             #[cfg(feature = "radar_examples")]
             GraphData::ComplexArray(a) => format!("{:?}", a),
+            #[cfg(feature = "radar_examples")]
+            GraphData::FloatArrayND(a) => format!("{:?}", a),
+            #[cfg(feature = "radar_examples")]
+            GraphData::ComplexArrayND(a) => format!("{:?}", a),
             GraphData::Map(m) => format!("{:?}", m),
+            GraphData::List(l) => format!("{:?}", l),
+            #[cfg(feature = "json")]
+            GraphData::Json(v) => v.to_string(),
+            #[cfg(feature = "polars")]
+            GraphData::DataFrame(df) => format!("<DataFrame {}x{}>", df.height(), df.width()),
+            #[cfg(feature = "mmap")]
+            GraphData::Mapped(m) => format!("<Mapped {}>", m.path().display()),
+            GraphData::Custom(_) => "<Custom>".to_string(),
+            GraphData::Lazy(_) => unreachable!("forced above"),
             #[cfg(feature = "python")]
             GraphData::PyObject(_) => "<PyObject>".to_string(),
             GraphData::None => "None".to_string(),
         }
     }
 
+    /// Bounded-length description of the value, safe to print for any payload size.
+    ///
+    /// Small values render exactly like a derived `Debug` (`Int(42)`, `FloatVec([1.0, 2.0])`).
+    /// Collections longer than `SUMMARY_PREVIEW_LEN` elements show their length, the first
+    /// few elements, and a checksum of the full contents; strings longer than
+    /// `SUMMARY_MAX_STRING_LEN` characters are cut off with their length appended.
+    pub fn summary(&self) -> String {
+        match self {
+            GraphData::Int(v) => format!("Int({})", v),
+            GraphData::Float(v) => format!("Float({:?})", v),
+            GraphData::Bool(v) => format!("Bool({})", v),
+            GraphData::String(s) => format!("String({})", summarize_str(s)),
+            GraphData::FloatVec(v) => format!(
+                "FloatVec({})",
+                summarize_seq(v.iter(), v.len(), || checksum(v.iter().map(|x| x.to_bits())))
+            ),
+            GraphData::IntVec(v) => format!(
+                "IntVec({})",
+                summarize_seq(v.iter(), v.len(), || checksum(v.iter().map(|x| *x as u64)))
+            ),
+            GraphData::FloatSlice(s) => format!(
+                "FloatSlice({})",
+                summarize_seq(s.iter(), s.len(), || checksum(s.iter().map(|x| x.to_bits())))
+            ),
+            GraphData::IntSlice(s) => format!(
+                "IntSlice({})",
+                summarize_seq(s.iter(), s.len(), || checksum(s.iter().map(|x| *x as u64)))
+            ),
+            GraphData::Bytes(b) => format!(
+                "Bytes({})",
+                summarize_seq(b.iter(), b.len(), || checksum(b.iter().map(|x| u64::from(*x))))
+            ),
+            #[cfg(feature = "radar_examples")]
+            GraphData::Complex(c) => format!("Complex({:?})", c),
+            #[cfg(feature = "radar_examples")]
+            GraphData::FloatArray(a) => format!(
+                "FloatArray({})",
+                summarize_seq(a.iter(), a.len(), || checksum(a.iter().map(|x| x.to_bits())))
+            ),
+            #[cfg(feature = "radar_examples")]
+            GraphData::ComplexArray(a) => format!(
+                "ComplexArray({})",
+                summarize_seq(a.iter(), a.len(), || {
+                    checksum(a.iter().flat_map(|c| [c.re.to_bits(), c.im.to_bits()]))
+                })
+            ),
+            #[cfg(feature = "radar_examples")]
+            GraphData::FloatArrayND(a) => format!(
+                "FloatArrayND(shape={:?}, {})",
+                a.shape(),
+                summarize_seq(a.iter(), a.len(), || checksum(a.iter().map(|x| x.to_bits())))
+            ),
+            #[cfg(feature = "radar_examples")]
+            GraphData::ComplexArrayND(a) => format!(
+                "ComplexArrayND(shape={:?}, {})",
+                a.shape(),
+                summarize_seq(a.iter(), a.len(), || {
+                    checksum(a.iter().flat_map(|c| [c.re.to_bits(), c.im.to_bits()]))
+                })
+            ),
+            GraphData::Map(m) => {
+                let mut keys: Vec<&String> = m.keys().collect();
+                keys.sort();
+                let entries: Vec<String> = keys
+                    .iter()
+                    .take(SUMMARY_PREVIEW_LEN)
+                    .map(|k| format!("{}: {}", summarize_str(k), m[*k].summary()))
+                    .collect();
+                if m.len() > SUMMARY_PREVIEW_LEN {
+                    format!("Map(len={}, {{{}, ...}})", m.len(), entries.join(", "))
+                } else {
+                    format!("Map({{{}}})", entries.join(", "))
+                }
+            }
+            GraphData::List(l) => format!(
+                "List({})",
+                summarize_seq(l.iter(), l.len(), || {
+                    checksum(l.iter().map(|x| checksum(x.summary().bytes().map(u64::from))))
+                })
+            ),
+            #[cfg(feature = "json")]
+            GraphData::Json(v) => format!("Json({})", summarize_str(&v.to_string())),
+            #[cfg(feature = "polars")]
+            GraphData::DataFrame(df) => {
+                let columns = df.get_column_names();
+                format!(
+                    "DataFrame(shape=({}, {}), columns={})",
+                    df.height(),
+                    df.width(),
+                    summarize_seq(columns.iter().map(|c| c.as_str()), columns.len(), || {
+                        checksum(columns.iter().flat_map(|c| c.bytes().map(u64::from)))
+                    })
+                )
+            }
+            #[cfg(feature = "mmap")]
+            GraphData::Mapped(m) => format!("Mapped({:?})", m),
+            GraphData::Custom(_) => "Custom(..)".to_string(),
+            GraphData::Lazy(lazy) => format!("Lazy({:?})", lazy),
+            #[cfg(feature = "python")]
+            GraphData::PyObject(obj) => format!("PyObject({:?})", obj),
+            GraphData::None => "None".to_string(),
+        }
+    }
+
     /// Try to parse GraphData from a string
     pub fn from_string(s: &str) -> Self {
         // Try to parse as i64
@@ -255,9 +879,105 @@ impl GraphData {
     }
 }
 
-impl Default for GraphData {
-    fn default() -> Self {
-        GraphData::None
+/// Maximum number of elements shown by `GraphData::summary()` before truncating.
+pub const SUMMARY_PREVIEW_LEN: usize = 8;
+
+/// Maximum number of characters of a string shown by `GraphData::summary()`.
+pub const SUMMARY_MAX_STRING_LEN: usize = 64;
+
+impl std::fmt::Debug for GraphData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.summary())
+    }
+}
+
+/// Quote a string for `summary()`, truncating long strings.
+fn summarize_str(s: &str) -> String {
+    let len = s.chars().count();
+    if len > SUMMARY_MAX_STRING_LEN {
+        let head: String = s.chars().take(SUMMARY_MAX_STRING_LEN).collect();
+        format!("{:?}... (len={})", head, len)
+    } else {
+        format!("{:?}", s)
+    }
+}
+
+/// Format a sequence for `summary()`: full list when short, otherwise
+/// `len=N, [first elements, ...], checksum=...`.
+fn summarize_seq<T: std::fmt::Debug>(
+    items: impl Iterator<Item = T>,
+    len: usize,
+    checksum: impl FnOnce() -> u64,
+) -> String {
+    let preview: Vec<String> = items
+        .take(SUMMARY_PREVIEW_LEN)
+        .map(|x| format!("{:?}", x))
+        .collect();
+    if len > SUMMARY_PREVIEW_LEN {
+        format!(
+            "len={}, [{}, ...], checksum={:016x}",
+            len,
+            preview.join(", "),
+            checksum()
+        )
+    } else {
+        format!("[{}]", preview.join(", "))
+    }
+}
+
+/// FNV-1a over 64-bit words; cheap and stable across runs and platforms.
+fn checksum(words: impl Iterator<Item = u64>) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for word in words {
+        for byte in word.to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+/// Serialize `Bytes` as a byte string rather than a sequence of numbers
+#[cfg(feature = "serde")]
+mod serde_bytes_arc {
+    use serde::de::{Deserializer, SeqAccess, Visitor};
+    use serde::Serializer;
+    use std::fmt;
+    use std::sync::Arc;
+
+    pub fn serialize<S: Serializer>(bytes: &Arc<Vec<u8>>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(bytes)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Arc<Vec<u8>>, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a byte string")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+                Ok(v.to_vec())
+            }
+
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+                Ok(v)
+            }
+
+            // Self-describing text formats (e.g. JSON) write bytes as a list of numbers
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+                let mut v = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(b) = seq.next_element()? {
+                    v.push(b);
+                }
+                Ok(v)
+            }
+        }
+
+        deserializer.deserialize_byte_buf(BytesVisitor).map(Arc::new)
     }
 }
 
@@ -273,6 +993,12 @@ impl From<f64> for GraphData {
     }
 }
 
+impl From<bool> for GraphData {
+    fn from(v: bool) -> Self {
+        GraphData::Bool(v)
+    }
+}
+
 impl From<String> for GraphData {
     fn from(v: String) -> Self {
         GraphData::String(v)
@@ -297,6 +1023,26 @@ impl From<Vec<i64>> for GraphData {
     }
 }
 
+impl From<Vec<u8>> for GraphData {
+    fn from(v: Vec<u8>) -> Self {
+        GraphData::Bytes(Arc::new(v))
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<JsonValue> for GraphData {
+    fn from(v: JsonValue) -> Self {
+        GraphData::Json(v)
+    }
+}
+
+#[cfg(feature = "polars")]
+impl From<DataFrame> for GraphData {
+    fn from(v: DataFrame) -> Self {
+        GraphData::DataFrame(v)
+    }
+}
+
 #[cfg(feature = "radar_examples")]
 impl From<Complex<f64>> for GraphData {
     fn from(v: Complex<f64>) -> Self {
@@ -318,7 +1064,22 @@ impl From<Array1<Complex<f64>>> for GraphData {
     }
 }
 
+#[cfg(feature = "radar_examples")]
+impl From<ArrayD<f64>> for GraphData {
+    fn from(v: ArrayD<f64>) -> Self {
+        GraphData::FloatArrayND(Arc::new(v))
+    }
+}
+
+#[cfg(feature = "radar_examples")]
+impl From<ArrayD<Complex<f64>>> for GraphData {
+    fn from(v: ArrayD<Complex<f64>>) -> Self {
+        GraphData::ComplexArrayND(Arc::new(v))
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::approx_constant)] // 3.14 is just a float fixture here
 mod tests {
     use super::*;
 
@@ -354,6 +1115,163 @@ mod tests {
         assert_eq!(data.as_int_vec(), Some(&vec![1, 2, 3]));
     }
 
+    #[test]
+    fn test_bool_construction() {
+        let data = GraphData::bool(true);
+        assert_eq!(data.as_bool(), Some(true));
+        assert!(data.as_int().is_none());
+        assert_eq!(data.kind(), GraphDataKind::Bool);
+        assert_eq!(data.summary(), "Bool(true)");
+    }
+
+    #[test]
+    fn test_bytes_construction() {
+        let data = GraphData::bytes(vec![0xde, 0xad]);
+        assert_eq!(data.as_bytes(), Some(&[0xde, 0xad][..]));
+        assert_eq!(data.summary(), "Bytes([222, 173])");
+        assert!(GraphData::bytes(vec![0; 4096]).summary().contains("len=4096"));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_construction() {
+        let data = GraphData::json(serde_json::json!({"window": "hann", "size": 256}));
+        assert_eq!(data.as_json().and_then(|v| v["size"].as_i64()), Some(256));
+        assert_eq!(data.kind(), GraphDataKind::Json);
+        assert_eq!(data.to_string_repr(), r#"{"size":256,"window":"hann"}"#);
+    }
+
+    #[cfg(feature = "radar_examples")]
+    #[test]
+    fn test_nd_array_keeps_shape() {
+        let pulses = ndarray::Array2::from_shape_fn((4, 16), |(p, s)| (p * 16 + s) as f64);
+        let data = GraphData::array(pulses.into_dyn());
+        assert_eq!(data.shape(), Some(&[4, 16][..]));
+        assert_eq!(data.as_array().map(|a| a[[1, 2]]), Some(18.0));
+        assert_eq!(data.kind(), GraphDataKind::FloatArrayND);
+        assert!(data.summary().starts_with("FloatArrayND(shape=[4, 16], len=64"));
+
+        let iq = ndarray::ArrayD::from_elem(vec![2, 3, 5], Complex::new(1.0, -1.0));
+        let data = GraphData::complex_array_nd(iq);
+        assert_eq!(data.shape(), Some(&[2, 3, 5][..]));
+        assert_eq!(data.as_complex_array_nd().map(|a| a.ndim()), Some(3));
+    }
+
+    #[test]
+    fn test_custom_downcast() {
+        #[derive(Debug, PartialEq)]
+        struct Model {
+            weights: Vec<f32>,
+        }
+        let data = GraphData::custom(Model { weights: vec![0.5, 0.25] });
+        assert_eq!(
+            data.downcast_ref::<Model>(),
+            Some(&Model { weights: vec![0.5, 0.25] })
+        );
+        assert!(data.downcast_ref::<String>().is_none());
+        assert!(GraphData::int(1).downcast_ref::<i64>().is_none());
+        assert_eq!(data.kind(), GraphDataKind::Custom);
+        assert_eq!(data.summary(), "Custom(..)");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut map = HashMap::new();
+        map.insert("flag".to_string(), GraphData::bool(true));
+        map.insert("blob".to_string(), GraphData::bytes(vec![1, 2, 255]));
+        let data = GraphData::list(vec![
+            GraphData::int(7),
+            GraphData::float_vec(vec![0.5, 1.5]),
+            GraphData::string("x"),
+            GraphData::map(map),
+            GraphData::none(),
+        ]);
+
+        let text = serde_json::to_string(&data).unwrap();
+        assert!(text.starts_with(r#"{"List":[{"Int":7},{"FloatVec":[0.5,1.5]}"#));
+        let back: GraphData = serde_json::from_str(&text).unwrap();
+        assert_eq!(back.summary(), data.summary());
+
+        assert!(serde_json::to_string(&GraphData::custom(1u8)).is_err());
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_data_frame_construction() {
+        use polars::prelude::*;
+        let df = df!("range_m" => [10.0, 20.0, 30.0], "snr_db" => [12.5, 9.0, 3.25]).unwrap();
+        let data = GraphData::data_frame(df);
+        assert_eq!(data.as_data_frame().map(|df| df.shape()), Some((3, 2)));
+        assert_eq!(data.kind(), GraphDataKind::DataFrame);
+        assert_eq!(
+            data.summary(),
+            "DataFrame(shape=(3, 2), columns=[\"range_m\", \"snr_db\"])"
+        );
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_mmap_float_file() {
+        let values = [1.5f64, -2.0, 3.25];
+        let path = std::env::temp_dir().join(format!("dagex_mmap_{}.f64", std::process::id()));
+        let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_ne_bytes()).collect();
+        std::fs::write(&path, bytes).unwrap();
+
+        let data = GraphData::mmap(&path).unwrap();
+        assert_eq!(data.as_float_slice(), Some(&values[..]));
+        assert_eq!(data.as_bytes().map(<[u8]>::len), Some(24));
+        assert_eq!(data.kind(), GraphDataKind::Mapped);
+        assert!(data.summary().ends_with("(24 bytes))"));
+        drop(data);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(GraphData::mmap(&path).is_err());
+    }
+
+    #[test]
+    fn test_slice_shares_buffer() {
+        let data = GraphData::int_vec((0..10).collect());
+        let tail = data.slice(4..).unwrap();
+        assert_eq!(tail.as_int_slice(), Some(&[4, 5, 6, 7, 8, 9][..]));
+        assert_eq!(tail.kind(), GraphDataKind::IntSlice);
+
+        // Slicing a slice is relative to it and still points into the original buffer
+        let middle = tail.slice(1..=2).unwrap();
+        assert_eq!(middle.as_int_slice(), Some(&[5, 6][..]));
+        let (GraphData::IntVec(buffer), GraphData::IntSlice(view)) = (&data, &middle) else {
+            panic!("unexpected variants");
+        };
+        assert!(Arc::ptr_eq(buffer, view.buffer()));
+        assert_eq!(view.range(), 5..7);
+
+        assert!(data.slice(5..11).is_none());
+        assert!(GraphData::int(1).slice(..).is_none());
+        assert_eq!(
+            GraphData::float_vec(vec![1.0, 2.0]).slice(1..).unwrap().summary(),
+            "FloatSlice([2.0])"
+        );
+    }
+
+    #[test]
+    fn test_lazy_computed_once_on_first_access() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let data = GraphData::lazy(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            GraphData::float_vec(vec![1.0, 2.0])
+        });
+        let copy = data.clone();
+        assert_eq!(data.summary(), "Lazy(<pending>)");
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        assert_eq!(copy.as_float_vec(), Some(&vec![1.0, 2.0]));
+        assert_eq!(data.kind(), GraphDataKind::FloatVec);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(data.summary(), "Lazy(FloatVec([1.0, 2.0]))");
+    }
+
     #[test]
     fn test_map_construction() {
         let mut map = HashMap::new();
@@ -385,6 +1303,12 @@ mod tests {
 
         let d4: GraphData = vec![1.0, 2.0].into();
         assert_eq!(d4.as_float_vec(), Some(&vec![1.0, 2.0]));
+
+        let d5: GraphData = false.into();
+        assert_eq!(d5.as_bool(), Some(false));
+
+        let d6: GraphData = b"raw".to_vec().into();
+        assert_eq!(d6.as_bytes(), Some(&b"raw"[..]));
     }
 
     #[test]
@@ -395,6 +1319,56 @@ mod tests {
         assert!(GraphData::none().to_string_repr().contains("None"));
     }
 
+    #[test]
+    fn test_summary_small_values_match_debug_style() {
+        assert_eq!(GraphData::int(42).summary(), "Int(42)");
+        assert_eq!(GraphData::float(2.5).summary(), "Float(2.5)");
+        assert_eq!(GraphData::string("hi").summary(), "String(\"hi\")");
+        assert_eq!(GraphData::float_vec(vec![1.0, 2.0]).summary(), "FloatVec([1.0, 2.0])");
+        assert_eq!(format!("{:?}", GraphData::int_vec(vec![1, 2])), "IntVec([1, 2])");
+        assert_eq!(GraphData::none().summary(), "None");
+        assert_eq!(
+            GraphData::list(vec![GraphData::int(1), GraphData::string("a")]).summary(),
+            "List([Int(1), String(\"a\")])"
+        );
+    }
+
+    #[test]
+    fn test_summary_is_bounded_for_large_values() {
+        let big = GraphData::float_vec((0..1_000_000).map(|i| i as f64).collect());
+        let summary = big.summary();
+        assert!(summary.len() < 200, "summary too long: {}", summary);
+        assert!(summary.contains("len=1000000"));
+        assert!(summary.contains("0.0, 1.0"));
+        assert!(summary.contains("checksum="));
+        assert_eq!(format!("{:?}", big), summary);
+
+        let long = GraphData::string("x".repeat(10_000));
+        assert!(long.summary().contains("len=10000"));
+        assert!(long.summary().len() < 100);
+    }
+
+    #[test]
+    fn test_summary_checksum_detects_changes() {
+        let a = GraphData::int_vec((0..100).collect());
+        let mut changed: Vec<i64> = (0..100).collect();
+        changed[99] = -1;
+        let b = GraphData::int_vec(changed);
+        assert_ne!(a.summary(), b.summary());
+        assert_eq!(a.summary(), GraphData::int_vec((0..100).collect()).summary());
+    }
+
+    #[test]
+    fn test_summary_map_is_sorted_and_bounded() {
+        let mut map = HashMap::new();
+        for i in 0..20 {
+            map.insert(format!("k{:02}", i), GraphData::int(i));
+        }
+        let summary = GraphData::map(map).summary();
+        assert!(summary.starts_with("Map(len=20, {\"k00\": Int(0)"));
+        assert!(!summary.contains("k19"));
+    }
+
     #[test]
     fn test_from_string() {
         let d1 = GraphData::from_string("42");
@@ -0,0 +1,173 @@
+//! Standalone HTML report of a timed execution
+//!
+//! `Dag::to_html_report()` bundles the Mermaid diagram, per-node timings, output
+//! previews, and a table of variant sweeps into one HTML document that can be
+//! written to disk and opened in a browser.
+
+use crate::dag::Dag;
+use crate::node::Node;
+use crate::report::ExecutionReport;
+use std::time::Duration;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:2em}\
+th,td{border:1px solid #ccc;padding:4px 8px;text-align:left;vertical-align:top}\
+th{background:#f0f0f0}td.num{text-align:right;font-variant-numeric:tabular-nums}\
+code{font-size:90%}.bar{background:#4a90d9;height:10px}";
+
+impl Dag {
+    /// Render an execution report as a self-contained HTML page
+    ///
+    /// The page contains:
+    /// - a summary (node count, depth, wall-clock and busy time),
+    /// - the Mermaid diagram of the DAG,
+    /// - a table of node timings with a preview of every output (`GraphData::summary()`),
+    /// - a table of variant nodes with their parameters, when the DAG contains sweeps.
+    ///
+    /// All data is inlined.  The diagram is rendered by mermaid.js loaded from a CDN;
+    /// without network access the Mermaid source is shown instead.
+    pub fn to_html_report(&self, report: &ExecutionReport) -> String {
+        let stats = self.stats();
+        let mut html = String::new();
+
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str("<title>dagex execution report</title>\n");
+        html.push_str(&format!("<style>{}</style>\n", STYLE));
+        html.push_str("</head>\n<body>\n<h1>Execution report</h1>\n");
+
+        // ── Summary ───────────────────────────────────────────────────────────
+        html.push_str("<table>\n");
+        for (name, value) in [
+            ("Nodes", stats.node_count.to_string()),
+            ("Depth", format!("{} levels", stats.depth)),
+            ("Max parallelism", stats.max_parallelism.to_string()),
+            ("Wall-clock time", format_ms(report.total_duration)),
+            ("Busy time (sum of nodes)", format_ms(report.busy_time())),
+        ] {
+            html.push_str(&format!(
+                "<tr><th>{}</th><td>{}</td></tr>\n",
+                name,
+                html_escape(&value)
+            ));
+        }
+        html.push_str("</table>\n");
+
+        // ── Diagram ───────────────────────────────────────────────────────────
+        html.push_str("<h2>Graph</h2>\n<pre class=\"mermaid\">\n");
+        html.push_str(&html_escape(&self.to_mermaid()));
+        html.push_str("</pre>\n");
+
+        // ── Node timings ──────────────────────────────────────────────────────
+        html.push_str("<h2>Nodes</h2>\n<table>\n");
+        html.push_str(
+            "<tr><th>ID</th><th>Label</th><th>Worker</th><th>Start</th>\
+             <th>Duration</th><th></th><th>Outputs</th></tr>\n",
+        );
+        let longest = report
+            .timings
+            .iter()
+            .map(|t| t.duration())
+            .max()
+            .unwrap_or_default()
+            .max(Duration::from_nanos(1));
+        let mut timings: Vec<_> = report.timings.iter().collect();
+        timings.sort_by_key(|t| (t.start, t.node_id));
+        for t in timings {
+            let width = 100.0 * t.duration().as_secs_f64() / longest.as_secs_f64();
+            html.push_str(&format!(
+                "<tr><td class=\"num\">{}</td><td>{}</td><td class=\"num\">{}</td>\
+                 <td class=\"num\">{}</td><td class=\"num\">{}</td>\
+                 <td style=\"width:120px\"><div class=\"bar\" style=\"width:{:.0}%\"></div></td>\
+                 <td>{}</td></tr>\n",
+                t.node_id,
+                html_escape(&t.label),
+                t.worker,
+                format_ms(t.start),
+                format_ms(t.duration()),
+                width,
+                outputs_cell(report, t.node_id)
+            ));
+        }
+        html.push_str("</table>\n");
+
+        // ── Variant sweeps ────────────────────────────────────────────────────
+        let mut variant_nodes: Vec<&Node> = self
+            .nodes()
+            .iter()
+            .filter(|n| n.variant_index.is_some())
+            .collect();
+        if !variant_nodes.is_empty() {
+            variant_nodes.sort_by_key(|n| (n.variant_index, n.id));
+            html.push_str("<h2>Variants</h2>\n<table>\n");
+            html.push_str(
+                "<tr><th>Variant</th><th>ID</th><th>Label</th><th>Parameters</th>\
+                 <th>Outputs</th></tr>\n",
+            );
+            for node in variant_nodes {
+                let mut params: Vec<String> = node
+                    .variant_params
+                    .iter()
+                    .map(|(k, v)| format!("{} = {}", k, v.to_string_repr()))
+                    .collect();
+                params.sort();
+                html.push_str(&format!(
+                    "<tr><td class=\"num\">{}</td><td class=\"num\">{}</td><td>{}</td>\
+                     <td>{}</td><td>{}</td></tr>\n",
+                    node.variant_index.unwrap_or_default(),
+                    node.id,
+                    html_escape(&node.display_name()),
+                    html_escape(&params.join(", ")),
+                    outputs_cell(report, node.id)
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+
+        html.push_str(
+            "<script type=\"module\">\n\
+             import mermaid from 'https://cdn.jsdelivr.net/npm/mermaid@10/dist/mermaid.esm.min.mjs';\n\
+             mermaid.initialize({ startOnLoad: true });\n\
+             </script>\n",
+        );
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+/// Bounded preview of a node's outputs, one `<code>` line per broadcast variable.
+fn outputs_cell(report: &ExecutionReport, node_id: usize) -> String {
+    let Some(outputs) = report.result.get_node_outputs(node_id) else {
+        return String::new();
+    };
+    let mut keys: Vec<&String> = outputs.keys().collect();
+    keys.sort();
+    keys.iter()
+        .map(|k| {
+            format!(
+                "<code>{} = {}</code>",
+                html_escape(k),
+                html_escape(&outputs[*k].summary())
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+fn format_ms(d: Duration) -> String {
+    format!("{:.3} ms", d.as_secs_f64() * 1000.0)
+}
+
+fn html_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,113 @@
+//! HTTP request nodes
+//!
+//! `http_get()` and `http_post()` build node functions performing one request
+//! per execution.  Map context variables onto their ports like any other node:
+//!
+//! ```ignore
+//! graph.add(
+//!     http_get(),
+//!     Some("Fetch"),
+//!     Some(vec![("endpoint", "url"), ("auth_headers", "headers")]),
+//!     Some(vec![("status", "fetch_status"), ("body", "raw_page")]),
+//! );
+//! ```
+//!
+//! Inputs:
+//! * `url` — string
+//! * `headers` — optional map of header names to string values
+//! * `body` (`http_post()` only) — string, bytes, or (with the `json` feature) a
+//!   JSON document sent as `application/json`
+//!
+//! Outputs:
+//! * `status` — integer status code
+//! * `body` — string, or bytes if the response is not UTF-8
+//! * `headers` — map of response header names (lowercase) to string values
+//!
+//! A request that gets no response (bad URL, connection refused, timeout)
+//! produces only an `error` string output, so nodes reading `status` or `body`
+//! see a missing input.  Error statuses (4xx, 5xx) are responses like any other.
+
+use crate::file_io::fallible;
+use crate::graph_data::GraphData;
+use reqwest::blocking::{Client, RequestBuilder};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Time limit of a whole request
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Node function issuing a GET request (see the module documentation)
+pub fn http_get(
+) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> + Send + Sync + 'static {
+    request_node(|client, url, _| Ok(client.get(url)))
+}
+
+/// Node function issuing a POST request with the `body` input (see the module
+/// documentation)
+pub fn http_post(
+) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> + Send + Sync + 'static {
+    request_node(|client, url, inputs| {
+        let request = client.post(url);
+        Ok(match inputs.get("body").map(|body| body.force()) {
+            None | Some(GraphData::None) => request,
+            Some(GraphData::String(text)) => request.body(text.clone()),
+            Some(GraphData::Bytes(bytes)) => request.body(bytes.to_vec()),
+            #[cfg(feature = "json")]
+            Some(GraphData::Json(value)) => {
+                request.header(reqwest::header::CONTENT_TYPE, "application/json").body(value.to_string())
+            }
+            Some(other) => return Err(format!("cannot send {} as a request body", other.kind())),
+        })
+    })
+}
+
+/// Node function sending the request `build` makes for the `url` input, with one
+/// client (and its connection pool) shared by all executions of the node
+fn request_node<F>(
+    build: F,
+) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> + Send + Sync + 'static
+where
+    F: Fn(&Client, &str, &HashMap<String, Arc<GraphData>>) -> Result<RequestBuilder, String>
+        + Send
+        + Sync
+        + 'static,
+{
+    let client = Client::builder().timeout(TIMEOUT).build().expect("failed to create the HTTP client");
+    fallible(move |inputs| send(&client, &build, inputs))
+}
+
+fn send<F>(
+    client: &Client,
+    build: &F,
+    inputs: &HashMap<String, Arc<GraphData>>,
+) -> Result<HashMap<String, GraphData>, String>
+where
+    F: Fn(&Client, &str, &HashMap<String, Arc<GraphData>>) -> Result<RequestBuilder, String>,
+{
+    let url = inputs.get("url").and_then(|url| url.as_string()).ok_or("missing string input 'url'")?;
+    let mut request = build(client, url, inputs)?;
+    if let Some(headers) = inputs.get("headers").and_then(|headers| headers.as_map()) {
+        for (name, value) in headers {
+            let value = value.as_string().ok_or_else(|| format!("header '{}' is not a string", name))?;
+            request = request.header(name, value);
+        }
+    }
+    let response = request.send().map_err(|e| e.to_string())?;
+    let status = GraphData::int(i64::from(response.status().as_u16()));
+    let headers = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), GraphData::string(value.to_str().ok()?))))
+        .collect();
+    let body = response.bytes().map_err(|e| e.to_string())?.to_vec();
+    let body = match String::from_utf8(body) {
+        Ok(text) => GraphData::string(text),
+        Err(e) => GraphData::bytes(e.into_bytes()),
+    };
+    Ok(HashMap::from([
+        ("status".to_string(), status),
+        ("body".to_string(), body),
+        ("headers".to_string(), GraphData::map(headers)),
+    ]))
+}
@@ -0,0 +1,120 @@
+//! Time-sliced cooperative execution
+//!
+//! `Dag::poll_execute()` advances an `IncrementalExecution` by as many nodes as fit
+//! into a time budget and then returns, so an application can drive a pipeline from
+//! its own event loop (e.g. a GUI frame callback) without spawning threads.
+//!
+//! ```ignore
+//! let mut run = dag.start_execution();
+//! while dag.poll_execute(&mut run, Duration::from_millis(8)) == ExecutionPoll::Pending {
+//!     redraw_progress(run.completed_nodes(), run.total_nodes());
+//! }
+//! let result = run.into_result();
+//! ```
+
+use crate::dag::{Dag, ExecutionResult};
+use crate::graph_data::GraphData;
+use crate::payload::Payload;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Outcome of a single `Dag::poll_execute()` call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionPoll {
+    /// Nodes remain to be executed; call `poll_execute()` again.
+    Pending,
+    /// Every node has been executed; the result is final.
+    Complete,
+}
+
+/// State of an execution that is advanced step by step with `Dag::poll_execute()`.
+#[derive(Debug, Clone)]
+pub struct IncrementalExecution<T = GraphData> {
+    result: ExecutionResult<T>,
+    /// Position of the next node in the DAG's execution order
+    next: usize,
+    total: usize,
+}
+
+impl<T> IncrementalExecution<T> {
+    /// Number of nodes executed so far.
+    pub fn completed_nodes(&self) -> usize {
+        self.next
+    }
+
+    /// Total number of nodes that will be executed.
+    pub fn total_nodes(&self) -> usize {
+        self.total
+    }
+
+    /// `true` once every node has been executed.
+    pub fn is_complete(&self) -> bool {
+        self.next >= self.total
+    }
+
+    /// Outputs produced so far.
+    pub fn result(&self) -> &ExecutionResult<T> {
+        &self.result
+    }
+
+    /// Consume the state and return the (possibly partial) result.
+    pub fn into_result(self) -> ExecutionResult<T> {
+        self.result
+    }
+}
+
+impl<T: Payload> Dag<T> {
+    /// Create the state for a time-sliced execution driven by `poll_execute()`.
+    pub fn start_execution(&self) -> IncrementalExecution<T> {
+        let mut result = ExecutionResult::new();
+        result.context.extend(
+            self.declared_inputs()
+                .iter()
+                .map(|(name, value)| (name.clone(), Arc::new(value.clone()))),
+        );
+        IncrementalExecution {
+            result,
+            next: 0,
+            total: self.execution_order().len(),
+        }
+    }
+
+    /// Advance `state` by executing nodes sequentially until `budget` has elapsed.
+    ///
+    /// Nodes are never interrupted, so a call can overrun the budget by up to one
+    /// node's duration.  At least one node is executed per call, which guarantees
+    /// progress even with a zero budget.  Results are identical to
+    /// `execute_detailed(false, None)`.
+    ///
+    /// `state` must have been created by `start_execution()` on this DAG.
+    pub fn poll_execute(
+        &self,
+        state: &mut IncrementalExecution<T>,
+        budget: Duration,
+    ) -> ExecutionPoll {
+        let started = Instant::now();
+        let order = self.execution_order();
+
+        while state.next < order.len() {
+            let node_id = order[state.next];
+            if let Some((node, plan)) = self.planned(node_id) {
+                let result = &mut state.result;
+                let upstream_skipped = plan.skipped_by_dependencies(|id| result.skipped_nodes.contains(id));
+                let outcome =
+                    node.execute_guarded(&plan.inputs, &result.context, upstream_skipped, self.middleware());
+                self.record_outcome(result, node, plan, outcome);
+            }
+            state.next += 1;
+
+            if started.elapsed() >= budget {
+                break;
+            }
+        }
+
+        if state.is_complete() {
+            ExecutionPoll::Complete
+        } else {
+            ExecutionPoll::Pending
+        }
+    }
+}
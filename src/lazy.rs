@@ -0,0 +1,78 @@
+//! `LazyValue` — a `GraphData` computed the first time it is read
+//!
+//! Created by `GraphData::lazy()`.  A source node can offer many expensive outputs
+//! as lazy values; only those that a downstream node actually reads (through an
+//! `as_*` accessor, `kind()`, or `GraphData::force()`) are ever computed.  Clones
+//! share state, so the computation runs at most once however many nodes read the
+//! value.
+
+use crate::graph_data::GraphData;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+
+type Init = Box<dyn FnOnce() -> GraphData + Send>;
+
+/// A deferred `GraphData` value, evaluated on first access.
+#[derive(Clone)]
+pub struct LazyValue {
+    value: Arc<OnceLock<GraphData>>,
+    init: Arc<Mutex<Option<Init>>>,
+}
+
+impl LazyValue {
+    /// Defer `init` until the value is first read.
+    pub fn new(init: impl FnOnce() -> GraphData + Send + 'static) -> Self {
+        Self {
+            value: Arc::new(OnceLock::new()),
+            init: Arc::new(Mutex::new(Some(Box::new(init)))),
+        }
+    }
+
+    /// The value, computing it if this is the first access.
+    pub fn get(&self) -> &GraphData {
+        self.value.get_or_init(|| {
+            let init = self
+                .init
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .take();
+            init.map_or(GraphData::None, |f| f())
+        })
+    }
+
+    /// Whether the value has been computed yet.
+    pub fn is_evaluated(&self) -> bool {
+        self.value.get().is_some()
+    }
+}
+
+impl fmt::Debug for LazyValue {
+    /// Does not trigger the computation.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.value.get() {
+            Some(value) => fmt::Debug::fmt(value, f),
+            None => f.write_str("<pending>"),
+        }
+    }
+}
+
+/// Serialized as the computed value (evaluating it if needed).
+#[cfg(feature = "serde")]
+impl serde::Serialize for LazyValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for LazyValue {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = GraphData::deserialize(deserializer)?;
+        let cell = OnceLock::new();
+        let _ = cell.set(value);
+        Ok(Self {
+            value: Arc::new(cell),
+            init: Arc::new(Mutex::new(None)),
+        })
+    }
+}
@@ -6,8 +6,10 @@
 //!
 //! - **Implicit Node Connections**: Nodes are automatically connected based on execution order
 //! - **Branching**: Create parallel execution paths with `.branch()`
-//! - **Config Sweeps**: Use `.variants()` to create configuration variations
-//! - **DAG Optimization**: Automatic inspection and optimization of execution paths
+//! - **Config Sweeps**: Use `.variants()` to create configuration variations, or `.variant()`
+//!   with `Linspace`/`Logspace`/`Geomspace` generators for parameter sweeps
+//! - **DAG Optimization**: Automatic inspection and optimization of execution paths, plus
+//!   opt-in optimization passes via `build_optimized()`
 //! - **Mermaid Visualization**: Generate diagrams with `to_mermaid()`
 //!
 //! ## Example
@@ -15,14 +17,15 @@
 //! ```rust
 //! use dagex::{Graph, GraphData};
 //! use std::collections::HashMap;
+//! use std::sync::Arc;
 //!
-//! fn data_source(_: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+//! fn data_source(_: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
 //!     let mut result = HashMap::new();
 //!     result.insert("output".to_string(), GraphData::string("Hello, World!"));
 //!     result
 //! }
 //!
-//! fn processor(inputs: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
+//! fn processor(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
 //!     let mut result = HashMap::new();
 //!     if let Some(data) = inputs.get("input").and_then(|d| d.as_string()) {
 //!         result.insert("output".to_string(), GraphData::string(data.to_uppercase()));
@@ -37,19 +40,102 @@
 //! let dag = graph.build();
 //! ```
 
+mod analysis;
 mod builder;
+mod compare;
+mod conflict;
+mod context;
 mod dag;
 mod distribution;
+mod file_io;
 mod graph_data;
+mod html_report;
+mod incremental;
+mod lazy;
+mod log_capture;
+mod middleware;
 mod node;
+mod optimizer;
+mod payload;
+mod plan;
+mod plan_cache;
+mod pool;
+mod progress;
+mod report;
+mod shared_slice;
 mod stat_result;
+pub mod std_nodes;
+mod storage;
+mod streaming;
+mod symbol;
+mod units;
+mod sweep;
+mod validation;
+mod watchdog;
+mod workers;
 
+#[cfg(feature = "radar_examples")]
+pub mod dsp;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "mmap")]
+mod mapped;
+#[cfg(feature = "object_store")]
+mod object_storage;
 #[cfg(feature = "python")]
 mod python_bindings;
+#[cfg(feature = "queue")]
+mod queue;
 
+pub use analysis::{Inspection, UnusedOutput};
 pub use builder::Graph;
-pub use dag::{Dag, DagStats, ExecutionContext, ExecutionResult, PredictTarget};
+pub use compare::{DagDiff, NodeChange};
+pub use conflict::ConflictPolicy;
+pub use context::{ContextError, ExecutionContext, FromGraphData};
+pub use dag::{Dag, DagStats, ExecutionResult, PredictTarget};
+pub use file_io::{read_csv, read_floats, write_csv, write_floats};
+#[cfg(feature = "json")]
+pub use file_io::{read_json, write_json};
 pub use distribution::{DistContext, DistTransferFn, Distribution, PortSummary};
-pub use graph_data::GraphData;
+pub use incremental::{ExecutionPoll, IncrementalExecution};
+pub use lazy::LazyValue;
+pub use log_capture::LogRecord;
+#[cfg(feature = "http")]
+pub use http::{http_get, http_post};
+#[cfg(feature = "log")]
+pub use log_capture::{install_log_capture, CaptureLogger};
+pub use middleware::Middleware;
+#[cfg(feature = "macros")]
+pub use dagex_macros::{graph, node};
+#[cfg(feature = "mmap")]
+pub use mapped::MappedFile;
+#[cfg(feature = "object_store")]
+pub use object_storage::ObjectStorage;
+pub use graph_data::{GraphData, GraphDataKind, SUMMARY_MAX_STRING_LEN, SUMMARY_PREVIEW_LEN};
 pub use stat_result::StatResult;
-pub use node::{NodeFunction, NodeId};
+pub use storage::{FileStorage, MemoryStorage, StorageBackend};
+pub use validation::{BuildError, BuildWarning, ExecutionError, InputError};
+pub use node::{Guard, Node, NodeFunction, NodeHandle, NodeId, Validator};
+pub use payload::{ContentHasher, Payload};
+pub use plan_cache::ExecutionPlan;
+pub use pool::{ExecutorPool, JobHandle, JobStatus};
+pub use progress::{CancelToken, NodeProgress};
+#[cfg(feature = "queue")]
+pub use queue::{ChannelQueue, TaskQueue};
+#[cfg(feature = "nats")]
+pub use queue::NatsQueue;
+pub use optimizer::{
+    DeadNodeElimination, DedupIdenticalNodes, LinearChainFusion, Optimizer, OptimizerConfig,
+    OptimizerPass, PassSummary, RedundantEdgeRemoval,
+};
+pub use report::{ExecutionReport, NodeTiming, VariantRun};
+pub use shared_slice::SharedSlice;
+pub use sweep::{
+    AdaptiveSweep, FloatFormat, Geomspace, IntoVariantValues, Linspace, Logspace, ParamGrid, RandomSweep,
+    SweepStep, Trial,
+};
+pub use symbol::Symbol;
+pub use watchdog::{HungNode, Watchdog};
+pub use workers::Workers;
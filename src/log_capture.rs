@@ -0,0 +1,125 @@
+//! Per-node capture of log records
+//!
+//! While a node runs during `Dag::execute_timed()` (and the other executors), log
+//! records emitted on the thread running it are collected and attached to the
+//! node's `NodeTiming::logs`, so the messages of one failing variant can be read
+//! in isolation instead of interleaved with those of every parallel node.
+//!
+//! With the `log` feature, install `CaptureLogger` as the global logger; it passes
+//! every record on to the logger it wraps, so output elsewhere is unchanged:
+//!
+//! ```ignore
+//! dagex::install_log_capture(Some(Box::new(env_logger::Logger::from_default_env())), LevelFilter::Info)?;
+//! let report = dag.execute_timed(true, None);
+//! for record in report.logs(node_id) {
+//!     println!("[{}] {}", record.level, record.message);
+//! }
+//! ```
+//!
+//! Only records are captured: stdout and stderr belong to the whole process and
+//! are shared by all worker threads, so text printed there cannot be attributed
+//! to a node.
+
+use std::cell::RefCell;
+
+/// A log record emitted while a node was running
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    /// Level of the record (`"ERROR"`, `"WARN"`, `"INFO"`, `"DEBUG"`, `"TRACE"`)
+    pub level: String,
+    /// Target of the record, by default the module that emitted it
+    pub target: String,
+    /// Formatted message
+    pub message: String,
+}
+
+thread_local! {
+    /// Records of the node running on this thread, if one is being captured
+    static CAPTURED: RefCell<Option<Vec<LogRecord>>> = const { RefCell::new(None) };
+}
+
+/// Run `f`, returning the log records emitted on this thread meanwhile
+///
+/// Captures nest: records of a DAG executed inside a node belong to the inner
+/// DAG's nodes, and the outer capture resumes afterwards.
+pub(crate) fn capture<R>(f: impl FnOnce() -> R) -> (R, Vec<LogRecord>) {
+    /// Restores the outer capture even if `f` panics
+    struct Restore(Option<Option<Vec<LogRecord>>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            if let Some(outer) = self.0.take() {
+                CAPTURED.with(|captured| captured.replace(outer));
+            }
+        }
+    }
+
+    let mut restore = Restore(Some(CAPTURED.with(|captured| captured.replace(Some(Vec::new())))));
+    let result = f();
+    let outer = restore.0.take().unwrap_or_default();
+    let records = CAPTURED.with(|captured| captured.replace(outer)).unwrap_or_default();
+    (result, records)
+}
+
+/// Add a record to the capture of the node running on this thread, if any
+#[cfg_attr(not(feature = "log"), allow(dead_code))]
+pub(crate) fn record(record: LogRecord) {
+    CAPTURED.with(|captured| {
+        if let Some(records) = captured.borrow_mut().as_mut() {
+            records.push(record);
+        }
+    });
+}
+
+/// Global logger that attaches records to the node being executed (see the
+/// module documentation) and forwards all records to an inner logger
+#[cfg(feature = "log")]
+pub struct CaptureLogger {
+    inner: Option<Box<dyn log::Log>>,
+}
+
+#[cfg(feature = "log")]
+impl CaptureLogger {
+    /// Capture records, passing them on to `inner` (if given)
+    pub fn new(inner: Option<Box<dyn log::Log>>) -> Self {
+        Self { inner }
+    }
+}
+
+#[cfg(feature = "log")]
+impl log::Log for CaptureLogger {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, rec: &log::Record) {
+        record(LogRecord {
+            level: rec.level().to_string(),
+            target: rec.target().to_string(),
+            message: rec.args().to_string(),
+        });
+        if let Some(inner) = self.inner.as_ref().filter(|inner| inner.enabled(rec.metadata())) {
+            inner.log(rec);
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(inner) = &self.inner {
+            inner.flush();
+        }
+    }
+}
+
+/// Install a `CaptureLogger` wrapping `inner` as the global logger, with records
+/// up to `max_level` enabled
+///
+/// Fails if a global logger was already set.
+#[cfg(feature = "log")]
+pub fn install_log_capture(
+    inner: Option<Box<dyn log::Log>>,
+    max_level: log::LevelFilter,
+) -> Result<(), log::SetLoggerError> {
+    log::set_boxed_logger(Box::new(CaptureLogger::new(inner)))?;
+    log::set_max_level(max_level);
+    Ok(())
+}
@@ -0,0 +1,93 @@
+//! `MappedFile` — a read-only memory map of an on-disk array
+//!
+//! Created by `GraphData::mmap()` (requires the `mmap` feature).  The file is
+//! treated as a flat array of native-endian `f64` values (or `Complex<f64>` pairs,
+//! with the `radar_examples` feature); pages are loaded by the OS as nodes touch
+//! them, so arrays larger than RAM can flow through a graph.  Clones share the
+//! mapping.
+//!
+//! The file must not be truncated or modified while it is mapped.
+
+use memmap2::Mmap;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[cfg(feature = "radar_examples")]
+use num_complex::Complex;
+
+/// A read-only, reference-counted memory map of a file.
+#[derive(Clone)]
+pub struct MappedFile {
+    map: Arc<Mmap>,
+    path: Arc<PathBuf>,
+}
+
+impl MappedFile {
+    /// Map `path` into memory (no data is read until it is accessed).
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        // SAFETY: the map is read-only; callers must not modify the file while it is
+        // mapped (documented on the module and on `GraphData::mmap()`).
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(Self {
+            map: Arc::new(map),
+            path: Arc::new(path.to_path_buf()),
+        })
+    }
+
+    /// The mapped file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The raw contents.
+    pub fn bytes(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// Size of the file in bytes.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Whether the file is empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// The contents as `f64` values, or `None` if the size is not a multiple of 8.
+    pub fn as_floats(&self) -> Option<&[f64]> {
+        cast(&self.map)
+    }
+
+    /// The contents as complex values (interleaved re/im `f64` pairs), or `None` if
+    /// the size is not a multiple of 16.
+    #[cfg(feature = "radar_examples")]
+    pub fn as_complex(&self) -> Option<&[Complex<f64>]> {
+        cast(&self.map)
+    }
+}
+
+/// View `bytes` as a slice of `T` if its length and alignment allow it.
+///
+/// Only used with `f64` and `Complex<f64>` (`repr(C)` pair of `f64`), for which every
+/// bit pattern is a valid value.
+fn cast<T>(bytes: &[u8]) -> Option<&[T]> {
+    let size = std::mem::size_of::<T>();
+    let aligned = bytes.as_ptr().align_offset(std::mem::align_of::<T>()) == 0;
+    if !aligned || !bytes.len().is_multiple_of(size) {
+        return None;
+    }
+    // SAFETY: length and alignment checked above; any bit pattern is a valid `T`.
+    Some(unsafe { std::slice::from_raw_parts(bytes.as_ptr().cast::<T>(), bytes.len() / size) })
+}
+
+impl fmt::Debug for MappedFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({} bytes)", self.path.display(), self.map.len())
+    }
+}
@@ -0,0 +1,67 @@
+//! `Middleware` — hooks that wrap every node execution
+//!
+//! Cross-cutting concerns such as logging, metric capture, input checks, or unit
+//! conversion apply to all nodes alike.  Rather than adding them to every node
+//! function, register a middleware on the `Dag`; it sees each node's inputs just
+//! before the function is called and its outputs just after:
+//!
+//! ```ignore
+//! struct Log;
+//!
+//! impl Middleware for Log {
+//!     fn before(&self, node: &Node, inputs: &mut HashMap<String, Arc<GraphData>>) {
+//!         println!("{} <- {:?}", node.display_name(), inputs.keys());
+//!     }
+//! }
+//!
+//! let mut dag = graph.build();
+//! dag.add_middleware(Log);
+//! ```
+
+use crate::graph_data::GraphData;
+use crate::node::Node;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Hooks called around every node function (see `Dag::add_middleware()`)
+///
+/// Both maps are keyed by the names the node function uses (the right-hand side
+/// of the input mapping, the left-hand side of the output mapping), and both may
+/// be modified: values inserted, replaced, or removed in `before()` are what the
+/// function receives, and those left in `after()` are what is written to the
+/// context.  Nodes skipped by a guard, by a missing required input, or by a
+/// rejected input (see `Graph::validate()`) are not wrapped; otherwise `after()`
+/// follows `before()`, or `unwound()` if the node function panics.  In parallel
+/// runs the hooks are called from worker threads.
+pub trait Middleware<T = GraphData>: Send + Sync {
+    /// Called with the inputs of a node before its function runs
+    fn before(&self, node: &Node<T>, inputs: &mut HashMap<String, Arc<T>>) {
+        let _ = (node, inputs);
+    }
+
+    /// Called with the outputs of a node after its function returned
+    fn after(&self, node: &Node<T>, outputs: &mut HashMap<String, Arc<T>>) {
+        let _ = (node, outputs);
+    }
+
+    /// Called instead of `after()` while a panic of the node function unwinds,
+    /// e.g. to release what `before()` recorded
+    fn unwound(&self, node: &Node<T>) {
+        let _ = node;
+    }
+}
+
+/// Shared middleware, e.g. to read what it recorded after a run
+impl<T, M: Middleware<T> + ?Sized> Middleware<T> for Arc<M> {
+    fn before(&self, node: &Node<T>, inputs: &mut HashMap<String, Arc<T>>) {
+        (**self).before(node, inputs);
+    }
+
+    fn after(&self, node: &Node<T>, outputs: &mut HashMap<String, Arc<T>>) {
+        (**self).after(node, outputs);
+    }
+
+    fn unwound(&self, node: &Node<T>) {
+        (**self).unwound(node);
+    }
+}
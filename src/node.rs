@@ -1,44 +1,156 @@
 //! Node representation and execution
 
+use crate::context::ExecutionContext;
 use crate::distribution::DistTransferFn;
-use crate::graph_data::GraphData;
-use std::collections::HashMap;
+use crate::graph_data::{GraphData, GraphDataKind};
+use crate::middleware::Middleware;
+use crate::payload::Payload;
+use crate::plan::{input_plan, InputSource};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 
 /// Unique identifier for a node
 pub type NodeId = usize;
 
-/// Type alias for node execution functions using GraphData
-/// Takes GraphData ports as input, returns output ports
-pub type NodeFunction = Arc<
-    dyn Fn(&HashMap<String, GraphData>) -> HashMap<String, GraphData>
-        + Send
-        + Sync,
->;
+/// Handle to a node added to a `Graph`, returned by `Graph::add()`
+///
+/// Handles stay valid after `build()`: the node keeps its ID in the `Dag` (unless an
+/// optimizer pass removed or fused it), so they can be used to look up the node's
+/// outputs with `ExecutionResult::get_node_outputs()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeHandle(NodeId);
+
+impl NodeHandle {
+    pub(crate) fn new(id: NodeId) -> Self {
+        Self(id)
+    }
+
+    /// ID of the node
+    pub fn id(self) -> NodeId {
+        self.0
+    }
+}
+
+impl From<NodeHandle> for NodeId {
+    fn from(handle: NodeHandle) -> Self {
+        handle.0
+    }
+}
+
+/// Take the values out of a shared map, copying only those still referenced elsewhere
+///
+/// Used where a node returns the context of a DAG it ran internally.
+pub(crate) fn unshare<T: Clone>(map: HashMap<String, Arc<T>>) -> HashMap<String, T> {
+    map.into_iter()
+        .map(|(key, value)| (key, Arc::unwrap_or_clone(value)))
+        .collect()
+}
+
+/// Type alias for node execution functions
+/// Takes input ports (GraphData by default) as input, returns output ports
+///
+/// Inputs are `Arc` clones of the context values, so large values are shared
+/// with every consumer rather than copied; outputs are moved into the context.
+pub type NodeFunction<T = GraphData> =
+    Arc<dyn Fn(&HashMap<String, Arc<T>>) -> HashMap<String, T> + Send + Sync>;
+
+/// Predicate deciding at run time whether a node executes (see `Graph::when()`)
+///
+/// Receives the execution context, keyed by broadcast variable names.
+pub type Guard<T = GraphData> = Arc<dyn Fn(&ExecutionContext<T>) -> bool + Send + Sync>;
+
+/// Check of one port value, returning a description of the problem if it is
+/// invalid (see `Graph::validate()`)
+pub type Validator<T = GraphData> = Arc<dyn Fn(&T) -> Result<(), String> + Send + Sync>;
+
+/// Why a node that was due to run did not produce outputs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum NodeFailure {
+    /// A required input is missing; the broadcast variable it reads
+    MissingInput(String),
+    /// A validator rejected the value of a port
+    Invalid {
+        /// Port (impl_var) whose value was rejected
+        port: String,
+        /// Whether the port is an output (checked after the function ran)
+        output: bool,
+        /// The validator's description of the problem
+        message: String,
+    },
+}
 
 /// Represents a node in the graph
-#[derive(Clone)]
-pub struct Node {
+pub struct Node<T = GraphData> {
     /// Unique identifier
     pub id: NodeId,
     /// Optional label for visualization
     pub label: Option<String>,
     /// Function to execute
-    pub function: NodeFunction,
+    pub function: NodeFunction<T>,
     /// Input mapping: broadcast_var -> impl_var (what the function sees)
     pub input_mapping: HashMap<String, String>,
     /// Output mapping: impl_var -> broadcast_var (where function output goes in context)
     pub output_mapping: HashMap<String, String>,
+    /// Additional (impl_var, broadcast_var) outputs carrying explicit connections
+    /// made with `Graph::connect()`
+    pub edge_outputs: Vec<(String, String)>,
     /// Branch ID for branch-specific variable resolution (None for main graph nodes)
     pub branch_id: Option<usize>,
+    /// Hierarchical branch ID, outermost first: `[2]` for a node of branch 2,
+    /// `[2, 1]` for branch 1 of a subgraph nested in branch 2 (empty outside branches)
+    pub branch_path: Vec<usize>,
+    /// Branches whose namespaces the node reads before the shared context,
+    /// innermost first (set at build time by `Graph::isolate_branches()`)
+    pub branch_scopes: Vec<usize>,
     /// Nodes that this node depends on (connected from)
     pub dependencies: Vec<NodeId>,
     /// Whether this node is part of a branch
     pub is_branch: bool,
     /// Variant index if this is part of a variant sweep
     pub variant_index: Option<usize>,
-    /// Variant parameters for this node (param_name -> value)
-    pub variant_params: HashMap<String, GraphData>,
+    /// Variant indices from the outermost chained sweep down to this node's own
+    /// (empty outside sweeps); names the node's namespaced outputs `"var#0.2"`
+    pub variant_path: Vec<usize>,
+    /// Variant parameters for this node (param_name -> value); the function also
+    /// receives each as an input of that name, unless a mapped input has the name
+    pub variant_params: HashMap<String, T>,
+    /// Static per-node configuration (key -> value), set with `Graph::with_config()`
+    pub config: HashMap<String, T>,
+    /// Whether each input receives a list of the values of all its producers
+    /// (nodes added with `Graph::collect()`)
+    pub collect_inputs: bool,
+    /// Whether the node still runs when some of its producers were skipped, with the
+    /// values that exist (`Graph::collect()`, `Graph::collect_variants()`)
+    pub partial_inputs: bool,
+    /// Condition for executing the node, set with `Graph::when()`
+    pub guard: Option<Guard<T>>,
+    /// Value written to every output when the guard fails (`Graph::when_or()`);
+    /// without one, the node and everything depending on it is skipped
+    pub skip_sentinel: Option<T>,
+    /// Inputs (impl_var names) the node cannot run without, set with
+    /// `Graph::require_input()`
+    pub required_inputs: HashSet<String>,
+    /// Free-form description, set with `Graph::with_description()`
+    pub description: Option<String>,
+    /// Key-value annotations such as `tag = "io"`, set with `Graph::with_metadata()`
+    pub metadata: BTreeMap<String, String>,
+    /// Declared input types: impl_var -> kind (checked at build time)
+    pub input_types: HashMap<String, GraphDataKind>,
+    /// Declared output types: impl_var -> kind (checked at build time)
+    pub output_types: HashMap<String, GraphDataKind>,
+    /// Checks of port values (impl_var, validator), run around the function,
+    /// set with `Graph::validate()`
+    pub validators: Vec<(String, Validator<T>)>,
+    /// Declared input units: impl_var -> unit string (checked at build time)
+    pub input_units: HashMap<String, String>,
+    /// Declared output units: impl_var -> unit string (checked at build time)
+    pub output_units: HashMap<String, String>,
+    /// Factors the inputs are multiplied by before the function sees them, converting
+    /// them to the port's unit (set at build time by `Graph::auto_scale_units()`)
+    pub input_scales: HashMap<String, f64>,
+    /// Whether input and output values are checked against `input_types` and
+    /// `output_types` when the node runs (set by `Graph::enforce_schema()`)
+    pub check_types: bool,
 
     /// Optional analytical distribution transfer.
     ///
@@ -48,11 +160,50 @@ pub struct Node {
     pub dist_transfer: Option<DistTransferFn>,
 }
 
-impl Node {
+// Manual impl: a derive would require `T: Clone` only through the function field,
+// which is an `Arc` and always cloneable.
+impl<T: Clone> Clone for Node<T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            label: self.label.clone(),
+            function: Arc::clone(&self.function),
+            input_mapping: self.input_mapping.clone(),
+            output_mapping: self.output_mapping.clone(),
+            edge_outputs: self.edge_outputs.clone(),
+            branch_id: self.branch_id,
+            branch_path: self.branch_path.clone(),
+            branch_scopes: self.branch_scopes.clone(),
+            dependencies: self.dependencies.clone(),
+            is_branch: self.is_branch,
+            variant_index: self.variant_index,
+            variant_path: self.variant_path.clone(),
+            variant_params: self.variant_params.clone(),
+            config: self.config.clone(),
+            collect_inputs: self.collect_inputs,
+            partial_inputs: self.partial_inputs,
+            guard: self.guard.clone(),
+            skip_sentinel: self.skip_sentinel.clone(),
+            required_inputs: self.required_inputs.clone(),
+            description: self.description.clone(),
+            metadata: self.metadata.clone(),
+            input_types: self.input_types.clone(),
+            output_types: self.output_types.clone(),
+            validators: self.validators.clone(),
+            input_units: self.input_units.clone(),
+            output_units: self.output_units.clone(),
+            input_scales: self.input_scales.clone(),
+            check_types: self.check_types,
+            dist_transfer: self.dist_transfer.clone(),
+        }
+    }
+}
+
+impl<T> Node<T> {
     /// Create a new node
     pub fn new(
         id: NodeId,
-        function: NodeFunction,
+        function: NodeFunction<T>,
         label: Option<String>,
         input_mapping: HashMap<String, String>,
         output_mapping: HashMap<String, String>,
@@ -63,64 +214,287 @@ impl Node {
             function,
             input_mapping,
             output_mapping,
+            edge_outputs: Vec::new(),
             branch_id: None,
+            branch_path: Vec::new(),
+            branch_scopes: Vec::new(),
             dependencies: Vec::new(),
             is_branch: false,
             variant_index: None,
+            variant_path: Vec::new(),
             variant_params: HashMap::new(),
+            config: HashMap::new(),
+            collect_inputs: false,
+            partial_inputs: false,
+            guard: None,
+            skip_sentinel: None,
+            required_inputs: HashSet::new(),
+            description: None,
+            metadata: BTreeMap::new(),
+            input_types: HashMap::new(),
+            output_types: HashMap::new(),
+            validators: Vec::new(),
+            input_units: HashMap::new(),
+            output_units: HashMap::new(),
+            input_scales: HashMap::new(),
+            check_types: false,
             dist_transfer: None,
         }
     }
 
-    /// Execute this node with the given context
-    pub fn execute(&self, context: &HashMap<String, GraphData>) -> HashMap<String, GraphData> {
-        // Map broadcast context vars to impl vars using input_mapping
-        // input_mapping: broadcast_var -> impl_var
-        // Special case: For merge nodes, broadcast_var may be "branch_id:var_name"
-        let inputs: HashMap<String, GraphData> = self
-            .input_mapping
+    /// Key under which this node stores `broadcast_var` in the execution context
+    ///
+    /// Branch nodes prefix their outputs with the branch ID so that parallel
+    /// branches writing the same variable do not overwrite each other.
+    pub(crate) fn context_key(&self, broadcast_var: &str) -> String {
+        match self.branch_id {
+            Some(branch_id) => format!("__branch_{}__{}", branch_id, broadcast_var),
+            None => broadcast_var.to_string(),
+        }
+    }
+
+    /// Hierarchical branch ID as a dotted string (`"2.1"`), `None` outside branches
+    pub fn branch_label(&self) -> Option<String> {
+        if self.branch_path.is_empty() {
+            return None;
+        }
+        let path: Vec<String> = self.branch_path.iter().map(|i| i.to_string()).collect();
+        Some(path.join("."))
+    }
+
+    /// Namespaced context key under which a variant node also stores `broadcast_var`
+    ///
+    /// `"result#1"` for the second variant of a sweep, `"result#1.0"` for the first
+    /// variant chained under it; `None` for nodes outside sweeps.
+    pub fn variant_key(&self, broadcast_var: &str) -> Option<String> {
+        if self.variant_path.is_empty() {
+            return None;
+        }
+        let path: Vec<String> = self.variant_path.iter().map(|i| i.to_string()).collect();
+        Some(format!("{}#{}", broadcast_var, path.join(".")))
+    }
+
+    /// All (impl_var, broadcast_var) outputs: the output mapping, then explicit connections
+    pub(crate) fn all_outputs(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.output_mapping
             .iter()
-            .filter_map(|(broadcast_key, impl_var)| {
-                // Handle merge node special format: "branch_id:broadcast_var"
-                if broadcast_key.contains(':') {
-                    // Parse "branch_id:var_name" and look for "__branch_{id}__{var}"
-                    let parts: Vec<&str> = broadcast_key.split(':').collect();
-                    if parts.len() == 2 {
-                        let prefixed_key = format!("__branch_{}__{}",  parts[0], parts[1]);
-                        context
-                            .get(&prefixed_key)
-                            .map(|val| (impl_var.clone(), val.clone()))
-                    } else {
-                        None
-                    }
-                } else {
-                    // Normal case: direct lookup
-                    context
-                        .get(broadcast_key)
-                        .map(|val| (impl_var.clone(), val.clone()))
+            .chain(self.edge_outputs.iter().map(|(impl_var, var)| (impl_var, var)))
+    }
+
+    /// Get display name for this node
+    pub fn display_name(&self) -> String {
+        self.label
+            .clone()
+            .unwrap_or_else(|| format!("Node {}", self.id))
+    }
+}
+
+/// Calls `Middleware::unwound()` when dropped, i.e. when a node function panics
+/// between `before()` and `after()` (it is forgotten otherwise)
+struct Unwinding<'a, T> {
+    node: &'a Node<T>,
+    middleware: &'a [Arc<dyn Middleware<T>>],
+}
+
+impl<T> Drop for Unwinding<'_, T> {
+    fn drop(&mut self) {
+        for layer in self.middleware.iter().rev() {
+            layer.unwound(self.node);
+        }
+    }
+}
+
+impl<T: Payload> Node<T> {
+    /// Execute this node unless it is skipped
+    ///
+    /// A node is skipped when `upstream_skipped` is set (see
+    /// `NodePlan::skipped_by_dependencies()`), or when its guard rejects the context and
+    /// it has no sentinel.  With a sentinel, a rejected node writes the sentinel to
+    /// each of its outputs instead of running.  Returns `Ok(None)` for a skipped
+    /// node, and `Err` for a required input that is missing (the function is not
+    /// called) or a value rejected by a validator.
+    pub(crate) fn execute_guarded(
+        &self,
+        plan: &[(String, InputSource)],
+        context: &ExecutionContext<T>,
+        upstream_skipped: bool,
+        middleware: &[Arc<dyn Middleware<T>>],
+    ) -> Result<Option<HashMap<String, Arc<T>>>, NodeFailure> {
+        if upstream_skipped {
+            return Ok(None);
+        }
+        match &self.guard {
+            Some(guard) if !guard(context) => Ok(self.skip_sentinel.as_ref().map(|sentinel| {
+                let sentinel = Arc::new(sentinel.clone());
+                self.all_outputs()
+                    .map(|(_, var)| (var.clone(), Arc::clone(&sentinel)))
+                    .collect()
+            })),
+            _ => {
+                let inputs = self.gather_inputs(plan, context);
+                let mut missing: Vec<&String> = self
+                    .required_inputs
+                    .iter()
+                    .filter(|impl_var| !inputs.contains_key(*impl_var))
+                    .collect();
+                missing.sort();
+                match missing.first() {
+                    Some(impl_var) => Err(NodeFailure::MissingInput(
+                        self.input_mapping
+                            .iter()
+                            .find(|(_, v)| v == impl_var)
+                            .map_or_else(|| impl_var.to_string(), |(var, _)| var.clone()),
+                    )),
+                    None => self.call_wrapped(inputs, middleware).map(Some),
                 }
+            }
+        }
+    }
+
+    /// Execute this node with the given context
+    pub fn execute(&self, context: &ExecutionContext<T>) -> HashMap<String, Arc<T>> {
+        self.map_outputs(self.call(&self.gather_inputs(&input_plan(self), context)))
+    }
+
+    /// The inputs the function sees: mapped context variables (resolved by `plan`,
+    /// see `plan::input_plan()`), variant parameters, and static configuration
+    fn gather_inputs(
+        &self,
+        plan: &[(String, InputSource)],
+        context: &ExecutionContext<T>,
+    ) -> HashMap<String, Arc<T>> {
+        let mut inputs: HashMap<String, Arc<T>> = plan
+            .iter()
+            .filter_map(|(impl_var, source)| {
+                let value = match source {
+                    InputSource::Keys(keys) => keys.iter().find_map(|&key| context.get_symbol(key)).cloned(),
+                    // Collecting node: values of all producers, gathered into a list
+                    InputSource::Collected(key) => context
+                        .get_symbol(*key)
+                        .cloned()
+                        .or_else(|| T::gather(Vec::new()).map(Arc::new)),
+                };
+                value.map(|value| (impl_var.clone(), value))
             })
             .collect();
 
-        // Execute function with inputs
-        let func_outputs = (self.function)(&inputs);
+        // Variant parameters are visible to the function alongside its mapped inputs
+        for (param, value) in &self.variant_params {
+            inputs
+                .entry(param.clone())
+                .or_insert_with(|| Arc::new(value.clone()));
+        }
+
+        // So is static configuration; mapped inputs and variant parameters take precedence
+        for (key, value) in &self.config {
+            inputs.entry(key.clone()).or_insert_with(|| Arc::new(value.clone()));
+        }
+        inputs
+    }
 
+    /// Call the function inside `middleware`, checking the values of validated
+    /// ports as they arrive and as they leave
+    ///
+    /// Inputs are converted to their ports' units before anything else sees them.
+    /// Checks run outside the middleware, so every `before()` call is paired with
+    /// an `after()` call, or with an `unwound()` call if the function panics.
+    fn call_wrapped(
+        &self,
+        mut inputs: HashMap<String, Arc<T>>,
+        middleware: &[Arc<dyn Middleware<T>>],
+    ) -> Result<HashMap<String, Arc<T>>, NodeFailure> {
+        self.scale_inputs(&mut inputs)?;
+        self.check_ports(&inputs, false)?;
+        self.check_kinds(&inputs, &self.input_types, false)?;
+        for layer in middleware {
+            layer.before(self, &mut inputs);
+        }
+        let unwinding = Unwinding { node: self, middleware };
+        let mut func_outputs = self.call(&inputs);
+        std::mem::forget(unwinding);
+        for layer in middleware.iter().rev() {
+            layer.after(self, &mut func_outputs);
+        }
+        self.check_ports(&func_outputs, true)?;
+        self.check_kinds(&func_outputs, &self.output_types, true)?;
+        Ok(self.map_outputs(func_outputs))
+    }
+
+    /// Convert inputs to the units of their ports (see `input_scales`)
+    fn scale_inputs(&self, inputs: &mut HashMap<String, Arc<T>>) -> Result<(), NodeFailure> {
+        for (port, &factor) in &self.input_scales {
+            if let Some(value) = inputs.get_mut(port) {
+                let scaled = value.scaled(factor).ok_or_else(|| NodeFailure::Invalid {
+                    port: port.clone(),
+                    output: false,
+                    message: format!("cannot convert {:?} to {}", value, self.input_units[port]),
+                })?;
+                *value = Arc::new(scaled);
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the validators of the ports present in `values`, in registration order
+    fn check_ports(&self, values: &HashMap<String, Arc<T>>, output: bool) -> Result<(), NodeFailure> {
+        for (port, validator) in &self.validators {
+            if let Some(value) = values.get(port) {
+                validator(value).map_err(|message| NodeFailure::Invalid {
+                    port: port.clone(),
+                    output,
+                    message,
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check values against their declared port types, if `check_types` is set
+    fn check_kinds(
+        &self,
+        values: &HashMap<String, Arc<T>>,
+        types: &HashMap<String, GraphDataKind>,
+        output: bool,
+    ) -> Result<(), NodeFailure> {
+        if !self.check_types {
+            return Ok(());
+        }
+        let mut ports: Vec<(&String, &GraphDataKind)> = types.iter().collect();
+        ports.sort();
+        for (port, &expected) in ports {
+            let found = values.get(port).and_then(|value| value.kind());
+            if let Some(found) = found.filter(|&found| !expected.accepts(found)) {
+                return Err(NodeFailure::Invalid {
+                    port: port.clone(),
+                    output,
+                    message: format!("expected {}, found {}", expected, found),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Call the function, wrapping each output in an `Arc` once
+    fn call(&self, inputs: &HashMap<String, Arc<T>>) -> HashMap<String, Arc<T>> {
+        (self.function)(inputs)
+            .into_iter()
+            .map(|(impl_var, value)| (impl_var, Arc::new(value)))
+            .collect()
+    }
+
+    /// Map function outputs to broadcast variables
+    ///
+    /// An output mapped to several broadcast variables shares the same value.
+    fn map_outputs(&self, func_outputs: HashMap<String, Arc<T>>) -> HashMap<String, Arc<T>> {
         // Map function outputs to broadcast vars using output_mapping
         // output_mapping: impl_var -> broadcast_var
         let mut context_outputs = HashMap::new();
-        for (impl_var, broadcast_var) in &self.output_mapping {
+        for (impl_var, broadcast_var) in self.all_outputs() {
             if let Some(value) = func_outputs.get(impl_var) {
-                context_outputs.insert(broadcast_var.clone(), value.clone());
+                context_outputs.insert(broadcast_var.clone(), Arc::clone(value));
             }
         }
 
         context_outputs
     }
-
-    /// Get display name for this node
-    pub fn display_name(&self) -> String {
-        self.label
-            .clone()
-            .unwrap_or_else(|| format!("Node {}", self.id))
-    }
 }
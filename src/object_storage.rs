@@ -0,0 +1,111 @@
+//! `ObjectStorage` — a `StorageBackend` on top of the `object_store` crate
+//!
+//! Checkpoints, cached plans, and large serialized buffers stored in S3, GCS, or
+//! Azure Blob Storage can be reached by every machine of a distributed sweep.
+//! Any `ObjectStore` works; enable the provider in your own dependency on
+//! `object_store` (e.g. `features = ["aws"]`) and hand the store to this
+//! backend:
+//!
+//! ```ignore
+//! let s3 = object_store::aws::AmazonS3Builder::from_env().with_bucket_name("sweeps").build()?;
+//! let storage = ObjectStorage::new(Arc::new(s3)).with_prefix("run-42");
+//! let report = dag.execute_interruptible(true, None, &storage, "checkpoint.json")?;
+//! ```
+//!
+//! `StorageBackend` is synchronous, so each call blocks on a small runtime
+//! owned by the backend.  Call it from ordinary threads (including DAG worker
+//! threads); from async code, use `tokio::task::spawn_blocking()`.
+
+use crate::storage::StorageBackend;
+use futures::TryStreamExt;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use std::io;
+use std::sync::Arc;
+
+/// `StorageBackend` writing one object per key (see the module documentation)
+#[derive(Debug)]
+pub struct ObjectStorage {
+    store: Arc<dyn ObjectStore>,
+    prefix: String,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl ObjectStorage {
+    /// Store objects in `store`, at the keys given
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime driving the store cannot be created.
+    pub fn new(store: Arc<dyn ObjectStore>) -> Self {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to create the object store runtime");
+        Self {
+            store,
+            prefix: String::new(),
+            runtime,
+        }
+    }
+
+    /// Store objects below `prefix` (a `/`-separated path) instead of at the top
+    /// of the store, so several runs can share a bucket
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.trim_matches('/').to_string();
+        self
+    }
+
+    /// Location of `key` in the store; the empty key is the prefix itself
+    fn location(&self, key: &str) -> io::Result<Path> {
+        let full = match (self.prefix.as_str(), key) {
+            ("", key) => key.to_string(),
+            (prefix, "") => prefix.to_string(),
+            (prefix, key) => format!("{}/{}", prefix, key),
+        };
+        Path::parse(&full)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("invalid storage key '{}'", key)))
+    }
+
+    /// Location of an object `key`, which must not be empty
+    fn object(&self, key: &str) -> io::Result<Path> {
+        if key.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid storage key ''"));
+        }
+        self.location(key)
+    }
+}
+
+impl StorageBackend for ObjectStorage {
+    fn put(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        let location = self.object(key)?;
+        self.runtime.block_on(self.store.put(&location, bytes.to_vec().into()))?;
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        let location = self.object(key)?;
+        self.runtime.block_on(async {
+            match self.store.get(&location).await {
+                Ok(object) => Ok(Some(object.bytes().await?.to_vec())),
+                Err(object_store::Error::NotFound { .. }) => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        })
+    }
+
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>> {
+        // Object stores list whole path segments; list the directory holding the
+        // prefix and filter by the rest
+        let location = self.location(prefix.rsplit_once('/').map_or("", |(dir, _)| dir))?;
+        let objects: Vec<_> = self.runtime.block_on(self.store.list(Some(&location)).try_collect())?;
+        let strip = if self.prefix.is_empty() { 0 } else { self.prefix.len() + 1 };
+        let mut keys: Vec<String> = objects
+            .into_iter()
+            .map(|meta| meta.location.as_ref()[strip..].to_string())
+            .filter(|key| key.starts_with(prefix))
+            .collect();
+        keys.sort();
+        Ok(keys)
+    }
+}
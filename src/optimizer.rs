@@ -0,0 +1,407 @@
+//! Optimization passes applied while building a DAG
+//!
+//! `Graph::build_optimized()` runs a pipeline of `OptimizerPass`es over the node
+//! list after data dependencies have been resolved and before the execution plan
+//! is computed.  Built-in passes are selected with `OptimizerConfig`; custom passes
+//! can be appended with `Optimizer::with_pass()` and run with
+//! `Graph::build_with_optimizer()`.
+
+use crate::analysis::live_nodes;
+use crate::context::ExecutionContext;
+use crate::graph_data::GraphData;
+use crate::node::{unshare, Node, NodeFunction, NodeId};
+use crate::payload::Payload;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+/// A transformation of the node list of a graph being built.
+pub trait OptimizerPass<T = GraphData>: Send + Sync {
+    /// Name shown in `Dag::optimization_log()`
+    fn name(&self) -> &str;
+
+    /// Transform `nodes` in place and return the number of changes made.
+    ///
+    /// Node dependencies have already been resolved; a pass that removes nodes must
+    /// also remove them from the `dependencies` of the remaining nodes.
+    fn run(&self, nodes: &mut Vec<Node<T>>) -> usize;
+}
+
+/// Number of changes made by one pass, as recorded in `Dag::optimization_log()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassSummary {
+    /// Name of the pass
+    pub pass: String,
+    /// Number of nodes or edges changed by the pass
+    pub changes: usize,
+}
+
+/// Selection of the built-in passes for `Graph::build_optimized()`.
+///
+/// All passes are off by default.
+///
+/// ```ignore
+/// let dag = graph.build_optimized(
+///     OptimizerConfig::default()
+///         .dedup_identical_nodes()
+///         .eliminate_dead_nodes(&["final"])
+///         .remove_redundant_edges(),
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OptimizerConfig {
+    /// Merge nodes that would compute identical outputs (`DedupIdenticalNodes`)
+    pub dedup_identical_nodes: bool,
+    /// Remove nodes that do not contribute to these outputs (`DeadNodeElimination`);
+    /// `None` disables the pass
+    pub keep_outputs: Option<Vec<String>>,
+    /// Remove dependencies implied by other dependencies (`RedundantEdgeRemoval`)
+    pub remove_redundant_edges: bool,
+    /// Fuse straight-line sequences of nodes into one node (`LinearChainFusion`)
+    pub fuse_linear_chains: bool,
+}
+
+impl OptimizerConfig {
+    /// Enable `DedupIdenticalNodes`.
+    pub fn dedup_identical_nodes(mut self) -> Self {
+        self.dedup_identical_nodes = true;
+        self
+    }
+
+    /// Enable `DeadNodeElimination`, keeping everything that contributes to `keep`.
+    pub fn eliminate_dead_nodes(mut self, keep: &[&str]) -> Self {
+        self.keep_outputs = Some(keep.iter().map(|s| s.to_string()).collect());
+        self
+    }
+
+    /// Enable `RedundantEdgeRemoval`.
+    pub fn remove_redundant_edges(mut self) -> Self {
+        self.remove_redundant_edges = true;
+        self
+    }
+
+    /// Enable `LinearChainFusion`.
+    pub fn fuse_linear_chains(mut self) -> Self {
+        self.fuse_linear_chains = true;
+        self
+    }
+}
+
+/// An ordered pipeline of optimization passes.
+pub struct Optimizer<T = GraphData> {
+    passes: Vec<Box<dyn OptimizerPass<T>>>,
+}
+
+impl<T: Payload> Default for Optimizer<T> {
+    fn default() -> Self {
+        Self { passes: Vec::new() }
+    }
+}
+
+impl<T: Payload> Optimizer<T> {
+    /// Create an optimizer without any passes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an optimizer running the built-in passes enabled in `config`.
+    ///
+    /// Passes run in this order: identical-node dedup, dead-node elimination,
+    /// redundant-edge removal, linear-chain fusion.
+    pub fn from_config(config: &OptimizerConfig) -> Self {
+        let mut optimizer = Self::new();
+        if config.dedup_identical_nodes {
+            optimizer = optimizer.with_pass(DedupIdenticalNodes);
+        }
+        if let Some(keep) = &config.keep_outputs {
+            optimizer = optimizer.with_pass(DeadNodeElimination { keep: keep.clone() });
+        }
+        if config.remove_redundant_edges {
+            optimizer = optimizer.with_pass(RedundantEdgeRemoval);
+        }
+        if config.fuse_linear_chains {
+            optimizer = optimizer.with_pass(LinearChainFusion);
+        }
+        optimizer
+    }
+
+    /// Append a pass to the pipeline.
+    pub fn with_pass(mut self, pass: impl OptimizerPass<T> + 'static) -> Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    /// Run every pass in order.
+    pub(crate) fn run(&self, nodes: &mut Vec<Node<T>>) -> Vec<PassSummary> {
+        self.passes
+            .iter()
+            .map(|pass| PassSummary {
+                pass: pass.name().to_string(),
+                changes: pass.run(nodes),
+            })
+            .collect()
+    }
+}
+
+// ── Built-in passes ───────────────────────────────────────────────────────────
+
+/// Removes nodes that do not contribute to any of the `keep` outputs.
+///
+/// See `Dag::dead_nodes()`.
+pub struct DeadNodeElimination {
+    /// Broadcast variables that must still be computed
+    pub keep: Vec<String>,
+}
+
+impl<T: Payload> OptimizerPass<T> for DeadNodeElimination {
+    fn name(&self) -> &str {
+        "dead node elimination"
+    }
+
+    fn run(&self, nodes: &mut Vec<Node<T>>) -> usize {
+        let keep: Vec<&str> = self.keep.iter().map(|s| s.as_str()).collect();
+        let live = live_nodes(nodes, &keep);
+        let before = nodes.len();
+        nodes.retain(|n| live.contains(&n.id));
+        for node in nodes.iter_mut() {
+            node.dependencies.retain(|dep| live.contains(dep));
+        }
+        before - nodes.len()
+    }
+}
+
+/// Removes dependencies that are implied by another dependency.
+///
+/// If `c` depends on both `a` and `b`, and `b` already depends (transitively) on
+/// `a`, the edge `a → c` adds nothing to the ordering and is dropped.  Execution
+/// order, levels, and results are unchanged; diagrams get less cluttered.
+pub struct RedundantEdgeRemoval;
+
+impl<T: Payload> OptimizerPass<T> for RedundantEdgeRemoval {
+    fn name(&self) -> &str {
+        "redundant edge removal"
+    }
+
+    fn run(&self, nodes: &mut Vec<Node<T>>) -> usize {
+        let deps: HashMap<NodeId, Vec<NodeId>> = nodes
+            .iter()
+            .map(|n| (n.id, n.dependencies.clone()))
+            .collect();
+
+        // All nodes reachable backwards from `start` through dependencies
+        let ancestors = |start: NodeId| {
+            let mut seen: HashSet<NodeId> = HashSet::new();
+            let mut stack = vec![start];
+            while let Some(id) = stack.pop() {
+                for &dep in deps.get(&id).into_iter().flatten() {
+                    if seen.insert(dep) {
+                        stack.push(dep);
+                    }
+                }
+            }
+            seen
+        };
+
+        let mut removed = 0;
+        for node in nodes.iter_mut() {
+            let implied: HashSet<NodeId> = node
+                .dependencies
+                .iter()
+                .flat_map(|&dep| ancestors(dep))
+                .collect();
+            let before = node.dependencies.len();
+            node.dependencies.retain(|dep| !implied.contains(dep));
+            removed += before - node.dependencies.len();
+        }
+        removed
+    }
+}
+
+/// Merges nodes that would compute identical outputs.
+///
+/// Two nodes are identical when they share the same function instance (the same
+/// `Arc`, as created when `add()` replicates a node over several frontier nodes or
+/// when a sweep repeats a value), read and write the same variables, receive the
+/// same variant parameters and config, and depend on the same nodes.  The node with
+/// the lowest ID is kept and dependents are rewired to it.  Only use this pass with
+/// deterministic node functions.
+pub struct DedupIdenticalNodes;
+
+impl DedupIdenticalNodes {
+    fn identity<T: Payload>(node: &Node<T>) -> String {
+        fn sorted<K: Ord>(mut v: Vec<K>) -> Vec<K> {
+            v.sort();
+            v
+        }
+        let mut key = format!("{:p}", Arc::as_ptr(&node.function) as *const ());
+        if let Some(guard) = &node.guard {
+            let _ = write!(key, " guard={:p} sentinel={:?}", Arc::as_ptr(guard) as *const (), node.skip_sentinel);
+        }
+        if !node.required_inputs.is_empty() {
+            let _ = write!(key, " required={:?}", sorted(node.required_inputs.iter().collect()));
+        }
+        let params = |m: &HashMap<String, T>| {
+            sorted(m.iter().map(|(k, v)| (k.clone(), format!("{:?}", v))).collect())
+        };
+        let _ = write!(
+            key,
+            " in={:?} out={:?} edges={:?} params={:?} config={:?} collect={} branch={:?} deps={:?}",
+            sorted(node.input_mapping.iter().collect()),
+            sorted(node.output_mapping.iter().collect()),
+            sorted(node.edge_outputs.clone()),
+            params(&node.variant_params),
+            params(&node.config),
+            node.collect_inputs,
+            node.branch_id,
+            sorted(node.dependencies.clone())
+        );
+        key
+    }
+}
+
+impl<T: Payload> OptimizerPass<T> for DedupIdenticalNodes {
+    fn name(&self) -> &str {
+        "identical node dedup"
+    }
+
+    fn run(&self, nodes: &mut Vec<Node<T>>) -> usize {
+        let mut removed = 0;
+        // Merging nodes can make their dependents identical in turn
+        loop {
+            nodes.sort_by_key(|n| n.id);
+            let mut first: HashMap<String, NodeId> = HashMap::new();
+            let mut replaced: HashMap<NodeId, NodeId> = HashMap::new();
+            for node in nodes.iter() {
+                let kept = *first.entry(Self::identity(node)).or_insert(node.id);
+                if kept != node.id {
+                    replaced.insert(node.id, kept);
+                }
+            }
+            if replaced.is_empty() {
+                return removed;
+            }
+
+            removed += replaced.len();
+            nodes.retain(|n| !replaced.contains_key(&n.id));
+            for node in nodes.iter_mut() {
+                let mut deps: Vec<NodeId> = node
+                    .dependencies
+                    .iter()
+                    .map(|dep| *replaced.get(dep).unwrap_or(dep))
+                    .collect();
+                deps.sort_unstable();
+                deps.dedup();
+                node.dependencies = deps;
+            }
+        }
+    }
+}
+
+/// Fuses straight-line sequences of nodes into a single composite node.
+///
+/// A node `b` is fused into `a` when `a` is its only dependency and `b` is the only
+/// node depending on `a`, so no parallelism is lost.  The fused node runs both
+/// functions back to back, keeps `a`'s ID, writes the outputs of both nodes to
+/// the context, and is labelled `"a + b"`; this saves the per-node scheduling and
+/// context round-trips of graphs made of many tiny mapping nodes.
+///
+/// Nodes that need their own identity at run time are left alone: branch and
+/// variant nodes, merge and collecting nodes, nodes with a distribution
+/// transfer, guarded nodes, nodes with required inputs, and nodes feeding an
+/// explicit connection.  Per-node outputs and timings are reported for the fused node.
+pub struct LinearChainFusion;
+
+impl LinearChainFusion {
+    fn fusible<T>(node: &Node<T>) -> bool {
+        node.branch_id.is_none()
+            && !node.is_branch
+            && node.variant_index.is_none()
+            && !node.collect_inputs
+            && node.dist_transfer.is_none()
+            && node.edge_outputs.is_empty()
+            && node.guard.is_none()
+            && node.required_inputs.is_empty()
+            && node.input_mapping.keys().all(|k| !k.contains(':'))
+    }
+
+    /// Composite node running `a` then `b`, with identity port mappings.
+    fn fuse<T: Payload>(a: Node<T>, b: Node<T>) -> Node<T> {
+        let a_outputs: HashSet<&String> = a.output_mapping.values().collect();
+        let mut inputs: Vec<String> = a.input_mapping.keys().cloned().collect();
+        inputs.extend(
+            b.input_mapping
+                .keys()
+                .filter(|var| !a_outputs.contains(var))
+                .cloned(),
+        );
+        let mut outputs: Vec<String> = a.output_mapping.values().cloned().collect();
+        outputs.extend(b.output_mapping.values().cloned());
+
+        let identity = |vars: Vec<String>| -> HashMap<String, String> {
+            vars.into_iter().map(|v| (v.clone(), v)).collect()
+        };
+        let label = format!("{} + {}", a.display_name(), b.display_name());
+        let (id, dependencies) = (a.id, a.dependencies.clone());
+
+        // Inputs arrive keyed by broadcast name, which is exactly the context view
+        // each inner node expects
+        let function: NodeFunction<T> = Arc::new(move |context: &HashMap<String, Arc<T>>| {
+            let mut local: ExecutionContext<T> =
+                context.iter().map(|(key, value)| (key, Arc::clone(value))).collect();
+            let mut outputs = a.execute(&local);
+            local.extend(outputs.clone());
+            outputs.extend(b.execute(&local));
+            drop(local);
+            unshare(outputs)
+        });
+
+        let mut fused = Node::new(id, function, Some(label), identity(inputs), identity(outputs));
+        fused.dependencies = dependencies;
+        fused
+    }
+}
+
+impl<T: Payload> OptimizerPass<T> for LinearChainFusion {
+    fn name(&self) -> &str {
+        "linear chain fusion"
+    }
+
+    fn run(&self, nodes: &mut Vec<Node<T>>) -> usize {
+        let mut fused = 0;
+        loop {
+            let mut dependents: HashMap<NodeId, usize> = HashMap::new();
+            for node in nodes.iter() {
+                for &dep in &node.dependencies {
+                    *dependents.entry(dep).or_default() += 1;
+                }
+            }
+
+            // Lowest-ID node whose single dependency feeds only it
+            let mut candidates: Vec<(NodeId, NodeId)> = nodes
+                .iter()
+                .filter(|b| Self::fusible(b) && b.dependencies.len() == 1)
+                .map(|b| (b.dependencies[0], b.id))
+                .filter(|(a, _)| dependents.get(a) == Some(&1))
+                .filter(|(a, _)| nodes.iter().any(|n| n.id == *a && Self::fusible(n)))
+                .collect();
+            candidates.sort_unstable();
+            let Some(&(a_id, b_id)) = candidates.first() else {
+                return fused;
+            };
+
+            let a_pos = nodes.iter().position(|n| n.id == a_id).unwrap_or_default();
+            let a = nodes.remove(a_pos);
+            let b_pos = nodes.iter().position(|n| n.id == b_id).unwrap_or_default();
+            let b = nodes.remove(b_pos);
+            nodes.push(Self::fuse(a, b));
+            for node in nodes.iter_mut() {
+                for dep in node.dependencies.iter_mut() {
+                    if *dep == b_id {
+                        *dep = a_id;
+                    }
+                }
+            }
+            fused += 1;
+        }
+    }
+}
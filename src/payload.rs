@@ -0,0 +1,111 @@
+//! The `Payload` trait: what a graph can carry between nodes
+//!
+//! `Graph`, `Dag`, and `Node` are generic over the type of the values stored in
+//! the execution context.  It defaults to `GraphData`, which supports every
+//! feature of the crate; any other `Clone + Send + Sync + Debug` type can be used
+//! by implementing `Payload`, so pipelines can pass their own enums or structs
+//! around without converting them to `GraphData`:
+//!
+//! ```ignore
+//! #[derive(Clone, Debug)]
+//! enum Value { Samples(Vec<f32>), Label(String) }
+//! impl Payload for Value {}
+//!
+//! let mut graph = Graph::<Value>::default();
+//! graph.add(load, Some("Load"), None, Some(vec![("samples", "samples")]));
+//! ```
+//!
+//! Features that need to look inside values — variant sweeps built from
+//! `IntoVariantValues`, `Dag::predict()`, `Dag::to_html_report()`, and the Python
+//! bindings — are only available for `GraphData` graphs.
+
+use crate::graph_data::{GraphData, GraphDataKind};
+use std::fmt::Debug;
+use std::hash::Hasher;
+
+/// A value type that can flow through a graph.
+///
+/// `Debug` is used wherever values are shown (diffs, reports); keep it short for
+/// large values, as `GraphData` does.
+pub trait Payload: Clone + Send + Sync + Debug + 'static {
+    /// Combine the values of several producers into one value.
+    ///
+    /// Used by `ConflictPolicy::Collect` and `Graph::collect()`.  The default
+    /// returns `None`, in which case nothing is gathered: collected variables keep
+    /// last-writer-wins semantics and collecting inputs stay missing.
+    fn gather(values: Vec<Self>) -> Option<Self> {
+        let _ = values;
+        None
+    }
+
+    /// Hash of the content of a value, equal for equal values in every run.
+    ///
+    /// Used by `Dag::execute_hashed()` to fingerprint node outputs.  The default
+    /// returns `None`, in which case nodes producing the value get no hash.  Types
+    /// implementing `Hash` can feed themselves to a `ContentHasher`, whose result
+    /// does not depend on the process or the build.
+    fn content_hash(&self) -> Option<u64> {
+        None
+    }
+
+    /// Kind of the value, checked against declared port types at run time when
+    /// the graph enforces its schema (`Graph::enforce_schema()`)
+    ///
+    /// The default returns `None`, which is never rejected.
+    fn kind(&self) -> Option<GraphDataKind> {
+        None
+    }
+
+    /// The value multiplied by `factor`, used to convert inputs between units
+    /// (`Graph::auto_scale_units()`)
+    ///
+    /// The default returns `None`: the value cannot be scaled, and a node whose
+    /// input needs converting fails with `ExecutionError::InvalidInput`.
+    fn scaled(&self, factor: f64) -> Option<Self> {
+        let _ = factor;
+        None
+    }
+}
+
+impl Payload for GraphData {
+    fn gather(values: Vec<Self>) -> Option<Self> {
+        Some(GraphData::List(values))
+    }
+
+    fn content_hash(&self) -> Option<u64> {
+        GraphData::content_hash(self)
+    }
+
+    fn kind(&self) -> Option<GraphDataKind> {
+        Some(GraphData::kind(self))
+    }
+
+    fn scaled(&self, factor: f64) -> Option<Self> {
+        GraphData::scaled(self, factor)
+    }
+}
+
+/// Hasher whose results are stable across runs and builds (64-bit FNV-1a)
+///
+/// `std`'s `DefaultHasher` may change between Rust releases, so hashes that are
+/// stored or compared between program runs use this instead.
+#[derive(Debug, Clone, Copy)]
+pub struct ContentHasher(u64);
+
+impl Default for ContentHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for ContentHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
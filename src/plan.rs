@@ -0,0 +1,229 @@
+//! Per-node execution plans, computed once when a `Dag` is built
+//!
+//! Where a node's inputs live in the context (a branch namespace, a merge key,
+//! a collected list) and which of its dependencies can cause it to be skipped
+//! only depend on the structure of the graph.  Resolving them up front means
+//! that executing a node looks up exactly the context keys it needs, without
+//! parsing mappings, formatting keys, or scanning other nodes.
+
+use crate::conflict::collected_key;
+use crate::context::ExecutionContext;
+use crate::node::{Node, NodeId};
+use crate::symbol::Symbol;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Where a node finds one of its inputs
+#[derive(Debug, Clone)]
+pub(crate) enum InputSource {
+    /// The first of these context keys that is present
+    Keys(Vec<Symbol>),
+    /// The list gathered from every producer of a variable (`Graph::collect()`)
+    Collected(Symbol),
+}
+
+/// Inputs of a node: (impl_var, source) pairs
+pub(crate) type InputPlan = Vec<(String, InputSource)>;
+
+/// Everything `Dag` needs to execute one node
+#[derive(Debug, Clone)]
+pub(crate) struct NodePlan {
+    /// Where each input comes from
+    pub(crate) inputs: InputPlan,
+    /// Context keys each output variable is stored under: its branch namespace or
+    /// the shared variable, and for variant nodes the variant's own entry
+    pub(crate) outputs: Vec<(String, Vec<Symbol>)>,
+    /// Collected context keys the node's outputs contribute to
+    pub(crate) collects_into: Vec<Symbol>,
+    /// Whether skipped producers never skip the node (collectors)
+    partial_inputs: bool,
+    /// Dependencies the node shares no variable with (explicit dependencies)
+    explicit_deps: Vec<NodeId>,
+    /// For each variable read from dependencies, the dependencies producing it
+    producers: Vec<Vec<NodeId>>,
+}
+
+/// Resolve the context keys of each input of `node`
+///
+/// Merge inputs (`"branch_id:var"`) read the branch's namespaced key; other
+/// inputs try the namespaces of isolated branches, innermost first, before the
+/// shared variable.
+pub(crate) fn input_plan<T>(node: &Node<T>) -> InputPlan {
+    node.input_mapping
+        .iter()
+        .map(|(broadcast_key, impl_var)| {
+            let source = if broadcast_key.contains(':') {
+                let parts: Vec<&str> = broadcast_key.split(':').collect();
+                let keys = if parts.len() == 2 {
+                    vec![Symbol::from(format!("__branch_{}__{}", parts[0], parts[1]))]
+                } else {
+                    Vec::new()
+                };
+                InputSource::Keys(keys)
+            } else if node.collect_inputs {
+                InputSource::Collected(collected_key(broadcast_key).into())
+            } else {
+                let mut keys: Vec<Symbol> = node
+                    .branch_scopes
+                    .iter()
+                    .map(|id| Symbol::from(format!("__branch_{}__{}", id, broadcast_key)))
+                    .collect();
+                keys.push(broadcast_key.into());
+                InputSource::Keys(keys)
+            };
+            (impl_var.clone(), source)
+        })
+        .collect()
+}
+
+impl NodePlan {
+    /// Plan the execution of `node`, whose dependencies are `deps`
+    pub(crate) fn new<T>(node: &Node<T>, deps: &[&Node<T>]) -> Self {
+        // Variable each input reads: merge keys name a branch before the variable
+        let read: HashSet<&str> = node
+            .input_mapping
+            .keys()
+            .map(|key| key.split_once(':').map_or(key.as_str(), |(_, var)| var))
+            .collect();
+        let produces = |dep: &Node<T>, var: &str| dep.all_outputs().any(|(_, v)| v == var);
+
+        let explicit_deps = deps
+            .iter()
+            .filter(|dep| !read.iter().any(|var| produces(dep, var)))
+            .map(|dep| dep.id)
+            .collect();
+        let mut read: Vec<&str> = read.into_iter().collect();
+        read.sort_unstable();
+        let producers = read
+            .into_iter()
+            .map(|var| {
+                deps.iter()
+                    .filter(|dep| produces(dep, var))
+                    .map(|dep| dep.id)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|ids| !ids.is_empty())
+            .collect();
+
+        let mut outputs: Vec<(String, Vec<Symbol>)> = Vec::new();
+        for (_, var) in node.all_outputs() {
+            if outputs.iter().any(|(known, _)| known == var) {
+                continue;
+            }
+            let mut keys = vec![Symbol::from(node.context_key(var))];
+            if let Some(namespaced) = node.variant_key(var) {
+                keys.push(node.context_key(&namespaced).into());
+            }
+            outputs.push((var.clone(), keys));
+        }
+
+        Self {
+            inputs: input_plan(node),
+            outputs,
+            collects_into: Vec::new(),
+            partial_inputs: node.partial_inputs,
+            explicit_deps,
+            producers,
+        }
+    }
+
+    /// Store a node's outputs in `context` under their planned keys; every key
+    /// shares the node's value
+    pub(crate) fn write_outputs<T>(&self, context: &mut ExecutionContext<T>, outputs: &HashMap<String, Arc<T>>) {
+        for (var, keys) in &self.outputs {
+            if let Some(value) = outputs.get(var) {
+                for &key in keys {
+                    context.insert(key, Arc::clone(value));
+                }
+            }
+        }
+    }
+
+    /// Whether the node must be skipped because nodes it depends on were skipped
+    /// (those for which `skipped` returns true)
+    ///
+    /// True when some variable the node reads was produced only by skipped nodes,
+    /// or when a skipped dependency shares no variable with it (an explicit
+    /// dependency).  A variable with another producer that ran is still available,
+    /// so consumers of `switch()` outputs run whichever branch was chosen.  Nodes
+    /// accepting partial inputs (collectors) are never skipped this way.
+    pub(crate) fn skipped_by_dependencies(&self, skipped: impl Fn(&NodeId) -> bool) -> bool {
+        if self.partial_inputs {
+            return false;
+        }
+        self.explicit_deps.iter().any(&skipped)
+            || self.producers.iter().any(|ids| ids.iter().all(&skipped))
+    }
+}
+
+/// A set of chains of nodes that the parallel executor runs concurrently, each
+/// on its own thread (see `parallel_stages()`)
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Stage {
+    /// Chains of nodes, each in execution order
+    pub(crate) chains: Vec<Vec<NodeId>>,
+    /// (chain, index in chain) of every node of the stage, in execution order
+    pub(crate) merge_order: Vec<(usize, usize)>,
+}
+
+/// Stages of the parallel executor, in order
+///
+/// A node joins the chain of its dependency when that dependency is its only one
+/// and it is the dependency's only dependent, so each chain only reads what
+/// earlier stages and its own nodes produced.  A pipeline hanging off a fan-out
+/// is one chain, so it runs start to finish without waiting for the nodes of its
+/// level in other pipelines.  Guarded and collecting nodes always start a chain, since they read
+/// more than their dependencies' outputs.
+pub(crate) fn parallel_stages<T>(nodes: &[Node<T>], execution_order: &[NodeId]) -> Vec<Stage> {
+    let node_of: HashMap<NodeId, &Node<T>> = nodes.iter().map(|node| (node.id, node)).collect();
+    let mut dependents: HashMap<NodeId, usize> = HashMap::new();
+    for node in nodes {
+        let deps: HashSet<NodeId> = node.dependencies.iter().copied().collect();
+        for dep in deps {
+            *dependents.entry(dep).or_default() += 1;
+        }
+    }
+
+    // (stage, chain) of every node placed so far
+    let mut placed: HashMap<NodeId, (usize, usize)> = HashMap::new();
+    let mut stages: Vec<Stage> = Vec::new();
+    for &id in execution_order {
+        let node = node_of[&id];
+        let deps: HashSet<NodeId> = node
+            .dependencies
+            .iter()
+            .copied()
+            .filter(|dep| node_of.contains_key(dep))
+            .collect();
+        let chained = match deps.iter().next() {
+            Some(dep) if deps.len() == 1 && dependents.get(dep) == Some(&1) => placed
+                .get(dep)
+                .copied()
+                .filter(|&(stage, chain)| stages[stage].chains[chain].last() == Some(dep)),
+            _ => None,
+        }
+        .filter(|_| node.guard.is_none() && !node.collect_inputs);
+
+        let (stage, chain) = match chained {
+            Some(place) => place,
+            None => {
+                let stage = deps
+                    .iter()
+                    .filter_map(|dep| placed.get(dep))
+                    .map(|&(stage, _)| stage + 1)
+                    .max()
+                    .unwrap_or(0);
+                if stages.len() <= stage {
+                    stages.resize_with(stage + 1, Stage::default);
+                }
+                stages[stage].chains.push(Vec::new());
+                (stage, stages[stage].chains.len() - 1)
+            }
+        };
+        let stage_ref = &mut stages[stage];
+        stage_ref.merge_order.push((chain, stage_ref.chains[chain].len()));
+        stage_ref.chains[chain].push(id);
+        placed.insert(id, (stage, chain));
+    }
+    stages
+}
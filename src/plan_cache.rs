@@ -0,0 +1,136 @@
+//! `ExecutionPlan` — the execution order and levels of a DAG, saved to disk
+//!
+//! Sorting a graph topologically and grouping it into levels is linear in its
+//! size, but for very large graphs that are rebuilt identically on every program
+//! start it is still work worth skipping.  The plan is keyed by a hash of the
+//! topology (node IDs and their dependencies), so a stale plan is never used: a
+//! graph whose wiring changed is analyzed again.
+//!
+//! ```ignore
+//! // Reads the plan if the file holds one for this topology, writes it otherwise
+//! let dag = graph.build_cached("pipeline.plan")?;
+//! // Or in any `StorageBackend`
+//! let dag = graph.build_cached_in(&storage, "pipeline.plan")?;
+//! ```
+
+use crate::node::{Node, NodeId};
+use crate::payload::ContentHasher;
+use crate::storage::StorageBackend;
+use std::fmt::Write as _;
+use std::hash::Hasher;
+use std::io;
+use std::path::Path;
+
+/// First line of a saved plan
+const HEADER: &str = "dagex-plan 1";
+
+/// Execution order and levels of a DAG, with the hash of the topology they were
+/// computed for (see `Dag::execution_plan()`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionPlan {
+    pub(crate) topology_hash: u64,
+    pub(crate) execution_order: Vec<NodeId>,
+    pub(crate) execution_levels: Vec<Vec<NodeId>>,
+}
+
+impl ExecutionPlan {
+    /// Hash of the node IDs and dependencies the plan was computed for
+    pub fn topology_hash(&self) -> u64 {
+        self.topology_hash
+    }
+
+    /// Node IDs in execution order
+    pub fn execution_order(&self) -> &[NodeId] {
+        &self.execution_order
+    }
+
+    /// Node IDs grouped into levels that can run in parallel
+    pub fn execution_levels(&self) -> &[Vec<NodeId>] {
+        &self.execution_levels
+    }
+
+    /// Write the plan to `path` as text
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_text())
+    }
+
+    /// Read a plan written by `save()`
+    ///
+    /// Fails with `io::ErrorKind::InvalidData` if the file is not a saved plan.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not a dagex execution plan"))
+    }
+
+    /// Store the plan under `key`, as written by `save()`
+    pub fn save_to(&self, storage: &dyn StorageBackend, key: &str) -> io::Result<()> {
+        storage.put(key, self.to_text().as_bytes())
+    }
+
+    /// Read a plan stored by `save_to()`
+    ///
+    /// Fails with `io::ErrorKind::NotFound` if nothing is stored under `key`, and
+    /// with `io::ErrorKind::InvalidData` if it is not a saved plan.
+    pub fn load_from(storage: &dyn StorageBackend, key: &str) -> io::Result<Self> {
+        let bytes = storage
+            .get(key)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no execution plan under '{}'", key)))?;
+        std::str::from_utf8(&bytes)
+            .ok()
+            .and_then(Self::parse)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not a dagex execution plan"))
+    }
+
+    /// One line each for the header, the hash, the order, and every level
+    fn to_text(&self) -> String {
+        fn line(tag: &str, ids: &[NodeId]) -> String {
+            let mut line = tag.to_string();
+            for id in ids {
+                let _ = write!(line, " {}", id);
+            }
+            line.push('\n');
+            line
+        }
+        let mut text = format!("{}\ntopology {:016x}\n", HEADER, self.topology_hash);
+        text += &line("order", &self.execution_order);
+        for level in &self.execution_levels {
+            text += &line("level", level);
+        }
+        text
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        if lines.next()? != HEADER {
+            return None;
+        }
+        let topology_hash = u64::from_str_radix(lines.next()?.strip_prefix("topology ")?, 16).ok()?;
+        let ids = |line: &str, tag: &str| -> Option<Vec<NodeId>> {
+            line.strip_prefix(tag)?.split_whitespace().map(|id| id.parse().ok()).collect()
+        };
+        let execution_order = ids(lines.next()?, "order")?;
+        let execution_levels = lines.map(|line| ids(line, "level")).collect::<Option<Vec<_>>>()?;
+        Some(Self {
+            topology_hash,
+            execution_order,
+            execution_levels,
+        })
+    }
+}
+
+/// Hash of what the execution order and levels depend on: the ID of every node
+/// and the IDs of its dependencies
+pub(crate) fn topology_hash<T>(nodes: &[Node<T>]) -> u64 {
+    let mut hasher = ContentHasher::default();
+    hasher.write_u64(nodes.len() as u64);
+    for node in nodes {
+        let mut deps = node.dependencies.clone();
+        deps.sort_unstable();
+        hasher.write_u64(node.id as u64);
+        hasher.write_u64(deps.len() as u64);
+        for dep in deps {
+            hasher.write_u64(dep as u64);
+        }
+    }
+    hasher.finish()
+}
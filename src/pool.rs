@@ -0,0 +1,241 @@
+//! `ExecutorPool` — long-lived worker threads for running many executions
+//!
+//! `Dag::execute()` runs on the calling thread (spawning scoped threads for
+//! parallel stages), which is the right default for scripts and batch jobs.  A
+//! service that executes graphs per request instead wants a fixed set of threads
+//! that outlive any single execution: jobs are queued, picked up by the next idle
+//! worker, and observed through a `JobHandle`.
+//!
+//! ```ignore
+//! let pool = ExecutorPool::new(4);
+//! let dag = Arc::new(graph.build());
+//!
+//! let jobs: Vec<_> = requests
+//!     .into_iter()
+//!     .map(|inputs| pool.execute(&dag, inputs))
+//!     .collect();
+//! for job in jobs {
+//!     println!("{:?}", job.wait().get("result"));
+//! }
+//! ```
+
+use crate::context::ExecutionContext;
+use crate::dag::Dag;
+use crate::payload::Payload;
+use std::collections::{HashMap, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Where a job submitted to an `ExecutorPool` is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    /// Waiting for an idle worker
+    Queued,
+    /// Running on a worker
+    Running,
+    /// Done; `JobHandle::wait()` returns immediately
+    Finished,
+}
+
+/// Fixed set of worker threads executing queued jobs
+///
+/// Each job runs on a single worker, so a pool of `n` threads runs up to `n`
+/// executions at once; `execute()` runs the DAG sequentially on its worker.
+/// Dropping the pool lets the workers finish every queued job, then joins them.
+pub struct ExecutorPool {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+struct Shared {
+    queue: Mutex<Queue>,
+    /// Signalled when a job is queued or the pool shuts down
+    work: Condvar,
+}
+
+#[derive(Default)]
+struct Queue {
+    jobs: VecDeque<Job>,
+    running: usize,
+    next_id: u64,
+    closed: bool,
+}
+
+impl ExecutorPool {
+    /// Start a pool of `threads` workers (`0` = one per available CPU)
+    pub fn new(threads: usize) -> Self {
+        let threads = match threads {
+            0 => thread::available_parallelism().map_or(1, usize::from),
+            n => n,
+        };
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(Queue::default()),
+            work: Condvar::new(),
+        });
+        let workers = (0..threads)
+            .map(|i| {
+                let shared = Arc::clone(&shared);
+                thread::Builder::new()
+                    .name(format!("dagex-worker-{}", i))
+                    .spawn(move || shared.work_loop())
+                    .expect("failed to spawn worker thread")
+            })
+            .collect();
+        Self { shared, workers }
+    }
+
+    /// Number of worker threads
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Jobs waiting for a worker
+    pub fn queued(&self) -> usize {
+        self.shared.lock().jobs.len()
+    }
+
+    /// Jobs currently running
+    pub fn running(&self) -> usize {
+        self.shared.lock().running
+    }
+
+    /// Queue `job` to run on the next idle worker
+    ///
+    /// A panic in the job does not take the worker down; it is raised again by
+    /// `JobHandle::wait()`.
+    pub fn spawn<R, F>(&self, job: F) -> JobHandle<R>
+    where
+        R: Send + 'static,
+        F: FnOnce() -> R + Send + 'static,
+    {
+        let state = Arc::new(JobState {
+            slot: Mutex::new((JobStatus::Queued, None)),
+            done: Condvar::new(),
+        });
+        let job_state = Arc::clone(&state);
+        let run: Job = Box::new(move || {
+            job_state.lock().0 = JobStatus::Running;
+            let outcome = panic::catch_unwind(AssertUnwindSafe(job));
+            *job_state.lock() = (JobStatus::Finished, Some(outcome));
+            job_state.done.notify_all();
+        });
+
+        let mut queue = self.shared.lock();
+        let id = queue.next_id;
+        queue.next_id += 1;
+        queue.jobs.push_back(run);
+        drop(queue);
+        self.shared.work.notify_one();
+        JobHandle { id, state }
+    }
+
+    /// Queue an execution of `dag` with `inputs` (see `Dag::execute_with_inputs()`)
+    pub fn execute<T: Payload>(
+        &self,
+        dag: &Arc<Dag<T>>,
+        inputs: HashMap<String, T>,
+    ) -> JobHandle<ExecutionContext<T>> {
+        let dag = Arc::clone(dag);
+        self.spawn(move || dag.execute_with_inputs(inputs, false, None))
+    }
+}
+
+impl Drop for ExecutorPool {
+    fn drop(&mut self) {
+        self.shared.lock().closed = true;
+        self.shared.work.notify_all();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Run queued jobs until the pool is closed and the queue is empty
+    fn work_loop(&self) {
+        let mut queue = self.lock();
+        loop {
+            if let Some(job) = queue.jobs.pop_front() {
+                queue.running += 1;
+                drop(queue);
+                job();
+                queue = self.lock();
+                queue.running -= 1;
+            } else if queue.closed {
+                return;
+            } else {
+                queue = self.work.wait(queue).unwrap_or_else(|e| e.into_inner());
+            }
+        }
+    }
+}
+
+/// The result slot of a job: its status and, once finished, its outcome
+struct JobState<R> {
+    slot: Mutex<(JobStatus, Option<thread::Result<R>>)>,
+    done: Condvar,
+}
+
+impl<R> JobState<R> {
+    fn lock(&self) -> MutexGuard<'_, (JobStatus, Option<thread::Result<R>>)> {
+        self.slot.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Handle to a job submitted to an `ExecutorPool`
+pub struct JobHandle<R> {
+    id: u64,
+    state: Arc<JobState<R>>,
+}
+
+impl<R> JobHandle<R> {
+    /// Sequence number of the job within its pool, in submission order
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Where the job is
+    pub fn status(&self) -> JobStatus {
+        self.state.lock().0
+    }
+
+    /// Whether the job is done
+    pub fn is_finished(&self) -> bool {
+        self.status() == JobStatus::Finished
+    }
+
+    /// Block until the job is done, at most `timeout`; returns whether it is done
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let slot = self.state.lock();
+        let (slot, _) = self
+            .state
+            .done
+            .wait_timeout_while(slot, timeout, |(status, _)| *status != JobStatus::Finished)
+            .unwrap_or_else(|e| e.into_inner());
+        slot.0 == JobStatus::Finished
+    }
+
+    /// Block until the job is done and return its result
+    ///
+    /// If the job panicked, the panic is resumed on the calling thread.
+    pub fn wait(self) -> R {
+        let slot = self.state.lock();
+        let mut slot = self
+            .state
+            .done
+            .wait_while(slot, |(_, outcome)| outcome.is_none())
+            .unwrap_or_else(|e| e.into_inner());
+        match slot.1.take().expect("job finished without an outcome") {
+            Ok(result) => result,
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
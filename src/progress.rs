@@ -0,0 +1,190 @@
+//! Progress reporting and cooperative cancellation for `Dag::execute_with_progress()`
+//!
+//! ```ignore
+//! let cancel = CancelToken::new();
+//! let stop = cancel.clone();
+//! std::thread::spawn(move || {
+//!     wait_for_abort_button();
+//!     stop.cancel();
+//! });
+//!
+//! let result = dag.execute_with_progress(true, None, Some(&cancel), |p| {
+//!     println!("[{}/{}] {}", p.completed, p.total, p.label);
+//! });
+//! if result.cancelled {
+//!     eprintln!("stopped early; {} nodes did not run", result.skipped_nodes.len());
+//! }
+//! ```
+//!
+//! With the `signals` feature, `Dag::execute_interruptible()` cancels its run on
+//! Ctrl-C (SIGINT) or SIGTERM instead of letting the signal kill the process.
+
+use crate::node::{Node, NodeId};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+#[cfg(feature = "signals")]
+use std::sync::Mutex;
+
+/// Flag that asks a running execution to stop
+///
+/// Clones share the flag, so one clone can be handed to a signal handler or UI
+/// thread while another is passed to `Dag::execute_with_progress()`.  Nodes that
+/// are already running finish; nodes that have not started are skipped.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    /// Create a token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every execution holding this token to stop
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    /// Whether `cancel()` has been called
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+}
+
+/// Report passed to the progress callback after each node
+#[derive(Debug, Clone)]
+pub struct NodeProgress {
+    /// Node that finished
+    pub node_id: NodeId,
+    /// Display name of that node
+    pub label: String,
+    /// Whether the node ran (false when a guard or missing input skipped it)
+    pub ran: bool,
+    /// Nodes finished so far, including this one
+    pub completed: usize,
+    /// Number of nodes in the DAG
+    pub total: usize,
+}
+
+/// Callback invoked with the progress of each finished node.
+pub(crate) type OnProgress<'a> = &'a (dyn Fn(&NodeProgress) + Sync);
+
+/// Hook wrapping the execution of each node, called on the thread that runs it
+pub(crate) type AroundNode<'a> = &'a (dyn Fn(&mut dyn FnMut()) + Sync);
+
+/// Progress callback and cancel token of one run
+pub(crate) struct RunControl<'a> {
+    on_progress: OnProgress<'a>,
+    cancel: Option<&'a CancelToken>,
+    around_node: Option<AroundNode<'a>>,
+    completed: AtomicUsize,
+    total: usize,
+}
+
+impl<'a> RunControl<'a> {
+    pub(crate) fn new(total: usize, cancel: Option<&'a CancelToken>, on_progress: OnProgress<'a>) -> Self {
+        Self {
+            on_progress,
+            cancel,
+            around_node: None,
+            completed: AtomicUsize::new(0),
+            total,
+        }
+    }
+
+    /// Run every node inside `around`, e.g. to set up per-run thread-local state
+    /// on the worker thread executing it
+    #[cfg(feature = "python")]
+    pub(crate) fn around_node(mut self, around: AroundNode<'a>) -> Self {
+        self.around_node = Some(around);
+        self
+    }
+
+    /// Execute one node through the hook of `control`, if any
+    pub(crate) fn run_node<R>(control: Option<&Self>, execute: impl FnOnce() -> R) -> R {
+        let Some(around) = control.and_then(|control| control.around_node) else {
+            return execute();
+        };
+        let (mut execute, mut outcome) = (Some(execute), None);
+        around(&mut || outcome = execute.take().map(|execute| execute()));
+        outcome.expect("the around_node hook must execute the node")
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancel.is_some_and(CancelToken::is_cancelled)
+    }
+
+    /// Count `node` as finished and report it
+    pub(crate) fn finish<T>(&self, node: &Node<T>, ran: bool) {
+        let completed = self.completed.fetch_add(1, Ordering::AcqRel) + 1;
+        (self.on_progress)(&NodeProgress {
+            node_id: node.id,
+            label: node.display_name(),
+            ran,
+            completed,
+            total: self.total,
+        });
+    }
+}
+
+/// Token cancelled by SIGINT and SIGTERM, once the handlers are installed
+#[cfg(feature = "signals")]
+static INTERRUPT: Mutex<Option<CancelToken>> = Mutex::new(None);
+
+/// Number of live `InterruptGuard`s
+#[cfg(feature = "signals")]
+static INTERRUPTIBLE_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// Turns SIGINT and SIGTERM into a cancellation while it is alive
+///
+/// The first signal cancels `token`; a second one, or any signal while no guard
+/// is alive, terminates the process with status 128 + signal as if unhandled.
+/// All live guards share one token, so a signal stops every interruptible run.
+#[cfg(feature = "signals")]
+pub(crate) struct InterruptGuard {
+    pub(crate) token: CancelToken,
+}
+
+#[cfg(feature = "signals")]
+impl InterruptGuard {
+    pub(crate) fn new() -> std::io::Result<Self> {
+        use signal_hook::consts::{SIGINT, SIGTERM};
+
+        let mut installed = INTERRUPT.lock().unwrap();
+        let token = match &*installed {
+            Some(token) => token.clone(),
+            None => {
+                let token = CancelToken::new();
+                for signal in [SIGINT, SIGTERM] {
+                    let cancelled = Arc::clone(&token.cancelled);
+                    // SAFETY: the action only uses atomics and `exit()`, which are
+                    // async-signal-safe
+                    unsafe {
+                        signal_hook::low_level::register(signal, move || {
+                            if INTERRUPTIBLE_RUNS.load(Ordering::Acquire) == 0
+                                || cancelled.swap(true, Ordering::AcqRel)
+                            {
+                                signal_hook::low_level::exit(128 + signal);
+                            }
+                        })?;
+                    }
+                }
+                installed.insert(token).clone()
+            }
+        };
+        if INTERRUPTIBLE_RUNS.fetch_add(1, Ordering::AcqRel) == 0 {
+            // A signal from an earlier run must not cancel this one
+            token.cancelled.store(false, Ordering::Release);
+        }
+        Ok(Self { token })
+    }
+}
+
+#[cfg(feature = "signals")]
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        let _installed = INTERRUPT.lock().unwrap();
+        INTERRUPTIBLE_RUNS.fetch_sub(1, Ordering::AcqRel);
+    }
+}
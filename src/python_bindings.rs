@@ -3,19 +3,23 @@
 //! This module provides PyO3 bindings to expose the Rust graph executor to Python.
 //! It is gated behind the "python" feature flag.
 
-use pyo3::exceptions::PyValueError;
+use pyo3::exceptions::{PyKeyboardInterrupt, PyTypeError, PyValueError};
 use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
 #[cfg(feature = "radar_examples")]
 use pyo3::types::PyComplex;
-use pyo3::types::{PyDict, PyList};
+use pyo3::types::{PyBool, PyBytes, PyDict, PyFloat, PyList, PyLong, PyString, PyTuple};
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use crate::builder::Graph;
-use crate::dag::{Dag, PredictTarget};
+use crate::dag::{Dag, DagStats, ExecutionResult, PredictTarget};
 use crate::distribution::{DistContext, Distribution};
 use crate::graph_data::GraphData;
+use crate::progress::{CancelToken, NodeProgress};
 use crate::stat_result::StatResult;
+use crate::sweep::{FloatFormat, Geomspace, IntoVariantValues, Linspace, Logspace};
 
 // ─── PyDistribution ─────────────────────────────────────────────────────
 
@@ -200,12 +204,426 @@ fn dist_context_to_py_dict(py: Python, ctx: Option<&DistContext>) -> PyObject {
     dict.to_object(py)
 }
 
+// ─── PySweep ─────────────────────────────────────────────────────────────────
+
+/// Labelled values of a parameter sweep, created by `linspace()`, `logspace()` or
+/// `geomspace()` and passed to `Graph.variant()`.
+#[pyclass(name = "Sweep")]
+struct PySweep {
+    values: Vec<(String, GraphData)>,
+}
+
+#[pymethods]
+impl PySweep {
+    /// The (rounded) values of the sweep.
+    fn values(&self, py: Python) -> PyObject {
+        let values: Vec<PyObject> = self
+            .values
+            .iter()
+            .map(|(_, v)| graph_data_to_python(py, v))
+            .collect();
+        values.to_object(py)
+    }
+
+    /// The labels used in variant node names, one per value.
+    fn labels(&self) -> Vec<String> {
+        self.values.iter().map(|(l, _)| l.clone()).collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.values.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Sweep([{}])", self.labels().join(", "))
+    }
+}
+
+/// Label a value from Python the way the Rust sweeps label theirs
+fn labelled_value(value: GraphData) -> (String, GraphData) {
+    let label = match value.as_float() {
+        Some(f) if value.as_int().is_none() => FloatFormat::Auto.format(f),
+        _ => value.to_string_repr(),
+    };
+    (label, value)
+}
+
+/// Scientific format with `precision` digits, or the default rounding
+fn sweep_format(precision: Option<usize>, fixed: bool) -> FloatFormat {
+    match precision {
+        Some(digits) if fixed => FloatFormat::Fixed(digits),
+        Some(digits) => FloatFormat::Scientific(digits),
+        None => FloatFormat::Auto,
+    }
+}
+
+/// `num` evenly spaced values over [start, stop], like `numpy.linspace`.
+///
+/// ``precision`` fixes the number of decimal places (labels like ``"0.30"``).
+#[pyfunction]
+#[pyo3(signature = (start, stop, num, precision=None))]
+fn linspace(start: f64, stop: f64, num: usize, precision: Option<usize>) -> PySweep {
+    let sweep = Linspace::new(start, stop, num).format(sweep_format(precision, true));
+    PySweep {
+        values: sweep.into_variant_values(),
+    }
+}
+
+/// `num` values from base**start to base**stop, like `numpy.logspace`.
+///
+/// ``precision`` sets the significant digits in scientific notation (``"1.00e-3"``).
+#[pyfunction]
+#[pyo3(signature = (start, stop, num, base=10.0, precision=None))]
+fn logspace(start: f64, stop: f64, num: usize, base: f64, precision: Option<usize>) -> PySweep {
+    let sweep = Logspace::new(start, stop, num)
+        .base(base)
+        .format(sweep_format(precision, false));
+    PySweep {
+        values: sweep.into_variant_values(),
+    }
+}
+
+/// `num` values spaced evenly on a log scale from start to stop, like `numpy.geomspace`.
+#[pyfunction]
+#[pyo3(signature = (start, stop, num, precision=None))]
+fn geomspace(start: f64, stop: f64, num: usize, precision: Option<usize>) -> PySweep {
+    let sweep = Geomspace::new(start, stop, num).format(sweep_format(precision, false));
+    PySweep {
+        values: sweep.into_variant_values(),
+    }
+}
+
 // ─── Python wrapper for Graph builder ─────────────────────────────────────
 
+/// Settings of the `Dag.execute()` call a node function runs for
+struct ActiveRun {
+    /// Executor that node functions are submitted to, if any
+    executor: Option<PyObject>,
+}
+
+thread_local! {
+    /// Run of the node executing on this thread, if any; `PyDag::run` sets it
+    /// around each node on the thread that runs it, so concurrent `execute()`
+    /// calls on one DAG keep their own settings
+    static ACTIVE_RUN: RefCell<Option<Arc<ActiveRun>>> = const { RefCell::new(None) };
+
+    /// Exception raised by the node function that last ran on this thread during a
+    /// run; `PyDag::run` takes it when the node is reported complete, which always
+    /// happens on the thread that ran the node
+    static NODE_ERROR: RefCell<Option<PyErr>> = const { RefCell::new(None) };
+}
+
+// `create_exception!` of PyO3 0.18 expands to a `cfg` that rustc does not know
+#[allow(unexpected_cfgs)]
+mod errors {
+    use pyo3::create_exception;
+    use pyo3::exceptions::PyRuntimeError;
+
+    create_exception!(
+        dagex,
+        NodeError,
+        PyRuntimeError,
+        "Raised by Dag.execute() when a node function raises. The original exception is \
+         its __cause__, and its node_id and label attributes name the node."
+    );
+}
+use errors::NodeError;
+
+/// Wrap the exception a node raised in a `NodeError` naming the node
+fn node_error(py: Python, progress: &NodeProgress, cause: PyErr) -> PyErr {
+    let error = NodeError::new_err(format!(
+        "node {} ({}) raised {}",
+        progress.node_id, progress.label, cause
+    ));
+    let value = error.value(py);
+    let _ = value.setattr("node_id", progress.node_id);
+    let _ = value.setattr("label", progress.label.as_str());
+    // Keep the node function's frames in the printed traceback
+    if let Some(traceback) = cause.traceback(py) {
+        let _ = cause.value(py).setattr("__traceback__", traceback);
+    }
+    error.set_cause(py, Some(cause));
+    error
+}
+
+/// Builder call recorded for `Graph.to_dict()`
+enum Step {
+    Add {
+        function: Option<PyObject>,
+        label: Option<String>,
+        inputs: Vec<(String, String)>,
+        outputs: Vec<(String, String)>,
+        config: Option<PyObject>,
+    },
+    Branch {
+        id: usize,
+        steps: Vec<Step>,
+    },
+    Merge {
+        function: PyObject,
+        label: Option<String>,
+        inputs: Vec<(usize, String, String)>,
+        outputs: Vec<(String, String)>,
+    },
+    Variants {
+        functions: Vec<PyObject>,
+        label: Option<String>,
+        inputs: Vec<(String, String)>,
+        outputs: Vec<(String, String)>,
+    },
+    Variant {
+        param: String,
+        values: Vec<(String, GraphData)>,
+        function: PyObject,
+        label: Option<String>,
+        inputs: Vec<(String, String)>,
+        outputs: Vec<(String, String)>,
+    },
+}
+
+/// Version of the dict layout written by `Graph.to_dict()`
+const GRAPH_DICT_VERSION: u32 = 1;
+
+/// Functions registered with `dagex.register()`, by name
+static REGISTRY: GILOnceCell<Py<PyDict>> = GILOnceCell::new();
+
+fn registry(py: Python<'_>) -> &PyDict {
+    REGISTRY.get_or_init(py, || PyDict::new(py).into()).as_ref(py)
+}
+
+/// Register a node function under a name, so graphs using it can be saved with
+/// `Graph.to_dict()` and rebuilt with `Graph.from_dict()`
+///
+/// Args:
+///     function: Python callable with signature (inputs) -> dict
+///     name: Name to store in graph dicts. Default: ``function.__name__``
+///
+/// Returns:
+///     ``function`` itself, so ``register`` can be used as a decorator
+///
+/// Example:
+///     @dagex.register
+///     def double(inputs):
+///         return {"y": inputs["x"] * 2}
+#[pyfunction]
+#[pyo3(signature = (function, name=None))]
+fn register(py: Python, function: PyObject, name: Option<String>) -> PyResult<PyObject> {
+    let name = match name {
+        Some(name) => name,
+        None => function.getattr(py, "__name__")?.extract(py)?,
+    };
+    registry(py).set_item(name, function.clone_ref(py))?;
+    Ok(function)
+}
+
+/// Registered name of `function`, preferring the most recent registration
+fn function_name(py: Python, function: &PyObject, label: &Option<String>) -> PyResult<String> {
+    let found = registry(py)
+        .iter()
+        .filter(|(_, f)| f.is(function.as_ref(py)))
+        .last();
+    match found {
+        Some((name, _)) => name.extract(),
+        None => Err(PyValueError::new_err(format!(
+            "function of node {} is not registered; call dagex.register() on it before to_dict()",
+            label.as_deref().map_or("without label".to_string(), |l| format!("'{}'", l)),
+        ))),
+    }
+}
+
+/// Look up a function named in a graph dict, in `functions` first, then the registry
+fn lookup_function(py: Python, name: &PyAny, functions: Option<&PyDict>) -> PyResult<PyObject> {
+    functions
+        .and_then(|f| f.get_item(name))
+        .or_else(|| registry(py).get_item(name))
+        .map(|f| f.to_object(py))
+        .ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "function '{}' is not registered and not in `functions`",
+                name
+            ))
+        })
+}
+
+impl Step {
+    fn to_dict(&self, py: Python) -> PyResult<PyObject> {
+        let dict = PyDict::new(py);
+        let mappings = |dict: &PyDict, inputs: &[(String, String)], outputs: &[(String, String)]| {
+            dict.set_item("inputs", inputs.to_vec())?;
+            dict.set_item("outputs", outputs.to_vec())
+        };
+        match self {
+            Step::Add { function, label, inputs, outputs, config } => {
+                dict.set_item("op", "add")?;
+                let name = function
+                    .as_ref()
+                    .map(|f| function_name(py, f, label))
+                    .transpose()?;
+                dict.set_item("function", name)?;
+                dict.set_item("label", label)?;
+                mappings(dict, inputs, outputs)?;
+                dict.set_item("config", config)?;
+            }
+            Step::Branch { id, steps } => {
+                dict.set_item("op", "branch")?;
+                dict.set_item("id", id)?;
+                dict.set_item("graph", steps_to_dict(py, steps)?)?;
+            }
+            Step::Merge { function, label, inputs, outputs } => {
+                dict.set_item("op", "merge")?;
+                dict.set_item("function", function_name(py, function, label)?)?;
+                dict.set_item("label", label)?;
+                dict.set_item("inputs", inputs.to_vec())?;
+                dict.set_item("outputs", outputs.to_vec())?;
+            }
+            Step::Variants { functions, label, inputs, outputs } => {
+                dict.set_item("op", "variants")?;
+                let names = functions
+                    .iter()
+                    .map(|f| function_name(py, f, label))
+                    .collect::<PyResult<Vec<_>>>()?;
+                dict.set_item("functions", names)?;
+                dict.set_item("label", label)?;
+                mappings(dict, inputs, outputs)?;
+            }
+            Step::Variant { param, values, function, label, inputs, outputs } => {
+                dict.set_item("op", "variant")?;
+                dict.set_item("param", param)?;
+                let (labels, values): (Vec<&String>, Vec<PyObject>) = values
+                    .iter()
+                    .map(|(l, v)| (l, graph_data_to_python(py, v)))
+                    .unzip();
+                dict.set_item("values", values)?;
+                dict.set_item("labels", labels)?;
+                dict.set_item("function", function_name(py, function, label)?)?;
+                dict.set_item("label", label)?;
+                mappings(dict, inputs, outputs)?;
+            }
+        }
+        Ok(dict.to_object(py))
+    }
+}
+
+fn steps_to_dict(py: Python, steps: &[Step]) -> PyResult<PyObject> {
+    let dict = PyDict::new(py);
+    dict.set_item("version", GRAPH_DICT_VERSION)?;
+    let steps = steps
+        .iter()
+        .map(|step| step.to_dict(py))
+        .collect::<PyResult<Vec<_>>>()?;
+    dict.set_item("steps", steps)?;
+    Ok(dict.to_object(py))
+}
+
 /// Python wrapper for Graph builder
 #[pyclass(name = "Graph")]
 struct PyGraph {
     graph: Option<Graph>,
+    /// Builder calls so far, for `to_dict()`
+    steps: Vec<Step>,
+}
+
+impl PyGraph {
+    /// Replay the builder calls of a dict written by `to_dict()`
+    fn replay(&mut self, py: Python, spec: &PyAny, functions: Option<&PyDict>) -> PyResult<()> {
+        let version: u32 = spec.get_item("version")?.extract()?;
+        if version != GRAPH_DICT_VERSION {
+            return Err(PyValueError::new_err(format!(
+                "unsupported graph dict version {} (expected {})",
+                version, GRAPH_DICT_VERSION
+            )));
+        }
+        // Missing and None entries are both treated as absent
+        let field = |step: &PyAny, key: &str| -> Option<PyObject> {
+            step.get_item(key)
+                .ok()
+                .filter(|value| !value.is_none())
+                .map(|value| value.to_object(py))
+        };
+        let label = |step: &PyAny| -> PyResult<Option<String>> {
+            field(step, "label").map(|l| l.extract(py)).transpose()
+        };
+        for step in spec.get_item("steps")?.iter()? {
+            let step = step?;
+            let inputs = field(step, "inputs");
+            let outputs = field(step, "outputs");
+            let inputs = inputs.as_ref().map(|i| i.as_ref(py));
+            let outputs = outputs.as_ref().map(|o| o.as_ref(py));
+            let op: String = step.get_item("op")?.extract()?;
+            match op.as_str() {
+                "add" => {
+                    let function = field(step, "function")
+                        .map(|name| lookup_function(py, name.as_ref(py), functions))
+                        .transpose()?;
+                    let config = field(step, "config");
+                    let config = config.as_ref().map(|c| c.as_ref(py).downcast::<PyDict>()).transpose()?;
+                    self.add(function, label(step)?, inputs, outputs, config)?;
+                }
+                "branch" => {
+                    let mut subgraph = PyGraph::new();
+                    subgraph.replay(py, step.get_item("graph")?, functions)?;
+                    let subgraph = PyCell::new(py, subgraph)?;
+                    let id = self.branch(subgraph.borrow_mut())?;
+                    let expected: usize = step.get_item("id")?.extract()?;
+                    if id != expected {
+                        return Err(PyValueError::new_err(format!(
+                            "branch was recorded with ID {} but rebuilt as {}; branches must keep their order",
+                            expected, id
+                        )));
+                    }
+                }
+                "merge" => {
+                    let function = lookup_function(py, step.get_item("function")?, functions)?;
+                    // JSON turns the (branch_id, broadcast_var, impl_var) tuples into lists
+                    let branch_inputs = inputs
+                        .map(|inputs| {
+                            inputs
+                                .iter()?
+                                .map(|item| match item?.extract::<Vec<&PyAny>>()?[..] {
+                                    [id, var, impl_var] => Ok((id.extract()?, var.extract()?, impl_var.extract()?)),
+                                    _ => Err(PyValueError::new_err(
+                                        "merge inputs must be (branch_id, broadcast_var, impl_var) triples",
+                                    )),
+                                })
+                                .collect::<PyResult<Vec<_>>>()
+                        })
+                        .transpose()?;
+                    self.merge(function, label(step)?, branch_inputs, outputs)?;
+                }
+                "variants" => {
+                    let functions = step
+                        .get_item("functions")?
+                        .iter()?
+                        .map(|name| lookup_function(py, name?, functions))
+                        .collect::<PyResult<Vec<_>>>()?;
+                    self.variants(functions, label(step)?, inputs, outputs)?;
+                }
+                "variant" => {
+                    let param: String = step.get_item("param")?.extract()?;
+                    let values: Vec<&PyAny> = step.get_item("values")?.extract()?;
+                    let values: Vec<(String, GraphData)> = match field(step, "labels") {
+                        Some(labels) => labels
+                            .extract::<Vec<String>>(py)?
+                            .into_iter()
+                            .zip(values)
+                            .map(|(l, v)| (l, python_to_graph_data(v)))
+                            .collect(),
+                        None => values
+                            .into_iter()
+                            .map(|v| labelled_value(python_to_graph_data(v)))
+                            .collect(),
+                    };
+                    let sweep = PyCell::new(py, PySweep { values })?;
+                    let function = lookup_function(py, step.get_item("function")?, functions)?;
+                    self.variant(param, sweep, function, label(step)?, inputs, outputs)?;
+                }
+                other => {
+                    return Err(PyValueError::new_err(format!("unknown graph dict op '{}'", other)));
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 #[pymethods]
@@ -215,9 +633,43 @@ impl PyGraph {
     fn new() -> Self {
         PyGraph {
             graph: Some(Graph::new()),
+            steps: Vec::new(),
         }
     }
 
+    /// Describe the graph as a dict of plain values, e.g. to store it as JSON
+    ///
+    /// The dict records every builder call in order, with labels, input/output
+    /// mappings, configs and sweep values.  Functions are stored by the name they
+    /// were registered under with ``dagex.register()``; distribution transfers are
+    /// not included.
+    ///
+    /// Returns:
+    ///     Dict that ``Graph.from_dict()`` turns back into an equivalent graph
+    ///
+    /// Example:
+    ///     json.dump(graph.to_dict(), open("pipeline.json", "w"))
+    fn to_dict(&self, py: Python) -> PyResult<PyObject> {
+        steps_to_dict(py, &self.steps)
+    }
+
+    /// Rebuild a graph from a dict written by ``to_dict()``
+    ///
+    /// Args:
+    ///     spec: Dict from ``to_dict()`` (possibly loaded from JSON or edited)
+    ///     functions: Optional dict of name -> callable, consulted before the
+    ///         functions registered with ``dagex.register()``
+    ///
+    /// Returns:
+    ///     New Graph
+    #[staticmethod]
+    #[pyo3(signature = (spec, functions=None))]
+    fn from_dict(py: Python, spec: &PyAny, functions: Option<&PyDict>) -> PyResult<PyGraph> {
+        let mut graph = PyGraph::new();
+        graph.replay(py, spec, functions)?;
+        Ok(graph)
+    }
+
     /// Add a node to the graph
     ///
     /// Args:
@@ -225,16 +677,18 @@ impl PyGraph {
     ///     label: Optional string label for the node
     ///     inputs: Optional list of (broadcast_var, impl_var) tuples or dict
     ///     outputs: Optional list of (impl_var, broadcast_var) tuples or dict
+    ///     config: Optional dict of static settings passed to the function in its inputs
     ///
     /// Returns:
     ///     Self for method chaining
-    #[pyo3(signature = (function=None, label=None, inputs=None, outputs=None))]
+    #[pyo3(signature = (function=None, label=None, inputs=None, outputs=None, config=None))]
     fn add(
         &mut self,
         function: Option<PyObject>,
         label: Option<String>,
         inputs: Option<&PyAny>,
         outputs: Option<&PyAny>,
+        config: Option<&PyDict>,
     ) -> PyResult<()> {
         let graph = self
             .graph
@@ -264,6 +718,13 @@ impl PyGraph {
             .iter()
             .map(|(a, b)| (a.as_str(), b.as_str()))
             .collect();
+        let step = Step::Add {
+            function: function.clone(),
+            label: label.clone(),
+            inputs: input_vec.clone(),
+            outputs: output_vec.clone(),
+            config: config.map(|c| c.to_object(c.py())),
+        };
 
         // Create the node function
         if let Some(py_func) = function {
@@ -286,7 +747,7 @@ impl PyGraph {
             );
         } else {
             // No-op function if None provided - graph.add will handle Arc wrapping
-            let noop = |_: &HashMap<String, GraphData>| HashMap::new();
+            let noop = |_: &HashMap<String, Arc<GraphData>>| HashMap::new();
             graph.add(
                 noop,
                 label.as_deref(),
@@ -303,6 +764,13 @@ impl PyGraph {
             );
         }
 
+        if let Some(config) = config {
+            for (key, value) in config.iter() {
+                graph.with_config(&key.extract::<String>()?, python_to_graph_data(value));
+            }
+        }
+
+        self.steps.push(step);
         Ok(())
     }
 
@@ -324,13 +792,74 @@ impl PyGraph {
             .take()
             .ok_or_else(|| PyValueError::new_err("Subgraph has already been built or consumed"))?;
 
-        Ok(graph.branch(subgraph_inner))
+        let id = graph.branch(subgraph_inner);
+        self.steps.push(Step::Branch {
+            id,
+            steps: std::mem::take(&mut subgraph.steps),
+        });
+        Ok(id)
+    }
+
+    /// Merge the outputs of several branches into a single node
+    ///
+    /// Args:
+    ///     function: Python callable with signature (inputs) -> dict
+    ///     label: Optional string label for the merge node
+    ///     inputs: List of (branch_id, broadcast_var, impl_var) tuples; each reads
+    ///         ``broadcast_var`` from that branch and passes it to the function
+    ///         as ``impl_var``
+    ///     outputs: Optional list of (impl_var, broadcast_var) tuples or dict
+    ///
+    /// Example:
+    ///     graph.merge(
+    ///         lambda inputs: {"combined": inputs["from_a"] + inputs["from_b"]},
+    ///         "Merge",
+    ///         [(branch_a, "result", "from_a"), (branch_b, "result", "from_b")],
+    ///         [("combined", "final")]
+    ///     )
+    #[pyo3(signature = (function, label=None, inputs=None, outputs=None))]
+    fn merge(
+        &mut self,
+        function: PyObject,
+        label: Option<String>,
+        inputs: Option<Vec<(usize, String, String)>>,
+        outputs: Option<&PyAny>,
+    ) -> PyResult<()> {
+        let graph = self
+            .graph
+            .as_mut()
+            .ok_or_else(|| PyValueError::new_err("Graph has already been built or consumed"))?;
+
+        let input_vec = inputs.unwrap_or_default();
+        let output_vec = outputs.map(parse_mapping).transpose()?.unwrap_or_default();
+        let input_refs: Vec<(usize, &str, &str)> = input_vec
+            .iter()
+            .map(|(id, a, b)| (*id, a.as_str(), b.as_str()))
+            .collect();
+        let output_refs: Vec<(&str, &str)> = output_vec
+            .iter()
+            .map(|(a, b)| (a.as_str(), b.as_str()))
+            .collect();
+
+        graph.merge(
+            create_python_node_function(function.clone()),
+            label.as_deref(),
+            input_refs,
+            (!output_refs.is_empty()).then_some(output_refs),
+        );
+        self.steps.push(Step::Merge {
+            function,
+            label,
+            inputs: input_vec,
+            outputs: output_vec,
+        });
+        Ok(())
     }
 
     /// Create variant nodes (parameter sweep)
     ///
     /// Args:
-    ///     functions: List of Python callables, each with signature (inputs, variant_params) -> dict
+    ///     functions: List of Python callables, each with signature (inputs) -> dict
     ///     label: Optional string label for the variant nodes
     ///     inputs: Optional list of (broadcast_var, impl_var) tuples or dict
     ///     outputs: Optional list of (impl_var, broadcast_var) tuples or dict
@@ -341,7 +870,7 @@ impl PyGraph {
     /// Example:
     ///     factors = np.linspace(0.5, 2.0, 5)
     ///     graph.variants(
-    ///         [lambda inputs, params, f=f: {"scaled": inputs["x"] * f} for f in factors],
+    ///         [lambda inputs, f=f: {"scaled": inputs["x"] * f} for f in factors],
     ///         "Scale",
     ///         [("data", "x")],
     ///         [("scaled", "result")]
@@ -405,6 +934,89 @@ impl PyGraph {
             },
         );
 
+        self.steps.push(Step::Variants {
+            functions,
+            label,
+            inputs: input_vec,
+            outputs: output_vec,
+        });
+        Ok(())
+    }
+
+    /// Create variant nodes from one function and a sweep of parameter values
+    ///
+    /// One node is created per value.  Each node's function receives the value in
+    /// its inputs under ``param``, alongside its mapped inputs, and each variant is
+    /// labelled ``"label (param=value)"``.
+    ///
+    /// Args:
+    ///     param: Name under which the swept value is passed to the function
+    ///     values: A sweep (``dagex.linspace``, ``logspace``, ``geomspace``) or any
+    ///         iterable of values, including lists, numpy arrays and generators
+    ///     function: Python callable with signature (inputs) -> dict
+    ///     label: Optional string label for the variant nodes
+    ///     inputs: Optional list of (broadcast_var, impl_var) tuples or dict
+    ///     outputs: Optional list of (impl_var, broadcast_var) tuples or dict
+    ///
+    /// Example:
+    ///     graph.variant(
+    ///         "factor",
+    ///         dagex.linspace(0.5, 2.0, 4),
+    ///         lambda inputs: {"scaled": inputs["x"] * inputs["factor"]},
+    ///         "Scale",
+    ///         [("data", "x")],
+    ///         [("scaled", "result")]
+    ///     )
+    #[pyo3(signature = (param, values, function, label=None, inputs=None, outputs=None))]
+    fn variant(
+        &mut self,
+        param: String,
+        values: &PyAny,
+        function: PyObject,
+        label: Option<String>,
+        inputs: Option<&PyAny>,
+        outputs: Option<&PyAny>,
+    ) -> PyResult<()> {
+        let graph = self
+            .graph
+            .as_mut()
+            .ok_or_else(|| PyValueError::new_err("Graph has already been built or consumed"))?;
+
+        let values = match values.downcast::<PyCell<PySweep>>() {
+            Ok(sweep) => sweep.borrow().values.clone(),
+            Err(_) => values
+                .iter()?
+                .map(|value| Ok(labelled_value(python_to_graph_data(value?))))
+                .collect::<PyResult<Vec<_>>>()?,
+        };
+
+        let input_vec = inputs.map(parse_mapping).transpose()?.unwrap_or_default();
+        let output_vec = outputs.map(parse_mapping).transpose()?.unwrap_or_default();
+        let input_refs: Vec<(&str, &str)> = input_vec
+            .iter()
+            .map(|(a, b)| (a.as_str(), b.as_str()))
+            .collect();
+        let output_refs: Vec<(&str, &str)> = output_vec
+            .iter()
+            .map(|(a, b)| (a.as_str(), b.as_str()))
+            .collect();
+
+        graph.variant(
+            &param,
+            values.clone(),
+            create_python_node_function(function.clone()),
+            label.as_deref(),
+            (!input_refs.is_empty()).then_some(input_refs),
+            (!output_refs.is_empty()).then_some(output_refs),
+        );
+        self.steps.push(Step::Variant {
+            param,
+            values,
+            function,
+            label,
+            inputs: input_vec,
+            outputs: output_vec,
+        });
         Ok(())
     }
 
@@ -418,7 +1030,9 @@ impl PyGraph {
             .take()
             .ok_or_else(|| PyValueError::new_err("Graph has already been built"))?;
 
-        Ok(PyDag { dag: graph.build() })
+        Ok(PyDag {
+            dag: graph.build(),
+        })
     }
 
     /// Attach an analytical distribution transfer to all nodes with the given label.
@@ -448,40 +1062,370 @@ impl PyGraph {
     }
 }
 
+/// Result of `Dag.execute_detailed()`
+#[pyclass(name = "ExecutionResult")]
+struct PyExecutionResult {
+    /// Final execution context, as returned by ``execute()``
+    #[pyo3(get)]
+    context: PyObject,
+    /// Outputs of each node: node ID -> dict
+    #[pyo3(get)]
+    node_outputs: PyObject,
+    /// Outputs of each branch: branch ID -> dict
+    #[pyo3(get)]
+    branch_outputs: PyObject,
+    /// Whether the ``cancel`` event stopped the run before every node ran
+    #[pyo3(get)]
+    cancelled: bool,
+}
+
+/// Result of `Dag.stats()`
+#[pyclass(name = "DagStats")]
+struct PyDagStats {
+    stats: DagStats,
+}
+
+#[pymethods]
+impl PyDagStats {
+    /// Total number of nodes
+    #[getter]
+    fn node_count(&self) -> usize {
+        self.stats.node_count
+    }
+
+    /// Number of execution levels
+    #[getter]
+    fn depth(&self) -> usize {
+        self.stats.depth
+    }
+
+    /// Maximum number of nodes that can execute in parallel
+    #[getter]
+    fn max_parallelism(&self) -> usize {
+        self.stats.max_parallelism
+    }
+
+    /// Number of branch nodes
+    #[getter]
+    fn branch_count(&self) -> usize {
+        self.stats.branch_count
+    }
+
+    /// Number of variants
+    #[getter]
+    fn variant_count(&self) -> usize {
+        self.stats.variant_count
+    }
+
+    /// Node IDs of the most expensive dependency chain, from source to sink
+    #[getter]
+    fn critical_path(&self) -> Vec<usize> {
+        self.stats.critical_path.clone()
+    }
+
+    /// Total cost of the critical path
+    #[getter]
+    fn critical_path_cost(&self) -> f64 {
+        self.stats.critical_path_cost
+    }
+
+    /// Cost of each execution level: its most expensive node
+    #[getter]
+    fn level_costs(&self) -> Vec<f64> {
+        self.stats.level_costs.clone()
+    }
+
+    /// Sum of all node costs (sequential execution)
+    #[getter]
+    fn serial_cost(&self) -> f64 {
+        self.stats.serial_cost
+    }
+
+    /// Sum of ``level_costs`` (level-by-level parallel execution)
+    #[getter]
+    fn parallel_cost(&self) -> f64 {
+        self.stats.parallel_cost
+    }
+
+    /// Theoretical speedup of parallel over sequential execution
+    #[getter]
+    fn speedup(&self) -> f64 {
+        self.stats.speedup
+    }
+
+    /// Number of nodes carrying each metadata pair, keyed ``"key=value"``
+    #[getter]
+    fn metadata_counts(&self, py: Python) -> PyObject {
+        self.stats.metadata_counts.clone().into_py(py)
+    }
+
+    /// Format stats as a human-readable string
+    fn summary(&self) -> String {
+        self.stats.summary()
+    }
+
+    fn __str__(&self) -> String {
+        self.stats.summary()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "DagStats(node_count={}, depth={}, max_parallelism={}, speedup={:.2})",
+            self.stats.node_count, self.stats.depth, self.stats.max_parallelism, self.stats.speedup
+        )
+    }
+}
+
 /// Python wrapper for DAG executor
 #[pyclass(name = "Dag")]
 struct PyDag {
     dag: Dag,
 }
 
+/// Makes a run the active run of this thread until dropped, then restores the
+/// previous one (a node function may execute another DAG)
+struct RunScope(Option<Arc<ActiveRun>>);
+
+impl RunScope {
+    fn enter(run: &Arc<ActiveRun>) -> Self {
+        RunScope(ACTIVE_RUN.with(|active| active.replace(Some(Arc::clone(run)))))
+    }
+}
+
+impl Drop for RunScope {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        ACTIVE_RUN.with(|active| *active.borrow_mut() = previous);
+    }
+}
+
+impl PyDag {
+    /// Run the DAG with the GIL released, reporting progress and polling for cancellation
+    ///
+    /// After each node, an exception its function raised is handled, then
+    /// `on_node_complete` is called with `(node_id, label, completed, total)`, then
+    /// pending signals are checked and `cancel.is_set()` is polled; a set event stops
+    /// the run before the next node starts.  An exception from the node (unless
+    /// `raise_on_error` is false, which prints it instead), the callback or a signal
+    /// handler (e.g. ``KeyboardInterrupt``) stops the run too and is raised once it
+    /// returns.  Node functions are submitted to `executor` if one is given.
+    #[allow(clippy::too_many_arguments)]
+    fn run(
+        &self,
+        py: Python,
+        parallel: bool,
+        max_threads: Option<usize>,
+        executor: Option<&PyAny>,
+        on_node_complete: Option<PyObject>,
+        cancel: Option<PyObject>,
+        raise_on_error: bool,
+    ) -> PyResult<ExecutionResult> {
+        let token = CancelToken::new();
+        let is_set = |py: Python| -> PyResult<bool> {
+            match &cancel {
+                Some(cancel) => cancel.call_method0(py, "is_set")?.is_true(py),
+                None => Ok(false),
+            }
+        };
+        if is_set(py)? {
+            token.cancel();
+        }
+
+        let error: Mutex<Option<PyErr>> = Mutex::new(None);
+        let on_progress = |progress: &NodeProgress| {
+            Python::with_gil(|py| {
+                let failed = match NODE_ERROR.with(|e| e.borrow_mut().take()) {
+                    Some(e) if raise_on_error => Err(node_error(py, progress, e)),
+                    Some(e) => {
+                        e.print(py);
+                        Ok(())
+                    }
+                    None => Ok(()),
+                };
+                let outcome = failed
+                    .and_then(|_| {
+                        on_node_complete.as_ref().map_or(Ok(()), |callback| {
+                            let args = (progress.node_id, progress.label.as_str(), progress.completed, progress.total);
+                            callback.call1(py, args).map(drop)
+                        })
+                    })
+                    .and_then(|_| py.check_signals())
+                    .and_then(|_| is_set(py));
+                match outcome {
+                    Ok(false) => {}
+                    Ok(true) => token.cancel(),
+                    Err(e) => {
+                        token.cancel();
+                        error.lock().unwrap().get_or_insert(e);
+                    }
+                }
+            })
+        };
+        let run = Arc::new(ActiveRun {
+            executor: executor.map(|e| e.to_object(py)),
+        });
+        let around_node = |execute: &mut dyn FnMut()| {
+            let _scope = RunScope::enter(&run);
+            execute();
+        };
+        let result = py.allow_threads(|| {
+            self.dag
+                .execute_around_nodes(parallel, max_threads, Some(&token), on_progress, &around_node)
+        });
+        match error.into_inner().unwrap() {
+            Some(e) => Err(e),
+            None => Ok(result),
+        }
+    }
+}
+
 #[pymethods]
 impl PyDag {
     /// Execute the DAG
     ///
+    /// Pressing Ctrl-C (or setting ``cancel``) stops the run once the nodes that are
+    /// already running finish; no further nodes are started.
+    ///
+    /// Python node functions hold the GIL while they run, so with ``parallel=True``
+    /// alone only code that releases it (I/O, ``time.sleep``, most numpy calls)
+    /// overlaps.  Pass a ``concurrent.futures.ProcessPoolExecutor`` as ``executor``
+    /// to run pure-Python nodes in parallel: each node function is submitted to it,
+    /// so functions must be picklable (defined at module level, not lambdas) and
+    /// inputs and outputs must be picklable values.
+    ///
     /// Args:
     ///     parallel (bool): If True, execute nodes at the same level concurrently. Default: False
     ///     max_threads (Optional[int]): Maximum number of threads to use per level. None = unlimited. Default: None
+    ///     executor (Optional[concurrent.futures.Executor]): Executor that node functions are
+    ///         submitted to for this run. None = call them in-process. Default: None
+    ///     on_node_complete (Optional[Callable]): Called after each node as
+    ///         ``on_node_complete(node_id, label, completed, total)``. Default: None
+    ///     cancel (Optional[threading.Event]): Stop the run once this event is set; the
+    ///         nodes that ran keep their outputs. Default: None
+    ///     raise_on_error (bool): If True, an exception raised by a node function stops
+    ///         the run and is raised as ``dagex.NodeError`` naming the node, with the
+    ///         original exception (and its traceback) as ``__cause__``. If False, the
+    ///         traceback is printed and the node produces no outputs. Default: True
     ///
     /// Returns:
     ///     Dictionary containing the execution context
-    #[pyo3(signature = (parallel=false, max_threads=None))]
+    ///
+    /// Example:
+    ///     with concurrent.futures.ProcessPoolExecutor(4) as pool:
+    ///         context = dag.execute(parallel=True, executor=pool)
+    ///
+    ///     with tqdm(total=dag.node_count()) as bar:
+    ///         context = dag.execute(on_node_complete=lambda *_: bar.update())
+    #[pyo3(signature = (parallel=false, max_threads=None, executor=None, on_node_complete=None, cancel=None, raise_on_error=true))]
+    #[allow(clippy::too_many_arguments)]
     fn execute(
         &self,
         py: Python,
         parallel: bool,
         max_threads: Option<usize>,
+        executor: Option<&PyAny>,
+        on_node_complete: Option<PyObject>,
+        cancel: Option<PyObject>,
+        raise_on_error: bool,
     ) -> PyResult<PyObject> {
-        // Release GIL during Rust execution
-        let context = py.allow_threads(|| self.dag.execute(parallel, max_threads));
+        let mut context = self
+            .run(py, parallel, max_threads, executor, on_node_complete, cancel, raise_on_error)?
+            .context;
+        let declared = self.dag.declared_outputs();
+        if !declared.is_empty() {
+            context.retain(|key, _| declared.iter().any(|name| key == *name));
+        }
 
-        // Convert HashMap<String, GraphData> to Python dict
+        // Convert the context to a Python dict
         let py_dict = PyDict::new(py);
         for (key, value) in context.iter() {
-            py_dict.set_item(key, graph_data_to_python(py, value))?;
+            py_dict.set_item(key.as_str(), graph_data_to_python(py, value))?;
         }
         Ok(py_dict.to_object(py))
     }
 
+    /// Execute the DAG and return outputs per node and per branch as well
+    ///
+    /// Args:
+    ///     parallel (bool): If True, execute nodes at the same level concurrently. Default: False
+    ///     max_threads (Optional[int]): Maximum number of threads to use per level. None = unlimited. Default: None
+    ///     executor (Optional[concurrent.futures.Executor]): Executor that node functions are
+    ///         submitted to for this run, as in ``execute()``. Default: None
+    ///     on_node_complete (Optional[Callable]): Progress callback, as in ``execute()``. Default: None
+    ///     cancel (Optional[threading.Event]): Cancel event, as in ``execute()``. Default: None
+    ///     raise_on_error (bool): Raise node exceptions, as in ``execute()``. Default: True
+    ///
+    /// Returns:
+    ///     ExecutionResult with ``context`` (the dict ``execute()`` returns),
+    ///     ``node_outputs`` (node ID -> dict of outputs) and ``branch_outputs``
+    ///     (branch ID -> dict of outputs)
+    #[pyo3(signature = (parallel=false, max_threads=None, executor=None, on_node_complete=None, cancel=None, raise_on_error=true))]
+    #[allow(clippy::too_many_arguments)]
+    fn execute_detailed(
+        &self,
+        py: Python,
+        parallel: bool,
+        max_threads: Option<usize>,
+        executor: Option<&PyAny>,
+        on_node_complete: Option<PyObject>,
+        cancel: Option<PyObject>,
+        raise_on_error: bool,
+    ) -> PyResult<PyObject> {
+        let result = self.run(py, parallel, max_threads, executor, on_node_complete, cancel, raise_on_error)?;
+
+        fn to_dict<'a>(
+            py: Python,
+            values: impl Iterator<Item = (&'a str, &'a Arc<GraphData>)>,
+        ) -> PyResult<PyObject> {
+            let dict = PyDict::new(py);
+            for (key, value) in values {
+                dict.set_item(key, graph_data_to_python(py, value))?;
+            }
+            Ok(dict.to_object(py))
+        }
+        let node_outputs = PyDict::new(py);
+        for (node_id, outputs) in &result.node_outputs {
+            node_outputs.set_item(node_id, to_dict(py, outputs.iter().map(|(k, v)| (k.as_str(), v)))?)?;
+        }
+        let branch_outputs = PyDict::new(py);
+        for (branch_id, outputs) in &result.branch_outputs {
+            branch_outputs.set_item(branch_id, to_dict(py, outputs.iter().map(|(k, v)| (k.as_str(), v)))?)?;
+        }
+
+        Ok(PyExecutionResult {
+            context: to_dict(py, result.context.iter().map(|(k, v)| (k.as_str(), v)))?,
+            node_outputs: node_outputs.to_object(py),
+            branch_outputs: branch_outputs.to_object(py),
+            cancelled: result.cancelled,
+        }
+        .into_py(py))
+    }
+
+    /// Get statistics about the DAG
+    ///
+    /// Args:
+    ///     costs (Optional[dict[int, float]]): Estimated cost of each node ID, in any
+    ///         unit. Nodes without a hint cost 1.0. Default: None
+    ///
+    /// Returns:
+    ///     DagStats with node counts, depth, parallelism, critical path and
+    ///     theoretical speedup; ``str(stats)`` gives a readable summary
+    #[pyo3(signature = (costs=None))]
+    fn stats(&self, costs: Option<HashMap<usize, f64>>) -> PyDagStats {
+        PyDagStats {
+            stats: self.dag.stats_with_costs(&costs.unwrap_or_default()),
+        }
+    }
+
+    /// Get the execution levels of the DAG
+    ///
+    /// Returns:
+    ///     List of levels in execution order, each a list of node IDs that can
+    ///     run concurrently
+    fn execution_levels(&self) -> Vec<Vec<usize>> {
+        self.dag.execution_levels().to_vec()
+    }
+
     /// Get Mermaid diagram representation
     ///
     /// Returns:
@@ -498,6 +1442,14 @@ impl PyDag {
         self.dag.nodes().len()
     }
 
+    /// Problems detected while building the DAG, e.g. inputs no node produces
+    ///
+    /// Returns:
+    ///     List of human-readable warning strings (empty when the wiring is complete)
+    fn build_warnings(&self) -> Vec<String> {
+        self.dag.build_warnings().iter().map(|w| w.to_string()).collect()
+    }
+
     /// Return a sorted list of unique node labels present in the DAG.
     ///
     /// Useful for discovering valid values to pass as `at_node` to `predict()`.
@@ -685,11 +1637,17 @@ fn parse_mapping(obj: &PyAny) -> PyResult<Vec<(String, String)>> {
         }
         Ok(result)
     } else if let Ok(list) = obj.downcast::<PyList>() {
-        // List of tuples: [("key", "value")]
+        // List of tuples: [("key", "value")], or of pairs as lists (e.g. from JSON)
         let mut result = Vec::new();
         for item in list.iter() {
-            let tuple: (String, String) = item.extract()?;
-            result.push(tuple);
+            match <[String; 2]>::try_from(item.extract::<Vec<String>>()?) {
+                Ok([key, value]) => result.push((key, value)),
+                Err(_) => {
+                    return Err(PyValueError::new_err(
+                        "inputs/outputs entries must be (name, name) pairs",
+                    ))
+                }
+            }
         }
         Ok(result)
     } else {
@@ -702,64 +1660,72 @@ fn parse_mapping(obj: &PyAny) -> PyResult<Vec<(String, String)>> {
 /// Create a node function that wraps a Python callable
 ///
 /// The returned closure is Send + Sync and properly handles GIL acquisition
-/// when calling the Python function.
+/// when calling the Python function.  While the run active on this thread (see
+/// `ACTIVE_RUN`) has an executor the call is submitted to it instead, and the GIL
+/// is released while waiting on the result so other nodes can submit theirs.  An
+/// exception during a run is left in `NODE_ERROR` for `PyDag::run`; outside a
+/// run (e.g. in `predict()`) it is printed.  Either way the node produces no
+/// outputs.
 fn create_python_node_function(
     py_func: PyObject,
-) -> impl Fn(&HashMap<String, GraphData>) -> HashMap<String, GraphData>
+) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData>
        + Send
        + Sync
        + 'static {
     // Wrap in Arc to make it cloneable and shareable
     let py_func = Arc::new(py_func);
 
-    move |inputs: &HashMap<String, GraphData>| {
+    move |inputs: &HashMap<String, Arc<GraphData>>| {
         // Acquire GIL only for the duration of this call
         Python::with_gil(|py| {
-            // Convert inputs to Python dict
-            let py_inputs = PyDict::new(py);
-            for (key, value) in inputs.iter() {
-                if let Err(e) = py_inputs.set_item(key, graph_data_to_python(py, value)) {
-                    // Log to Python's stderr for better integration
-                    let _ = py
-                        .import("sys")
-                        .and_then(|sys| sys.getattr("stderr"))
-                        .and_then(|stderr| {
-                            stderr.call_method1(
-                                "write",
-                                (format!("Error setting input '{}': {}\n", key, e),),
-                            )
-                        });
-                    return HashMap::new();
+            let (in_run, executor) = match ACTIVE_RUN.with(|run| run.borrow().clone()) {
+                Some(run) => (true, run.executor.as_ref().map(|e| e.clone_ref(py))),
+                None => (false, None),
+            };
+
+            let call = || -> PyResult<HashMap<String, GraphData>> {
+                // Convert inputs to Python dict
+                let py_inputs = PyDict::new(py);
+                for (key, value) in inputs.iter() {
+                    py_inputs.set_item(key, graph_data_to_python(py, value))?;
                 }
-            }
 
-            // Call the Python function with just inputs
-            let result = py_func.call1(py, (py_inputs,));
-
-            match result {
-                Ok(py_result) => {
-                    // Convert result back to HashMap
-                    if let Ok(result_dict) = py_result.downcast::<PyDict>(py) {
-                        let mut output = HashMap::new();
-                        for (key, value) in result_dict.iter() {
-                            if let Ok(k) = key.extract::<String>() {
-                                output.insert(k, python_to_graph_data(value));
-                            }
-                        }
-                        output
-                    } else {
-                        let _ = py
-                            .import("sys")
-                            .and_then(|sys| sys.getattr("stderr"))
-                            .and_then(|stderr| {
-                                stderr.call_method1(
-                                    "write",
-                                    ("Error: Python function did not return a dict\n",),
-                                )
-                            });
-                        HashMap::new()
+                // Call the Python function with just inputs
+                let py_result = match executor {
+                    Some(executor) => executor
+                        .call_method1(py, "submit", (py_func.as_ref(), py_inputs))
+                        .and_then(|future| future.call_method0(py, "result")),
+                    None => py_func.call1(py, (py_inputs,)),
+                }?;
+
+                // Convert result back to HashMap
+                let result_dict = py_result.as_ref(py).downcast::<PyDict>().map_err(|_| {
+                    PyTypeError::new_err(format!(
+                        "node function must return a dict, not {}",
+                        py_result.as_ref(py).get_type().name().unwrap_or("?")
+                    ))
+                })?;
+                let mut output = HashMap::new();
+                for (key, value) in result_dict.iter() {
+                    if let Ok(k) = key.extract::<String>() {
+                        output.insert(k, python_to_graph_data(value));
                     }
                 }
+                Ok(output)
+            };
+
+            match call() {
+                Ok(output) => output,
+                Err(e) if e.is_instance_of::<PyKeyboardInterrupt>(py) => {
+                    // Flag the interrupt again so the run stops after this node
+                    // (see `PyDag::run`) instead of the node swallowing it
+                    unsafe { pyo3::ffi::PyErr_SetInterrupt() };
+                    HashMap::new()
+                }
+                Err(e) if in_run => {
+                    NODE_ERROR.with(|slot| *slot.borrow_mut() = Some(e));
+                    HashMap::new()
+                }
                 Err(e) => {
                     // Use Python's traceback printing for better error visibility
                     e.print(py);
@@ -775,9 +1741,13 @@ fn graph_data_to_python(py: Python, data: &GraphData) -> PyObject {
     match data {
         GraphData::Int(v) => v.to_object(py),
         GraphData::Float(v) => v.to_object(py),
+        GraphData::Bool(v) => v.to_object(py),
         GraphData::String(s) => s.to_object(py),
         GraphData::FloatVec(v) => v.to_object(py),
         GraphData::IntVec(v) => v.to_object(py),
+        GraphData::FloatSlice(v) => v.to_object(py),
+        GraphData::IntSlice(v) => v.to_object(py),
+        GraphData::Bytes(b) => PyBytes::new(py, b).to_object(py),
         GraphData::Map(m) => {
             // Check if this is a complex array structure (keys are indices, values have "re" and "im")
             let mut is_complex_array = true;
@@ -856,7 +1826,44 @@ fn graph_data_to_python(py: Python, data: &GraphData) -> PyObject {
                 dict.to_object(py)
             }
         }
+        GraphData::List(l) => {
+            let list = PyList::empty(py);
+            for v in l {
+                let _ = list.append(graph_data_to_python(py, v));
+            }
+            list.to_object(py)
+        }
         GraphData::None => py.None(),
+        GraphData::Lazy(lazy) => graph_data_to_python(py, lazy.get()),
+        // Mapped arrays are copied into a list of floats (raw bytes if the size is uneven)
+        #[cfg(feature = "mmap")]
+        GraphData::Mapped(m) => match m.as_floats() {
+            Some(values) => values.to_object(py),
+            None => PyBytes::new(py, m.bytes()).to_object(py),
+        },
+        // Application types have no Python equivalent
+        GraphData::Custom(_) => py.None(),
+        #[cfg(feature = "polars")]
+        GraphData::DataFrame(df) => {
+            // Plain dict of column name -> list of values
+            let dict = PyDict::new(py);
+            for column in df.get_columns() {
+                let values = PyList::empty(py);
+                for v in column.as_materialized_series().iter() {
+                    let _ = values.append(any_value_to_python(py, v));
+                }
+                let _ = dict.set_item(column.name().as_str(), values);
+            }
+            dict.to_object(py)
+        }
+        #[cfg(feature = "json")]
+        GraphData::Json(v) => {
+            // Round-trip through Python's json module to get plain dicts/lists
+            py.import("json")
+                .and_then(|json| json.call_method1("loads", (v.to_string(),)))
+                .map(|obj| obj.to_object(py))
+                .unwrap_or_else(|_| v.to_string().to_object(py))
+        }
         #[cfg(feature = "python")]
         GraphData::PyObject(obj) => {
             // Return the stored Python object directly without conversion
@@ -882,32 +1889,130 @@ fn graph_data_to_python(py: Python, data: &GraphData) -> PyObject {
             }
             list.to_object(py)
         }
+        #[cfg(feature = "radar_examples")]
+        GraphData::FloatArrayND(a) => nd_to_python(py, a.view(), &|py, x| x.to_object(py)),
+        #[cfg(feature = "radar_examples")]
+        GraphData::ComplexArrayND(a) => nd_to_python(py, a.view(), &|py, c| {
+            PyComplex::from_doubles(py, c.re, c.im).to_object(py)
+        }),
     }
 }
 
+/// Convert a single DataFrame cell to a Python scalar (its string form for nested types)
+#[cfg(feature = "polars")]
+fn any_value_to_python(py: Python, v: polars::prelude::AnyValue) -> PyObject {
+    use polars::prelude::AnyValue;
+    match v {
+        AnyValue::Null => py.None(),
+        AnyValue::Boolean(b) => b.to_object(py),
+        AnyValue::String(s) => s.to_object(py),
+        v if v.is_integer() => v.extract::<i64>().to_object(py),
+        v if v.is_float() => v.extract::<f64>().to_object(py),
+        v => v.to_string().to_object(py),
+    }
+}
+
+/// Convert an N-dimensional array to nested Python lists (a scalar for 0-D arrays)
+#[cfg(feature = "radar_examples")]
+fn nd_to_python<T>(
+    py: Python,
+    a: ndarray::ArrayViewD<T>,
+    element: &dyn Fn(Python, &T) -> PyObject,
+) -> PyObject {
+    if a.ndim() == 0 {
+        return a.iter().next().map_or_else(|| py.None(), |x| element(py, x));
+    }
+    let list = PyList::empty(py);
+    for sub in a.outer_iter() {
+        let _ = list.append(nd_to_python(py, sub, element));
+    }
+    list.to_object(py)
+}
+
 /// Convert Python object to GraphData
+///
+/// Scalars keep their type (`int` → `Int`, `float` → `Float`), homogeneous
+/// numeric lists and 1-D numpy arrays become `IntVec`/`FloatVec`, other lists
+/// and tuples become `List`s of converted elements, and `None` becomes `None`.
 fn python_to_graph_data(obj: &PyAny) -> GraphData {
-    // Try numeric scalars first
-    if let Ok(f) = obj.extract::<f64>() {
-        return GraphData::Float(f);
+    if obj.is_none() {
+        return GraphData::None;
     }
-    if let Ok(i) = obj.extract::<i64>() {
-        return GraphData::Int(i);
+    // bool is a subclass of int, so it must be checked before the numeric scalars
+    if let Ok(b) = obj.downcast::<PyBool>() {
+        return GraphData::Bool(b.is_true());
     }
-    if let Ok(s) = obj.extract::<String>() {
-        return GraphData::String(s);
+    if let Ok(b) = obj.downcast::<PyBytes>() {
+        return GraphData::bytes(b.as_bytes().to_vec());
     }
-    // Try list-of-floats → FloatVec, list-of-ints → IntVec
-    if let Ok(list) = obj.extract::<Vec<f64>>() {
-        return GraphData::FloatVec(std::sync::Arc::new(list));
+    // int before float: a Python int also extracts as f64
+    if obj.downcast::<PyLong>().is_ok() {
+        if let Ok(i) = obj.extract::<i64>() {
+            return GraphData::Int(i);
+        }
     }
-    if let Ok(list) = obj.extract::<Vec<i64>>() {
-        return GraphData::IntVec(std::sync::Arc::new(list));
+    if let Ok(f) = obj.downcast::<PyFloat>() {
+        return GraphData::Float(f.value());
+    }
+    #[cfg(feature = "radar_examples")]
+    if let Ok(c) = obj.downcast::<PyComplex>() {
+        return GraphData::complex(num_complex::Complex::new(c.real(), c.imag()));
+    }
+    if let Ok(s) = obj.downcast::<PyString>() {
+        return GraphData::String(s.to_string_lossy().into_owned());
+    }
+    if let Some(data) = numpy_to_graph_data(obj) {
+        return data;
+    }
+    if obj.downcast::<PyList>().is_ok() || obj.downcast::<PyTuple>().is_ok() {
+        return sequence_to_graph_data(obj);
     }
     // Fall back to opaque PyObject for anything else (dicts, custom types, etc.)
     GraphData::PyObject(obj.to_object(obj.py()))
 }
 
+/// Convert a list or tuple: a numeric vector when every element is an int (or
+/// every element an int or float), a `List` otherwise
+fn sequence_to_graph_data(obj: &PyAny) -> GraphData {
+    let items: Vec<GraphData> = match obj.iter() {
+        Ok(iter) => iter.filter_map(Result::ok).map(python_to_graph_data).collect(),
+        Err(_) => return GraphData::PyObject(obj.to_object(obj.py())),
+    };
+    if !items.is_empty() {
+        if let Some(ints) = items.iter().map(GraphData::as_int).collect::<Option<Vec<_>>>() {
+            return GraphData::int_vec(ints);
+        }
+        if let Some(floats) = items.iter().map(GraphData::as_float).collect::<Option<Vec<_>>>() {
+            return GraphData::float_vec(floats);
+        }
+    }
+    GraphData::list(items)
+}
+
+/// Convert numpy scalars and arrays, identified by their `dtype`; `None` for
+/// other objects
+fn numpy_to_graph_data(obj: &PyAny) -> Option<GraphData> {
+    let kind: String = obj.getattr("dtype").ok()?.getattr("kind").ok()?.extract().ok()?;
+    let ndim: usize = obj.getattr("ndim").ok()?.extract().ok()?;
+    if ndim == 0 {
+        // numpy scalar (or 0-d array): convert the equivalent Python scalar
+        return Some(python_to_graph_data(obj.call_method0("item").ok()?));
+    }
+    match (kind.as_str(), ndim) {
+        ("f", 1) => Some(GraphData::float_vec(obj.call_method0("tolist").ok()?.extract().ok()?)),
+        ("i" | "u", 1) => Some(GraphData::int_vec(obj.call_method0("tolist").ok()?.extract().ok()?)),
+        #[cfg(feature = "radar_examples")]
+        ("f", _) => {
+            let shape: Vec<usize> = obj.getattr("shape").ok()?.extract().ok()?;
+            let flat: Vec<f64> = obj.call_method0("ravel").ok()?.call_method0("tolist").ok()?.extract().ok()?;
+            let array = ndarray::ArrayD::from_shape_vec(shape, flat).ok()?;
+            Some(GraphData::array(array))
+        }
+        // Other dtypes and dimensions go through nested Python lists
+        _ => Some(python_to_graph_data(obj.call_method0("tolist").ok()?)),
+    }
+}
+
 /// Create a dist_transfer closure that wraps a Python callable.
 ///
 /// The Python function receives a dict of `{impl_var: Distribution}` and should
@@ -1020,12 +2125,21 @@ fn empirical(samples: Vec<f64>) -> PyDistribution {
 
 /// Initialize the Python module
 #[pymodule]
-fn dagex(_py: Python, m: &PyModule) -> PyResult<()> {
+fn dagex(py: Python, m: &PyModule) -> PyResult<()> {
     // PyO3 0.18.3 with auto-initialize feature handles multi-threading initialization automatically
     m.add_class::<PyGraph>()?;
+    m.add("NodeError", py.get_type::<NodeError>())?;
+    m.add_function(wrap_pyfunction!(register, m)?)?;
     m.add_class::<PyDag>()?;
+    m.add_class::<PyExecutionResult>()?;
+    m.add_class::<PyDagStats>()?;
     m.add_class::<PyDistribution>()?;
     m.add_class::<PyStatResult>()?;
+    m.add_class::<PySweep>()?;
+    // Sweep constructor functions
+    m.add_function(wrap_pyfunction!(linspace, m)?)?;
+    m.add_function(wrap_pyfunction!(logspace, m)?)?;
+    m.add_function(wrap_pyfunction!(geomspace, m)?)?;
     // Distribution constructor functions
     m.add_function(wrap_pyfunction!(normal, m)?)?;
     m.add_function(wrap_pyfunction!(uniform, m)?)?;
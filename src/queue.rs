@@ -0,0 +1,350 @@
+//! Message-queue execution — sweeps spread over worker processes
+//!
+//! A variant sweep over inputs is embarrassingly parallel: every set of inputs
+//! is an independent execution of the same DAG.  With a `TaskQueue` connecting
+//! processes (`NatsQueue` with the `nats` feature, or any other broker behind the
+//! trait), a coordinator publishes one task per input set and any number of
+//! workers, each holding the same DAG, execute them and publish the results back:
+//!
+//! ```ignore
+//! // On every worker machine
+//! let queue = NatsQueue::worker("nats://broker:4222", "sweep")?;
+//! dag.serve_tasks(&queue, true, None, &CancelToken::new())?;
+//!
+//! // On the coordinator
+//! let queue = NatsQueue::coordinator("nats://broker:4222", "sweep")?;
+//! let inputs = grid.combinations().into_iter().map(|(_, inputs)| inputs).collect();
+//! for result in dag.execute_on_queue(&queue, inputs, Duration::from_secs(600))? {
+//!     println!("{:?}", result.map(|context| context.get_f64("score")));
+//! }
+//! ```
+//!
+//! Tasks and results are JSON (the `serde` form of `GraphData`), so inputs and
+//! outputs must be serializable; node functions never leave their process.  A
+//! task names the `Dag::fingerprint()` it was made for, and workers refuse tasks
+//! of another graph.  Each results channel belongs to one coordinator at a time.
+
+use crate::context::ExecutionContext;
+use crate::dag::Dag;
+use crate::graph_data::GraphData;
+use crate::progress::CancelToken;
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long `Dag::serve_tasks()` waits for a task before checking its stop token
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Transport of tasks to workers and of results back to the coordinator
+///
+/// Each task must be delivered to exactly one worker (a work queue, e.g. a NATS
+/// queue group or a RabbitMQ queue with competing consumers); results go to the
+/// coordinator.  Messages are opaque bytes.
+pub trait TaskQueue: Send + Sync {
+    /// Publish a task for any worker
+    fn push_task(&self, task: Vec<u8>) -> io::Result<()>;
+
+    /// Take the next task, waiting up to `timeout`
+    fn pop_task(&self, timeout: Duration) -> io::Result<Option<Vec<u8>>>;
+
+    /// Publish the result of a task
+    fn push_result(&self, result: Vec<u8>) -> io::Result<()>;
+
+    /// Take the next result, waiting up to `timeout`
+    fn pop_result(&self, timeout: Duration) -> io::Result<Option<Vec<u8>>>;
+}
+
+/// One execution requested by a coordinator
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Task {
+    /// Random ID of the `execute_on_queue()` call, to drop stale results
+    sweep: u64,
+    index: usize,
+    fingerprint: u64,
+    inputs: HashMap<String, GraphData>,
+}
+
+/// What a worker sends back for a task
+#[derive(serde::Serialize, serde::Deserialize)]
+struct TaskResult {
+    sweep: u64,
+    index: usize,
+    outputs: Result<HashMap<String, GraphData>, String>,
+}
+
+fn invalid_data(e: serde_json::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+impl Dag {
+    /// Execute tasks from `queue` until `stop` is cancelled (see the module
+    /// documentation)
+    ///
+    /// Each task runs `execute_with_inputs()` with `parallel` and `max_threads`;
+    /// failures (a panicking node, a task for another graph, outputs that cannot be
+    /// serialized) are sent back as the task's error.  Returns the number of tasks
+    /// served, or the first error of the queue itself or of a malformed task.
+    pub fn serve_tasks(
+        &self,
+        queue: &dyn TaskQueue,
+        parallel: bool,
+        max_threads: Option<usize>,
+        stop: &CancelToken,
+    ) -> io::Result<usize> {
+        let mut served = 0;
+        while !stop.is_cancelled() {
+            let Some(bytes) = queue.pop_task(POLL_INTERVAL)? else {
+                continue;
+            };
+            let task: Task = serde_json::from_slice(&bytes).map_err(invalid_data)?;
+            let outputs = if task.fingerprint != self.fingerprint() {
+                Err(format!(
+                    "graph fingerprint mismatch: task is for {:016x}, worker runs {:016x}",
+                    task.fingerprint,
+                    self.fingerprint()
+                ))
+            } else {
+                let run = || self.execute_with_inputs(task.inputs, parallel, max_threads);
+                panic::catch_unwind(AssertUnwindSafe(run))
+                    .map(|context| {
+                        context
+                            .into_iter()
+                            .map(|(name, value)| (name.as_str().to_string(), Arc::unwrap_or_clone(value)))
+                            .collect()
+                    })
+                    .map_err(|e| {
+                        let message = e
+                            .downcast_ref::<&str>()
+                            .map(|s| s.to_string())
+                            .or_else(|| e.downcast_ref::<String>().cloned())
+                            .unwrap_or_else(|| "node panicked".to_string());
+                        format!("execution panicked: {}", message)
+                    })
+            };
+            let result = TaskResult {
+                sweep: task.sweep,
+                index: task.index,
+                outputs,
+            };
+            let bytes = serde_json::to_vec(&result).or_else(|e| {
+                serde_json::to_vec(&TaskResult {
+                    outputs: Err(format!("outputs cannot be serialized: {}", e)),
+                    ..result
+                })
+                .map_err(invalid_data)
+            })?;
+            queue.push_result(bytes)?;
+            served += 1;
+        }
+        Ok(served)
+    }
+
+    /// Execute the DAG once per input set on the workers serving `queue`
+    ///
+    /// Returns each execution's context (as `execute_with_inputs()` would) or the
+    /// error a worker reported, in the order of `input_sets`.  Fails with
+    /// `io::ErrorKind::TimedOut` if no result arrives for `timeout`, e.g. because
+    /// no worker is running, and with `io::ErrorKind::InvalidInput` if an input
+    /// cannot be serialized.
+    pub fn execute_on_queue(
+        &self,
+        queue: &dyn TaskQueue,
+        input_sets: Vec<HashMap<String, GraphData>>,
+        timeout: Duration,
+    ) -> io::Result<Vec<Result<ExecutionContext, String>>> {
+        let sweep: u64 = rand::random();
+        let fingerprint = self.fingerprint();
+        let mut results: Vec<Option<Result<ExecutionContext, String>>> = input_sets.iter().map(|_| None).collect();
+        for (index, inputs) in input_sets.into_iter().enumerate() {
+            let task = Task {
+                sweep,
+                index,
+                fingerprint,
+                inputs,
+            };
+            let bytes = serde_json::to_vec(&task).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            queue.push_task(bytes)?;
+        }
+
+        let mut pending = results.len();
+        while pending > 0 {
+            let bytes = queue
+                .pop_result(timeout)?
+                .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, format!("{} tasks unanswered", pending)))?;
+            let result: TaskResult = serde_json::from_slice(&bytes).map_err(invalid_data)?;
+            let Some(slot) = results.get_mut(result.index).filter(|slot| result.sweep == sweep && slot.is_none())
+            else {
+                continue;
+            };
+            *slot = Some(result.outputs.map(|outputs| {
+                outputs.into_iter().map(|(name, value)| (name, Arc::new(value))).collect()
+            }));
+            pending -= 1;
+        }
+        Ok(results.into_iter().flatten().collect())
+    }
+}
+
+/// FIFO of messages with blocking, timed reads
+#[derive(Default)]
+struct Channel {
+    messages: Mutex<VecDeque<Vec<u8>>>,
+    ready: Condvar,
+}
+
+impl Channel {
+    fn push(&self, message: Vec<u8>) {
+        self.messages.lock().unwrap().push_back(message);
+        self.ready.notify_one();
+    }
+
+    fn pop(&self, timeout: Duration) -> Option<Vec<u8>> {
+        let deadline = Instant::now() + timeout;
+        let mut messages = self.messages.lock().unwrap();
+        loop {
+            if let Some(message) = messages.pop_front() {
+                return Some(message);
+            }
+            let left = deadline.checked_duration_since(Instant::now())?;
+            messages = self.ready.wait_timeout(messages, left).unwrap().0;
+        }
+    }
+}
+
+/// `TaskQueue` between threads of one process
+///
+/// Clones share the queue.  Useful for tests and for trying out a distributed
+/// setup before a broker is involved.
+#[derive(Clone, Default)]
+pub struct ChannelQueue {
+    channels: Arc<(Channel, Channel)>,
+}
+
+impl ChannelQueue {
+    /// Create an empty queue
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TaskQueue for ChannelQueue {
+    fn push_task(&self, task: Vec<u8>) -> io::Result<()> {
+        self.channels.0.push(task);
+        Ok(())
+    }
+
+    fn pop_task(&self, timeout: Duration) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.channels.0.pop(timeout))
+    }
+
+    fn push_result(&self, result: Vec<u8>) -> io::Result<()> {
+        self.channels.1.push(result);
+        Ok(())
+    }
+
+    fn pop_result(&self, timeout: Duration) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.channels.1.pop(timeout))
+    }
+}
+
+/// `TaskQueue` on a NATS server
+///
+/// Tasks are published on `"{subject}.tasks"` and taken by the queue group
+/// `"dagex-workers"`, so each goes to one worker; results are published on
+/// `"{subject}.results"`.  A worker queue can only pop tasks and a coordinator
+/// queue only results.  Core NATS does not persist messages: start the workers
+/// before the coordinator publishes.
+#[cfg(feature = "nats")]
+pub struct NatsQueue {
+    client: async_nats::Client,
+    subject: String,
+    tasks: Option<Mutex<async_nats::Subscriber>>,
+    results: Option<Mutex<async_nats::Subscriber>>,
+    runtime: tokio::runtime::Runtime,
+}
+
+#[cfg(feature = "nats")]
+impl NatsQueue {
+    /// Connect to the server at `url` as a worker taking tasks of `subject`
+    pub fn worker(url: &str, subject: &str) -> io::Result<Self> {
+        Self::connect(url, subject, true)
+    }
+
+    /// Connect to the server at `url` as the coordinator of `subject`
+    pub fn coordinator(url: &str, subject: &str) -> io::Result<Self> {
+        Self::connect(url, subject, false)
+    }
+
+    fn connect(url: &str, subject: &str, worker: bool) -> io::Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        let (client, subscriber) = runtime.block_on(async {
+            let client = async_nats::connect(url).await.map_err(io::Error::other)?;
+            let subscriber = if worker {
+                client.queue_subscribe(format!("{}.tasks", subject), "dagex-workers".to_string()).await
+            } else {
+                client.subscribe(format!("{}.results", subject)).await
+            }
+            .map_err(io::Error::other)?;
+            io::Result::Ok((client, Mutex::new(subscriber)))
+        })?;
+        let (tasks, results) = if worker { (Some(subscriber), None) } else { (None, Some(subscriber)) };
+        Ok(Self {
+            client,
+            subject: subject.to_string(),
+            tasks,
+            results,
+            runtime,
+        })
+    }
+
+    fn publish(&self, channel: &str, message: Vec<u8>) -> io::Result<()> {
+        self.runtime.block_on(async {
+            self.client
+                .publish(format!("{}.{}", self.subject, channel), message.into())
+                .await
+                .map_err(io::Error::other)?;
+            self.client.flush().await.map_err(io::Error::other)
+        })
+    }
+
+    fn next(
+        &self,
+        subscriber: &Option<Mutex<async_nats::Subscriber>>,
+        timeout: Duration,
+    ) -> io::Result<Option<Vec<u8>>> {
+        use futures::StreamExt;
+
+        let subscriber = subscriber
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "not subscribed to this channel"))?;
+        let mut subscriber = subscriber.lock().unwrap();
+        self.runtime.block_on(async {
+            match tokio::time::timeout(timeout, subscriber.next()).await {
+                Ok(Some(message)) => Ok(Some(message.payload.to_vec())),
+                Ok(None) => Err(io::Error::new(io::ErrorKind::ConnectionAborted, "NATS subscription closed")),
+                Err(_) => Ok(None),
+            }
+        })
+    }
+}
+
+#[cfg(feature = "nats")]
+impl TaskQueue for NatsQueue {
+    fn push_task(&self, task: Vec<u8>) -> io::Result<()> {
+        self.publish("tasks", task)
+    }
+
+    fn pop_task(&self, timeout: Duration) -> io::Result<Option<Vec<u8>>> {
+        self.next(&self.tasks, timeout)
+    }
+
+    fn push_result(&self, result: Vec<u8>) -> io::Result<()> {
+        self.publish("results", result)
+    }
+
+    fn pop_result(&self, timeout: Duration) -> io::Result<Option<Vec<u8>>> {
+        self.next(&self.results, timeout)
+    }
+}
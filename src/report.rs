@@ -0,0 +1,360 @@
+//! `ExecutionReport` — an `ExecutionResult` plus timing information for every node.
+//!
+//! Returned by `Dag::execute_timed()`.  Each executed node gets a `NodeTiming` entry
+//! recording when it started and finished (relative to the start of the run) and which
+//! worker slot ran it, which is enough to reconstruct a timeline of the execution.
+//! Each executed variant node also gets a `VariantRun` entry recording its index and
+//! the parameters it was created with, so outputs can be joined back to them.
+//! Runs from `Dag::execute_hashed()` also carry a content hash of every node's
+//! outputs, for checking that two runs computed the same thing.  `manifest()`
+//! writes all of it, with the inputs of the run, as one JSON document.
+
+use crate::dag::ExecutionResult;
+use crate::graph_data::GraphData;
+use crate::log_capture::LogRecord;
+use crate::node::{Node, NodeId};
+use crate::payload::{ContentHasher, Payload};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Start/end times of a single node execution.
+#[derive(Debug, Clone)]
+pub struct NodeTiming {
+    /// Node that was executed
+    pub node_id: NodeId,
+    /// Display name of the node at execution time
+    pub label: String,
+    /// Offset from the start of the run at which the node started
+    pub start: Duration,
+    /// Offset from the start of the run at which the node finished
+    pub end: Duration,
+    /// Worker slot that ran the node (0 for sequential execution).
+    ///
+    /// In parallel execution each level is split into chunks of at most `max_threads`
+    /// nodes; the worker is the node's position within its chunk.
+    pub worker: usize,
+    /// Log records the node emitted while running (see `CaptureLogger`)
+    pub logs: Vec<LogRecord>,
+}
+
+impl NodeTiming {
+    pub(crate) fn new<T>(node: &Node<T>, start: Duration, end: Duration, worker: usize) -> Self {
+        Self {
+            node_id: node.id,
+            label: node.display_name(),
+            start,
+            end,
+            worker,
+            logs: Vec::new(),
+        }
+    }
+
+    /// Wall-clock time spent in the node.
+    pub fn duration(&self) -> Duration {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+/// A variant node that ran, with the parameters injected into it.
+#[derive(Debug, Clone)]
+pub struct VariantRun<T = GraphData> {
+    /// Variant node that was executed
+    pub node_id: NodeId,
+    /// Display name of the node at execution time
+    pub label: String,
+    /// Index of the node among the variants it was created with
+    pub variant_index: usize,
+    /// Variant indices from the outermost sweep inwards (see `Node::variant_key()`)
+    pub variant_path: Vec<usize>,
+    /// Parameters injected into the node, such as the swept value of `Graph::variant()`
+    pub params: BTreeMap<String, T>,
+}
+
+impl<T: Clone> VariantRun<T> {
+    pub(crate) fn new(node: &Node<T>) -> Option<Self> {
+        Some(Self {
+            node_id: node.id,
+            label: node.display_name(),
+            variant_index: node.variant_index?,
+            variant_path: node.variant_path.clone(),
+            params: node
+                .variant_params
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        })
+    }
+}
+
+/// Execution result with per-node timings.
+#[derive(Debug, Clone)]
+pub struct ExecutionReport<T = GraphData> {
+    /// Context, per-node, and per-branch outputs (same as `Dag::execute_detailed()`)
+    pub result: ExecutionResult<T>,
+    /// One entry per executed node, in execution order (level order when parallel)
+    pub timings: Vec<NodeTiming>,
+    /// Wall-clock time of the whole run
+    pub total_duration: Duration,
+    /// One entry per executed variant node, in execution order
+    pub variants: Vec<VariantRun<T>>,
+    /// Hash of each node's outputs; empty unless the run came from `Dag::execute_hashed()`
+    pub output_hashes: BTreeMap<NodeId, u64>,
+    /// Structural hash of the DAG that ran (`Dag::fingerprint()`)
+    pub graph_fingerprint: u64,
+    /// Values in the context before the first node ran: declared input defaults and
+    /// the inputs passed to the run
+    pub inputs: BTreeMap<String, Arc<T>>,
+}
+
+impl<T> ExecutionReport<T> {
+    /// Get the timing entry for a node.
+    pub fn timing(&self, node_id: NodeId) -> Option<&NodeTiming> {
+        self.timings.iter().find(|t| t.node_id == node_id)
+    }
+
+    /// Log records a node emitted while running (empty if it did not run).
+    pub fn logs(&self, node_id: NodeId) -> &[LogRecord] {
+        self.timing(node_id).map_or(&[], |t| t.logs.as_slice())
+    }
+
+    /// Get the variant entry for a node, if it is a variant node that ran.
+    pub fn variant(&self, node_id: NodeId) -> Option<&VariantRun<T>> {
+        self.variants.iter().find(|v| v.node_id == node_id)
+    }
+
+    /// Each executed variant writing `broadcast_var`, paired with the value it wrote,
+    /// sorted by variant path.
+    pub fn variant_results(&self, broadcast_var: &str) -> Vec<(&VariantRun<T>, &T)> {
+        let mut results: Vec<(&VariantRun<T>, &T)> = self
+            .variants
+            .iter()
+            .filter_map(|v| Some((v, self.result.get_from_node(v.node_id, broadcast_var)?)))
+            .collect();
+        results.sort_by(|a, b| a.0.variant_path.cmp(&b.0.variant_path));
+        results
+    }
+
+    /// Hash of a node's outputs, if hashes were recorded and all its outputs are hashable.
+    pub fn output_hash(&self, node_id: NodeId) -> Option<u64> {
+        self.output_hashes.get(&node_id).copied()
+    }
+
+    /// Nodes whose output hashes differ between this run and `other`, sorted by ID.
+    ///
+    /// A node hashed in only one of the runs counts as differing.
+    pub fn differing_outputs(&self, other: &Self) -> Vec<NodeId> {
+        let mut nodes: Vec<NodeId> = self
+            .output_hashes
+            .keys()
+            .chain(other.output_hashes.keys())
+            .filter(|id| self.output_hashes.get(id) != other.output_hashes.get(id))
+            .copied()
+            .collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+        nodes
+    }
+
+    /// Sum of all node durations — the time a purely sequential run would have spent
+    /// inside node functions.
+    pub fn busy_time(&self) -> Duration {
+        self.timings.iter().map(|t| t.duration()).sum()
+    }
+
+    /// Render the run as a Mermaid `gantt` chart with one section per worker.
+    ///
+    /// Times are in milliseconds from the start of the run.  Mermaid cannot draw
+    /// zero-length bars, so every bar is at least 1 ms long.
+    pub fn to_mermaid_gantt(&self) -> String {
+        let mut gantt = String::from("gantt\n");
+        gantt.push_str("    title DAG Execution\n");
+        gantt.push_str("    dateFormat x\n");
+        gantt.push_str("    axisFormat %S.%L s\n");
+
+        let mut by_worker: BTreeMap<usize, Vec<&NodeTiming>> = BTreeMap::new();
+        for timing in &self.timings {
+            by_worker.entry(timing.worker).or_default().push(timing);
+        }
+
+        for (worker, mut timings) in by_worker {
+            timings.sort_by_key(|t| (t.start, t.node_id));
+            gantt.push_str(&format!("    section Worker {}\n", worker));
+            for t in timings {
+                let start_ms = t.start.as_millis();
+                let end_ms = t.end.as_millis().max(start_ms + 1);
+                gantt.push_str(&format!(
+                    "    {} : n{}, {}, {}\n",
+                    gantt_task_name(&t.label),
+                    t.node_id,
+                    start_ms,
+                    end_ms
+                ));
+            }
+        }
+
+        gantt
+    }
+
+    /// Render the run in the Chrome trace-event JSON format.
+    ///
+    /// The output can be saved to a `.json` file and opened in `chrome://tracing` or
+    /// <https://ui.perfetto.dev>.  Each worker becomes one thread track and each node
+    /// one complete (`"X"`) slice, with timestamps in microseconds from the start of the run.
+    pub fn to_chrome_trace(&self) -> String {
+        let mut events: Vec<String> = Vec::new();
+
+        let mut workers: Vec<usize> = self.timings.iter().map(|t| t.worker).collect();
+        workers.sort_unstable();
+        workers.dedup();
+        events.push(
+            r#"{"name":"process_name","ph":"M","pid":1,"tid":0,"args":{"name":"dagex"}}"#
+                .to_string(),
+        );
+        for worker in workers {
+            events.push(format!(
+                r#"{{"name":"thread_name","ph":"M","pid":1,"tid":{},"args":{{"name":"Worker {}"}}}}"#,
+                worker, worker
+            ));
+        }
+
+        let mut timings: Vec<&NodeTiming> = self.timings.iter().collect();
+        timings.sort_by_key(|t| (t.start, t.node_id));
+        for t in timings {
+            events.push(format!(
+                r#"{{"name":"{}","cat":"node","ph":"X","pid":1,"tid":{},"ts":{},"dur":{},"args":{{"node_id":{}}}}}"#,
+                json_escape(&t.label),
+                t.worker,
+                t.start.as_micros(),
+                t.duration().as_micros(),
+                t.node_id
+            ));
+        }
+
+        format!(
+            "{{\"traceEvents\":[\n{}\n],\"displayTimeUnit\":\"ms\"}}\n",
+            events.join(",\n")
+        )
+    }
+}
+
+impl<T: Debug> ExecutionReport<T> {
+    /// Record of the run as JSON, for auditing experiments and comparing runs.
+    ///
+    /// Holds the graph fingerprint, the inputs, the values swept by variant nodes
+    /// (`"variant_grids"`, each parameter's distinct values in order of first use),
+    /// every variant node with its parameters, and every executed node with its
+    /// timing and output hash, followed by whether a `CancelToken` stopped the run
+    /// and the nodes it skipped.  Values are written as strings of their `Debug` form,
+    /// which for `GraphData` is the bounded `GraphData::summary()`; hashes are written
+    /// as 16-digit hex strings and times in microseconds from the start of the run.
+    pub fn manifest(&self) -> String {
+        fn value<T: Debug>(value: &T) -> String {
+            format!("\"{}\"", json_escape(&format!("{:?}", value)))
+        }
+        fn object<'a>(entries: impl Iterator<Item = (&'a String, String)>) -> String {
+            let entries: Vec<String> =
+                entries.map(|(key, value)| format!("\"{}\":{}", json_escape(key), value)).collect();
+            format!("{{{}}}", entries.join(","))
+        }
+        fn list(items: Vec<String>, indent: &str) -> String {
+            if items.is_empty() {
+                return "[]".to_string();
+            }
+            let sep = format!(",\n{}  ", indent);
+            format!("[\n{}  {}\n{}]", indent, items.join(&sep), indent)
+        }
+        let hash = |hash: Option<u64>| hash.map_or("null".to_string(), |h| format!("\"{:016x}\"", h));
+
+        let mut grids: BTreeMap<&String, Vec<String>> = BTreeMap::new();
+        for variant in &self.variants {
+            for (param, v) in &variant.params {
+                let v = value(v);
+                let values = grids.entry(param).or_default();
+                if !values.contains(&v) {
+                    values.push(v);
+                }
+            }
+        }
+        let variants = self
+            .variants
+            .iter()
+            .map(|v| {
+                format!(
+                    "{{\"node_id\":{},\"label\":\"{}\",\"variant_path\":{:?},\"params\":{}}}",
+                    v.node_id,
+                    json_escape(&v.label),
+                    v.variant_path,
+                    object(v.params.iter().map(|(k, p)| (k, value(p))))
+                )
+            })
+            .collect();
+        let nodes = self
+            .timings
+            .iter()
+            .map(|t| {
+                format!(
+                    "{{\"node_id\":{},\"label\":\"{}\",\"start_us\":{},\"end_us\":{},\"worker\":{},\"output_hash\":{}}}",
+                    t.node_id,
+                    json_escape(&t.label),
+                    t.start.as_micros(),
+                    t.end.as_micros(),
+                    t.worker,
+                    hash(self.output_hash(t.node_id))
+                )
+            })
+            .collect();
+        let mut skipped: Vec<&NodeId> = self.result.skipped_nodes.iter().collect();
+        skipped.sort();
+
+        format!(
+            "{{\n  \"graph_fingerprint\": {},\n  \"total_duration_us\": {},\n  \"inputs\": {},\n  \"variant_grids\": {},\n  \"variants\": {},\n  \"nodes\": {},\n  \"cancelled\": {},\n  \"skipped_nodes\": {:?}\n}}\n",
+            hash(Some(self.graph_fingerprint)),
+            self.total_duration.as_micros(),
+            object(self.inputs.iter().map(|(k, v)| (k, value(v.as_ref())))),
+            object(grids.into_iter().map(|(k, values)| (k, format!("[{}]", values.join(","))))),
+            list(variants, "  "),
+            list(nodes, "  "),
+            self.result.cancelled,
+            skipped
+        )
+    }
+}
+
+/// Hash of a node's outputs, combining each variable name with the hash of its
+/// value in name order; `None` if any value cannot be hashed.
+pub(crate) fn outputs_hash<T: Payload>(outputs: &HashMap<String, Arc<T>>) -> Option<u64> {
+    let mut vars: Vec<&String> = outputs.keys().collect();
+    vars.sort();
+    let mut hasher = ContentHasher::default();
+    hasher.write_usize(vars.len());
+    for var in vars {
+        var.hash(&mut hasher);
+        hasher.write_u64(outputs[var].content_hash()?);
+    }
+    Some(hasher.finish())
+}
+
+/// Escape a string for embedding in a JSON string literal.
+pub(crate) fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Mermaid gantt task names may not contain `:` or `#`; replace them.
+fn gantt_task_name(label: &str) -> String {
+    label.replace([':', '#'], " ")
+}
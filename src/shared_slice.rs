@@ -0,0 +1,92 @@
+//! `SharedSlice` — a window into an Arc'd vector without copying it
+//!
+//! Created by `GraphData::slice()`.  Slicing only bumps the reference count of the
+//! underlying buffer, so fan-out nodes can each receive their own segment of a
+//! large array while all segments share one allocation.
+
+use std::fmt;
+use std::ops::{Bound, Deref, Range, RangeBounds};
+use std::sync::Arc;
+
+/// A contiguous range of an `Arc<Vec<T>>`, dereferencing to `&[T]`.
+#[derive(Clone)]
+pub struct SharedSlice<T> {
+    data: Arc<Vec<T>>,
+    range: Range<usize>,
+}
+
+impl<T> SharedSlice<T> {
+    /// View of `range` within `data`, or `None` if the range is out of bounds.
+    pub fn new(data: Arc<Vec<T>>, range: impl RangeBounds<usize>) -> Option<Self> {
+        let range = resolve(range, data.len())?;
+        Some(Self { data, range })
+    }
+
+    /// Narrow this view further; `range` is relative to the start of the view.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Option<Self> {
+        let inner = resolve(range, self.range.len())?;
+        Some(Self {
+            data: Arc::clone(&self.data),
+            range: self.range.start + inner.start..self.range.start + inner.end,
+        })
+    }
+
+    /// The buffer this view points into.
+    pub fn buffer(&self) -> &Arc<Vec<T>> {
+        &self.data
+    }
+
+    /// Position of the view within the buffer.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+}
+
+impl<T> Deref for SharedSlice<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.data[self.range.clone()]
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SharedSlice<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// Turn `range` into a concrete range within `0..len`.
+fn resolve(range: impl RangeBounds<usize>, len: usize) -> Option<Range<usize>> {
+    let start = match range.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s.checked_add(1)?,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&e) => e.checked_add(1)?,
+        Bound::Excluded(&e) => e,
+        Bound::Unbounded => len,
+    };
+    (start <= end && end <= len).then_some(start..end)
+}
+
+/// Serialized as a plain sequence; deserializing allocates a buffer of its own.
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for SharedSlice<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for SharedSlice<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = Vec::<T>::deserialize(deserializer)?;
+        let len = data.len();
+        Ok(Self {
+            data: Arc::new(data),
+            range: 0..len,
+        })
+    }
+}
@@ -0,0 +1,256 @@
+//! Reusable node functions
+//!
+//! Building blocks for demos, tests, and the simple steps of real pipelines.
+//! Each constructor returns a `NodeFunction` to add with `Graph::add_shared()`;
+//! map context variables onto the port names listed here:
+//!
+//! ```ignore
+//! graph.add_shared(std_nodes::multiply(), Some("Gain"),
+//!                  Some(vec![("signal", "a"), ("gain", "b")]),
+//!                  Some(vec![("result", "amplified")]));
+//! ```
+//!
+//! | Constructor                                     | Inputs         | Outputs           |
+//! |-------------------------------------------------|----------------|-------------------|
+//! | `add()`, `subtract()`, `multiply()`, `divide()` | `a`, `b`       | `result`          |
+//! | `format(template)`                              | `{name}`s used | `result`          |
+//! | `threshold(level)`                              | `x`            | `result`          |
+//! | `clamp(min, max)`                               | `x`            | `result`          |
+//! | `delay(duration)`                               | any            | the same          |
+//! | `random_uniform(low, high, seed)`               | `n` (optional) | `result`          |
+//! | `random_normal(mean, std_dev, seed)`            | `n` (optional) | `result`          |
+//! | `running_sum()`                                 | `x`            | `result`, `count` |
+//! | `history(capacity)`                             | `x`            | `result`          |
+//!
+//! Arithmetic works on numbers and float vectors: two integers give an integer
+//! (except for `divide()`), vectors combine element-wise, and a number combines
+//! with every element of a vector.  Random generators and accumulators keep
+//! their state in the node function, across executions of the DAG.
+//!
+//! On wrong or missing inputs a node outputs only an `error` string, so its
+//! dependents see a missing input.
+
+use crate::file_io::fallible;
+use crate::graph_data::GraphData;
+use crate::node::NodeFunction;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub(crate) type Inputs = HashMap<String, Arc<GraphData>>;
+
+/// Node function from a fallible one (see `file_io::fallible()`)
+pub(crate) fn node<F>(f: F) -> NodeFunction
+where
+    F: Fn(&Inputs) -> Result<HashMap<String, GraphData>, String> + Send + Sync + 'static,
+{
+    Arc::new(fallible(f))
+}
+
+/// The input `name`
+pub(crate) fn input<'a>(inputs: &'a Inputs, name: &str) -> Result<&'a GraphData, String> {
+    inputs.get(name).map(|value| value.force()).ok_or_else(|| format!("missing input '{}'", name))
+}
+
+/// The number in input `name`
+fn number(inputs: &Inputs, name: &str) -> Result<f64, String> {
+    let value = input(inputs, name)?;
+    value.as_float().ok_or_else(|| format!("input '{}' is {}, not a number", name, value.kind()))
+}
+
+/// Outputs with only `result`
+pub(crate) fn result(value: GraphData) -> HashMap<String, GraphData> {
+    HashMap::from([("result".to_string(), value)])
+}
+
+/// `a op b` for numbers and float vectors (see the module documentation)
+fn arithmetic(
+    name: &'static str,
+    int_op: Option<fn(i64, i64) -> Option<i64>>,
+    float_op: fn(f64, f64) -> f64,
+) -> NodeFunction {
+    node(move |inputs| {
+        let (a, b) = (input(inputs, "a")?, input(inputs, "b")?);
+        if let (Some(int_op), Some(x), Some(y)) = (int_op, a.as_int(), b.as_int()) {
+            if let Some(value) = int_op(x, y) {
+                return Ok(result(GraphData::int(value)));
+            }
+        }
+        let value = match (a.as_float(), b.as_float(), a.as_float_slice(), b.as_float_slice()) {
+            (Some(x), Some(y), _, _) => GraphData::float(float_op(x, y)),
+            (_, _, Some(xs), Some(ys)) if xs.len() == ys.len() => {
+                GraphData::float_vec(xs.iter().zip(ys).map(|(&x, &y)| float_op(x, y)).collect())
+            }
+            (_, Some(y), Some(xs), _) => GraphData::float_vec(xs.iter().map(|&x| float_op(x, y)).collect()),
+            (Some(x), _, _, Some(ys)) => GraphData::float_vec(ys.iter().map(|&y| float_op(x, y)).collect()),
+            _ => return Err(format!("cannot {} {} and {}", name, a.summary(), b.summary())),
+        };
+        Ok(result(value))
+    })
+}
+
+/// `result = a + b`
+pub fn add() -> NodeFunction {
+    arithmetic("add", Some(i64::checked_add), |x, y| x + y)
+}
+
+/// `result = a - b`
+pub fn subtract() -> NodeFunction {
+    arithmetic("subtract", Some(i64::checked_sub), |x, y| x - y)
+}
+
+/// `result = a * b`
+pub fn multiply() -> NodeFunction {
+    arithmetic("multiply", Some(i64::checked_mul), |x, y| x * y)
+}
+
+/// `result = a / b`, always as floats
+pub fn divide() -> NodeFunction {
+    arithmetic("divide", None, |x, y| x / y)
+}
+
+/// `result` is `template` with every `{name}` replaced by the input `name`
+///
+/// Strings are inserted as they are, other values in their plain text form
+/// (`GraphData::to_string_repr()`).  `{{` and `}}` stand for literal braces.
+pub fn format(template: &str) -> NodeFunction {
+    let template = template.to_string();
+    node(move |inputs| {
+        let mut text = String::with_capacity(template.len());
+        let mut rest = template.as_str();
+        while let Some(start) = rest.find(['{', '}']) {
+            text += &rest[..start];
+            let tail = &rest[start..];
+            if tail.starts_with("{{") || tail.starts_with("}}") {
+                text.push_str(&tail[..1]);
+                rest = &tail[2..];
+            } else if let (true, Some(end)) = (tail.starts_with('{'), tail.find('}')) {
+                let value = input(inputs, &tail[1..end])?;
+                text += &value.as_string().map_or_else(|| value.to_string_repr(), str::to_string);
+                rest = &tail[end + 1..];
+            } else {
+                return Err(format!("unmatched brace in template '{}'", template));
+            }
+        }
+        text += rest;
+        Ok(result(GraphData::string(text)))
+    })
+}
+
+/// `result` is whether `x` is above `level`: a bool for a number, a vector of
+/// 0s and 1s for a float vector
+pub fn threshold(level: f64) -> NodeFunction {
+    node(move |inputs| {
+        let x = input(inputs, "x")?;
+        let value = match (x.as_float(), x.as_float_slice()) {
+            (Some(v), _) => GraphData::bool(v > level),
+            (_, Some(vs)) => GraphData::int_vec(vs.iter().map(|&v| i64::from(v > level)).collect()),
+            _ => return Err(format!("cannot threshold {}", x.summary())),
+        };
+        Ok(result(value))
+    })
+}
+
+/// `result` is `x` limited to `min..=max`, element-wise for a float vector
+/// (integers stay integers)
+///
+/// # Panics
+///
+/// Panics if `min` is greater than `max` or either is NaN.
+pub fn clamp(min: f64, max: f64) -> NodeFunction {
+    assert!(min <= max, "clamp bounds must satisfy min <= max");
+    node(move |inputs| {
+        let x = input(inputs, "x")?;
+        let value = match (x.as_int(), x.as_float(), x.as_float_slice()) {
+            (Some(v), _, _) => GraphData::int((v as f64).max(min.ceil()).min(max.floor()) as i64),
+            (_, Some(v), _) => GraphData::float(v.clamp(min, max)),
+            (_, _, Some(vs)) => GraphData::float_vec(vs.iter().map(|v| v.clamp(min, max)).collect()),
+            _ => return Err(format!("cannot clamp {}", x.summary())),
+        };
+        Ok(result(value))
+    })
+}
+
+/// Wait for `duration`, then output every input under its own name
+///
+/// Useful to simulate slow stages, or to pace a source.
+pub fn delay(duration: Duration) -> NodeFunction {
+    node(move |inputs| {
+        std::thread::sleep(duration);
+        Ok(inputs.iter().map(|(name, value)| (name.clone(), GraphData::clone(value))).collect())
+    })
+}
+
+/// Draw `result` from `sample`, or a vector of `n` draws if the input `n` is given
+fn random<F>(seed: u64, sample: F) -> NodeFunction
+where
+    F: Fn(&mut StdRng) -> f64 + Send + Sync + 'static,
+{
+    let rng = Mutex::new(StdRng::seed_from_u64(seed));
+    node(move |inputs| {
+        let mut rng = rng.lock().unwrap();
+        match inputs.get("n").map(|n| n.as_int().filter(|&n| n >= 0)) {
+            None => Ok(result(GraphData::float(sample(&mut rng)))),
+            Some(Some(n)) => Ok(result(GraphData::float_vec((0..n).map(|_| sample(&mut rng)).collect()))),
+            Some(None) => Err("input 'n' is not a non-negative integer".to_string()),
+        }
+    })
+}
+
+/// `result` drawn uniformly from `low..high`
+///
+/// The sequence of draws across executions is fixed by `seed`.
+///
+/// # Panics
+///
+/// Panics if `low` is not less than `high`, or either is NaN or infinite.
+pub fn random_uniform(low: f64, high: f64, seed: u64) -> NodeFunction {
+    assert!(low < high && (high - low).is_finite(), "random_uniform range must be finite with low < high");
+    random(seed, move |rng| rng.gen_range(low..high))
+}
+
+/// `result` drawn from a normal distribution
+///
+/// The sequence of draws across executions is fixed by `seed`.
+///
+/// # Panics
+///
+/// Panics if `std_dev` is negative or not finite.
+pub fn random_normal(mean: f64, std_dev: f64, seed: u64) -> NodeFunction {
+    let normal = Normal::new(mean, std_dev).expect("invalid standard deviation");
+    random(seed, move |rng| normal.sample(rng))
+}
+
+/// `result` is the sum of `x` over all executions so far, `count` their number
+pub fn running_sum() -> NodeFunction {
+    let state = Mutex::new((0.0, 0));
+    node(move |inputs| {
+        let x = number(inputs, "x")?;
+        let mut state = state.lock().unwrap();
+        state.0 += x;
+        state.1 += 1;
+        Ok(HashMap::from([
+            ("result".to_string(), GraphData::float(state.0)),
+            ("count".to_string(), GraphData::int(state.1)),
+        ]))
+    })
+}
+
+/// `result` is a float vector of the last `capacity` values of `x`, oldest first
+pub fn history(capacity: usize) -> NodeFunction {
+    let values = Mutex::new(VecDeque::with_capacity(capacity));
+    node(move |inputs| {
+        let x = number(inputs, "x")?;
+        let mut values = values.lock().unwrap();
+        if values.len() == capacity {
+            values.pop_front();
+        }
+        if capacity > 0 {
+            values.push_back(x);
+        }
+        Ok(result(GraphData::float_vec(values.iter().copied().collect())))
+    })
+}
@@ -0,0 +1,153 @@
+//! `StorageBackend` — where intermediate state such as checkpoints and cached
+//! execution plans is kept
+//!
+//! A backend stores byte blobs under string keys.  Keys are `/`-separated paths
+//! (`"sweep/checkpoint.json"`), so a backend can map them to files, object-store
+//! keys, or anything else with a prefix listing.  Two backends are built in:
+//! `FileStorage` below a directory and `MemoryStorage` for tests and short-lived
+//! processes; others (object stores, key-value servers) implement the trait,
+//! behind their own features when they need extra dependencies.
+//!
+//! ```ignore
+//! let storage = FileStorage::new("runs");
+//! let dag = graph.build_cached_in(&storage, "pipeline.plan")?;
+//! let report = dag.execute_interruptible(true, None, &storage, "sweep/checkpoint.json")?;
+//! ```
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+/// Suffix of the temporary files `FileStorage::put()` writes before renaming
+const TMP_SUFFIX: &str = ".dagex-tmp";
+
+/// Byte blobs stored by key (see the module documentation)
+///
+/// Implementations are shared between threads, so they take `&self` and handle
+/// their own synchronization.
+pub trait StorageBackend: Send + Sync {
+    /// Store `bytes` under `key`, replacing what was stored there
+    fn put(&self, key: &str, bytes: &[u8]) -> io::Result<()>;
+
+    /// Bytes stored under `key`, or `None` if there are none
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+
+    /// Every key starting with `prefix`, sorted
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>>;
+}
+
+/// Storage in files below a root directory, one file per key
+///
+/// Writes go to a temporary file that is then renamed over the target, so a
+/// process killed mid-write leaves the previous value intact.
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    root: PathBuf,
+}
+
+impl FileStorage {
+    /// Store files below `root`, which is created on the first `put()`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Directory holding the files
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Path of the file for `key`; keys that would leave the root are rejected
+    fn path(&self, key: &str) -> io::Result<PathBuf> {
+        let relative = Path::new(key);
+        if key.is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid storage key '{}'", key),
+            ));
+        }
+        Ok(self.root.join(relative))
+    }
+
+    /// Add the keys of the files below `dir` to `keys`
+    fn collect(dir: &Path, key: &str, prefix: &str, keys: &mut Vec<String>) -> io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            let key = if key.is_empty() { name } else { format!("{}/{}", key, name) };
+            if entry.file_type()?.is_dir() {
+                Self::collect(&entry.path(), &key, prefix, keys)?;
+            } else if key.starts_with(prefix) && !key.ends_with(TMP_SUFFIX) {
+                keys.push(key);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl StorageBackend for FileStorage {
+    fn put(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.path(key)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(TMP_SUFFIX);
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, &path)
+    }
+
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match std::fs::read(self.path(key)?) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>> {
+        let mut keys = Vec::new();
+        match Self::collect(&self.root, "", prefix, &mut keys) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            result => result?,
+        }
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+/// Storage in a map held by the process
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    entries: Mutex<BTreeMap<String, Vec<u8>>>,
+}
+
+impl MemoryStorage {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StorageBackend for MemoryStorage {
+    fn put(&self, key: &str, bytes: &[u8]) -> io::Result<()> {
+        self.entries.lock().unwrap().insert(key.to_string(), bytes.to_vec());
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.entries.lock().unwrap().get(key).cloned())
+    }
+
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>> {
+        let entries = self.entries.lock().unwrap();
+        Ok(entries
+            .range(prefix.to_string()..)
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+}
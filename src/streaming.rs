@@ -0,0 +1,86 @@
+//! Bookkeeping for `Dag::execute_streaming()`
+//!
+//! A context variable is *final* once every node that declares it as an output has
+//! run.  `FinalOutputs` counts the remaining writers of each variable and reports
+//! the moment a count drops to zero, so the value can be handed to the user's
+//! callback while the rest of the DAG is still executing.
+
+use crate::graph_data::GraphData;
+use crate::node::Node;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Callback invoked with a context key and its final value.
+pub(crate) type OnFinal<'a, T = GraphData> = &'a (dyn Fn(&str, &T) + Sync);
+
+pub(crate) struct FinalOutputs<'a, T = GraphData> {
+    callback: OnFinal<'a, T>,
+    /// Context key -> number of writers that have not finished yet
+    remaining: HashMap<String, AtomicUsize>,
+}
+
+impl<'a, T> FinalOutputs<'a, T> {
+    pub(crate) fn new(nodes: &[Node<T>], callback: OnFinal<'a, T>) -> Self {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for node in nodes {
+            for (_, key) in context_keys(node) {
+                *counts.entry(key).or_default() += 1;
+            }
+        }
+        Self {
+            callback,
+            remaining: counts
+                .into_iter()
+                .map(|(key, n)| (key, AtomicUsize::new(n)))
+                .collect(),
+        }
+    }
+
+    /// Record that `node` finished and return the context keys (with their broadcast
+    /// names) that have no writers left.
+    pub(crate) fn finish(&self, node: &Node<T>) -> Vec<(String, String)> {
+        context_keys(node)
+            .into_iter()
+            .filter(|(_, key)| {
+                self.remaining
+                    .get(key)
+                    .is_some_and(|n| n.fetch_sub(1, Ordering::AcqRel) == 1)
+            })
+            .collect()
+    }
+
+    pub(crate) fn emit(&self, key: &str, value: Option<&T>) {
+        if let Some(value) = value {
+            (self.callback)(key, value);
+        }
+    }
+}
+
+/// Context keys written by more than one node of `level`.
+///
+/// Their final value is only known once the level's outputs have been merged in
+/// level order, so they are reported after the level instead of from a worker.
+pub(crate) fn shared_keys<T>(level: &[&Node<T>]) -> HashSet<String> {
+    let mut seen = HashSet::new();
+    let mut shared = HashSet::new();
+    for node in level {
+        for (_, key) in context_keys(node) {
+            if !seen.insert(key.clone()) {
+                shared.insert(key);
+            }
+        }
+    }
+    shared
+}
+
+/// `(broadcast_var, context_key)` for every output a node declares, deduplicated.
+pub(crate) fn context_keys<T>(node: &Node<T>) -> Vec<(String, String)> {
+    let mut keys: Vec<(String, String)> = node
+        .output_mapping
+        .values()
+        .map(|var| (var.clone(), node.context_key(var)))
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
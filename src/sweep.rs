@@ -0,0 +1,789 @@
+//! Value generators for parameter sweeps
+//!
+//! Generators such as `Linspace` and `Logspace` expand into a list of labelled
+//! `GraphData` values that `Graph::variant()` turns into one node per value.
+//! A `ParamGrid` combines several such sweeps into every combination of their
+//! values for `Graph::variant_grid()`.  `RandomSweep` draws seeded random values
+//! instead, for random-search style hyperparameter sweeps, and an `AdaptiveSweep`
+//! scores candidates round by round for `Graph::adaptive_sweep()`, pruning the
+//! weak ones (successive halving by default).
+//!
+//! Floating-point values are rounded according to a `FloatFormat` before they are
+//! handed to nodes, so that variant labels, context keys, and reports show `0.3`
+//! rather than `0.30000000000000004`, and so that the same sweep always produces
+//! bit-identical parameters.
+
+use crate::graph_data::GraphData;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// ─── FloatFormat ──────────────────────────────────────────────────────────────
+
+/// How generated floats are rounded and rendered as labels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FloatFormat {
+    /// Round to 12 significant digits and print the shortest representation.
+    /// This strips accumulated floating-point noise without losing real precision.
+    #[default]
+    Auto,
+    /// Round to a fixed number of decimal places and always print that many (`0.30`).
+    Fixed(usize),
+    /// Round to `n + 1` significant digits and print in scientific notation (`1.00e-3`).
+    Scientific(usize),
+}
+
+impl FloatFormat {
+    /// Round `value` to the precision implied by this format.
+    pub fn round(&self, value: f64) -> f64 {
+        match self {
+            FloatFormat::Auto => round_significant(value, 12),
+            FloatFormat::Fixed(decimals) => round_decimals(value, *decimals),
+            FloatFormat::Scientific(digits) => round_significant(value, digits + 1),
+        }
+    }
+
+    /// Render `value` (rounded first) as a human-readable label.
+    pub fn format(&self, value: f64) -> String {
+        let rounded = self.round(value);
+        match self {
+            FloatFormat::Auto => rounded.to_string(),
+            FloatFormat::Fixed(decimals) => format!("{:.*}", decimals, rounded),
+            FloatFormat::Scientific(digits) => format!("{:.*e}", digits, rounded),
+        }
+    }
+}
+
+/// Round to a number of decimal places.
+///
+/// Dividing two exactly-representable values yields the correctly rounded quotient,
+/// so the result is the double closest to the intended decimal.
+fn round_decimals(value: f64, decimals: usize) -> f64 {
+    if !value.is_finite() {
+        return value;
+    }
+    let scale = 10f64.powi(decimals.min(22) as i32);
+    (value * scale).round() / scale
+}
+
+/// Round to a number of significant digits.
+fn round_significant(value: f64, digits: usize) -> f64 {
+    if !value.is_finite() || value == 0.0 {
+        return value;
+    }
+    let magnitude = value.abs().log10().floor() as i32;
+    let shift = digits as i32 - 1 - magnitude;
+    if shift >= 0 {
+        let scale = 10f64.powi(shift.min(22));
+        (value * scale).round() / scale
+    } else {
+        let scale = 10f64.powi((-shift).min(22));
+        (value / scale).round() * scale
+    }
+}
+
+// ─── IntoVariantValues ────────────────────────────────────────────────────────
+
+/// Anything that can be expanded into a list of `(label, value)` pairs for a sweep.
+///
+/// The label is used in node labels (`"Scale (factor=0.3)"`); the value is injected
+/// into the node's inputs under the sweep parameter name.
+pub trait IntoVariantValues {
+    /// Expand into one `(label, value)` pair per variant.
+    fn into_variant_values(self) -> Vec<(String, GraphData)>;
+}
+
+impl IntoVariantValues for Vec<f64> {
+    fn into_variant_values(self) -> Vec<(String, GraphData)> {
+        self.into_iter()
+            .map(|v| (FloatFormat::Auto.format(v), GraphData::float(v)))
+            .collect()
+    }
+}
+
+impl IntoVariantValues for Vec<i64> {
+    fn into_variant_values(self) -> Vec<(String, GraphData)> {
+        self.into_iter()
+            .map(|v| (v.to_string(), GraphData::int(v)))
+            .collect()
+    }
+}
+
+impl IntoVariantValues for Vec<bool> {
+    fn into_variant_values(self) -> Vec<(String, GraphData)> {
+        self.into_iter()
+            .map(|v| (v.to_string(), GraphData::bool(v)))
+            .collect()
+    }
+}
+
+impl IntoVariantValues for Vec<&str> {
+    fn into_variant_values(self) -> Vec<(String, GraphData)> {
+        self.into_iter()
+            .map(|v| (v.to_string(), GraphData::string(v)))
+            .collect()
+    }
+}
+
+impl IntoVariantValues for Vec<GraphData> {
+    fn into_variant_values(self) -> Vec<(String, GraphData)> {
+        self.into_iter()
+            .map(|v| (v.to_string_repr(), v))
+            .collect()
+    }
+}
+
+impl IntoVariantValues for Vec<(String, GraphData)> {
+    fn into_variant_values(self) -> Vec<(String, GraphData)> {
+        self
+    }
+}
+
+// ─── Linspace ─────────────────────────────────────────────────────────────────
+
+/// `num` evenly spaced values over `[start, stop]` (inclusive), like `numpy.linspace`.
+#[derive(Debug, Clone)]
+pub struct Linspace {
+    start: f64,
+    stop: f64,
+    num: usize,
+    format: FloatFormat,
+}
+
+impl Linspace {
+    /// Create a linear sweep from `start` to `stop` with `num` points.
+    pub fn new(start: f64, stop: f64, num: usize) -> Self {
+        Self {
+            start,
+            stop,
+            num,
+            format: FloatFormat::Auto,
+        }
+    }
+
+    /// Set how values are rounded and labelled.
+    pub fn format(mut self, format: FloatFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Shorthand for `.format(FloatFormat::Fixed(decimals))`.
+    pub fn precision(self, decimals: usize) -> Self {
+        self.format(FloatFormat::Fixed(decimals))
+    }
+
+    /// The rounded values of the sweep.
+    pub fn values(&self) -> Vec<f64> {
+        let raw: Vec<f64> = match self.num {
+            0 => Vec::new(),
+            1 => vec![self.start],
+            n => {
+                let step = (self.stop - self.start) / (n - 1) as f64;
+                (0..n).map(|i| self.start + step * i as f64).collect()
+            }
+        };
+        raw.into_iter().map(|v| self.format.round(v)).collect()
+    }
+
+    /// The labels of the sweep, one per value.
+    pub fn labels(&self) -> Vec<String> {
+        self.values().into_iter().map(|v| self.format.format(v)).collect()
+    }
+}
+
+impl IntoVariantValues for Linspace {
+    fn into_variant_values(self) -> Vec<(String, GraphData)> {
+        self.values()
+            .into_iter()
+            .map(|v| (self.format.format(v), GraphData::float(v)))
+            .collect()
+    }
+}
+
+// ─── Logspace ─────────────────────────────────────────────────────────────────
+
+/// `num` values spaced evenly on a log scale, `base^start ..= base^stop`, like `numpy.logspace`.
+#[derive(Debug, Clone)]
+pub struct Logspace {
+    start: f64,
+    stop: f64,
+    num: usize,
+    base: f64,
+    format: FloatFormat,
+}
+
+impl Logspace {
+    /// Create a base-10 log sweep from `10^start` to `10^stop` with `num` points.
+    pub fn new(start: f64, stop: f64, num: usize) -> Self {
+        Self {
+            start,
+            stop,
+            num,
+            base: 10.0,
+            format: FloatFormat::Auto,
+        }
+    }
+
+    /// Use a base other than 10.
+    pub fn base(mut self, base: f64) -> Self {
+        self.base = base;
+        self
+    }
+
+    /// Set how values are rounded and labelled.
+    pub fn format(mut self, format: FloatFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Shorthand for `.format(FloatFormat::Scientific(digits))`.
+    pub fn precision(self, digits: usize) -> Self {
+        self.format(FloatFormat::Scientific(digits))
+    }
+
+    /// The rounded values of the sweep.
+    pub fn values(&self) -> Vec<f64> {
+        Linspace::new(self.start, self.stop, self.num)
+            .format(FloatFormat::Auto)
+            .values()
+            .into_iter()
+            .map(|exp| self.format.round(self.base.powf(exp)))
+            .collect()
+    }
+
+    /// The labels of the sweep, one per value.
+    pub fn labels(&self) -> Vec<String> {
+        self.values().into_iter().map(|v| self.format.format(v)).collect()
+    }
+}
+
+impl IntoVariantValues for Logspace {
+    fn into_variant_values(self) -> Vec<(String, GraphData)> {
+        self.values()
+            .into_iter()
+            .map(|v| (self.format.format(v), GraphData::float(v)))
+            .collect()
+    }
+}
+
+// ─── Geomspace ────────────────────────────────────────────────────────────────
+
+/// `num` values spaced evenly on a log scale between `start` and `stop` themselves
+/// (both > 0), like `numpy.geomspace`.
+#[derive(Debug, Clone)]
+pub struct Geomspace {
+    start: f64,
+    stop: f64,
+    num: usize,
+    format: FloatFormat,
+}
+
+impl Geomspace {
+    /// Create a geometric sweep from `start` to `stop` with `num` points.
+    pub fn new(start: f64, stop: f64, num: usize) -> Self {
+        Self {
+            start,
+            stop,
+            num,
+            format: FloatFormat::Auto,
+        }
+    }
+
+    /// Set how values are rounded and labelled.
+    pub fn format(mut self, format: FloatFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Shorthand for `.format(FloatFormat::Scientific(digits))`.
+    pub fn precision(self, digits: usize) -> Self {
+        self.format(FloatFormat::Scientific(digits))
+    }
+
+    /// The rounded values of the sweep.
+    pub fn values(&self) -> Vec<f64> {
+        let raw: Vec<f64> = match self.num {
+            0 => Vec::new(),
+            1 => vec![self.start],
+            n => {
+                let (log_start, log_stop) = (self.start.ln(), self.stop.ln());
+                let step = (log_stop - log_start) / (n - 1) as f64;
+                // Endpoints are exact, as in numpy
+                (0..n)
+                    .map(|i| match i {
+                        0 => self.start,
+                        i if i == n - 1 => self.stop,
+                        i => (log_start + step * i as f64).exp(),
+                    })
+                    .collect()
+            }
+        };
+        raw.into_iter().map(|v| self.format.round(v)).collect()
+    }
+
+    /// The labels of the sweep, one per value.
+    pub fn labels(&self) -> Vec<String> {
+        self.values().into_iter().map(|v| self.format.format(v)).collect()
+    }
+}
+
+impl IntoVariantValues for Geomspace {
+    fn into_variant_values(self) -> Vec<(String, GraphData)> {
+        self.values()
+            .into_iter()
+            .map(|v| (self.format.format(v), GraphData::float(v)))
+            .collect()
+    }
+}
+
+// ─── RandomSweep ──────────────────────────────────────────────────────────────
+
+/// `n` values drawn at random from a seeded generator, so the same seed always
+/// yields the same sweep.
+///
+/// ```ignore
+/// graph.variant("lr", RandomSweep::new(8, 42).log_uniform(1e-4, 1e-1), train, ...);
+/// graph.variant("act", RandomSweep::new(4, 7).choice(vec!["relu", "tanh"]), train, ...);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RandomSweep {
+    n: usize,
+    seed: u64,
+    sampler: Sampler,
+    format: FloatFormat,
+}
+
+#[derive(Debug, Clone)]
+enum Sampler {
+    Uniform(f64, f64),
+    LogUniform(f64, f64),
+    Choice(Vec<(String, GraphData)>),
+}
+
+impl RandomSweep {
+    /// Create a sweep of `n` draws seeded with `seed`, uniform on `[0, 1)` until
+    /// another distribution is chosen.
+    pub fn new(n: usize, seed: u64) -> Self {
+        Self {
+            n,
+            seed,
+            sampler: Sampler::Uniform(0.0, 1.0),
+            format: FloatFormat::Auto,
+        }
+    }
+
+    /// Draw floats uniformly from `[low, high)`.
+    ///
+    /// Draws are then rounded by the sweep's `format`, which can move a value onto
+    /// `high` (or, for coarse formats, just outside the range).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `low` is not less than `high`, or either is NaN or infinite.
+    pub fn uniform(mut self, low: f64, high: f64) -> Self {
+        assert!(low < high && (high - low).is_finite(), "uniform range must be finite with low < high");
+        self.sampler = Sampler::Uniform(low, high);
+        self
+    }
+
+    /// Draw floats whose logarithm is uniform, between `low` and `high` (both > 0).
+    ///
+    /// As with `uniform()`, the `format` rounding can move a value onto or past a bound.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `0 < low < high` and `high` is finite.
+    pub fn log_uniform(mut self, low: f64, high: f64) -> Self {
+        assert!(0.0 < low && low < high && high.is_finite(), "log_uniform range must be finite with 0 < low < high");
+        self.sampler = Sampler::LogUniform(low, high);
+        self
+    }
+
+    /// Draw each value from `options` with equal probability (with replacement).
+    pub fn choice<V: IntoVariantValues>(mut self, options: V) -> Self {
+        self.sampler = Sampler::Choice(options.into_variant_values());
+        self
+    }
+
+    /// Set how drawn floats are rounded and labelled (ignored for `choice`).
+    pub fn format(mut self, format: FloatFormat) -> Self {
+        self.format = format;
+        self
+    }
+}
+
+impl IntoVariantValues for RandomSweep {
+    fn into_variant_values(self) -> Vec<(String, GraphData)> {
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let float = |v: f64| (self.format.format(v), GraphData::float(self.format.round(v)));
+        match &self.sampler {
+            Sampler::Uniform(low, high) => (0..self.n)
+                .map(|_| float(low + (high - low) * rng.gen::<f64>()))
+                .collect(),
+            Sampler::LogUniform(low, high) => {
+                let (low, high) = (low.ln(), high.ln());
+                (0..self.n)
+                    .map(|_| float((low + (high - low) * rng.gen::<f64>()).exp()))
+                    .collect()
+            }
+            Sampler::Choice(options) if options.is_empty() => Vec::new(),
+            Sampler::Choice(options) => (0..self.n)
+                .map(|_| options[rng.gen_range(0..options.len())].clone())
+                .collect(),
+        }
+    }
+}
+
+// ─── ParamGrid ────────────────────────────────────────────────────────────────
+
+/// Several named sweeps expanded into every combination of their values.
+///
+/// ```ignore
+/// let grid = ParamGrid::new()
+///     .add("lr", Logspace::new(-3.0, -1.0, 3))
+///     .add("batch", vec![32i64, 64]);
+/// assert_eq!(grid.len(), 6);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ParamGrid {
+    axes: Vec<(String, Vec<(String, GraphData)>)>,
+}
+
+impl ParamGrid {
+    /// Create an empty grid.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a parameter swept over `values`.  Adding a name twice replaces its values.
+    pub fn add<V: IntoVariantValues>(mut self, name: &str, values: V) -> Self {
+        let values = values.into_variant_values();
+        match self.axes.iter_mut().find(|(n, _)| n == name) {
+            Some(axis) => axis.1 = values,
+            None => self.axes.push((name.to_string(), values)),
+        }
+        self
+    }
+
+    /// Parameter names, in the order they were added.
+    pub fn names(&self) -> Vec<&str> {
+        self.axes.iter().map(|(n, _)| n.as_str()).collect()
+    }
+
+    /// Number of combinations (the product of the sweep lengths).
+    pub fn len(&self) -> usize {
+        if self.axes.is_empty() {
+            return 0;
+        }
+        self.axes.iter().map(|(_, v)| v.len()).product()
+    }
+
+    /// Whether the grid has no combinations.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Every combination, labelled `"name=value, name=value"`.
+    ///
+    /// The last parameter added varies fastest.
+    pub fn combinations(&self) -> Vec<(String, HashMap<String, GraphData>)> {
+        let mut combos: Vec<(Vec<String>, HashMap<String, GraphData>)> = if self.axes.is_empty() {
+            Vec::new()
+        } else {
+            vec![(Vec::new(), HashMap::new())]
+        };
+        for (name, values) in &self.axes {
+            combos = combos
+                .into_iter()
+                .flat_map(|(labels, params)| {
+                    values.iter().map(move |(label, value)| {
+                        let mut labels = labels.clone();
+                        labels.push(format!("{}={}", name, label));
+                        let mut params = params.clone();
+                        params.insert(name.clone(), value.clone());
+                        (labels, params)
+                    })
+                })
+                .collect();
+        }
+        combos
+            .into_iter()
+            .map(|(labels, params)| (labels.join(", "), params))
+            .collect()
+    }
+}
+
+// ─── AdaptiveSweep ────────────────────────────────────────────────────────────
+
+/// One evaluated candidate of an `AdaptiveSweep`.
+#[derive(Debug, Clone)]
+pub struct Trial {
+    /// Label of the candidate value (as in variant labels)
+    pub label: String,
+    /// The candidate value
+    pub value: GraphData,
+    /// Round in which the candidate was evaluated, starting at 0
+    pub round: usize,
+    /// Score returned by the scoring callback (higher is better)
+    pub score: f64,
+    /// Full inner context of the candidate's execution
+    pub outputs: HashMap<String, Arc<GraphData>>,
+}
+
+/// What an `AdaptiveSweep` does after a round, decided by its pruning callback.
+#[derive(Debug, Clone)]
+pub enum SweepStep {
+    /// Evaluate these trials of the round again (indices into the round's trials)
+    Keep(Vec<usize>),
+    /// Evaluate a fresh set of candidates, e.g. a narrower range around the best
+    Refine(Vec<(String, GraphData)>),
+    /// Stop; the best trial of this round is the result
+    Stop,
+}
+
+type ScoreFn = Arc<dyn Fn(&HashMap<String, Arc<GraphData>>) -> f64 + Send + Sync>;
+type PruneFn = Arc<dyn Fn(&[Trial]) -> SweepStep + Send + Sync>;
+
+/// A sweep evaluated in rounds, where a callback scores each candidate and decides
+/// which ones go on to the next round.
+///
+/// By default the better half of the candidates survives each round until one is
+/// left (successive halving).  With `budget()`, each round also passes a growing
+/// budget (such as a number of training epochs) to the subgraph, so weak
+/// candidates are dropped before they get expensive.
+///
+/// ```ignore
+/// let sweep = AdaptiveSweep::new("lr", Logspace::new(-4.0, -1.0, 8))
+///     .score(|ctx| -ctx["loss"].as_float().unwrap_or(f64::INFINITY))
+///     .budget("epochs", 1, 2);
+/// graph.adaptive_sweep(sweep, train_graph(), Some("Tune"), Some(vec![("data", "x")]),
+///     Some(vec![("lr", "best_lr"), ("loss", "best_loss")]));
+/// ```
+#[derive(Clone)]
+pub struct AdaptiveSweep {
+    pub(crate) param: String,
+    pub(crate) values: Vec<(String, GraphData)>,
+    pub(crate) score: ScoreFn,
+    pub(crate) prune: PruneFn,
+    pub(crate) budget: Option<(String, i64, i64)>,
+    pub(crate) max_rounds: usize,
+}
+
+impl AdaptiveSweep {
+    /// Sweep `param` over `values`, scoring every candidate 0 until `score()` is set.
+    pub fn new<V: IntoVariantValues>(param: &str, values: V) -> Self {
+        Self {
+            param: param.to_string(),
+            values: values.into_variant_values(),
+            score: Arc::new(|_| 0.0),
+            prune: Arc::new(halve),
+            budget: None,
+            max_rounds: usize::MAX,
+        }
+    }
+
+    /// Score a candidate from the inner context of its execution (higher is better).
+    ///
+    /// Non-finite scores (NaN, infinities) rank below every finite score, so a
+    /// diverged run is neither kept by the default policy nor picked as the best.
+    pub fn score<F>(mut self, score: F) -> Self
+    where
+        F: Fn(&HashMap<String, Arc<GraphData>>) -> f64 + Send + Sync + 'static,
+    {
+        self.score = Arc::new(score);
+        self
+    }
+
+    /// Replace successive halving with a custom policy, called with the trials of
+    /// each round in candidate order.
+    pub fn prune<F>(mut self, prune: F) -> Self
+    where
+        F: Fn(&[Trial]) -> SweepStep + Send + Sync + 'static,
+    {
+        self.prune = Arc::new(prune);
+        self
+    }
+
+    /// Pass an integer budget to the subgraph under `name`: `start` in the first
+    /// round, multiplied by `factor` each following round.
+    pub fn budget(mut self, name: &str, start: i64, factor: i64) -> Self {
+        self.budget = Some((name.to_string(), start, factor));
+        self
+    }
+
+    /// Stop after at most `rounds` rounds, even if candidates remain.
+    pub fn max_rounds(mut self, rounds: usize) -> Self {
+        self.max_rounds = rounds;
+        self
+    }
+
+    /// Budget for `round`, if one is configured.
+    pub(crate) fn budget_for(&self, round: usize) -> Option<(String, GraphData)> {
+        self.budget.as_ref().map(|(name, start, factor)| {
+            let scale = factor.saturating_pow(round.min(u32::MAX as usize) as u32);
+            (name.clone(), GraphData::int(start.saturating_mul(scale)))
+        })
+    }
+}
+
+/// Default pruning policy: keep the better half (rounded up) until one trial is left.
+///
+/// Trials with a non-finite score rank last.
+fn halve(trials: &[Trial]) -> SweepStep {
+    if trials.len() <= 1 {
+        return SweepStep::Stop;
+    }
+    let rank = |i: usize| Some(trials[i].score).filter(|s| s.is_finite()).unwrap_or(f64::NEG_INFINITY);
+    let mut order: Vec<usize> = (0..trials.len()).collect();
+    order.sort_by(|&a, &b| rank(b).total_cmp(&rank(a)));
+    order.truncate(trials.len().div_ceil(2));
+    order.sort_unstable();
+    SweepStep::Keep(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_format_strips_float_noise() {
+        assert_eq!(FloatFormat::Auto.format(0.1 + 0.2), "0.3");
+        assert_eq!(FloatFormat::Auto.round(0.1 + 0.2), 0.3);
+        assert_eq!(FloatFormat::Auto.format(1234.5), "1234.5");
+    }
+
+    #[test]
+    fn test_fixed_and_scientific_format() {
+        assert_eq!(FloatFormat::Fixed(2).format(0.3), "0.30");
+        assert_eq!(FloatFormat::Fixed(1).round(0.26), 0.3);
+        assert_eq!(FloatFormat::Scientific(2).format(0.001), "1.00e-3");
+        assert_eq!(FloatFormat::Scientific(1).round(123456.0), 120000.0);
+    }
+
+    #[test]
+    fn test_linspace_values_and_labels() {
+        let sweep = Linspace::new(0.0, 1.0, 11);
+        assert_eq!(sweep.values()[3], 0.3);
+        assert_eq!(sweep.labels()[3], "0.3");
+        assert_eq!(sweep.values().len(), 11);
+
+        let fixed = Linspace::new(0.0, 1.0, 3).precision(2);
+        assert_eq!(fixed.labels(), vec!["0.00", "0.50", "1.00"]);
+    }
+
+    #[test]
+    fn test_linspace_edge_counts() {
+        assert!(Linspace::new(0.0, 1.0, 0).values().is_empty());
+        assert_eq!(Linspace::new(2.0, 5.0, 1).values(), vec![2.0]);
+    }
+
+    #[test]
+    fn test_logspace_values() {
+        let sweep = Logspace::new(-3.0, 0.0, 4);
+        assert_eq!(sweep.values(), vec![0.001, 0.01, 0.1, 1.0]);
+        assert_eq!(sweep.labels(), vec!["0.001", "0.01", "0.1", "1"]);
+
+        let base2 = Logspace::new(0.0, 3.0, 4).base(2.0);
+        assert_eq!(base2.values(), vec![1.0, 2.0, 4.0, 8.0]);
+    }
+
+    #[test]
+    fn test_geomspace_values() {
+        assert_eq!(Geomspace::new(1.0, 1000.0, 4).values(), vec![1.0, 10.0, 100.0, 1000.0]);
+        assert_eq!(Geomspace::new(2.0, 16.0, 4).labels(), vec!["2", "4", "8", "16"]);
+    }
+
+    #[test]
+    fn test_into_variant_values() {
+        let pairs = Linspace::new(0.0, 0.2, 3).into_variant_values();
+        assert_eq!(pairs[1].0, "0.1");
+        assert_eq!(pairs[1].1.as_float(), Some(0.1));
+
+        let ints = vec![32i64, 64].into_variant_values();
+        assert_eq!(ints[1].0, "64");
+        assert_eq!(ints[1].1.as_int(), Some(64));
+    }
+
+    #[test]
+    fn test_param_grid_combinations() {
+        let grid = ParamGrid::new()
+            .add("lr", Logspace::new(-2.0, -1.0, 2))
+            .add("batch", vec![32i64, 64]);
+        assert_eq!(grid.len(), 4);
+        assert_eq!(grid.names(), vec!["lr", "batch"]);
+
+        let combos = grid.combinations();
+        let labels: Vec<&str> = combos.iter().map(|(l, _)| l.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "lr=0.01, batch=32",
+                "lr=0.01, batch=64",
+                "lr=0.1, batch=32",
+                "lr=0.1, batch=64"
+            ]
+        );
+        assert_eq!(combos[3].1["lr"].as_float(), Some(0.1));
+        assert_eq!(combos[3].1["batch"].as_int(), Some(64));
+
+        assert!(ParamGrid::new().is_empty());
+        assert!(ParamGrid::new().add("x", Vec::<i64>::new()).is_empty());
+    }
+
+    #[test]
+    fn test_random_sweep_is_seeded() {
+        let draw = |seed| -> Vec<f64> {
+            RandomSweep::new(5, seed)
+                .uniform(2.0, 3.0)
+                .into_variant_values()
+                .into_iter()
+                .map(|(_, v)| v.as_float().unwrap())
+                .collect()
+        };
+        let a = draw(42);
+        assert_eq!(a.len(), 5);
+        assert_eq!(a, draw(42));
+        assert_ne!(a, draw(43));
+        assert!(a.iter().all(|v| (2.0..3.0).contains(v)));
+
+        let log = RandomSweep::new(20, 1).log_uniform(1e-4, 1e-1).into_variant_values();
+        assert!(log.iter().all(|(_, v)| (1e-4..=1e-1).contains(&v.as_float().unwrap())));
+
+        let picks = RandomSweep::new(10, 7).choice(vec!["relu", "tanh"]).into_variant_values();
+        assert_eq!(picks.len(), 10);
+        assert!(picks.iter().all(|(l, _)| l == "relu" || l == "tanh"));
+    }
+
+    #[test]
+    #[should_panic(expected = "uniform range must be finite with low < high")]
+    fn test_random_sweep_rejects_reversed_uniform_range() {
+        let _ = RandomSweep::new(3, 0).uniform(1.0, 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "log_uniform range must be finite with 0 < low < high")]
+    fn test_random_sweep_rejects_non_positive_log_uniform_range() {
+        let _ = RandomSweep::new(3, 0).log_uniform(0.0, 1.0);
+    }
+
+    #[test]
+    fn test_halving_keeps_better_half() {
+        let trial = |score| Trial {
+            label: String::new(),
+            value: GraphData::none(),
+            round: 0,
+            score,
+            outputs: HashMap::new(),
+        };
+        let trials = vec![trial(1.0), trial(4.0), trial(3.0), trial(2.0), trial(0.0)];
+        match halve(&trials) {
+            SweepStep::Keep(kept) => assert_eq!(kept, vec![1, 2, 3]),
+            other => panic!("unexpected step: {:?}", other),
+        }
+        assert!(matches!(halve(&trials[..1]), SweepStep::Stop));
+
+        let sweep = AdaptiveSweep::new("lr", vec![1.0, 2.0]).budget("epochs", 1, 3);
+        assert_eq!(sweep.budget_for(2).unwrap().1.as_int(), Some(9));
+    }
+}
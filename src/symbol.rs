@@ -0,0 +1,157 @@
+//! `Symbol` — interned variable names
+//!
+//! Every context key is interned once in a process-wide table and afterwards
+//! handled as a `u32` id: copying a key allocates nothing and hashing it is a
+//! single multiplication.  `Dag` resolves the keys each node reads and writes
+//! when it is built (see `plan`), so execution never hashes a string; only
+//! lookups by name (`ExecutionContext::get("x")`) go through the table.
+//!
+//! Interned names are never freed.  Graphs intern a bounded set of names (one per
+//! variable, branch namespace, and variant), so the table stays small.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::ops::Deref;
+use std::sync::{OnceLock, RwLock};
+
+/// An interned variable name
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol(u32);
+
+/// Names and ids of every interned symbol
+#[derive(Default)]
+struct SymbolTable {
+    ids: HashMap<&'static str, Symbol>,
+    names: Vec<&'static str>,
+}
+
+fn table() -> &'static RwLock<SymbolTable> {
+    static TABLE: OnceLock<RwLock<SymbolTable>> = OnceLock::new();
+    TABLE.get_or_init(Default::default)
+}
+
+impl Symbol {
+    /// The symbol for `name`, interning it on first use
+    pub fn new(name: &str) -> Self {
+        if let Some(symbol) = Self::lookup(name) {
+            return symbol;
+        }
+        let mut table = table().write().unwrap_or_else(|e| e.into_inner());
+        if let Some(&symbol) = table.ids.get(name) {
+            return symbol;
+        }
+        let symbol = Symbol(u32::try_from(table.names.len()).expect("too many interned symbols"));
+        let name: &'static str = Box::leak(name.into());
+        table.names.push(name);
+        table.ids.insert(name, symbol);
+        symbol
+    }
+
+    /// The symbol for `name` if it was ever interned
+    ///
+    /// A name that was never interned cannot be a key of any context, so lookups
+    /// by name use this instead of growing the table.
+    pub fn lookup(name: &str) -> Option<Self> {
+        let table = table().read().unwrap_or_else(|e| e.into_inner());
+        table.ids.get(name).copied()
+    }
+
+    /// The interned name
+    pub fn as_str(self) -> &'static str {
+        let table = table().read().unwrap_or_else(|e| e.into_inner());
+        table.names[self.0 as usize]
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<&String> for Symbol {
+    fn from(name: &String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(name: String) -> Self {
+        Self::new(&name)
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(symbol: Symbol) -> Self {
+        symbol.as_str().to_string()
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for Symbol {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+/// Hasher for `Symbol` keys: ids are small and dense, so a Fibonacci multiply
+/// spreads them well enough
+#[derive(Default)]
+pub(crate) struct SymbolHasher(u64);
+
+impl Hasher for SymbolHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0.rotate_left(8) ^ u64::from(byte)).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        }
+    }
+
+    fn write_u32(&mut self, id: u32) {
+        self.0 = u64::from(id).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    }
+}
+
+/// Map keyed by interned names
+pub(crate) type SymbolMap<V> = HashMap<Symbol, V, BuildHasherDefault<SymbolHasher>>;
@@ -0,0 +1,155 @@
+//! Physical units of node ports
+//!
+//! Ports can be annotated with a unit string (`Graph::with_input_unit()`,
+//! `Graph::with_output_unit()`).  `Graph::build()` then checks that every
+//! producer and consumer of a variable agree: `"MHz"` feeding a `"Hz"` input is a
+//! `BuildError::UnitMismatch` unless the graph converts units automatically
+//! (`Graph::auto_scale_units()`), in which case the consumer receives its input
+//! multiplied by 1e6.  Units of different dimensions (`"Hz"` and `"m"`) never
+//! connect.
+//!
+//! A unit is a base unit with an optional SI prefix (`"ns"`, `"kHz"`, `"mm"`,
+//! `"kg"`), or one of `"min"`, `"h"` (seconds) and `"deg"` (radians).  Any other
+//! string is its own dimension and only matches itself exactly.
+
+use crate::node::{Node, NodeId};
+use crate::validation::BuildError;
+use std::collections::HashMap;
+
+/// Base units understood with SI prefixes
+const BASE_UNITS: &[&str] = &["Hz", "s", "m", "g", "rad", "V", "A", "W", "J", "N", "Pa", "K", "B", "bit"];
+
+/// SI prefixes and their factors
+const PREFIXES: &[(&str, f64)] = &[
+    ("T", 1e12),
+    ("G", 1e9),
+    ("M", 1e6),
+    ("k", 1e3),
+    ("c", 1e-2),
+    ("m", 1e-3),
+    ("u", 1e-6),
+    ("µ", 1e-6),
+    ("n", 1e-9),
+    ("p", 1e-12),
+];
+
+/// Units that are multiples of a base unit without an SI prefix
+const MULTIPLES: &[(&str, &str, f64)] = &[
+    ("min", "s", 60.0),
+    ("h", "s", 3600.0),
+    ("deg", "rad", std::f64::consts::PI / 180.0),
+];
+
+/// A unit as a dimension (its base unit) and the factor converting it to the base
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Unit<'a> {
+    base: &'a str,
+    factor: f64,
+}
+
+impl<'a> Unit<'a> {
+    /// Parse a unit string; unknown units are their own base
+    pub(crate) fn parse(unit: &'a str) -> Self {
+        if let Some(&base) = BASE_UNITS.iter().find(|&&base| base == unit) {
+            return Self { base, factor: 1.0 };
+        }
+        if let Some(&(_, base, factor)) = MULTIPLES.iter().find(|(name, _, _)| *name == unit) {
+            return Self { base, factor };
+        }
+        for &(prefix, factor) in PREFIXES {
+            if let Some(rest) = unit.strip_prefix(prefix) {
+                if let Some(&base) = BASE_UNITS.iter().find(|&&base| base == rest) {
+                    return Self { base, factor };
+                }
+            }
+        }
+        Self { base: unit, factor: 1.0 }
+    }
+
+    /// Factor converting values in this unit to `target`, or `None` if the two
+    /// have different dimensions
+    pub(crate) fn factor_to(&self, target: &Unit) -> Option<f64> {
+        (self.base == target.base).then(|| self.factor / target.factor)
+    }
+}
+
+/// Check the units of every producer and consumer of a variable
+///
+/// Matching follows `check_port_types()`: plain inputs against main-graph
+/// producers, `"branch_id:var"` merge inputs against that branch's producers.
+/// Ports without a unit are not checked.  With `auto_scale`, a consumer whose
+/// unit differs from its producers' only by a factor gets that factor in
+/// `Node::input_scales`, provided all producers of the variable agree.
+pub(crate) fn check_port_units<T>(nodes: &mut [Node<T>], auto_scale: bool) -> Result<(), BuildError> {
+    // Producer units by (branch, variable), in node order
+    let mut unit_outputs: HashMap<_, Vec<(NodeId, String)>> = HashMap::new();
+    for producer in nodes.iter() {
+        for (out_impl, out_var) in &producer.output_mapping {
+            if let Some(unit) = producer.output_units.get(out_impl) {
+                unit_outputs
+                    .entry((producer.branch_id, out_var.clone()))
+                    .or_default()
+                    .push((producer.id, unit.clone()));
+            }
+        }
+    }
+
+    for consumer in nodes.iter_mut() {
+        let mut inputs: Vec<(String, String)> = consumer
+            .input_mapping
+            .iter()
+            .map(|(key, impl_var)| (key.clone(), impl_var.clone()))
+            .collect();
+        inputs.sort();
+        for (broadcast_key, impl_var) in inputs {
+            let Some(expected) = consumer.input_units.get(&impl_var) else {
+                continue;
+            };
+            let (branch, var) = match broadcast_key.split_once(':') {
+                Some((id, var)) => (id.parse::<usize>().ok(), var),
+                None => (None, broadcast_key.as_str()),
+            };
+            let producers = unit_outputs.get(&(branch, var.to_string())).map_or(&[][..], Vec::as_slice);
+            let mut scale: Option<f64> = None;
+            for (producer, produced) in producers {
+                let factor = Unit::parse(produced).factor_to(&Unit::parse(expected));
+                let agrees = factor.is_some_and(|f| (f == 1.0 || auto_scale) && scale.is_none_or(|s| s == f));
+                if !agrees {
+                    return Err(BuildError::UnitMismatch {
+                        broadcast_var: broadcast_key.clone(),
+                        producer: *producer,
+                        produced: produced.clone(),
+                        consumer: consumer.id,
+                        expected: expected.clone(),
+                    });
+                }
+                scale = factor;
+            }
+            if let Some(factor) = scale.filter(|&f| f != 1.0) {
+                consumer.input_scales.insert(impl_var, factor);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefixed_units_convert() {
+        assert_eq!(Unit::parse("MHz").factor_to(&Unit::parse("Hz")), Some(1e6));
+        assert_eq!(Unit::parse("ms").factor_to(&Unit::parse("s")), Some(1e-3));
+        assert_eq!(Unit::parse("h").factor_to(&Unit::parse("min")), Some(60.0));
+        assert_eq!(Unit::parse("km").factor_to(&Unit::parse("m")), Some(1e3));
+    }
+
+    #[test]
+    fn test_units_of_other_dimensions_do_not_convert() {
+        assert_eq!(Unit::parse("Hz").factor_to(&Unit::parse("s")), None);
+        assert_eq!(Unit::parse("m").factor_to(&Unit::parse("min")), None);
+        assert_eq!(Unit::parse("samples").factor_to(&Unit::parse("samples")), Some(1.0));
+        assert_eq!(Unit::parse("ksamples").factor_to(&Unit::parse("samples")), None);
+    }
+}
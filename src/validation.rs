@@ -0,0 +1,449 @@
+//! Build-time checks on the wiring of a DAG
+//!
+//! `Dag::new()` runs these checks and keeps the findings, retrievable with
+//! `Dag::build_warnings()`.  They are warnings rather than errors: a variable that
+//! no node produces may legitimately be supplied from outside (e.g. an input
+//! distribution passed to `Dag::predict()`), but far more often it is a typo in a
+//! broadcast variable name that would otherwise only show up as a missing input
+//! at run time.
+//!
+//! Problems that make a DAG unusable are reported as a `BuildError` by
+//! `Graph::try_build()` instead.
+
+use crate::graph_data::GraphDataKind;
+use crate::node::{Node, NodeId};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+/// A problem found while building a DAG.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildWarning {
+    /// A node consumes a broadcast variable that no node in the DAG produces.
+    UnsatisfiedInput {
+        /// Node with the dangling input
+        node_id: NodeId,
+        /// Display name of that node
+        label: String,
+        /// Broadcast variable the node reads (`"branch_id:var"` for merge inputs)
+        broadcast_var: String,
+        /// Closest produced variable name, if one is similar enough to be a likely typo
+        suggestion: Option<String>,
+    },
+}
+
+/// A problem that prevents a DAG from being built, returned by `Graph::try_build()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// Several nodes write the same variable under `ConflictPolicy::Error`.
+    ConflictingProducers {
+        /// Variable written more than once
+        broadcast_var: String,
+        /// Nodes writing it, sorted by ID
+        producers: Vec<NodeId>,
+    },
+    /// A producer and a consumer of a variable declare incompatible port types.
+    TypeMismatch {
+        /// Variable passed between the two nodes
+        broadcast_var: String,
+        /// Node writing the variable
+        producer: NodeId,
+        /// Type declared for the producer's output port
+        produced: GraphDataKind,
+        /// Node reading the variable
+        consumer: NodeId,
+        /// Type declared for the consumer's input port
+        expected: GraphDataKind,
+    },
+    /// A producer and a consumer of a variable declare units that do not convert
+    /// (different dimensions, or different scales without `Graph::auto_scale_units()`).
+    UnitMismatch {
+        /// Variable passed between the two nodes
+        broadcast_var: String,
+        /// Node writing the variable
+        producer: NodeId,
+        /// Unit declared for the producer's output port
+        produced: String,
+        /// Node reading the variable
+        consumer: NodeId,
+        /// Unit declared for the consumer's input port
+        expected: String,
+    },
+    /// A node reads or writes a variable missing from the schema (`Graph::schema()`).
+    NotInSchema {
+        /// Variable the node reads or writes
+        broadcast_var: String,
+        /// Node reading or writing it
+        node: NodeId,
+    },
+    /// A node declares a port type the schema's kind for its variable contradicts.
+    SchemaMismatch {
+        /// Variable the port reads or writes
+        broadcast_var: String,
+        /// Node owning the port
+        node: NodeId,
+        /// Type declared for the port
+        declared: GraphDataKind,
+        /// Kind of the variable in the schema
+        schema: GraphDataKind,
+    },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::ConflictingProducers {
+                broadcast_var,
+                producers,
+            } => write!(
+                f,
+                "broadcast variable '{}' is produced by nodes {}",
+                broadcast_var,
+                producers
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            BuildError::TypeMismatch {
+                broadcast_var,
+                producer,
+                produced,
+                consumer,
+                expected,
+            } => write!(
+                f,
+                "node {} expects '{}' to be {} but node {} produces {}",
+                consumer, broadcast_var, expected, producer, produced
+            ),
+            BuildError::UnitMismatch {
+                broadcast_var,
+                producer,
+                produced,
+                consumer,
+                expected,
+            } => write!(
+                f,
+                "node {} expects '{}' in {} but node {} produces {}",
+                consumer, broadcast_var, expected, producer, produced
+            ),
+            BuildError::NotInSchema {
+                broadcast_var,
+                node,
+            } => write!(f, "node {} uses '{}', which is not in the schema", node, broadcast_var),
+            BuildError::SchemaMismatch {
+                broadcast_var,
+                node,
+                declared,
+                schema,
+            } => write!(
+                f,
+                "node {} declares '{}' as {} but the schema says {}",
+                node, broadcast_var, declared, schema
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Inputs rejected by `Dag::try_execute_with_inputs()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputError {
+    /// A supplied variable is not among the inputs declared with `Graph::input()`.
+    Undeclared {
+        /// Supplied variable
+        name: String,
+        /// Inputs the graph declares, in declaration order
+        declared: Vec<String>,
+    },
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Undeclared { name, declared } => write!(
+                f,
+                "'{}' is not an input of this graph (declared inputs: {})",
+                name,
+                declared.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// A node that could not run, recorded in `ExecutionResult::errors`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionError {
+    /// A required input (see `Graph::require_input()`) was not in the context.
+    MissingInput {
+        /// Node that was not run
+        node_id: NodeId,
+        /// Display name of that node
+        label: String,
+        /// Broadcast variable the node reads (`"branch_id:var"` for merge inputs)
+        broadcast_var: String,
+        /// Closest produced variable name, if no node produces `broadcast_var`
+        suggestion: Option<String>,
+        /// Nodes producing `broadcast_var` (or `suggestion`), with display names
+        producers: Vec<(NodeId, String)>,
+    },
+    /// A validator (see `Graph::validate()`) rejected an input; the function was not called.
+    InvalidInput {
+        /// Node that was not run
+        node_id: NodeId,
+        /// Display name of that node
+        label: String,
+        /// Input port (impl_var) holding the rejected value
+        port: String,
+        /// The validator's description of the problem
+        message: String,
+    },
+    /// A validator rejected an output; none of the node's outputs were stored.
+    InvalidOutput {
+        /// Node whose outputs were discarded
+        node_id: NodeId,
+        /// Display name of that node
+        label: String,
+        /// Output port (impl_var) holding the rejected value
+        port: String,
+        /// The validator's description of the problem
+        message: String,
+    },
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionError::MissingInput {
+                node_id,
+                label,
+                broadcast_var,
+                suggestion,
+                producers,
+            } => {
+                write!(
+                    f,
+                    "node {} ({}) requires '{}', which is missing",
+                    node_id, label, broadcast_var
+                )?;
+                let producers = producers
+                    .iter()
+                    .map(|(id, label)| format!("node {} ({})", id, label))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                match suggestion {
+                    Some(s) => write!(f, "; did you mean '{}', produced by {}?", s, producers),
+                    None if producers.is_empty() => write!(f, "; no node produces it"),
+                    None => write!(f, "; produced by {}, which did not write it", producers),
+                }
+            }
+            ExecutionError::InvalidInput {
+                node_id,
+                label,
+                port,
+                message,
+            } => write!(f, "node {} ({}) rejected input '{}': {}", node_id, label, port, message),
+            ExecutionError::InvalidOutput {
+                node_id,
+                label,
+                port,
+                message,
+            } => write!(f, "node {} ({}) produced an invalid '{}': {}", node_id, label, port, message),
+        }
+    }
+}
+
+impl std::error::Error for ExecutionError {}
+
+impl fmt::Display for BuildWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildWarning::UnsatisfiedInput {
+                node_id,
+                label,
+                broadcast_var,
+                suggestion,
+            } => {
+                write!(
+                    f,
+                    "node {} ({}) reads '{}', which no node produces",
+                    node_id, label, broadcast_var
+                )?;
+                if let Some(s) = suggestion {
+                    write!(f, " (did you mean '{}'?)", s)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Find inputs that no node produces.
+///
+/// Inputs whose impl name is covered by a variant parameter or a config value are
+/// not reported, since the node still receives a value for them.
+pub(crate) fn unsatisfied_inputs<T>(nodes: &[Node<T>]) -> Vec<BuildWarning> {
+    let mut produced: HashSet<String> = HashSet::new();
+    for node in nodes {
+        for var in node.output_mapping.values() {
+            produced.insert(var.clone());
+            produced.extend(node.variant_key(var));
+            if let Some(branch_id) = node.branch_id {
+                produced.insert(format!("{}:{}", branch_id, var));
+            }
+        }
+    }
+
+    // Many nodes may read the same missing variable; look for a suggestion once
+    let mut suggestions: HashMap<&str, Option<String>> = HashMap::new();
+    let mut warnings = Vec::new();
+    for node in nodes {
+        let mut inputs: Vec<(&String, &String)> = node.input_mapping.iter().collect();
+        inputs.sort();
+        for (broadcast_var, impl_var) in inputs {
+            if produced.contains(broadcast_var)
+                || node.variant_params.contains_key(impl_var)
+                || node.config.contains_key(impl_var)
+            {
+                continue;
+            }
+            warnings.push(BuildWarning::UnsatisfiedInput {
+                node_id: node.id,
+                label: node.display_name(),
+                broadcast_var: broadcast_var.clone(),
+                suggestion: suggestions
+                    .entry(broadcast_var)
+                    .or_insert_with(|| closest_name(broadcast_var, &produced))
+                    .clone(),
+            });
+        }
+    }
+    warnings.sort_by_key(|w| match w {
+        BuildWarning::UnsatisfiedInput { node_id, .. } => *node_id,
+    });
+    warnings
+}
+
+/// Closest candidate within an edit distance of 2 (ties broken alphabetically).
+pub(crate) fn closest_name(name: &str, candidates: &HashSet<String>) -> Option<String> {
+    candidates
+        .iter()
+        .map(|c| (edit_distance(name, c), c))
+        .filter(|(d, _)| *d <= 2)
+        .min()
+        .map(|(_, c)| c.clone())
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Check the variables every node reads and writes against a schema, then give
+/// each port of a schema variable that declares no type the schema's kind.
+///
+/// Private variables of explicit connections (`__edge_*`, `__via_*`) are not
+/// checked; merge inputs are checked by their variable name.
+pub(crate) fn apply_schema<T>(
+    nodes: &mut [Node<T>],
+    schema: &BTreeMap<String, GraphDataKind>,
+) -> Result<(), BuildError> {
+    for node in nodes {
+        let mut inputs: Vec<(String, String)> = node
+            .input_mapping
+            .iter()
+            .map(|(key, impl_var)| (key.clone(), impl_var.clone()))
+            .collect();
+        inputs.sort();
+        let mut outputs: Vec<(String, String)> = node
+            .all_outputs()
+            .map(|(impl_var, var)| (var.clone(), impl_var.clone()))
+            .collect();
+        outputs.sort();
+        for (output, ports) in [(false, inputs), (true, outputs)] {
+            for (broadcast_key, impl_var) in ports {
+                let var = broadcast_key.split_once(':').map_or(broadcast_key.as_str(), |(_, var)| var);
+                if var.starts_with("__") {
+                    continue;
+                }
+                let Some(&kind) = schema.get(var) else {
+                    return Err(BuildError::NotInSchema {
+                        broadcast_var: var.to_string(),
+                        node: node.id,
+                    });
+                };
+                let types = if output { &mut node.output_types } else { &mut node.input_types };
+                let declared = *types.entry(impl_var).or_insert(kind);
+                let agrees = if output { kind.accepts(declared) } else { declared.accepts(kind) };
+                if !agrees {
+                    return Err(BuildError::SchemaMismatch {
+                        broadcast_var: var.to_string(),
+                        node: node.id,
+                        declared,
+                        schema: kind,
+                    });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check declared port types between every producer and consumer of a variable.
+///
+/// Plain inputs are matched against main-graph producers and `"branch_id:var"`
+/// merge inputs against the producers of that branch, which is what the node
+/// reads at run time.
+pub(crate) fn check_port_types<T>(nodes: &[Node<T>]) -> Result<(), BuildError> {
+    // Typed outputs by (branch, variable), in node order
+    let mut typed_outputs: HashMap<_, Vec<(NodeId, GraphDataKind)>> = HashMap::new();
+    for producer in nodes {
+        for (out_impl, out_var) in &producer.output_mapping {
+            if let Some(&produced) = producer.output_types.get(out_impl) {
+                typed_outputs
+                    .entry((producer.branch_id, out_var.as_str()))
+                    .or_default()
+                    .push((producer.id, produced));
+            }
+        }
+    }
+
+    for consumer in nodes {
+        let mut inputs: Vec<(&String, &String)> = consumer.input_mapping.iter().collect();
+        inputs.sort();
+        for (broadcast_key, impl_var) in inputs {
+            let Some(&expected) = consumer.input_types.get(impl_var) else {
+                continue;
+            };
+            let (branch, var) = match broadcast_key.split_once(':') {
+                Some((id, var)) => (id.parse::<usize>().ok(), var),
+                None => (None, broadcast_key.as_str()),
+            };
+            let producers = typed_outputs.get(&(branch, var)).map_or(&[][..], Vec::as_slice);
+            let mismatch = producers.iter().find(|(_, produced)| !expected.accepts(*produced));
+            if let Some(&(producer, produced)) = mismatch {
+                return Err(BuildError::TypeMismatch {
+                    broadcast_var: broadcast_key.clone(),
+                    producer,
+                    produced,
+                    consumer: consumer.id,
+                    expected,
+                });
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,180 @@
+//! `Watchdog` — flags nodes that run much longer than they usually do
+//!
+//! The watchdog is a `Middleware`: it records when each node starts and how long
+//! it took, keeping a running average per node.  A background thread checks the
+//! running nodes periodically and reports each one that has been running for more
+//! than a multiple of its average, once per execution of the node:
+//!
+//! ```ignore
+//! let mut dag = graph.build();
+//! dag.add_middleware(Watchdog::new(5.0, Duration::from_millis(250), |hung| {
+//!     eprintln!(
+//!         "{} on {} running for {:?} (usually {:?})",
+//!         hung.label, hung.thread, hung.running_for, hung.average
+//!     );
+//! }));
+//! ```
+//!
+//! Nodes are only judged once they have a history, i.e. from their second run on.
+//! The report names the worker thread running the node so a debugger can be
+//! attached to it; the standard library cannot capture the stack of another
+//! thread, so no backtrace is included.
+
+use crate::middleware::Middleware;
+use crate::node::{Node, NodeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+/// A node the watchdog found running for too long
+#[derive(Debug, Clone)]
+pub struct HungNode {
+    /// Node that is still running
+    pub node_id: NodeId,
+    /// Display name of that node
+    pub label: String,
+    /// Name of the thread running it (its `ThreadId` if unnamed)
+    pub thread: String,
+    /// Time since the node started
+    pub running_for: Duration,
+    /// Average duration of its earlier runs
+    pub average: Duration,
+}
+
+/// Callback receiving hung nodes
+type OnHung = Box<dyn Fn(&HungNode) + Send + Sync>;
+
+/// A node that started and has not finished
+struct Running {
+    node_id: NodeId,
+    label: String,
+    thread: String,
+    started: Instant,
+    /// Whether it was already reported
+    reported: bool,
+}
+
+/// Everything the middleware hooks and the checking thread share
+struct State {
+    multiple: f64,
+    on_hung: OnHung,
+    /// Nodes currently running, per thread (a stack: a node may run a nested DAG)
+    running: Mutex<HashMap<ThreadId, Vec<Running>>>,
+    /// Number of finished runs and their average duration, per node
+    history: Mutex<HashMap<NodeId, (u32, Duration)>>,
+}
+
+impl State {
+    /// Remove the innermost node running on this thread, returning its start
+    fn finish(&self) -> Option<Instant> {
+        let id = thread::current().id();
+        let mut running = self.running.lock().unwrap();
+        let stack = running.get_mut(&id)?;
+        let started = stack.pop().map(|r| r.started);
+        if stack.is_empty() {
+            running.remove(&id);
+        }
+        started
+    }
+
+    /// Report running nodes that exceed `multiple` times their average
+    fn check(&self) {
+        let history = self.history.lock().unwrap().clone();
+        let mut hung = Vec::new();
+        for running in self.running.lock().unwrap().values_mut().flatten() {
+            let Some(&(_, average)) = history.get(&running.node_id) else {
+                continue;
+            };
+            let running_for = running.started.elapsed();
+            if !running.reported && running_for.as_secs_f64() > average.as_secs_f64() * self.multiple {
+                running.reported = true;
+                hung.push(HungNode {
+                    node_id: running.node_id,
+                    label: running.label.clone(),
+                    thread: running.thread.clone(),
+                    running_for,
+                    average,
+                });
+            }
+        }
+        // Outside the locks, so the callback may take its time
+        for node in &hung {
+            (self.on_hung)(node);
+        }
+    }
+}
+
+/// Middleware reporting nodes that run longer than `multiple` times their
+/// historical average (see the module documentation)
+///
+/// Add it to a DAG with `Dag::add_middleware()`.  The checking thread stops once
+/// the watchdog is dropped.
+pub struct Watchdog {
+    state: Arc<State>,
+}
+
+impl Watchdog {
+    /// Start a watchdog checking the running nodes every `interval`
+    ///
+    /// `on_hung` is called from the watchdog's thread.
+    pub fn new<F>(multiple: f64, interval: Duration, on_hung: F) -> Self
+    where
+        F: Fn(&HungNode) + Send + Sync + 'static,
+    {
+        let state = Arc::new(State {
+            multiple,
+            on_hung: Box::new(on_hung),
+            running: Mutex::new(HashMap::new()),
+            history: Mutex::new(HashMap::new()),
+        });
+        let weak: Weak<State> = Arc::downgrade(&state);
+        thread::Builder::new()
+            .name("dagex-watchdog".to_string())
+            .spawn(move || loop {
+                thread::sleep(interval);
+                match weak.upgrade() {
+                    Some(state) => state.check(),
+                    None => break,
+                }
+            })
+            .expect("failed to spawn the watchdog thread");
+        Self { state }
+    }
+
+    /// Average duration of the finished runs of a node, if it ran while watched
+    pub fn average(&self, node_id: NodeId) -> Option<Duration> {
+        self.state.history.lock().unwrap().get(&node_id).map(|&(_, average)| average)
+    }
+}
+
+impl<T> Middleware<T> for Watchdog {
+    fn before(&self, node: &Node<T>, _: &mut HashMap<String, Arc<T>>) {
+        let current = thread::current();
+        let thread = current
+            .name()
+            .map_or_else(|| format!("{:?}", current.id()), str::to_string);
+        self.state.running.lock().unwrap().entry(current.id()).or_default().push(Running {
+            node_id: node.id,
+            label: node.display_name(),
+            thread,
+            started: Instant::now(),
+            reported: false,
+        });
+    }
+
+    fn after(&self, node: &Node<T>, _: &mut HashMap<String, Arc<T>>) {
+        if let Some(started) = self.state.finish() {
+            let duration = started.elapsed();
+            let mut history = self.state.history.lock().unwrap();
+            let (count, average) = history.entry(node.id).or_insert((0, Duration::ZERO));
+            *count += 1;
+            *average = average.mul_f64(f64::from(*count - 1) / f64::from(*count)) + duration / *count;
+        }
+    }
+
+    /// A panicking node stops running without adding to its history
+    fn unwound(&self, _: &Node<T>) {
+        self.state.finish();
+    }
+}
@@ -0,0 +1,68 @@
+//! `Workers` — the threads the parallel executor runs chains on
+//!
+//! By default every parallel stage spawns scoped threads, one per chain.  An
+//! application that already sizes a thread pool for its machine can hand that
+//! pool to the `Dag` instead, so graph execution shares its threads rather than
+//! oversubscribing the CPU:
+//!
+//! ```ignore
+//! let pool = Arc::new(rayon::ThreadPoolBuilder::new().num_threads(8).build()?);
+//! let mut dag = graph.build();
+//! dag.set_workers(Workers::Rayon(pool));
+//! let context = dag.execute(true, None);
+//! ```
+//!
+//! With the `tokio` feature, `Dag::spawn_on()` runs a whole execution on the
+//! blocking pool of an existing runtime, so async code can await it.
+
+use std::panic;
+#[cfg(feature = "rayon")]
+use std::sync::Arc;
+
+/// Where the chains of a parallel stage run (see `Dag::set_workers()`)
+#[derive(Debug, Clone, Default)]
+pub enum Workers {
+    /// Scoped threads spawned for each stage, one per chain
+    #[default]
+    Scoped,
+    /// Tasks on an existing rayon pool
+    ///
+    /// Executions started from inside the pool (within `ThreadPool::install()`)
+    /// take part in its work stealing instead of blocking a worker.
+    #[cfg(feature = "rayon")]
+    Rayon(Arc<rayon::ThreadPool>),
+}
+
+impl Workers {
+    /// Run every job concurrently and return their results in order
+    ///
+    /// A panic in a job is resumed on the calling thread once all jobs are done.
+    pub(crate) fn run_all<R, F>(&self, jobs: Vec<F>) -> Vec<R>
+    where
+        R: Send,
+        F: FnOnce() -> R + Send,
+    {
+        match self {
+            Workers::Scoped => std::thread::scope(|s| {
+                let handles: Vec<_> = jobs.into_iter().map(|job| s.spawn(job)).collect();
+                handles
+                    .into_iter()
+                    .map(|h| h.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+                    .collect()
+            }),
+            #[cfg(feature = "rayon")]
+            Workers::Rayon(pool) => {
+                let mut results: Vec<Option<R>> = jobs.iter().map(|_| None).collect();
+                pool.scope(|s| {
+                    for (slot, job) in results.iter_mut().zip(jobs) {
+                        s.spawn(move |_| *slot = Some(job()));
+                    }
+                });
+                results
+                    .into_iter()
+                    .map(|result| result.expect("rayon scope returned before a job finished"))
+                    .collect()
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+#![cfg(feature = "radar_examples")]
+
+use dagex::dsp::{self, FftDirection, Window};
+use dagex::{Graph, GraphData};
+use ndarray::Array2;
+use num_complex::Complex;
+use std::f64::consts::PI;
+
+/// Barker code of length 13, the reference pulse of these tests
+const BARKER_13: [f64; 13] = [1.0, 1.0, 1.0, 1.0, 1.0, -1.0, -1.0, 1.0, 1.0, -1.0, 1.0, -1.0, 1.0];
+
+/// Index of the largest magnitude
+fn peak(values: impl Iterator<Item = f64>) -> usize {
+    values.enumerate().max_by(|a, b| a.1.total_cmp(&b.1)).unwrap().0
+}
+
+#[test]
+fn test_fft_round_trip_and_impulse() {
+    let mut graph = Graph::new();
+    graph.constant("impulse", GraphData::float_vec(vec![1.0, 0.0, 0.0, 0.0]));
+    graph.add_shared(dsp::lfm_chirp(64, 10.0, 40.0), Some("Chirp"), None, Some(vec![("result", "pulse")]));
+    graph.add_shared(dsp::fft(), Some("Flat"), Some(vec![("impulse", "x")]), Some(vec![("result", "flat")]));
+    graph.add_shared(dsp::fft(), Some("FFT"), Some(vec![("pulse", "x")]), Some(vec![("result", "spectrum")]));
+    graph.add_shared(dsp::ifft(), Some("IFFT"), Some(vec![("spectrum", "x")]), Some(vec![("result", "back")]));
+    let context = graph.build().execute(false, None);
+
+    let flat = context.get("flat").unwrap().as_complex_slice().unwrap();
+    assert!(flat.iter().all(|x| (x - Complex::new(1.0, 0.0)).norm() < 1e-12));
+    let pulse = context.get("pulse").unwrap().as_complex_slice().unwrap();
+    let back = context.get("back").unwrap().as_complex_slice().unwrap();
+    assert_eq!(pulse.len(), 64);
+    assert!(pulse.iter().all(|x| (x.norm() - 1.0).abs() < 1e-12));
+    assert!(pulse.iter().zip(back).all(|(x, y)| (x - y).norm() < 1e-9));
+}
+
+#[test]
+fn test_fft_node_pads_to_its_size_across_executions() {
+    let samples = vec![1.0, -2.0, 0.5, 3.0, 0.0];
+    let mut padded = samples.clone();
+    padded.resize(8, 0.0);
+    let mut graph = Graph::new();
+    graph.constant("short", GraphData::float_vec(samples));
+    graph.constant("padded", GraphData::float_vec(padded));
+    graph.add_shared(
+        dsp::fft_node(8, FftDirection::Forward),
+        Some("Planned"),
+        Some(vec![("short", "x")]),
+        Some(vec![("result", "spectrum")]),
+    );
+    graph.add_shared(dsp::fft(), Some("Unplanned"), Some(vec![("padded", "x")]), Some(vec![("result", "expected")]));
+    graph.add_shared(
+        dsp::fft_node(4, FftDirection::Inverse),
+        Some("Truncated"),
+        Some(vec![("spectrum", "x")]),
+        Some(vec![("result", "back")]),
+    );
+    let dag = graph.build();
+
+    for _ in 0..3 {
+        let context = dag.execute(false, None);
+        let spectrum = context.get("spectrum").unwrap().as_complex_slice().unwrap();
+        let expected = context.get("expected").unwrap().as_complex_slice().unwrap();
+        assert_eq!(spectrum.len(), 8);
+        assert!(spectrum.iter().zip(expected).all(|(x, y)| (x - y).norm() < 1e-12));
+        assert_eq!(context.get("back").unwrap().as_complex_slice().unwrap().len(), 4);
+    }
+}
+
+#[test]
+fn test_matched_filter_finds_the_delay() {
+    let mut signal = vec![0.0; 64];
+    signal[20..33].copy_from_slice(&BARKER_13);
+    let mut graph = Graph::new();
+    graph.constant("rx", GraphData::float_vec(signal));
+    graph.constant("code", GraphData::float_vec(BARKER_13.to_vec()));
+    graph.constant("ones", GraphData::float_vec(vec![1.0; 5]));
+    graph.add_shared(
+        dsp::matched_filter(),
+        Some("Match"),
+        Some(vec![("rx", "signal"), ("code", "reference")]),
+        Some(vec![("result", "compressed")]),
+    );
+    graph.add_shared(dsp::window(Window::Hann), Some("Taper"), Some(vec![("ones", "x")]), Some(vec![("result", "hann")]));
+    graph.add_shared(
+        dsp::magnitude_db(),
+        Some("Bad"),
+        Some(vec![("code", "y")]),
+        Some(vec![("result", "nonsense"), ("error", "error")]),
+    );
+    let context = graph.build().execute(false, None);
+
+    let compressed = context.get("compressed").unwrap().as_complex_slice().unwrap();
+    assert_eq!(compressed.len(), 64);
+    assert_eq!(peak(compressed.iter().map(|x| x.norm())), 20);
+    assert!((compressed[20].re - 13.0).abs() < 1e-9);
+    let hann: Vec<f64> = context.get("hann").unwrap().as_complex_slice().unwrap().iter().map(|x| x.re).collect();
+    assert!(hann.iter().zip([0.0, 0.5, 1.0, 0.5, 0.0]).all(|(x, y)| (x - y).abs() < 1e-12));
+    assert_eq!(context.get_str("error"), Some("missing input 'x'"));
+}
+
+#[test]
+fn test_range_doppler_map_locates_a_moving_target() {
+    // 16 pulses of 48 samples; the echo starts at sample 10 and turns by a
+    // quarter cycle per pulse (Doppler bin 4, row 12 once centered)
+    let (count, length, delay) = (16, 48, 10);
+    let echoes = Array2::from_shape_fn((count, length), |(p, n)| match n.checked_sub(delay) {
+        Some(i) if i < BARKER_13.len() => Complex::from_polar(BARKER_13[i], 2.0 * PI * p as f64 / 4.0),
+        _ => Complex::new(0.0, 0.0),
+    });
+    let mut graph = Graph::new();
+    graph.constant("echoes", GraphData::complex_array_nd(echoes.into_dyn()));
+    graph.constant("code", GraphData::float_vec(BARKER_13.to_vec()));
+    graph.add_shared(
+        dsp::range_doppler(Window::Hamming),
+        Some("RangeDoppler"),
+        Some(vec![("echoes", "pulses"), ("code", "reference")]),
+        Some(vec![("result", "rd_map")]),
+    );
+    graph.add_shared(dsp::magnitude_db(), Some("dB"), Some(vec![("rd_map", "x")]), Some(vec![("result", "rd_db")]));
+    let context = graph.build().execute(false, None);
+
+    let map = context.get("rd_map").unwrap();
+    assert_eq!(map.shape(), Some(&[count, length][..]));
+    let db = context.get("rd_db").unwrap().as_array().unwrap();
+    assert_eq!(db.shape(), &[count, length]);
+    let index = peak(db.iter().copied());
+    assert_eq!((index / length, index % length), (12, delay));
+}
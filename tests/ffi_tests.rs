@@ -0,0 +1,165 @@
+#![cfg(feature = "ffi")]
+
+use dagex::ffi::*;
+use std::ffi::{c_char, c_int, c_void, CStr};
+use std::ptr;
+
+unsafe extern "C" fn source(_: *const DagexMap, out: *mut DagexMap, _: *mut c_void) -> c_int {
+    dagex_map_set_int(out, c"value".as_ptr(), 50)
+}
+
+unsafe extern "C" fn add_offset(inputs: *const DagexMap, out: *mut DagexMap, offset: *mut c_void) -> c_int {
+    let mut x = 0;
+    if dagex_map_get_int(inputs, c"x".as_ptr(), &mut x) == 0 {
+        return 1;
+    }
+    dagex_map_set_int(out, c"result".as_ptr(), x + *(offset as *const i64))
+}
+
+unsafe extern "C" fn combine(inputs: *const DagexMap, out: *mut DagexMap, _: *mut c_void) -> c_int {
+    let (mut a, mut b) = (0, 0);
+    if dagex_map_get_int(inputs, c"from_a".as_ptr(), &mut a) == 0
+        || dagex_map_get_int(inputs, c"from_b".as_ptr(), &mut b) == 0
+    {
+        return 1;
+    }
+    dagex_map_set_int(out, c"combined".as_ptr(), a + b)
+}
+
+unsafe extern "C" fn fail(_: *const DagexMap, out: *mut DagexMap, _: *mut c_void) -> c_int {
+    dagex_map_set_int(out, c"value".as_ptr(), 1);
+    -1
+}
+
+unsafe extern "C" fn scale_array(inputs: *const DagexMap, out: *mut DagexMap, _: *mut c_void) -> c_int {
+    let mut len = 0;
+    let data = dagex_map_get_float_array(inputs, c"samples".as_ptr(), &mut len);
+    if data.is_null() {
+        return 1;
+    }
+    let scaled: Vec<f64> = std::slice::from_raw_parts(data, len).iter().map(|x| x * 2.0).collect();
+    dagex_map_set_float_array(out, c"scaled".as_ptr(), scaled.as_ptr(), scaled.len())
+}
+
+fn pairs(list: &[&'static CStr]) -> Vec<*const c_char> {
+    list.iter().map(|s| s.as_ptr()).chain([ptr::null()]).collect()
+}
+
+#[test]
+fn test_ffi_branch_and_merge() {
+    static TEN: i64 = 10;
+    static TWENTY: i64 = 20;
+
+    unsafe {
+        let graph = dagex_graph_new();
+        let source_out = pairs(&[c"value", c"x"]);
+        assert_eq!(
+            dagex_graph_add(graph, Some(source), ptr::null_mut(), c"Source".as_ptr(), ptr::null(), source_out.as_ptr()),
+            0
+        );
+
+        let path_in = pairs(&[c"x", c"x"]);
+        let path_out = pairs(&[c"result", c"result"]);
+        let mut branch_ids = Vec::new();
+        for offset in [&TEN, &TWENTY] {
+            let branch = dagex_graph_new();
+            let user_data = offset as *const i64 as *mut c_void;
+            dagex_graph_add(branch, Some(add_offset), user_data, ptr::null(), path_in.as_ptr(), path_out.as_ptr());
+            branch_ids.push(dagex_graph_branch(graph, branch));
+        }
+        assert!(branch_ids.iter().all(|&id| id != 0));
+
+        let merge_in = pairs(&[c"result", c"from_a", c"result", c"from_b"]);
+        let merge_out = pairs(&[c"combined", c"final"]);
+        assert_eq!(
+            dagex_graph_merge(
+                graph,
+                Some(combine),
+                ptr::null_mut(),
+                c"Merge".as_ptr(),
+                branch_ids.as_ptr(),
+                merge_in.as_ptr(),
+                merge_out.as_ptr(),
+            ),
+            0
+        );
+
+        let dag = dagex_graph_build(graph);
+        assert!(!dag.is_null());
+
+        let mermaid = dagex_dag_to_mermaid(dag);
+        assert!(CStr::from_ptr(mermaid).to_str().unwrap().contains("Merge"));
+        dagex_string_free(mermaid);
+
+        for parallel in [0, 1] {
+            let context = dagex_dag_execute(dag, ptr::null(), parallel, 0);
+            assert!(!context.is_null());
+            let mut value = 0;
+            assert_eq!(dagex_map_get_int(context, c"final".as_ptr(), &mut value), 1);
+            assert_eq!(value, 130);
+            dagex_map_free(context);
+        }
+        dagex_dag_free(dag);
+    }
+}
+
+#[test]
+fn test_ffi_inputs_and_arrays() {
+    unsafe {
+        let graph = dagex_graph_new();
+        let inputs = pairs(&[c"samples", c"samples"]);
+        let outputs = pairs(&[c"scaled", c"out"]);
+        dagex_graph_add(graph, Some(scale_array), ptr::null_mut(), c"Scale".as_ptr(), inputs.as_ptr(), outputs.as_ptr());
+        let dag = dagex_graph_build(graph);
+
+        let seed = dagex_map_new();
+        let samples = [1.0, 2.5, -3.0];
+        assert_eq!(dagex_map_set_float_array(seed, c"samples".as_ptr(), samples.as_ptr(), samples.len()), 0);
+        assert_eq!(dagex_map_set_string(seed, c"name".as_ptr(), c"run-1".as_ptr()), 0);
+
+        let context = dagex_dag_execute(dag, seed, 0, 0);
+        let mut len = 0;
+        let out = dagex_map_get_float_array(context, c"out".as_ptr(), &mut len);
+        assert_eq!(std::slice::from_raw_parts(out, len), &[2.0, 5.0, -6.0]);
+
+        // Seed values stay in the context
+        let name = dagex_map_get_string(context, c"name".as_ptr(), &mut len);
+        assert_eq!(std::slice::from_raw_parts(name as *const u8, len), b"run-1");
+        assert!(dagex_map_get_int_array(context, c"out".as_ptr(), &mut len).is_null());
+
+        dagex_map_free(context);
+        dagex_map_free(seed);
+        dagex_dag_free(dag);
+    }
+}
+
+#[test]
+fn test_ffi_failed_callback_discards_outputs() {
+    unsafe {
+        let graph = dagex_graph_new();
+        let outputs = pairs(&[c"value", c"value"]);
+        dagex_graph_add(graph, Some(fail), ptr::null_mut(), c"Fail".as_ptr(), ptr::null(), outputs.as_ptr());
+        let dag = dagex_graph_build(graph);
+
+        let context = dagex_dag_execute(dag, ptr::null(), 0, 0);
+        assert!(!context.is_null());
+        assert_eq!(dagex_map_contains(context, c"value".as_ptr()), 0);
+
+        dagex_map_free(context);
+        dagex_dag_free(dag);
+    }
+}
+
+#[test]
+fn test_ffi_rejects_invalid_arguments() {
+    unsafe {
+        let graph = dagex_graph_new();
+        let odd = pairs(&[c"only_one"]);
+        assert_eq!(dagex_graph_add(graph, None, ptr::null_mut(), ptr::null(), ptr::null(), ptr::null()), -1);
+        assert_eq!(dagex_graph_add(graph, Some(source), ptr::null_mut(), ptr::null(), odd.as_ptr(), ptr::null()), -1);
+        assert_eq!(dagex_graph_branch(graph, ptr::null_mut()), 0);
+        assert_eq!(dagex_map_set_int(ptr::null_mut(), c"x".as_ptr(), 1), -1);
+        assert!(dagex_graph_build(ptr::null_mut()).is_null());
+        dagex_graph_free(graph);
+    }
+}
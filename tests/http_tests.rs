@@ -0,0 +1,113 @@
+#![cfg(feature = "http")]
+
+use dagex::{http_get, http_post, Graph, GraphData};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+
+/// Serve `count` requests, answering each with its method, path, `x-token`
+/// header, and body
+fn echo_server(count: usize) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming().take(count) {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let (mut length, mut token) = (0, String::new());
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                let (name, value) = line.split_once(": ").unwrap();
+                match name.to_ascii_lowercase().as_str() {
+                    "content-length" => length = value.parse().unwrap(),
+                    "x-token" => token = value.to_string(),
+                    _ => {}
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let mut parts = request_line.split_whitespace();
+            let reply = format!(
+                "{} {} {} {}",
+                parts.next().unwrap(),
+                parts.next().unwrap(),
+                token,
+                String::from_utf8(body).unwrap()
+            );
+            let status = if reply.contains("/missing") { "404 Not Found" } else { "200 OK" };
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\ncontent-length: {}\r\nx-served-by: echo\r\nconnection: close\r\n\r\n{}",
+                status,
+                reply.len(),
+                reply
+            )
+            .unwrap();
+        }
+    });
+    url
+}
+
+#[test]
+fn test_http_nodes_map_context_to_requests() {
+    let url = echo_server(3);
+    let mut graph = Graph::new();
+    graph.constant("endpoint", GraphData::string(format!("{}/items", url)));
+    graph.constant("missing", GraphData::string(format!("{}/missing", url)));
+    graph.constant(
+        "auth",
+        GraphData::map(HashMap::from([("x-token".to_string(), GraphData::string("secret"))])),
+    );
+    graph.constant("payload", GraphData::string("hello"));
+    graph.add(
+        http_get(),
+        Some("Get"),
+        Some(vec![("endpoint", "url"), ("auth", "headers")]),
+        Some(vec![("status", "get_status"), ("body", "get_body"), ("headers", "get_headers")]),
+    );
+    graph.add(
+        http_post(),
+        Some("Post"),
+        Some(vec![("endpoint", "url"), ("payload", "body")]),
+        Some(vec![("status", "post_status"), ("body", "post_body")]),
+    );
+    graph.add(
+        http_get(),
+        Some("Missing"),
+        Some(vec![("missing", "url")]),
+        Some(vec![("status", "missing_status")]),
+    );
+    let context = graph.build().execute(false, None);
+
+    assert_eq!(context.get_int("get_status"), Some(200));
+    assert_eq!(context.get_str("get_body"), Some("GET /items secret "));
+    let headers = context.get("get_headers").unwrap().as_map().unwrap();
+    assert_eq!(headers["x-served-by"].as_string(), Some("echo"));
+    assert_eq!(context.get_int("post_status"), Some(200));
+    assert_eq!(context.get_str("post_body"), Some("POST /items  hello"));
+    assert_eq!(context.get_int("missing_status"), Some(404));
+}
+
+#[test]
+fn test_failed_request_reports_an_error_instead_of_outputs() {
+    // Nothing listens on a port that was just released
+    let url = format!("http://{}", TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap());
+    let mut graph = Graph::new();
+    graph.constant("endpoint", GraphData::string(url));
+    graph.add(
+        http_get(),
+        Some("Get"),
+        Some(vec![("endpoint", "url")]),
+        Some(vec![("status", "status"), ("error", "error")]),
+    );
+    let context = graph.build().execute(false, None);
+    assert!(!context.contains_key("status"));
+    assert!(!context.get_str("error").unwrap().is_empty());
+}
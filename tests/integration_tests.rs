@@ -1,12 +1,24 @@
 //! Integration tests for graph-sp
 
-use dagex::{Dag, Distribution, Graph, GraphData, PredictTarget};
+use dagex::{
+    AdaptiveSweep, BuildError, BuildWarning, CancelToken, ConflictPolicy, ContextError, Dag, Distribution,
+    ExecutionContext, ExecutionError, ExecutionPlan, ExecutionPoll, ExecutorPool, FileStorage, Graph,
+    GraphData, GraphDataKind, HungNode, InputError, IntoVariantValues, JobStatus, Linspace, Logspace,
+    MemoryStorage, Middleware, Node, Optimizer, OptimizerConfig, OptimizerPass, ParamGrid, PassSummary,
+    Payload, PredictTarget, StorageBackend, SweepStep, Symbol, Watchdog, read_csv, read_floats, std_nodes,
+    write_csv, write_floats,
+};
+#[cfg(feature = "json")]
+use dagex::{read_json, write_json};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 // Helper functions for tests
 
 fn data_source(
-    _: &HashMap<String, GraphData>,
+    _: &HashMap<String, Arc<GraphData>>,
 ) -> HashMap<String, GraphData> {
     let mut result = HashMap::new();
     result.insert("raw_data".to_string(), GraphData::int(100));
@@ -14,7 +26,7 @@ fn data_source(
 }
 
 fn processor(
-    inputs: &HashMap<String, GraphData>,
+    inputs: &HashMap<String, Arc<GraphData>>,
 ) -> HashMap<String, GraphData> {
     let mut result = HashMap::new();
     if let Some(data) = inputs.get("input_data") {
@@ -26,7 +38,7 @@ fn processor(
 }
 
 fn adder(
-    inputs: &HashMap<String, GraphData>,
+    inputs: &HashMap<String, Arc<GraphData>>,
 ) -> HashMap<String, GraphData> {
     let mut result = HashMap::new();
     if let Some(val) = inputs.get("input") {
@@ -58,8 +70,8 @@ fn test_simple_pipeline() {
     let dag = graph.build();
     let context = dag.execute(false, None);
 
-    assert_eq!(context.get("data").and_then(|d: &GraphData| d.as_int()), Some(100));
-    assert_eq!(context.get("result").and_then(|d: &GraphData| d.as_int()), Some(200));
+    assert_eq!(context.get("data").and_then(|d| d.as_int()), Some(100));
+    assert_eq!(context.get("result").and_then(|d| d.as_int()), Some(200));
 }
 
 #[test]
@@ -77,9 +89,9 @@ fn test_branching() {
     // Branch A
     let mut branch_a = Graph::new();
     branch_a.add(
-        |inputs: &HashMap<String, GraphData>| {
+        |inputs: &HashMap<String, Arc<GraphData>>| {
             let mut result = HashMap::new();
-            if let Some(val) = inputs.get("x").and_then(|d: &GraphData| d.as_int()) {
+            if let Some(val) = inputs.get("x").and_then(|d| d.as_int()) {
                 result.insert("output".to_string(), GraphData::int(val * 2));
             }
             result
@@ -92,9 +104,9 @@ fn test_branching() {
     // Branch B
     let mut branch_b = Graph::new();
     branch_b.add(
-        |inputs: &HashMap<String, GraphData>| {
+        |inputs: &HashMap<String, Arc<GraphData>>| {
             let mut result = HashMap::new();
-            if let Some(val) = inputs.get("x").and_then(|d: &GraphData| d.as_int()) {
+            if let Some(val) = inputs.get("x").and_then(|d| d.as_int()) {
                 result.insert("output".to_string(), GraphData::int(val * 3));
             }
             result
@@ -110,9 +122,9 @@ fn test_branching() {
     let dag = graph.build();
     let context = dag.execute(false, None);
 
-    assert_eq!(context.get("data").and_then(|d: &GraphData| d.as_int()), Some(100));
-    assert_eq!(context.get("result_a").and_then(|d: &GraphData| d.as_int()), Some(200));
-    assert_eq!(context.get("result_b").and_then(|d: &GraphData| d.as_int()), Some(300));
+    assert_eq!(context.get("data").and_then(|d| d.as_int()), Some(100));
+    assert_eq!(context.get("result_a").and_then(|d| d.as_int()), Some(200));
+    assert_eq!(context.get("result_b").and_then(|d| d.as_int()), Some(300));
 }
 
 #[test]
@@ -130,9 +142,9 @@ fn test_merge() {
     // Branch A
     let mut branch_a = Graph::new();
     branch_a.add(
-        |inputs: &HashMap<String, GraphData>| {
+        |inputs: &HashMap<String, Arc<GraphData>>| {
             let mut result = HashMap::new();
-            if let Some(val) = inputs.get("x").and_then(|d: &GraphData| d.as_int()) {
+            if let Some(val) = inputs.get("x").and_then(|d| d.as_int()) {
                 result.insert("output".to_string(), GraphData::int(val + 10));
             }
             result
@@ -145,9 +157,9 @@ fn test_merge() {
     // Branch B
     let mut branch_b = Graph::new();
     branch_b.add(
-        |inputs: &HashMap<String, GraphData>| {
+        |inputs: &HashMap<String, Arc<GraphData>>| {
             let mut result = HashMap::new();
-            if let Some(val) = inputs.get("x").and_then(|d: &GraphData| d.as_int()) {
+            if let Some(val) = inputs.get("x").and_then(|d| d.as_int()) {
                 result.insert("output".to_string(), GraphData::int(val + 20));
             }
             result
@@ -162,10 +174,10 @@ fn test_merge() {
 
     // Merge function combines both branches
     graph.merge(
-        |inputs: &HashMap<String, GraphData>| {
+        |inputs: &HashMap<String, Arc<GraphData>>| {
             let mut result = HashMap::new();
-            let a = inputs.get("from_a").and_then(|d: &GraphData| d.as_int()).unwrap_or(0);
-            let b = inputs.get("from_b").and_then(|d: &GraphData| d.as_int()).unwrap_or(0);
+            let a = inputs.get("from_a").and_then(|d| d.as_int()).unwrap_or(0);
+            let b = inputs.get("from_b").and_then(|d| d.as_int()).unwrap_or(0);
             result.insert("merged".to_string(), GraphData::int(a + b));
             result
         },
@@ -183,16 +195,153 @@ fn test_merge() {
     // Branch A: 100 + 10 = 110
     // Branch B: 100 + 20 = 120
     // Merge: 110 + 120 = 230
-    assert_eq!(context.get("final").and_then(|d: &GraphData| d.as_int()), Some(230));
+    assert_eq!(context.get("final").and_then(|d| d.as_int()), Some(230));
 }
 
 #[test]
+fn test_nested_branches_keep_their_identity() {
+    fn offset(name: &'static str, by: i64) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
+        move |inputs| {
+            let mut out = HashMap::new();
+            out.insert(name.to_string(), GraphData::int(inputs["x"].as_int().unwrap() + by));
+            out
+        }
+    }
+
+    let mut inner = Graph::new();
+    inner.add(offset("y", 2), Some("Inner"), Some(vec![("data", "x")]), Some(vec![("y", "result")]));
+    let mut outer_branch = Graph::new();
+    outer_branch.add(offset("y", 1), Some("Outer"), Some(vec![("data", "x")]), Some(vec![("y", "result")]));
+    outer_branch.branch(inner);
+
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    let outer = graph.branch(outer_branch);
+    let nested = graph.nested_branch(&[outer, 1]).unwrap();
+    assert_ne!(nested, outer);
+    assert_eq!(graph.nested_branch(&[outer]), Some(outer));
+    graph.merge(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let pair = vec![inputs["o"].as_int().unwrap(), inputs["n"].as_int().unwrap()];
+            let mut out = HashMap::new();
+            out.insert("pair".to_string(), GraphData::int_vec(pair));
+            out
+        },
+        Some("Join"),
+        vec![(outer, "result", "o"), (nested, "result", "n")],
+        Some(vec![("pair", "pair")]),
+    );
+
+    let dag = graph.build();
+    let inner_node = dag.nodes().iter().find(|n| n.display_name() == "Inner" && n.is_branch).unwrap();
+    assert_eq!(inner_node.branch_label().as_deref(), Some("1.1"));
+    let result = dag.execute_detailed(false, None);
+    assert_eq!(result.context["pair"].as_int_slice(), Some(&[101i64, 102][..]));
+    assert_eq!(result.get_from_branch_path(&[outer], "result").and_then(|v| v.as_int()), Some(101));
+    assert_eq!(result.get_from_branch_path(&[outer, 1], "result").and_then(|v| v.as_int()), Some(102));
+}
+
+#[test]
+fn test_isolated_branches_do_not_share_variables() {
+    fn two_step(factor: i64) -> Graph {
+        let mut branch = Graph::new();
+        branch.add(
+            move |inputs: &HashMap<String, Arc<GraphData>>| {
+                let mut out = HashMap::new();
+                out.insert("tmp".to_string(), GraphData::int(inputs["x"].as_int().unwrap() * factor));
+                out
+            },
+            Some("Scale"),
+            Some(vec![("data", "x")]),
+            Some(vec![("tmp", "tmp")]),
+        );
+        branch.add(
+            |inputs: &HashMap<String, Arc<GraphData>>| {
+                let mut out = HashMap::new();
+                out.insert("y".to_string(), GraphData::int(inputs["t"].as_int().unwrap() + 1));
+                out
+            },
+            Some("Increment"),
+            Some(vec![("tmp", "t")]),
+            Some(vec![("y", "result")]),
+        );
+        branch
+    }
+
+    let mut graph = Graph::new();
+    graph.isolate_branches(true);
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    let a = graph.branch(two_step(2));
+    let b = graph.branch(two_step(3));
+    graph.merge(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let sum = inputs["a"].as_int().unwrap() + inputs["b"].as_int().unwrap();
+            let mut out = HashMap::new();
+            out.insert("sum".to_string(), GraphData::int(sum));
+            out
+        },
+        Some("Sum"),
+        vec![(a, "result", "a"), (b, "result", "b")],
+        Some(vec![("sum", "sum")]),
+    );
+    let dag = graph.build();
+    assert_eq!(dag.nodes().len(), 6);
+
+    for parallel in [false, true] {
+        let result = dag.execute_detailed(parallel, None);
+        assert_eq!(result.get_from_branch(a, "result").and_then(|v| v.as_int()), Some(201));
+        assert_eq!(result.get_from_branch(b, "result").and_then(|v| v.as_int()), Some(301));
+        assert_eq!(result.context.get_int("sum"), Some(502));
+        assert!(!result.context.contains_key("tmp"));
+        assert!(!result.context.contains_key("result"));
+    }
+}
+
+#[test]
+fn test_merge_all_passes_every_branch_output() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    for offset in [10, 20] {
+        let mut branch = Graph::new();
+        branch.add(
+            move |inputs: &HashMap<String, Arc<GraphData>>| {
+                let mut result = HashMap::new();
+                result.insert("output".to_string(), GraphData::int(inputs["x"].as_int().unwrap() + offset));
+                result
+            },
+            Some("Offset"),
+            Some(vec![("data", "x")]),
+            Some(vec![("output", "result")]),
+        );
+        graph.branch(branch);
+    }
+    graph.merge_all(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let mut keys: Vec<&str> = inputs.keys().map(String::as_str).collect();
+            keys.sort();
+            let diff = inputs["2:result"].as_int().unwrap() - inputs["1:result"].as_int().unwrap();
+            let mut result = HashMap::new();
+            result.insert("keys".to_string(), GraphData::string(keys.join(",")));
+            result.insert("diff".to_string(), GraphData::int(diff));
+            result
+        },
+        Some("Join"),
+        Some(vec![("keys", "keys"), ("diff", "diff")]),
+    );
+
+    let context = graph.build().execute(false, None);
+    assert_eq!(context.get_str("keys"), Some("1:result,2:result"));
+    assert_eq!(context.get_int("diff"), Some(10));
+}
+
+#[test]
+#[allow(clippy::useless_vec)]
 fn test_variants() {
     let mut graph = Graph::new();
 
     // Source
     graph.add(
-        |_: &HashMap<String, GraphData>| {
+        |_: &HashMap<String, Arc<GraphData>>| {
             let mut result = HashMap::new();
             result.insert("value".to_string(), GraphData::int(10));
             result
@@ -205,23 +354,23 @@ fn test_variants() {
     // Variant sweep: multiply by different factors using closures
     let _factors = vec![2.0, 3.0, 5.0];
     let multipliers = vec![
-        (|inputs: &HashMap<String, GraphData>| {
+        (|inputs: &HashMap<String, Arc<GraphData>>| {
             let mut result = HashMap::new();
-            if let Some(val) = inputs.get("x").and_then(|d: &GraphData| d.as_float()) {
+            if let Some(val) = inputs.get("x").and_then(|d| d.as_float()) {
                 result.insert("scaled".to_string(), GraphData::float(val * 1.5));
             }
             result
         }),
-        (|inputs: &HashMap<String, GraphData>| {
+        (|inputs: &HashMap<String, Arc<GraphData>>| {
             let mut result = HashMap::new();
-            if let Some(val) = inputs.get("x").and_then(|d: &GraphData| d.as_float()) {
+            if let Some(val) = inputs.get("x").and_then(|d| d.as_float()) {
                 result.insert("scaled".to_string(), GraphData::float(val * 2.0));
             }
             result
         }),
-        (|inputs: &HashMap<String, GraphData>| {
+        (|inputs: &HashMap<String, Arc<GraphData>>| {
             let mut result = HashMap::new();
-            if let Some(val) = inputs.get("x").and_then(|d: &GraphData| d.as_float()) {
+            if let Some(val) = inputs.get("x").and_then(|d| d.as_float()) {
                 result.insert("scaled".to_string(), GraphData::float(val * 3.0));
             }
             result
@@ -246,6 +395,327 @@ fn test_variants() {
     assert!(stats.max_parallelism >= 3);
 }
 
+#[test]
+fn test_chained_variants_product_and_zip() {
+    fn scale(factor: i64) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
+        move |inputs| {
+            let mut out = HashMap::new();
+            let x = inputs.get("x").and_then(|d| d.as_int()).unwrap_or(1);
+            out.insert("y".to_string(), GraphData::int(x * factor));
+            out
+        }
+    }
+    let build = |zip: bool| {
+        let mut graph = Graph::new();
+        graph.constant("seed", GraphData::int(1));
+        graph.variants(vec![scale(2), scale(3)], Some("First"), Some(vec![("seed", "x")]), Some(vec![("y", "a")]));
+        if zip {
+            graph.variants_zip(vec![scale(5), scale(7)], Some("Second"), Some(vec![("a", "x")]), Some(vec![("y", "b")]));
+        } else {
+            graph.variants_product(vec![scale(5), scale(7)], Some("Second"), Some(vec![("a", "x")]), Some(vec![("y", "b")]));
+        }
+        graph.build()
+    };
+
+    let second = |dag: &Dag| -> Vec<(String, Vec<usize>)> {
+        let mut nodes: Vec<_> = dag
+            .nodes()
+            .iter()
+            .filter(|n| n.display_name().starts_with("Second"))
+            .map(|n| {
+                let mut parents: Vec<usize> = n
+                    .dependencies
+                    .iter()
+                    .filter(|&&d| dag.nodes().iter().any(|p| p.id == d && p.display_name().starts_with("First")))
+                    .copied()
+                    .collect();
+                parents.sort();
+                (n.display_name(), parents)
+            })
+            .collect();
+        nodes.sort();
+        nodes
+    };
+
+    // Cartesian: 2 × 2 downstream nodes, each under one upstream variant
+    let product = build(false);
+    let nodes = second(&product);
+    assert_eq!(nodes.len(), 4);
+    assert!(nodes.iter().all(|(_, parents)| !parents.is_empty()));
+
+    // Pairwise: the i-th downstream variant sits under the i-th upstream one
+    let zip = build(true);
+    let first: Vec<usize> = zip
+        .nodes()
+        .iter()
+        .filter(|n| n.display_name().starts_with("First"))
+        .map(|n| n.id)
+        .collect();
+    let nodes = second(&zip);
+    assert_eq!(nodes.len(), 2);
+    assert!(nodes[0].1.contains(&first[0]));
+    assert!(nodes[1].1.contains(&first[1]));
+}
+
+#[test]
+fn test_variant_outputs_are_namespaced() {
+    let dag = sweep_writing_result(ConflictPolicy::LastWriterWins).build();
+    let result = dag.execute_detailed(true, None);
+    let values: Vec<(Vec<usize>, i64)> = result
+        .variant_outputs("result")
+        .into_iter()
+        .map(|(path, v)| (path, v.as_int().unwrap()))
+        .collect();
+    assert_eq!(values, vec![(vec![0], 100), (vec![1], 200), (vec![2], 300)]);
+    assert_eq!(result.context.get_int("result#1"), Some(200));
+}
+
+#[test]
+fn test_variants_inside_branches_keep_params_and_namespaces() {
+    let mut graph = Graph::new();
+    graph.constant("x", 1.0);
+    let mut sub = Graph::new();
+    sub.variant(
+        "k",
+        vec![2.0, 3.0],
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let y = inputs["x"].as_float().unwrap() * inputs["k"].as_float().unwrap();
+            HashMap::from([("y".to_string(), GraphData::float(y))])
+        },
+        Some("Scale"),
+        Some(vec![("x", "x")]),
+        Some(vec![("y", "out")]),
+    );
+    let branch = graph.branch(sub);
+    let dag = graph.build();
+
+    let copies: Vec<&Node> = dag.nodes().iter().filter(|n| n.branch_id == Some(branch)).collect();
+    assert_eq!(copies.len(), 2);
+    for parallel in [false, true] {
+        let result = dag.execute_detailed(parallel, None);
+        for (i, node) in copies.iter().enumerate() {
+            let k = node.variant_params["k"].as_float().unwrap();
+            assert_eq!(node.variant_path, vec![i]);
+            assert_eq!(result.get_from_node(node.id, "out").and_then(|d| d.as_float()), Some(k));
+        }
+        let values: Vec<(Vec<usize>, f64)> = result
+            .variant_outputs("out")
+            .into_iter()
+            .map(|(path, v)| (path, v.as_float().unwrap()))
+            .collect();
+        assert_eq!(values, vec![(vec![0], 2.0), (vec![1], 3.0)]);
+    }
+}
+
+#[test]
+fn test_collect_variants_gathers_values_and_params() {
+    let mut graph = sweep_writing_result(ConflictPolicy::LastWriterWins);
+    graph.collect_variants("result", "all_results");
+    graph.push(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let values = inputs["v"].as_list().unwrap();
+            let params = inputs["p"].as_list().unwrap();
+            let (best, best_params) = values
+                .iter()
+                .zip(params)
+                .max_by_key(|(v, _)| v.as_int().unwrap())
+                .unwrap();
+            let mut out = HashMap::new();
+            out.insert("best".to_string(), best.clone());
+            out.insert("factor".to_string(), best_params.as_map().unwrap()["factor"].clone());
+            out
+        },
+        Some("Best"),
+        Some(vec![("all_results", "v"), ("all_results_params", "p")]),
+        Some(vec![("best", "best"), ("factor", "best_factor")]),
+    );
+    let dag = graph.build();
+    assert!(dag.build_warnings().is_empty());
+    assert_eq!(dag.nodes().iter().filter(|n| n.display_name() == "Collect result").count(), 1);
+
+    for parallel in [false, true] {
+        let context = dag.execute(parallel, None);
+        let values: Vec<i64> = context["all_results"].as_list().unwrap().iter().map(|v| v.as_int().unwrap()).collect();
+        assert_eq!(values, vec![100, 200, 300]);
+        assert_eq!(context.get_int("best"), Some(300));
+        assert_eq!(context.get_int("best_factor"), Some(3));
+    }
+}
+
+#[test]
+fn test_top_k_ranks_collected_variants() {
+    let mut graph = Graph::new();
+    graph.constant("target", 0.3);
+    graph.variant(
+        "lr",
+        vec![0.1, 0.25, 0.5, 0.32],
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let error = (inputs["lr"].as_float().unwrap() - inputs["t"].as_float().unwrap()).abs();
+            let mut metrics = HashMap::new();
+            metrics.insert("error".to_string(), GraphData::float(error));
+            let mut out = HashMap::new();
+            out.insert("metrics".to_string(), GraphData::map(metrics));
+            out
+        },
+        Some("Fit"),
+        Some(vec![("target", "t")]),
+        Some(vec![("metrics", "metrics")]),
+    );
+    graph.collect_variants("metrics", "all_metrics");
+    graph.top_k("all_metrics", 2, Some("error"), true, "best");
+    let dag = graph.build();
+    assert!(dag.build_warnings().is_empty());
+
+    let context = dag.execute(true, None);
+    assert_eq!(context["best_index"].as_int_slice(), Some(&[3i64, 1][..]));
+    let lrs: Vec<f64> = context["best_params"]
+        .as_list()
+        .unwrap()
+        .iter()
+        .map(|p| p.as_map().unwrap()["lr"].as_float().unwrap())
+        .collect();
+    assert_eq!(lrs, vec![0.32, 0.25]);
+    let scores = context["best"].as_float_slice().unwrap();
+    assert!((scores[0] - 0.02).abs() < 1e-12);
+}
+
+#[test]
+fn test_chained_variants_read_their_own_upstream_variant() {
+    fn scale(factor: i64) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
+        move |inputs| {
+            let mut out = HashMap::new();
+            out.insert("y".to_string(), GraphData::int(inputs["x"].as_int().unwrap() * factor));
+            out
+        }
+    }
+    let mut graph = Graph::new();
+    graph.constant("seed", GraphData::int(1));
+    graph.variants(vec![scale(2), scale(3)], Some("First"), Some(vec![("seed", "x")]), Some(vec![("y", "a")]));
+    graph.variants(vec![scale(5), scale(7)], Some("Second"), Some(vec![("a", "x")]), Some(vec![("y", "b")]));
+    let dag = graph.build();
+    assert!(dag.build_warnings().is_empty());
+
+    for parallel in [false, true] {
+        let result = dag.execute_detailed(parallel, None);
+        let values: Vec<(Vec<usize>, i64)> = result
+            .variant_outputs("b")
+            .into_iter()
+            .map(|(path, v)| (path, v.as_int().unwrap()))
+            .collect();
+        assert_eq!(
+            values,
+            vec![(vec![0, 0], 10), (vec![0, 1], 14), (vec![1, 0], 15), (vec![1, 1], 21)]
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "one function per frontier node")]
+fn test_variants_zip_requires_matching_counts() {
+    let noop = |_: &HashMap<String, Arc<GraphData>>| HashMap::new();
+    let mut graph = Graph::new();
+    graph.variants(vec![noop, noop], Some("First"), None, None);
+    graph.variants_zip(vec![noop, noop, noop], Some("Second"), None, None);
+}
+
+#[test]
+fn test_variant_sweep_with_linspace() {
+    let mut graph = Graph::new();
+    graph.add(
+        |_: &HashMap<String, Arc<GraphData>>| {
+            let mut result = HashMap::new();
+            result.insert("value".to_string(), GraphData::float(10.0));
+            result
+        },
+        Some("Source"),
+        None,
+        Some(vec![("value", "data")]),
+    );
+
+    graph.variant(
+        "factor",
+        Linspace::new(0.1, 0.3, 3),
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let mut result = HashMap::new();
+            let x = inputs.get("x").and_then(|d| d.as_float()).unwrap_or(0.0);
+            let factor = inputs.get("factor").and_then(|d| d.as_float()).unwrap_or(0.0);
+            result.insert("scaled".to_string(), GraphData::float(x * factor));
+            result
+        },
+        Some("Scale"),
+        Some(vec![("data", "x")]),
+        Some(vec![("scaled", "result")]),
+    );
+
+    let dag = graph.build();
+    assert_eq!(dag.stats().variant_count, 3);
+
+    let mut labels: Vec<String> = dag.nodes().iter().map(|n| n.display_name()).collect();
+    labels.sort();
+    assert_eq!(
+        labels,
+        vec!["Scale (factor=0.1)", "Scale (factor=0.2)", "Scale (factor=0.3)", "Source"]
+    );
+
+    let result = dag.execute_detailed(false, None);
+    for node in dag.nodes().iter().filter(|n| n.variant_index.is_some()) {
+        let factor = node.variant_params.get("factor").and_then(|d| d.as_float()).unwrap();
+        let scaled = result.get_from_node(node.id, "result").and_then(|d| d.as_float()).unwrap();
+        assert!((scaled - 10.0 * factor).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_variant_grid_covers_every_combination() {
+    let mut graph = Graph::new();
+    graph.constant("data", 10.0);
+
+    let grid = ParamGrid::new()
+        .add("lr", Logspace::new(-2.0, -1.0, 2))
+        .add("batch", vec![32i64, 64]);
+    graph.variant_grid(
+        grid,
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let x = inputs.get("x").and_then(|d| d.as_float()).unwrap_or(0.0);
+            let lr = inputs.get("lr").and_then(|d| d.as_float()).unwrap_or(0.0);
+            let batch = inputs.get("batch").and_then(|d| d.as_int()).unwrap_or(0);
+            let mut result = HashMap::new();
+            result.insert("loss".to_string(), GraphData::float(x * lr * batch as f64));
+            result
+        },
+        Some("Train"),
+        Some(vec![("data", "x")]),
+        Some(vec![("loss", "loss")]),
+    );
+
+    let dag = graph.build();
+    assert_eq!(dag.stats().variant_count, 4);
+    let mut labels: Vec<String> = dag
+        .nodes()
+        .iter()
+        .filter(|n| n.variant_index.is_some())
+        .map(|n| n.display_name())
+        .collect();
+    labels.sort();
+    assert_eq!(
+        labels,
+        vec![
+            "Train (lr=0.01, batch=32)",
+            "Train (lr=0.01, batch=64)",
+            "Train (lr=0.1, batch=32)",
+            "Train (lr=0.1, batch=64)",
+        ]
+    );
+
+    let result = dag.execute_detailed(false, None);
+    for node in dag.nodes().iter().filter(|n| n.variant_index.is_some()) {
+        let lr = node.variant_params["lr"].as_float().unwrap();
+        let batch = node.variant_params["batch"].as_int().unwrap();
+        let loss = result.get_from_node(node.id, "loss").and_then(|d| d.as_float()).unwrap();
+        assert!((loss - 10.0 * lr * batch as f64).abs() < 1e-9);
+    }
+}
+
 #[test]
 fn test_dag_stats() {
     let mut graph = Graph::new();
@@ -277,6 +747,43 @@ fn test_dag_stats() {
     assert_eq!(stats.max_parallelism, 1); // All sequential, no parallelism
 }
 
+#[test]
+fn test_dag_stats_critical_path_with_costs() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.add(processor, Some("Slow"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "a")]));
+    graph.add(processor, Some("Fast"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "b")]));
+    graph.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let a = inputs.get("a").and_then(|d| d.as_int()).unwrap_or(0);
+            let b = inputs.get("b").and_then(|d| d.as_int()).unwrap_or(0);
+            let mut out = HashMap::new();
+            out.insert("sum".to_string(), GraphData::int(a + b));
+            out
+        },
+        Some("Join"),
+        Some(vec![("a", "a"), ("b", "b")]),
+        Some(vec![("sum", "sum")]),
+    );
+    let dag = graph.build();
+
+    // Unit costs: every chain through the diamond has length 3
+    let stats = dag.stats();
+    assert_eq!(stats.critical_path.len(), 3);
+    assert_eq!(stats.serial_cost, 4.0);
+    assert_eq!(stats.parallel_cost, 3.0);
+
+    let costs: HashMap<usize, f64> = [(1, 5.0), (2, 2.0)].into_iter().collect();
+    let stats = dag.stats_with_costs(&costs);
+    assert_eq!(stats.critical_path, vec![0, 1, 3]);
+    assert_eq!(stats.critical_path_cost, 7.0);
+    assert_eq!(stats.level_costs, vec![1.0, 5.0, 1.0]);
+    assert_eq!(stats.serial_cost, 9.0);
+    assert_eq!(stats.parallel_cost, 7.0);
+    assert!((stats.speedup - 9.0 / 7.0).abs() < 1e-12);
+    assert!(stats.summary().contains("Critical Path: 0 → 1 → 3 (cost 7)"));
+}
+
 #[test]
 fn test_mermaid_visualization() {
     let mut graph = Graph::new();
@@ -343,7 +850,7 @@ fn test_execute_detailed_branch_outputs() {
 
     let mut branch_a = Graph::new();
     branch_a.add(
-        |inputs: &HashMap<String, GraphData>| {
+        |inputs: &HashMap<String, Arc<GraphData>>| {
             let mut out = HashMap::new();
             if let Some(v) = inputs.get("x").and_then(|d| d.as_int()) {
                 out.insert("output".to_string(), GraphData::int(v + 1));
@@ -369,7 +876,7 @@ fn test_execute_get_from_node_and_branch() {
     graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
     let mut branch = Graph::new();
     branch.add(
-        |inputs: &HashMap<String, GraphData>| {
+        |inputs: &HashMap<String, Arc<GraphData>>| {
             let mut out = HashMap::new();
             if let Some(v) = inputs.get("x").and_then(|d| d.as_int()) {
                 out.insert("y".to_string(), GraphData::int(v * 10));
@@ -416,7 +923,7 @@ fn test_parallel_branches() {
         let mut graph = Graph::new();
         graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
         let mut b1 = Graph::new();
-        b1.add(|inputs: &HashMap<String, GraphData>| {
+        b1.add(|inputs: &HashMap<String, Arc<GraphData>>| {
             let mut o = HashMap::new();
             if let Some(v) = inputs.get("x").and_then(|d| d.as_int()) {
                 o.insert("out".to_string(), GraphData::int(v + 1));
@@ -424,7 +931,7 @@ fn test_parallel_branches() {
             o
         }, Some("B1"), Some(vec![("data", "x")]), Some(vec![("out", "r1")]));
         let mut b2 = Graph::new();
-        b2.add(|inputs: &HashMap<String, GraphData>| {
+        b2.add(|inputs: &HashMap<String, Arc<GraphData>>| {
             let mut o = HashMap::new();
             if let Some(v) = inputs.get("x").and_then(|d| d.as_int()) {
                 o.insert("out".to_string(), GraphData::int(v + 2));
@@ -441,6 +948,2978 @@ fn test_parallel_branches() {
     assert_eq!(seq.get("r2").and_then(|d| d.as_int()), par.get("r2").and_then(|d| d.as_int()));
 }
 
+#[test]
+fn test_context_values_are_shared_not_copied() {
+    // A Map is not Arc-backed inside GraphData, so sharing has to come from the context
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let mut graph = Graph::new();
+    graph.add(
+        |_: &HashMap<String, Arc<GraphData>>| {
+            let table: HashMap<String, GraphData> =
+                (0..1000).map(|i| (format!("k{}", i), GraphData::int(i))).collect();
+            HashMap::from([("table".to_string(), GraphData::map(table))])
+        },
+        Some("Source"),
+        None,
+        Some(vec![("table", "table")]),
+    );
+    for label in ["A", "B"] {
+        let seen = Arc::clone(&seen);
+        graph.add(
+            move |inputs: &HashMap<String, Arc<GraphData>>| {
+                seen.lock().unwrap().push(Arc::as_ptr(&inputs["t"]) as usize);
+                HashMap::new()
+            },
+            Some(label),
+            Some(vec![("table", "t")]),
+            None,
+        );
+    }
+
+    let dag = graph.build();
+    for parallel in [false, true] {
+        seen.lock().unwrap().clear();
+        let result = dag.execute_detailed(parallel, None);
+        let stored = result.context.get("table").unwrap();
+        assert!(Arc::ptr_eq(stored, &result.node_outputs[&0]["table"]));
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert!(seen.iter().all(|&ptr| ptr == Arc::as_ptr(stored) as usize));
+    }
+}
+
+#[test]
+fn test_parallel_chains_do_not_wait_for_each_other() {
+    // A three-step chain finishes while a slow one-step chain is still running:
+    // each chain runs on its own view of the context, not level by level
+    let step = |delay_ms: u64| {
+        move |inputs: &HashMap<String, Arc<GraphData>>| {
+            std::thread::sleep(Duration::from_millis(delay_ms));
+            HashMap::from([("y".to_string(), GraphData::int(inputs["x"].as_int().unwrap() + 1))])
+        }
+    };
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.add(step(0), Some("Fast1"), Some(vec![("data", "x")]), Some(vec![("y", "f1")]));
+    graph.add(step(0), Some("Fast2"), Some(vec![("f1", "x")]), Some(vec![("y", "f2")]));
+    graph.add(step(0), Some("Fast3"), Some(vec![("f2", "x")]), Some(vec![("y", "fast")]));
+    graph.add(step(200), Some("Slow"), Some(vec![("data", "x")]), Some(vec![("y", "slow")]));
+    let dag = graph.build();
+
+    let report = dag.execute_timed(true, None);
+    let end_of = |label: &str| report.timings.iter().find(|t| t.label == label).unwrap().end;
+    assert!(end_of("Fast3") < end_of("Slow"));
+    assert_eq!(report.result.get("fast").and_then(|d| d.as_int()), Some(103));
+    assert_eq!(report.result.get("slow").and_then(|d| d.as_int()), Some(101));
+    assert_eq!(report.timings.len(), 5);
+}
+
+#[test]
+fn test_context_view_is_copy_on_write() {
+    let mut parent: ExecutionContext = ExecutionContext::new();
+    parent.insert("a", Arc::new(GraphData::int(1)));
+    parent.insert("b", Arc::new(GraphData::int(2)));
+    let parent = Arc::new(parent);
+
+    let mut view = ExecutionContext::view(Arc::clone(&parent));
+    assert!(Arc::ptr_eq(view.get("a").unwrap(), parent.get("a").unwrap()));
+    let previous = view.insert("b", Arc::new(GraphData::int(20)));
+    assert_eq!(previous.and_then(|d| d.as_int()), Some(2));
+    view.insert("c", Arc::new(GraphData::int(3)));
+
+    assert_eq!(view.len(), 3);
+    assert_eq!(view.get_int("b"), Some(20));
+    assert_eq!(parent.get_int("b"), Some(2));
+    assert!(!parent.contains_key("c"));
+
+    view.remove("a");
+    assert_eq!(view.len(), 2);
+    assert!(parent.contains_key("a"));
+    let mut keys: Vec<String> = view.into_inner().into_keys().collect();
+    keys.sort();
+    assert_eq!(keys, vec!["b", "c"]);
+}
+
+#[test]
+fn test_thousands_of_nodes() {
+    // A 2000-node chain feeding a 1000-node fan: every node runs exactly once and
+    // reads only its own inputs
+    let mut graph = Graph::new();
+    graph.add(
+        |_: &HashMap<String, Arc<GraphData>>| HashMap::from([("v".to_string(), GraphData::int(0))]),
+        Some("Source"),
+        None,
+        Some(vec![("v", "v0")]),
+    );
+    let step = |inputs: &HashMap<String, Arc<GraphData>>| {
+        let v = inputs["v"].as_int().unwrap();
+        HashMap::from([("v".to_string(), GraphData::int(v + 1))])
+    };
+    let names: Vec<String> = (0..=2000).map(|i| format!("v{}", i)).collect();
+    for pair in names.windows(2) {
+        graph.add(step, None, Some(vec![(pair[0].as_str(), "v")]), Some(vec![("v", pair[1].as_str())]));
+    }
+    let fan: Vec<String> = (0..1000).map(|i| format!("f{}", i)).collect();
+    for name in &fan {
+        graph.add(step, None, Some(vec![("v2000", "v")]), Some(vec![("v", name.as_str())]));
+    }
+
+    let dag = graph.build();
+    assert_eq!(dag.nodes().len(), 3001);
+    for parallel in [false, true] {
+        let result = dag.execute_detailed(parallel, Some(8));
+        assert_eq!(result.node_outputs.len(), 3001);
+        assert_eq!(result.get("v2000").and_then(|d| d.as_int()), Some(2000));
+        assert!(fan.iter().all(|name| result.get(name).and_then(|d| d.as_int()) == Some(2001)));
+    }
+}
+
+#[test]
+fn test_large_sweeps_and_branches_build_deterministically() {
+    // 500 typed three-node branches next to a 150 × 150 cartesian sweep
+    let build = || {
+        let mut graph = Graph::new();
+        graph.constant("data", GraphData::int(1));
+        let scale = |inputs: &HashMap<String, Arc<GraphData>>| {
+            let x = inputs["x"].as_int().unwrap() * inputs["factor"].as_int().unwrap();
+            HashMap::from([("y".to_string(), GraphData::int(x))])
+        };
+        for b in 0..500 {
+            let mut branch = Graph::new();
+            let names = [format!("p{}", b), format!("q{}", b), format!("r{}", b)];
+            branch.add(processor, None, Some(vec![("data", "input_data")]), Some(vec![("processed_value", &names[0])]));
+            for pair in names.windows(2) {
+                branch.push(adder, None, Some(vec![(&pair[0], "input")]), Some(vec![("sum", &pair[1])]))
+                    .with_input_type("input", GraphDataKind::Int);
+            }
+            graph.branch(branch);
+        }
+        graph.variant("factor", (1..=150).collect::<Vec<i64>>(), scale, Some("A"),
+                      Some(vec![("data", "x")]), Some(vec![("y", "a")]));
+        graph.variant("factor", (1..=150).collect::<Vec<i64>>(), scale, Some("B"),
+                      Some(vec![("a", "x")]), Some(vec![("y", "b")]));
+        graph.build()
+    };
+
+    let dag = build();
+    assert_eq!(dag.nodes().len(), 1 + 150 + 150 * 150 + 2 * 500 * 3);
+    assert_eq!(dag.execution_levels().len(), 4);
+    assert_eq!(dag.execution_order(), build().execution_order());
+    let b = dag.nodes().iter().find(|n| n.label.as_deref() == Some("B (factor=7)")).unwrap();
+    assert_eq!(b.dependencies.len(), 1);
+}
+
+// ─── execute_timed ────────────────────────────────────────────────────────────
+
+#[test]
+fn test_execute_timed_records_every_node() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.add(processor, Some("Process"),
+        Some(vec![("data", "input_data")]),
+        Some(vec![("processed_value", "result")]));
+    let dag = graph.build();
+
+    let report = dag.execute_timed(true, None);
+    assert_eq!(report.result.get("result").and_then(|d| d.as_int()), Some(200));
+    assert_eq!(report.timings.len(), 2);
+
+    let order = dag.execution_order();
+    let source = report.timing(order[0]).unwrap();
+    let process = report.timing(order[1]).unwrap();
+    assert_eq!(source.label, "Source");
+    assert!(source.end <= process.start);
+    assert!(report.busy_time() <= report.total_duration);
+}
+
+#[test]
+fn test_execute_timed_gantt_has_worker_sections() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.add(processor, Some("A"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "a")]));
+    graph.add(processor, Some("B"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "b")]));
+    let dag = graph.build();
+
+    let gantt = dag.execute_timed(true, None).to_mermaid_gantt();
+    assert!(gantt.starts_with("gantt\n"));
+    assert!(gantt.contains("section Worker 0"));
+    assert!(gantt.contains("section Worker 1"));
+    assert!(gantt.contains("Source : n"));
+}
+
+#[test]
+fn test_execute_timed_chrome_trace() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source \"raw\""), None, Some(vec![("raw_data", "data")]));
+    graph.add(processor, Some("Process"),
+        Some(vec![("data", "input_data")]),
+        Some(vec![("processed_value", "result")]));
+    let dag = graph.build();
+
+    let trace = dag.execute_timed(false, None).to_chrome_trace();
+    assert!(trace.starts_with("{\"traceEvents\":["));
+    assert!(trace.contains(r#""name":"thread_name""#));
+    assert!(trace.contains(r#""name":"Source \"raw\"""#));
+    assert_eq!(trace.matches(r#""ph":"X""#).count(), 2);
+}
+
+#[test]
+fn test_execute_timed_records_variant_params() {
+    let mut graph = Graph::new();
+    graph.constant("data", 10i64);
+    graph.variant_grid(
+        ParamGrid::new().add("factor", vec![1i64, 2]).add("offset", vec![0i64, 5]),
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let x = inputs["x"].as_int().unwrap();
+            let factor = inputs["factor"].as_int().unwrap();
+            let offset = inputs["offset"].as_int().unwrap();
+            let mut out = HashMap::new();
+            out.insert("y".to_string(), GraphData::int(x * factor + offset));
+            out
+        },
+        Some("Affine"),
+        Some(vec![("data", "x")]),
+        Some(vec![("y", "result")]),
+    );
+    let report = graph.build().execute_timed(true, None);
+
+    assert_eq!(report.variants.len(), 4);
+    let results = report.variant_results("result");
+    assert_eq!(results.len(), 4);
+    for (i, (run, value)) in results.iter().enumerate() {
+        assert_eq!(run.variant_index, i);
+        let factor = run.params["factor"].as_int().unwrap();
+        let offset = run.params["offset"].as_int().unwrap();
+        assert_eq!(value.as_int(), Some(10 * factor + offset));
+        assert_eq!(report.variant(run.node_id).map(|v| v.variant_index), Some(i));
+    }
+    assert!(report.variant(0).is_none());
+}
+
+#[test]
+fn test_variant_params_are_inputs_of_the_function() {
+    let affine = |inputs: &HashMap<String, Arc<GraphData>>| {
+        let x = inputs["x"].as_int().unwrap();
+        let y = x * inputs["factor"].as_int().unwrap() + inputs["offset"].as_int().unwrap();
+        HashMap::from([("y".to_string(), GraphData::int(y))])
+    };
+    let mut graph = Graph::new();
+    graph.constant("data", 10i64);
+    graph.constant("base", 7i64);
+    graph.constant("gain", 4i64);
+    graph.constant("bias", 5i64);
+    graph.variant("factor", vec![2i64, 3], affine, Some("Scale"), Some(vec![("data", "x")]), Some(vec![("y", "scaled")]));
+    // A parameter takes precedence over configuration of the same name
+    graph.with_config("factor", 100i64).with_config("offset", 1i64);
+    // ...and a mapped input over a parameter (chained: once per "Scale" variant)
+    graph.variant(
+        "x",
+        vec![1i64, 2],
+        affine,
+        Some("Shadowed"),
+        Some(vec![("base", "x"), ("gain", "factor"), ("bias", "offset")]),
+        Some(vec![("y", "shadowed")]),
+    );
+    let result = graph.build().execute_detailed(false, None);
+
+    let values = |var| -> Vec<i64> { result.variant_outputs(var).iter().map(|(_, v)| v.as_int().unwrap()).collect() };
+    assert_eq!(values("scaled"), vec![21, 31]);
+    assert_eq!(values("shadowed"), vec![33; 4]);
+}
+
+#[test]
+fn test_html_report_contains_timings_outputs_and_variants() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.variant(
+        "factor",
+        vec![2i64, 3],
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let mut o = HashMap::new();
+            let x = inputs.get("x").and_then(|d| d.as_int()).unwrap_or(0);
+            let f = inputs.get("factor").and_then(|d| d.as_int()).unwrap_or(0);
+            o.insert("y".to_string(), GraphData::int(x * f));
+            o
+        },
+        Some("Scale<x>"),
+        Some(vec![("data", "x")]),
+        Some(vec![("y", "scaled")]),
+    );
+    let dag = graph.build();
+    let report = dag.execute_timed(false, None);
+    let html = dag.to_html_report(&report);
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<pre class=\"mermaid\">"));
+    assert!(html.contains("<h2>Variants</h2>"));
+    assert!(html.contains("factor = 3"));
+    assert!(html.contains("scaled = Int(300)"));
+    // Labels are escaped
+    assert!(html.contains("Scale&lt;x&gt; (factor=2)"));
+    assert!(!html.contains("Scale<x>"));
+}
+
+// ─── Isomorphism ──────────────────────────────────────────────────────────────
+
+fn scaled_branch(label: &str, factor: i64, output: &str) -> Graph {
+    let mut branch = Graph::new();
+    branch.add(
+        move |inputs: &HashMap<String, Arc<GraphData>>| {
+            let mut o = HashMap::new();
+            if let Some(v) = inputs.get("x").and_then(|d| d.as_int()) {
+                o.insert("out".to_string(), GraphData::int(v * factor));
+            }
+            o
+        },
+        Some(label), Some(vec![("data", "x")]), Some(vec![("out", output)]),
+    );
+    branch
+}
+
+#[test]
+fn test_is_isomorphic_ignores_construction_order() {
+    let mut first = Graph::new();
+    first.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    first.branch(scaled_branch("A", 2, "a"));
+    first.branch(scaled_branch("B", 3, "b"));
+
+    let mut second = Graph::new();
+    second.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    second.branch(scaled_branch("B", 3, "b"));
+    second.branch(scaled_branch("A", 2, "a"));
+
+    let (first, second) = (first.build(), second.build());
+    assert!(first.is_isomorphic(&second));
+    assert!(second.is_isomorphic(&first));
+
+    let mapping = first.isomorphism(&second).unwrap();
+    for node in first.nodes() {
+        let other = second.nodes().iter().find(|n| n.id == mapping[&node.id]).unwrap();
+        assert_eq!(node.label, other.label);
+    }
+}
+
+#[test]
+fn test_is_isomorphic_detects_differences() {
+    let build = |label: &str, input: &str| {
+        let mut graph = Graph::new();
+        graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+        graph.add(processor, Some(label), Some(vec![(input, "input_data")]), Some(vec![("processed_value", "result")]));
+        graph.build()
+    };
+
+    let base = build("Process", "data");
+    assert!(base.is_isomorphic(&build("Process", "data")));
+    // Different label
+    assert!(!base.is_isomorphic(&build("Other", "data")));
+    // Same labels, but the consumer no longer reads the source's output (edge removed)
+    assert!(!base.is_isomorphic(&build("Process", "missing")));
+}
+
+#[test]
+fn test_parallel_same_level_writers_are_deterministic() {
+    // Several nodes in the same level write the same broadcast variable; the value
+    // that survives must not depend on thread scheduling.
+    let build = || {
+        let mut graph = Graph::new();
+        graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+        for delay_ms in [5u64, 0, 3, 1] {
+            graph.add(
+                move |_: &HashMap<String, Arc<GraphData>>| {
+                    std::thread::sleep(std::time::Duration::from_millis(delay_ms));
+                    let mut o = HashMap::new();
+                    o.insert("v".to_string(), GraphData::int(delay_ms as i64));
+                    o
+                },
+                Some("Writer"),
+                Some(vec![("data", "x")]),
+                Some(vec![("v", "shared")]),
+            );
+        }
+        graph.build()
+    };
+
+    let dag = build();
+    let expected = dag.execute(false, None).get("shared").and_then(|d| d.as_int());
+    assert!(expected.is_some());
+    for _ in 0..10 {
+        let par = dag.execute(true, None);
+        assert_eq!(par.get("shared").and_then(|d| d.as_int()), expected);
+        let limited = dag.execute(true, Some(2));
+        assert_eq!(limited.get("shared").and_then(|d| d.as_int()), expected);
+    }
+}
+
+// ─── Diff ─────────────────────────────────────────────────────────────────────
+
+#[test]
+fn test_diff_identical_dags_is_empty() {
+    let mut first = Graph::new();
+    first.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    first.branch(scaled_branch("A", 2, "a"));
+    first.branch(scaled_branch("B", 3, "b"));
+
+    let mut second = Graph::new();
+    second.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    second.branch(scaled_branch("B", 3, "b"));
+    second.branch(scaled_branch("A", 2, "a"));
+
+    let diff = first.build().diff(&second.build());
+    assert!(diff.is_empty(), "{}", diff.summary());
+    assert_eq!(diff.summary(), "No differences");
+}
+
+#[test]
+fn test_diff_reports_nodes_and_edges() {
+    let mut before = Graph::new();
+    before.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    before.add(processor, Some("Process"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "result")]));
+    before.add(adder, Some("Add"), Some(vec![("result", "input")]), Some(vec![("sum", "final")]));
+
+    let mut after = Graph::new();
+    after.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    after.add(processor, Some("Process"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "value")]));
+    after.add(adder, Some("Report"), Some(vec![("value", "input")]), Some(vec![("sum", "final")]));
+
+    let diff = before.build().diff(&after.build());
+    assert_eq!(diff.added_nodes, vec!["Report".to_string()]);
+    assert_eq!(diff.removed_nodes, vec!["Add".to_string()]);
+    assert_eq!(diff.changed_nodes.len(), 1);
+    assert_eq!(diff.changed_nodes[0].label, "Process");
+    assert!(diff.changed_nodes[0].changes[0].starts_with("outputs:"));
+    assert_eq!(diff.added_edges, vec![("Process".to_string(), "Report".to_string())]);
+    assert_eq!(diff.removed_edges, vec![("Process".to_string(), "Add".to_string())]);
+    assert!(diff.summary().contains("+ node Report"));
+}
+
+// ─── Conflict policy ──────────────────────────────────────────────────────────
+
+fn sweep_writing_result(policy: ConflictPolicy) -> Graph {
+    let mut graph = Graph::new();
+    graph.on_conflict(policy);
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.variant(
+        "factor",
+        vec![1i64, 2, 3],
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let x = inputs.get("x").and_then(|d| d.as_int()).unwrap_or(0);
+            let factor = inputs.get("factor").and_then(|d| d.as_int()).unwrap_or(1);
+            let mut out = HashMap::new();
+            out.insert("y".to_string(), GraphData::int(x * factor));
+            out
+        },
+        Some("Scale"),
+        Some(vec![("data", "x")]),
+        Some(vec![("y", "result")]),
+    );
+    graph
+}
+
+#[test]
+fn test_conflict_policy_error_rejects_shared_outputs() {
+    let Err(err) = sweep_writing_result(ConflictPolicy::Error).try_build() else {
+        panic!("expected a conflict error");
+    };
+    assert_eq!(
+        err,
+        BuildError::ConflictingProducers {
+            broadcast_var: "result".to_string(),
+            producers: vec![1, 2, 3],
+        }
+    );
+    assert_eq!(err.to_string(), "broadcast variable 'result' is produced by nodes 1, 2, 3");
+
+    let mut ok = Graph::new();
+    ok.on_conflict(ConflictPolicy::Error);
+    ok.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    assert!(ok.try_build().is_ok());
+}
+
+#[test]
+fn test_conflict_policy_collect_gathers_all_values() {
+    let mut graph = sweep_writing_result(ConflictPolicy::Collect);
+    graph.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let total: i64 = inputs["all"]
+                .as_list()
+                .map(|l| l.iter().filter_map(|d| d.as_int()).sum())
+                .unwrap_or(-1);
+            let mut out = HashMap::new();
+            out.insert("total".to_string(), GraphData::int(total));
+            out
+        },
+        Some("Sum"),
+        Some(vec![("result", "all")]),
+        Some(vec![("total", "total")]),
+    );
+    let dag = graph.build();
+
+    for parallel in [false, true] {
+        let context = dag.execute(parallel, None);
+        let values: Vec<i64> = context["result"]
+            .as_list()
+            .unwrap()
+            .iter()
+            .filter_map(|d| d.as_int())
+            .collect();
+        assert_eq!(values, vec![100, 200, 300]);
+        // "Sum" is replicated once per variant; every copy sees the full list
+        let totals: Vec<i64> = context["total"]
+            .as_list()
+            .unwrap()
+            .iter()
+            .filter_map(|d| d.as_int())
+            .collect();
+        assert_eq!(totals, vec![600, 600, 600]);
+    }
+}
+
+#[test]
+fn test_collect_node_receives_every_variant_output() {
+    let mut graph = sweep_writing_result(ConflictPolicy::LastWriterWins);
+    graph.collect(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let all = inputs["all"].as_list().cloned().unwrap_or_default();
+            let missing = inputs["missing"].as_list().map_or(usize::MAX, |l| l.len());
+            let mut out = HashMap::new();
+            out.insert("total".to_string(), GraphData::int(all.iter().filter_map(|d| d.as_int()).sum()));
+            out.insert("count".to_string(), GraphData::int(all.len() as i64 + missing as i64));
+            out
+        },
+        Some("Sum"),
+        Some(vec![("result", "all"), ("nothing", "missing")]),
+        Some(vec![("total", "total"), ("count", "count")]),
+    );
+    let dag = graph.build();
+    assert_eq!(dag.nodes().iter().filter(|n| n.label.as_deref() == Some("Sum")).count(), 1);
+
+    for parallel in [false, true] {
+        let context = dag.execute(parallel, None);
+        assert_eq!(context.get("total").and_then(|d| d.as_int()), Some(600));
+        assert_eq!(context.get("count").and_then(|d| d.as_int()), Some(3));
+        // Other consumers still see the last writer
+        assert!(context["result"].as_int().is_some());
+    }
+}
+
+// ─── execute_streaming ────────────────────────────────────────────────────────
+
+#[test]
+fn test_execute_streaming_reports_fast_outputs_before_slow_ones() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.add(processor, Some("Stats"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "stats")]));
+    graph.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            std::thread::sleep(Duration::from_millis(100));
+            let mut out = HashMap::new();
+            out.insert("model".to_string(), GraphData::clone(&inputs["x"]));
+            out
+        },
+        Some("Train"),
+        Some(vec![("data", "x")]),
+        Some(vec![("model", "model")]),
+    );
+    let dag = graph.build();
+
+    let seen = std::sync::Mutex::new(Vec::new());
+    let result = dag.execute_streaming(true, None, |key, value| {
+        seen.lock().unwrap().push((key.to_string(), value.as_int()));
+    });
+    let seen = seen.into_inner().unwrap();
+
+    assert_eq!(
+        seen,
+        vec![
+            ("data".to_string(), Some(100)),
+            ("stats".to_string(), Some(200)),
+            ("model".to_string(), Some(100)),
+        ]
+    );
+    assert_eq!(result.get("model").and_then(|d| d.as_int()), Some(100));
+}
+
+#[test]
+fn test_execute_streaming_waits_for_last_writer() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "value")]));
+    graph.add(processor, Some("Overwrite"), Some(vec![("value", "input_data")]), Some(vec![("processed_value", "value")]));
+    let dag = graph.build();
+
+    for parallel in [false, true] {
+        let seen = std::sync::Mutex::new(Vec::new());
+        dag.execute_streaming(parallel, None, |key, value| {
+            seen.lock().unwrap().push((key.to_string(), value.as_int()));
+        });
+        assert_eq!(seen.into_inner().unwrap(), vec![("value".to_string(), Some(200))]);
+    }
+}
+
+// ─── execute_with_progress ────────────────────────────────────────────────────
+
+#[test]
+fn test_execute_with_progress_reports_every_node() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.variants(
+        vec![processor, processor, processor],
+        Some("Double"),
+        Some(vec![("data", "input_data")]),
+        Some(vec![("processed_value", "doubled")]),
+    );
+    let dag = graph.build();
+
+    for parallel in [false, true] {
+        let seen = std::sync::Mutex::new(Vec::new());
+        let result = dag.execute_with_progress(parallel, None, None, |p| {
+            seen.lock().unwrap().push((p.completed, p.total, p.ran));
+        });
+        let mut seen = seen.into_inner().unwrap();
+        seen.sort();
+        assert_eq!(seen, (1..=4).map(|i| (i, 4, true)).collect::<Vec<_>>());
+        assert!(!result.cancelled);
+        assert_eq!(result.get("doubled").and_then(|d| d.as_int()), Some(200));
+    }
+}
+
+#[test]
+fn test_execute_with_progress_stops_when_cancelled() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "input_data")]));
+    graph.add(processor, Some("Double"), Some(vec![("input_data", "input_data")]), Some(vec![("processed_value", "doubled")]));
+    graph.add(adder, Some("Add"), Some(vec![("doubled", "input")]), Some(vec![("sum", "sum")]));
+    let dag = graph.build();
+
+    for parallel in [false, true] {
+        let cancel = CancelToken::new();
+        let labels = std::sync::Mutex::new(Vec::new());
+        let result = dag.execute_with_progress(parallel, None, Some(&cancel), |p| {
+            labels.lock().unwrap().push(p.label.clone());
+            cancel.cancel();
+        });
+        assert_eq!(labels.into_inner().unwrap(), vec!["Source".to_string()]);
+        assert!(result.cancelled);
+        assert_eq!(result.skipped_nodes.len(), 2);
+        assert!(result.get("input_data").is_some());
+        assert!(result.get("doubled").is_none());
+    }
+}
+
+// ─── Dead-node analysis ───────────────────────────────────────────────────────
+
+#[test]
+fn test_unused_outputs_and_dead_node_pruning() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.add(processor, Some("Process"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "result")]));
+    graph.add(processor, Some("Debug"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "debug")]));
+    graph.add(adder, Some("Add"), Some(vec![("result", "input")]), Some(vec![("sum", "final")]));
+    let mut dag = graph.build();
+
+    let unused: Vec<(usize, String)> = dag
+        .unused_outputs()
+        .into_iter()
+        .map(|u| (u.node_id, u.broadcast_var))
+        .collect();
+    assert_eq!(unused, vec![(2, "debug".to_string()), (3, "final".to_string())]);
+
+    assert_eq!(dag.dead_nodes(&["final"]), vec![2]);
+    assert_eq!(dag.dead_nodes(&["final", "debug"]), Vec::<usize>::new());
+
+    assert_eq!(dag.prune_dead_nodes(&["final"]), vec![2]);
+    assert_eq!(dag.nodes().len(), 3);
+    let context = dag.execute(true, None);
+    assert_eq!(context.get("final").and_then(|d| d.as_int()), Some(210));
+    assert!(!context.contains_key("debug"));
+}
+
+#[test]
+fn test_inspect_reports_structure_and_suggestions() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.add(processor, Some("Process"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "result")]));
+    graph.add(processor, Some("Debug"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "debug")]));
+    graph.add(adder, Some("Add"), Some(vec![("result", "input")]), Some(vec![("sum", "final")]));
+    graph.add(adder, Some("Typo"), Some(vec![("reslt", "input")]), Some(vec![("sum", "other")]));
+    let inspection = graph.build().inspect();
+
+    assert_eq!(inspection.sources, vec![0, 4]);
+    assert_eq!(inspection.sinks, vec![2, 3, 4]);
+    assert_eq!(inspection.isolated, vec![4]);
+    assert_eq!(inspection.depth, 3);
+    assert_eq!(inspection.width, 2);
+    assert!(inspection.suggestions.iter().any(|s| s.contains("node 4 (Typo) is isolated")));
+    assert!(inspection.suggestions.iter().any(|s| s.contains("did you mean 'result'")));
+
+    let mut chain = Graph::new();
+    chain.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    chain.add(processor, Some("Process"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "result")]));
+    let inspection = chain.build().inspect();
+    assert!(inspection.isolated.is_empty());
+    assert!(inspection.suggestions.iter().any(|s| s.contains("parallel execution cannot speed")));
+}
+
+#[test]
+fn test_insert_between_routes_only_that_edge() {
+    let mut graph = Graph::new();
+    let source = graph.add(
+        |_: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("mv".to_string(), GraphData::float(1500.0));
+            out
+        },
+        Some("Source"),
+        None,
+        Some(vec![("mv", "reading")]),
+    );
+    let echo = |inputs: &HashMap<String, Arc<GraphData>>| {
+        let mut out = HashMap::new();
+        out.insert("out".to_string(), GraphData::clone(&inputs["v"]));
+        out
+    };
+    let display = graph.add(echo, Some("Display"), Some(vec![("reading", "v")]), Some(vec![("out", "shown")]));
+    graph.add(echo, Some("Log"), Some(vec![("reading", "v")]), Some(vec![("out", "logged")]));
+
+    let converter = graph.insert_between(
+        source,
+        display,
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("v".to_string(), GraphData::float(inputs["v"].as_float().unwrap() / 1000.0));
+            out
+        },
+        Some("mV to V"),
+    );
+    assert!(converter.is_some());
+    assert!(graph.insert_between(display, source, |_: &HashMap<String, Arc<GraphData>>| HashMap::new(), None).is_none());
+
+    let dag = graph.build();
+    let display_node = dag.nodes().iter().find(|n| n.id == display.id()).unwrap();
+    assert_eq!(display_node.dependencies, vec![converter.unwrap().id()]);
+    let context = dag.execute(false, None);
+    assert_eq!(context["shown"].as_float(), Some(1.5));
+    assert_eq!(context["logged"].as_float(), Some(1500.0));
+}
+
+// ─── Optimizer ────────────────────────────────────────────────────────────────
+
+#[test]
+fn test_build_optimized_dedups_replicated_nodes() {
+    let build = || {
+        let mut graph = sweep_writing_result(ConflictPolicy::LastWriterWins);
+        graph.add(adder, Some("Report"), Some(vec![("data", "input")]), Some(vec![("sum", "report")]));
+        graph
+    };
+    // `add()` after a sweep replicates "Report" once per variant
+    assert_eq!(build().build().nodes().len(), 7);
+
+    let dag = build().build_optimized(OptimizerConfig::default().dedup_identical_nodes());
+    assert_eq!(dag.nodes().len(), 5);
+    assert_eq!(
+        dag.optimization_log(),
+        &[PassSummary { pass: "identical node dedup".to_string(), changes: 2 }]
+    );
+    assert_eq!(dag.execute(true, None).get("report").and_then(|d| d.as_int()), Some(110));
+}
+
+#[test]
+fn test_build_optimized_removes_dead_nodes_and_redundant_edges() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.add(processor, Some("Process"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "result")]));
+    graph.add(processor, Some("Debug"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "debug")]));
+    graph.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let r = inputs.get("r").and_then(|d| d.as_int()).unwrap_or(0);
+            let d = inputs.get("d").and_then(|d| d.as_int()).unwrap_or(0);
+            let mut out = HashMap::new();
+            out.insert("sum".to_string(), GraphData::int(r + d));
+            out
+        },
+        Some("Combine"),
+        Some(vec![("result", "r"), ("data", "d")]),
+        Some(vec![("sum", "final")]),
+    );
+    let dag = graph.build_optimized(
+        OptimizerConfig::default()
+            .eliminate_dead_nodes(&["final"])
+            .remove_redundant_edges(),
+    );
+
+    let changes: Vec<usize> = dag.optimization_log().iter().map(|p| p.changes).collect();
+    assert_eq!(changes, vec![1, 1]);
+    assert!(dag.nodes().iter().all(|n| n.label.as_deref() != Some("Debug")));
+    let combine = dag.nodes().iter().find(|n| n.label.as_deref() == Some("Combine")).unwrap();
+    assert_eq!(combine.dependencies, vec![1]);
+    assert_eq!(dag.execution_levels().len(), 3);
+    assert_eq!(dag.execute(false, None).get("final").and_then(|d| d.as_int()), Some(300));
+}
+
+#[test]
+fn test_linear_chain_fusion() {
+    let build = || {
+        let mut graph = Graph::new();
+        graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+        graph.add(processor, Some("Double"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "doubled")]));
+        graph.add(adder, Some("Add"), Some(vec![("doubled", "input")]), Some(vec![("sum", "final")]));
+        // Two readers of "final": the chain stops here
+        graph.add(processor, Some("Left"), Some(vec![("final", "input_data")]), Some(vec![("processed_value", "left")]));
+        graph.add(adder, Some("Right"), Some(vec![("final", "input")]), Some(vec![("sum", "right")]));
+        graph
+    };
+    let plain = build().build();
+    let fused = build().build_optimized(OptimizerConfig::default().fuse_linear_chains());
+
+    assert_eq!(fused.optimization_log()[0].changes, 2);
+    assert_eq!(fused.nodes().len(), 3);
+    assert!(fused
+        .nodes()
+        .iter()
+        .any(|n| n.label.as_deref() == Some("Source + Double + Add")));
+
+    for parallel in [false, true] {
+        let expected = plain.execute(parallel, None);
+        let actual = fused.execute(parallel, None);
+        for key in ["data", "doubled", "final", "left", "right"] {
+            assert_eq!(
+                actual.get(key).and_then(|d| d.as_int()),
+                expected.get(key).and_then(|d| d.as_int()),
+                "{}",
+                key
+            );
+        }
+    }
+}
+
+#[test]
+fn test_custom_optimizer_pass() {
+    struct DropLabel(&'static str);
+    impl OptimizerPass for DropLabel {
+        fn name(&self) -> &str {
+            "drop label"
+        }
+        fn run(&self, nodes: &mut Vec<Node>) -> usize {
+            let before = nodes.len();
+            nodes.retain(|n| n.label.as_deref() != Some(self.0));
+            before - nodes.len()
+        }
+    }
+
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.add(processor, Some("Process"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "result")]));
+    let dag = graph
+        .build_with_optimizer(&Optimizer::new().with_pass(DropLabel("Process")))
+        .ok()
+        .unwrap();
+    assert_eq!(dag.nodes().len(), 1);
+    assert_eq!(dag.optimization_log()[0].pass, "drop label");
+}
+
+// ─── Typed ports ──────────────────────────────────────────────────────────────
+
+#[test]
+fn test_typed_ports_reject_mismatched_types() {
+    let mut graph = Graph::new();
+    graph
+        .push(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]))
+        .with_output_type("raw_data", GraphDataKind::String);
+    graph
+        .push(processor, Some("Process"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "result")]))
+        .with_input_type("input_data", GraphDataKind::Int);
+
+    let Err(err) = graph.try_build() else {
+        panic!("expected a type mismatch");
+    };
+    assert_eq!(
+        err,
+        BuildError::TypeMismatch {
+            broadcast_var: "data".to_string(),
+            producer: 0,
+            produced: GraphDataKind::String,
+            consumer: 1,
+            expected: GraphDataKind::Int,
+        }
+    );
+    assert_eq!(err.to_string(), "node 1 expects 'data' to be Int but node 0 produces String");
+}
+
+#[test]
+fn test_typed_ports_accept_compatible_types() {
+    let mut graph = Graph::new();
+    graph
+        .push(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]))
+        .with_output_type("raw_data", GraphDataKind::Int);
+    graph
+        .push(processor, Some("Process"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "result")]))
+        .with_input_type("input_data", GraphDataKind::Float);
+    let Ok(dag) = graph.try_build() else {
+        panic!("an Int output should feed a Float input");
+    };
+    assert_eq!(dag.execute(false, None)["data"].kind(), GraphDataKind::Int);
+}
+
+// ─── Context schema ───────────────────────────────────────────────────────────
+
+fn schema_pipeline(source_kind: GraphDataKind) -> Graph {
+    let mut graph = Graph::new();
+    graph
+        .schema("data", GraphDataKind::Int)
+        .schema("result", GraphDataKind::Int);
+    graph
+        .push(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]))
+        .with_output_type("raw_data", source_kind);
+    graph.add(processor, Some("Process"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "result")]));
+    graph
+}
+
+#[test]
+fn test_schema_checks_node_mappings_at_build() {
+    assert!(schema_pipeline(GraphDataKind::Int).try_build().is_ok());
+
+    let Err(err) = schema_pipeline(GraphDataKind::Float).try_build() else {
+        panic!("expected a schema mismatch");
+    };
+    assert_eq!(
+        err,
+        BuildError::SchemaMismatch {
+            broadcast_var: "data".to_string(),
+            node: 0,
+            declared: GraphDataKind::Float,
+            schema: GraphDataKind::Int,
+        }
+    );
+
+    let mut graph = schema_pipeline(GraphDataKind::Int);
+    graph.add(adder, Some("Add"), Some(vec![("result", "input")]), Some(vec![("sum", "total")]));
+    let Err(err) = graph.try_build() else {
+        panic!("expected an unknown variable");
+    };
+    assert_eq!(err.to_string(), "node 2 uses 'total', which is not in the schema");
+
+    // Ports without declared types take the schema's kinds
+    let dag = schema_pipeline(GraphDataKind::Int).build();
+    assert_eq!(dag.nodes()[1].input_types["input_data"], GraphDataKind::Int);
+    assert_eq!(dag.nodes()[1].output_types["processed_value"], GraphDataKind::Int);
+}
+
+#[test]
+fn test_enforced_schema_validates_runtime_values() {
+    let build = |data: GraphData, enforce: bool| {
+        let mut graph = Graph::new();
+        graph.schema("data", GraphDataKind::Int).schema("result", GraphDataKind::Int);
+        graph.enforce_schema(enforce);
+        graph.input("data", data);
+        graph.add(processor, Some("Process"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "result")]));
+        graph.build()
+    };
+
+    let context = build(GraphData::int(4), true).try_execute(false, None).unwrap();
+    assert_eq!(context.get_int("result"), Some(8));
+
+    // Not enforced, the value reaches the node unchecked
+    assert!(build(GraphData::string("four"), false).try_execute(false, None).is_ok());
+
+    let dag = build(GraphData::string("four"), true);
+    for parallel in [false, true] {
+        let err = dag.try_execute(parallel, None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "node 0 (Process) rejected input 'input_data': expected Int, found String"
+        );
+    }
+}
+
+#[test]
+fn test_enforced_schema_validates_branch_nodes() {
+    let mut graph = Graph::new();
+    graph.schema("data", GraphDataKind::Int).schema("result", GraphDataKind::Int);
+    graph.enforce_schema(true);
+    graph.input("data", GraphData::string("four"));
+    let mut sub = Graph::new();
+    sub.add(processor, Some("Process"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "result")]));
+    let branch = graph.branch(sub);
+    let dag = graph.build();
+
+    assert!(dag.nodes().iter().all(|n| n.check_types));
+    for parallel in [false, true] {
+        let result = dag.execute_detailed(parallel, None);
+        assert!(result.get_from_branch(branch, "result").is_none());
+        assert!(result.errors.iter().any(|e| e.to_string().ends_with("rejected input 'input_data': expected Int, found String")));
+    }
+}
+
+// ─── Units ────────────────────────────────────────────────────────────────────
+
+fn tuned_mixer(carrier_unit: &str, auto_scale: bool) -> Result<Dag, BuildError> {
+    let mut graph = Graph::new();
+    graph.auto_scale_units(auto_scale);
+    graph
+        .push(
+            |_: &HashMap<String, Arc<GraphData>>| HashMap::from([("f".to_string(), GraphData::float(2.5))]),
+            Some("Tune"),
+            None,
+            Some(vec![("f", "carrier")]),
+        )
+        .with_output_unit("f", carrier_unit);
+    graph
+        .push(
+            |inputs: &HashMap<String, Arc<GraphData>>| {
+                HashMap::from([("out".to_string(), GraphData::clone(&inputs["freq"]))])
+            },
+            Some("Mix"),
+            Some(vec![("carrier", "freq")]),
+            Some(vec![("out", "mixed")]),
+        )
+        .with_input_unit("freq", "Hz");
+    graph.try_build()
+}
+
+#[test]
+fn test_units_must_match_without_auto_scaling() {
+    assert!(tuned_mixer("Hz", false).is_ok());
+
+    let Err(err) = tuned_mixer("MHz", false) else {
+        panic!("expected a unit mismatch");
+    };
+    assert_eq!(
+        err,
+        BuildError::UnitMismatch {
+            broadcast_var: "carrier".to_string(),
+            producer: 0,
+            produced: "MHz".to_string(),
+            consumer: 1,
+            expected: "Hz".to_string(),
+        }
+    );
+    assert_eq!(err.to_string(), "node 1 expects 'carrier' in Hz but node 0 produces MHz");
+
+    // Other dimensions never connect
+    assert!(matches!(tuned_mixer("m", true), Err(BuildError::UnitMismatch { .. })));
+}
+
+#[test]
+fn test_auto_scaled_units_convert_inputs() {
+    let dag = tuned_mixer("MHz", true).unwrap();
+    for parallel in [false, true] {
+        let context = dag.execute(parallel, None);
+        assert_eq!(context.get("carrier").and_then(|d| d.as_float()), Some(2.5));
+        assert_eq!(context.get("mixed").and_then(|d| d.as_float()), Some(2.5e6));
+    }
+    let dag = tuned_mixer("kHz", true).unwrap();
+    assert_eq!(dag.execute(false, None).get("mixed").and_then(|d| d.as_float()), Some(2500.0));
+}
+
+#[test]
+fn test_units_are_checked_and_scaled_inside_branches() {
+    let build = |auto_scale: bool| {
+        let mut graph = Graph::new();
+        graph.auto_scale_units(auto_scale);
+        graph
+            .push(
+                |_: &HashMap<String, Arc<GraphData>>| HashMap::from([("f".to_string(), GraphData::float(2.5))]),
+                Some("Tune"),
+                None,
+                Some(vec![("f", "carrier")]),
+            )
+            .with_output_unit("f", "MHz");
+        let mut sub = Graph::new();
+        sub.push(
+            |inputs: &HashMap<String, Arc<GraphData>>| {
+                HashMap::from([("out".to_string(), GraphData::clone(&inputs["freq"]))])
+            },
+            Some("Mix"),
+            Some(vec![("carrier", "freq")]),
+            Some(vec![("out", "mixed")]),
+        )
+        .with_input_unit("freq", "Hz");
+        let branch = graph.branch(sub);
+        graph.try_build().map(|dag| (dag, branch))
+    };
+
+    assert!(matches!(build(false), Err(BuildError::UnitMismatch { .. })));
+    let (dag, branch) = build(true).unwrap();
+    for parallel in [false, true] {
+        let result = dag.execute_detailed(parallel, None);
+        assert_eq!(result.get_from_branch(branch, "mixed").and_then(|d| d.as_float()), Some(2.5e6));
+    }
+}
+
+// ─── Custom payload ───────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+enum Signal {
+    Samples(Vec<f32>),
+    Energy(f32),
+    Gathered(Vec<Signal>),
+}
+
+impl Payload for Signal {
+    fn gather(values: Vec<Self>) -> Option<Self> {
+        Some(Signal::Gathered(values))
+    }
+}
+
+#[test]
+fn test_graph_over_custom_payload() {
+    let mut graph = Graph::<Signal>::default();
+    graph.add(
+        |_: &HashMap<String, Arc<Signal>>| {
+            let mut out = HashMap::new();
+            out.insert("s".to_string(), Signal::Samples(vec![1.0, 2.0, 3.0]));
+            out
+        },
+        Some("Load"),
+        None,
+        Some(vec![("s", "samples")]),
+    );
+    graph
+        .push(
+            |inputs: &HashMap<String, Arc<Signal>>| {
+                let Some(Signal::Samples(s)) = inputs.get("x").map(Arc::as_ref) else {
+                    return HashMap::new();
+                };
+                let Some(Signal::Energy(gain)) = inputs.get("gain").map(Arc::as_ref) else {
+                    return HashMap::new();
+                };
+                let mut out = HashMap::new();
+                out.insert("e".to_string(), Signal::Energy(gain * s.iter().map(|v| v * v).sum::<f32>()));
+                out
+            },
+            Some("Energy"),
+            Some(vec![("samples", "x")]),
+            Some(vec![("e", "energy")]),
+        )
+        .with_config("gain", Signal::Energy(2.0));
+    graph.collect(
+        |inputs: &HashMap<String, Arc<Signal>>| {
+            let mut out = HashMap::new();
+            out.insert("all".to_string(), Signal::clone(&inputs["energy"]));
+            out
+        },
+        Some("Gather"),
+        Some(vec![("energy", "energy")]),
+        Some(vec![("all", "energies")]),
+    );
+
+    let dag: Dag<Signal> = graph.build();
+    let context = dag.execute(true, None);
+    assert_eq!(context["energy"], Signal::Energy(28.0));
+    assert_eq!(context["energies"], Signal::Gathered(vec![Signal::Energy(28.0)]));
+}
+
+// ─── Typed context accessors ──────────────────────────────────────────────────
+
+#[test]
+fn test_typed_context_accessors() {
+    let mut graph = Graph::new();
+    graph.add(
+        |_: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("n".to_string(), GraphData::int(3));
+            out.insert("gain".to_string(), GraphData::string("2.5"));
+            out.insert("name".to_string(), GraphData::string("radar"));
+            out
+        },
+        Some("Source"),
+        None,
+        Some(vec![("n", "n"), ("gain", "gain"), ("name", "name")]),
+    );
+
+    let context = graph.build().execute(false, None);
+    assert_eq!(context.get_int("n"), Some(3));
+    assert_eq!(context.get_f64("n"), Some(3.0));
+    assert_eq!(context.get_f64("gain"), Some(2.5));
+    assert_eq!(context.get_str("name"), Some("radar"));
+    assert_eq!(context.require::<i32>("n"), Ok(3));
+
+    assert_eq!(
+        context.require::<f64>("missing"),
+        Err(ContextError::Missing { key: "missing".to_string() })
+    );
+    let err = context.require::<i64>("name").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "variable 'name' holds String, which cannot be read as i64"
+    );
+}
+
+#[test]
+fn test_context_is_keyed_by_interned_names() {
+    assert_eq!(Symbol::new("gain"), Symbol::from("gain".to_string()));
+    assert_ne!(Symbol::new("gain"), Symbol::new("offset"));
+    assert_eq!(Symbol::new("gain").as_str(), "gain");
+    assert_eq!(Symbol::lookup("never interned anywhere"), None);
+
+    let mut context: ExecutionContext = ExecutionContext::new();
+    context.insert("gain", Arc::new(GraphData::float(2.5)));
+    context.insert("offset".to_string(), Arc::new(GraphData::int(1)));
+    assert_eq!(context.len(), 2);
+    assert!(Arc::ptr_eq(context.get("gain").unwrap(), context.get_symbol(Symbol::new("gain")).unwrap()));
+    assert!(!context.contains_key("never interned anywhere"));
+
+    context.retain(|key, _| key != "offset");
+    let keys: Vec<Symbol> = context.keys().collect();
+    assert_eq!(keys, vec![Symbol::new("gain")]);
+    let map: HashMap<String, Arc<GraphData>> = context.into_inner();
+    assert_eq!(map["gain"].as_float(), Some(2.5));
+}
+
+// ─── Explicit connections ─────────────────────────────────────────────────────
+
+#[test]
+fn test_connect_overrides_implicit_resolution() {
+    fn constant(value: i64) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
+        move |_| {
+            let mut out = HashMap::new();
+            out.insert("value".to_string(), GraphData::int(value));
+            out
+        }
+    }
+
+    let mut graph = Graph::new();
+    let calibrated = graph.add(constant(1), Some("Calibrated"), None, Some(vec![("value", "gain")]));
+    let default = graph.add(constant(2), Some("Default"), None, Some(vec![("value", "gain")]));
+    let apply = graph.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("y".to_string(), GraphData::int(inputs["g"].as_int().unwrap() * 100));
+            out
+        },
+        Some("Apply"),
+        Some(vec![("gain", "g")]),
+        Some(vec![("y", "result")]),
+    );
+    graph.connect(calibrated, "value", apply, "g");
+
+    let dag = graph.build();
+    let apply_node = dag.nodes().iter().find(|n| n.id == apply.id()).unwrap();
+    assert_eq!(apply_node.dependencies, vec![calibrated.id()]);
+
+    let result = dag.execute_detailed(false, None);
+    assert_eq!(result.context["result"].as_int(), Some(100));
+    assert_eq!(result.get_from_node(apply, "result").and_then(|v| v.as_int()), Some(100));
+    // The implicit variable is still produced for other consumers
+    assert_eq!(result.get_from_node(default, "gain").and_then(|v| v.as_int()), Some(2));
+}
+
+// ─── Editing the builder ──────────────────────────────────────────────────────
+
+#[test]
+fn test_remove_and_replace_nodes() {
+    fn emit(value: i64) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
+        move |_| {
+            let mut out = HashMap::new();
+            out.insert("v".to_string(), GraphData::int(value));
+            out
+        }
+    }
+
+    let mut graph = Graph::new();
+    let source = graph.add(emit(1), Some("Source"), None, Some(vec![("v", "x")]));
+    let debug = graph.add(emit(0), Some("Debug"), None, Some(vec![("v", "debug")]));
+    graph.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("y".to_string(), GraphData::int(inputs["x"].as_int().unwrap() + 1));
+            out
+        },
+        Some("Increment"),
+        Some(vec![("x", "x")]),
+        Some(vec![("y", "result")]),
+    );
+
+    assert!(graph.remove_node(debug));
+    assert!(!graph.remove_node(debug));
+    assert!(graph.replace_function(source, emit(41)));
+    assert!(!graph.replace_function(debug, emit(0)));
+
+    let dag = graph.build();
+    assert_eq!(dag.nodes().len(), 2);
+    let context = dag.execute(false, None);
+    assert_eq!(context["result"].as_int(), Some(42));
+    assert!(!context.contains_key("debug"));
+}
+
+// ─── Subgraph nodes ───────────────────────────────────────────────────────────
+
+#[test]
+fn test_add_subgraph_keeps_inner_variables_private() {
+    fn map_int(
+        input: &'static str,
+        f: fn(i64) -> i64,
+    ) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
+        move |inputs| {
+            let mut out = HashMap::new();
+            out.insert("out".to_string(), GraphData::int(f(inputs[input].as_int().unwrap())));
+            out
+        }
+    }
+
+    // Component: tmp = echo * 2; compressed = tmp + 1
+    let mut component = Graph::new();
+    component.add(map_int("x", |x| x * 2), Some("Double"), Some(vec![("echo", "x")]), Some(vec![("out", "tmp")]));
+    component.add(map_int("t", |t| t + 1), Some("Inc"), Some(vec![("tmp", "t")]), Some(vec![("out", "compressed")]));
+
+    let mut graph = Graph::new();
+    graph.add(
+        |_: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("rx".to_string(), GraphData::int(20));
+            out.insert("tmp".to_string(), GraphData::int(-1));
+            out
+        },
+        Some("Source"),
+        None,
+        Some(vec![("rx", "rx_samples"), ("tmp", "tmp")]),
+    );
+    let compression = graph.add_subgraph(
+        component,
+        Some("Range compression"),
+        Some(vec![("rx_samples", "echo")]),
+        Some(vec![("compressed", "range_profile")]),
+    );
+
+    let dag = graph.build();
+    assert_eq!(dag.nodes().len(), 2);
+    let result = dag.execute_detailed(false, None);
+    assert_eq!(result.context["range_profile"].as_int(), Some(41));
+    assert_eq!(result.context["tmp"].as_int(), Some(-1));
+    assert_eq!(result.get_node_outputs(compression).map(|o| o.len()), Some(1));
+}
+
+// ─── Graph composition ────────────────────────────────────────────────────────
+
+#[test]
+fn test_extend_appends_fragment_with_fresh_ids() {
+    fn offset(by: i64) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
+        move |inputs| {
+            let mut out = HashMap::new();
+            out.insert("y".to_string(), GraphData::int(inputs["x"].as_int().unwrap() + by));
+            out
+        }
+    }
+    // Library fragment: raw -> clean -> scaled, with an explicit connection inside
+    fn preprocessing() -> Graph {
+        let mut fragment = Graph::new();
+        let clean = fragment.add(offset(1), Some("Clean"), Some(vec![("raw", "x")]), Some(vec![("y", "clean")]));
+        let scale = fragment.add(offset(10), Some("Scale"), Some(vec![("clean", "x")]), Some(vec![("y", "scaled")]));
+        fragment.connect(clean, "y", scale, "x");
+        fragment
+    }
+
+    let mut graph = Graph::new();
+    graph.add(
+        |_: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("v".to_string(), GraphData::int(100));
+            out
+        },
+        Some("Load"),
+        None,
+        Some(vec![("v", "raw")]),
+    );
+    graph.extend(preprocessing());
+    graph.push(offset(1000), Some("Analyse"), Some(vec![("scaled", "x")]), Some(vec![("y", "result")]));
+
+    let dag = graph.build();
+    let mut ids: Vec<_> = dag.nodes().iter().map(|n| n.id).collect();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 4);
+    let context = dag.execute(false, None);
+    assert_eq!(context["result"].as_int(), Some(1111));
+}
+
+// ─── Guards ───────────────────────────────────────────────────────────────────
+
+#[test]
+fn test_when_skips_node_and_dependents() {
+    fn forward(from: &'static str) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
+        move |inputs| {
+            let mut out = HashMap::new();
+            out.insert("out".to_string(), GraphData::clone(&inputs[from]));
+            out
+        }
+    }
+    let high_snr = |ctx: &ExecutionContext| ctx["snr"].as_float().is_some_and(|snr| snr > 10.0);
+
+    for parallel in [false, true] {
+        let mut graph = Graph::new();
+        graph.add(
+            |_: &HashMap<String, Arc<GraphData>>| {
+                let mut out = HashMap::new();
+                out.insert("snr".to_string(), GraphData::float(3.0));
+                out
+            },
+            Some("Source"),
+            None,
+            Some(vec![("snr", "snr")]),
+        );
+        let detect = graph.add(forward("x"), Some("Detect"), Some(vec![("snr", "x")]), Some(vec![("out", "hits")]));
+        graph.when(high_snr);
+        let report = graph.add(forward("h"), Some("Report"), Some(vec![("hits", "h")]), Some(vec![("out", "report")]));
+        graph
+            .push(forward("x"), Some("Estimate"), Some(vec![("snr", "x")]), Some(vec![("out", "estimate")]))
+            .when_or(high_snr, GraphData::none());
+        graph.add(forward("e"), Some("Log"), Some(vec![("estimate", "e")]), Some(vec![("out", "logged")]));
+
+        let result = graph.build().execute_detailed(parallel, None);
+        let mut skipped: Vec<_> = result.skipped_nodes.iter().copied().collect();
+        skipped.sort();
+        assert_eq!(skipped, vec![detect.id(), report.id()]);
+        assert!(!result.context.contains_key("hits"));
+        assert!(!result.context.contains_key("report"));
+        assert!(result.context["logged"].is_none());
+    }
+}
+
+// ─── Switch ───────────────────────────────────────────────────────────────────
+
+#[test]
+fn test_extend_keeps_nested_branch_paths_distinct() {
+    fn offset(by: i64) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
+        move |inputs| HashMap::from([("y".to_string(), GraphData::int(inputs["x"].as_int().unwrap() + by))])
+    }
+    fn single(label: &str, by: i64) -> Graph {
+        let mut graph = Graph::new();
+        graph.add(offset(by), Some(label), Some(vec![("data", "x")]), Some(vec![("y", "result")]));
+        graph
+    }
+
+    // Fragment with a branch holding a nested branch: paths [1] and [1, 1]
+    let mut fragment = Graph::new();
+    fragment.add(offset(0), Some("Prep"), Some(vec![("data", "x")]), Some(vec![("y", "prepped")]));
+    let mut outer = single("Outer", 1);
+    outer.branch(single("Inner", 2));
+    fragment.branch(outer);
+
+    let mut graph = Graph::new();
+    graph.isolate_branches(true);
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    let host = graph.branch(single("Host", 5));
+    graph.extend(fragment);
+
+    let dag = graph.build();
+    let path = |label: &str| {
+        let node = dag.nodes().iter().find(|n| n.display_name() == label && n.is_branch).unwrap();
+        node.branch_path.clone()
+    };
+    let outer = path("Outer")[0];
+    assert_eq!(path("Host"), vec![host]);
+    assert_ne!(outer, host);
+    assert_eq!(path("Inner"), vec![outer, 1]);
+
+    let result = dag.execute_detailed(false, None);
+    let value = |path: &[usize]| result.get_from_branch_path(path, "result").and_then(|v| v.as_int());
+    assert_eq!(value(&[host]), Some(105));
+    assert_eq!(value(&[outer]), Some(101));
+    assert_eq!(value(&[outer, 1]), Some(102));
+}
+
+#[test]
+fn test_switch_runs_only_selected_branch() {
+    fn scale(factor: i64) -> Graph {
+        let mut branch = Graph::new();
+        branch.add(
+            move |inputs: &HashMap<String, Arc<GraphData>>| {
+                let mut out = HashMap::new();
+                out.insert("y".to_string(), GraphData::int(inputs["x"].as_int().unwrap() * factor));
+                out
+            },
+            Some("Scale"),
+            Some(vec![("n", "x")]),
+            Some(vec![("y", "scaled")]),
+        );
+        branch
+    }
+
+    for (n, expected) in [(3, 30), (50_000, 100_000)] {
+        for parallel in [false, true] {
+            let mut graph = Graph::new();
+            graph
+                .push(
+                    move |_: &HashMap<String, Arc<GraphData>>| {
+                        let mut out = HashMap::new();
+                        out.insert("n".to_string(), GraphData::int(n));
+                        out
+                    },
+                    Some("Source"),
+                    None,
+                    Some(vec![("n", "n")]),
+                )
+                .switch(
+                    |ctx| if ctx["n"].as_int().unwrap_or(0) > 10_000 { 1 } else { 0 },
+                    vec![scale(10), scale(2)],
+                )
+                .push(
+                    |inputs: &HashMap<String, Arc<GraphData>>| {
+                        let mut out = HashMap::new();
+                        out.insert("r".to_string(), GraphData::clone(&inputs["s"]));
+                        out
+                    },
+                    Some("Report"),
+                    Some(vec![("scaled", "s")]),
+                    Some(vec![("r", "report")]),
+                );
+
+            let result = graph.build().execute_detailed(parallel, None);
+            assert_eq!(result.skipped_nodes.len(), 1);
+            assert_eq!(result.context["report"].as_int(), Some(expected));
+        }
+    }
+}
+
+// ─── Loops and collections ────────────────────────────────────────────────────
+
+#[test]
+fn test_repeat_feeds_outputs_back_until_done() {
+    // Newton's method for sqrt(2)
+    let mut step = Graph::new();
+    step.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let x = inputs["x"].as_float().unwrap();
+            let next = 0.5 * (x + 2.0 / x);
+            let mut out = HashMap::new();
+            out.insert("x".to_string(), GraphData::float(next));
+            out.insert("delta".to_string(), GraphData::float((next - x).abs()));
+            out
+        },
+        Some("Step"),
+        Some(vec![("x", "x")]),
+        Some(vec![("x", "x"), ("delta", "delta")]),
+    );
+
+    let mut graph = Graph::new();
+    graph.add(
+        |_: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("x0".to_string(), GraphData::float(1.0));
+            out
+        },
+        Some("Guess"),
+        None,
+        Some(vec![("x0", "guess")]),
+    );
+    graph.repeat(
+        step,
+        |ctx| ctx["delta"].as_float().is_some_and(|d| d < 1e-12),
+        50,
+        Some("Newton"),
+        Some(vec![("guess", "x")]),
+        Some(vec![("x", "root")]),
+    );
+
+    let context = graph.build().execute(false, None);
+    assert!((context.get_f64("root").unwrap() - 2f64.sqrt()).abs() < 1e-12);
+
+    // max_iters caps the loop
+    let mut capped = Graph::new();
+    let mut count = Graph::new();
+    count.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("n".to_string(), GraphData::int(inputs["n"].as_int().unwrap() + 1));
+            out
+        },
+        Some("Increment"),
+        Some(vec![("n", "n")]),
+        Some(vec![("n", "n")]),
+    );
+    capped.add(
+        |_: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("n".to_string(), GraphData::int(0));
+            out
+        },
+        Some("Zero"),
+        None,
+        Some(vec![("n", "start")]),
+    );
+    capped.repeat(count, |_| false, 7, Some("Count"), Some(vec![("start", "n")]), Some(vec![("n", "total")]));
+    assert_eq!(capped.build().execute(false, None).get_int("total"), Some(7));
+}
+
+#[test]
+fn test_map_over_runs_subgraph_per_element() {
+    let mut square = Graph::new();
+    square.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let x = inputs["x"].as_int().unwrap();
+            let mut out = HashMap::new();
+            out.insert("y".to_string(), GraphData::int(x * x));
+            out
+        },
+        Some("Square"),
+        Some(vec![("value", "x")]),
+        Some(vec![("y", "squared")]),
+    );
+
+    let mut graph = Graph::new();
+    graph.add(
+        |_: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("v".to_string(), GraphData::int_vec(vec![1, 2, 3, 4]));
+            out
+        },
+        Some("Source"),
+        None,
+        Some(vec![("v", "values")]),
+    );
+    graph.map_over("values", "value", square, Some("Square each"), Some(vec![("squared", "squares")]));
+
+    let context = graph.build().execute(false, None);
+    let squares: Vec<i64> = context["squares"]
+        .as_list()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_int().unwrap())
+        .collect();
+    assert_eq!(squares, vec![1, 4, 9, 16]);
+}
+
+#[test]
+fn test_map_over_runs_elements_in_parallel_in_order() {
+    static RUNNING: AtomicUsize = AtomicUsize::new(0);
+    static PEAK: AtomicUsize = AtomicUsize::new(0);
+    let mut slow = Graph::new();
+    slow.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let x = inputs["x"].as_int().unwrap();
+            let running = RUNNING.fetch_add(1, Ordering::SeqCst) + 1;
+            PEAK.fetch_max(running, Ordering::SeqCst);
+            // Earlier elements take longer, so they finish last
+            std::thread::sleep(Duration::from_millis(10 * (8 - x as u64)));
+            RUNNING.fetch_sub(1, Ordering::SeqCst);
+            HashMap::from([("y".to_string(), GraphData::int(x * 10))])
+        },
+        Some("Slow"),
+        Some(vec![("value", "x")]),
+        Some(vec![("y", "scaled")]),
+    );
+
+    let mut graph = Graph::new();
+    graph.constant("values", GraphData::int_vec((0..8).collect()));
+    graph.map_over("values", "value", slow, Some("Scale each"), Some(vec![("scaled", "all")]));
+    let context = graph.build().execute(false, None);
+
+    let all: Vec<i64> = context["all"].as_list().unwrap().iter().map(|v| v.as_int().unwrap()).collect();
+    assert_eq!(all, (0..8).map(|x| x * 10).collect::<Vec<_>>());
+    let cores = std::thread::available_parallelism().map_or(1, usize::from);
+    assert!(PEAK.load(Ordering::SeqCst) <= cores.min(8));
+    if cores > 1 {
+        assert!(PEAK.load(Ordering::SeqCst) > 1);
+    }
+}
+
+#[test]
+fn test_reduce_folds_list_in_order() {
+    let mut graph = Graph::new();
+    graph.add(
+        |_: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert(
+                "words".to_string(),
+                GraphData::list(vec!["a".into(), "b".into(), "c".into()]),
+            );
+            out.insert("values".to_string(), GraphData::float_vec(vec![1.5, -2.0, 4.0]));
+            out
+        },
+        Some("Source"),
+        None,
+        Some(vec![("words", "words"), ("values", "values")]),
+    );
+    graph.reduce(
+        "words",
+        "",
+        |acc, word| GraphData::string(format!("{}{}", acc.as_string().unwrap(), word.as_string().unwrap())),
+        Some("Concatenate"),
+        "joined",
+    );
+    graph.reduce(
+        "values",
+        f64::NEG_INFINITY,
+        |acc, x| GraphData::float(acc.as_float().unwrap().max(x.as_float().unwrap())),
+        Some("Max"),
+        "max",
+    );
+
+    let context = graph.build().execute(false, None);
+    assert_eq!(context.get_str("joined"), Some("abc"));
+    assert_eq!(context.get_f64("max"), Some(4.0));
+}
+
+#[test]
+fn test_scatter_gather_preserves_chunk_order() {
+    fn double() -> Graph {
+        let mut double = Graph::new();
+        double.add(
+            |inputs: &HashMap<String, Arc<GraphData>>| {
+                let block = inputs["x"].as_int_slice().unwrap();
+                let mut out = HashMap::new();
+                out.insert("y".to_string(), GraphData::int_vec(block.iter().map(|v| v * 2).collect()));
+                out.insert("n".to_string(), GraphData::int(block.len() as i64));
+                out
+            },
+            Some("Double"),
+            Some(vec![("block", "x")]),
+            Some(vec![("y", "doubled"), ("n", "size")]),
+        );
+        double
+    }
+
+    let mut graph = Graph::new();
+    graph.add(
+        |_: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("data".to_string(), GraphData::int_vec((0..10).collect()));
+            out
+        },
+        Some("Source"),
+        None,
+        Some(vec![("data", "data")]),
+    );
+    graph.scatter_gather(3, double(), Some("Double"), ("data", "block"), ("doubled", "result"));
+    graph.scatter_gather(3, double(), Some("Sizes"), ("data", "block"), ("size", "sizes"));
+
+    let context = graph.build().execute(false, None);
+    assert_eq!(context["result"].as_int_slice().unwrap(), (0..10).map(|v| v * 2).collect::<Vec<_>>().as_slice());
+    let sizes: Vec<i64> = context["sizes"].as_list().unwrap().iter().map(|v| v.as_int().unwrap()).collect();
+    assert_eq!(sizes, vec![3, 3, 4]);
+}
+
+#[test]
+fn test_scatter_gather_runs_many_chunks_on_few_threads() {
+    static THREADS: std::sync::Mutex<Vec<std::thread::ThreadId>> = std::sync::Mutex::new(Vec::new());
+    let mut negate = Graph::new();
+    negate.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let mut threads = THREADS.lock().unwrap();
+            let id = std::thread::current().id();
+            if !threads.contains(&id) {
+                threads.push(id);
+            }
+            let block = inputs["x"].as_int_slice().unwrap();
+            HashMap::from([("y".to_string(), GraphData::int_vec(block.iter().map(|v| -v).collect()))])
+        },
+        Some("Negate"),
+        Some(vec![("block", "x")]),
+        Some(vec![("y", "negated")]),
+    );
+
+    let mut graph = Graph::new();
+    graph.constant("data", GraphData::int_vec((0..1000).collect()));
+    graph.scatter_gather(500, negate, Some("Negate"), ("data", "block"), ("negated", "result"));
+    let context = graph.build().execute(false, None);
+
+    assert_eq!(context["result"].as_int_slice().unwrap(), (0..1000).map(|v| -v).collect::<Vec<_>>().as_slice());
+    let cores = std::thread::available_parallelism().map_or(1, usize::from);
+    assert!(THREADS.lock().unwrap().len() <= cores);
+}
+
+fn quadratic_training() -> Graph {
+    let mut train = Graph::new();
+    train.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let lr = inputs["lr"].as_float().unwrap();
+            let epochs = inputs.get("epochs").and_then(|d| d.as_int()).unwrap_or(1);
+            let mut out = HashMap::new();
+            out.insert("loss".to_string(), GraphData::float((lr - 0.3).powi(2) + 1.0 / epochs as f64));
+            out
+        },
+        Some("Train"),
+        Some(vec![("lr", "lr"), ("epochs", "epochs")]),
+        Some(vec![("loss", "loss")]),
+    );
+    train
+}
+
+#[test]
+fn test_adaptive_sweep_halves_candidates() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let runs = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&runs);
+    let sweep = AdaptiveSweep::new("lr", Linspace::new(0.1, 0.8, 8))
+        .score(move |ctx| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            -ctx["loss"].as_float().unwrap()
+        })
+        .budget("epochs", 1, 2);
+
+    let mut graph = Graph::new();
+    graph.adaptive_sweep(
+        sweep,
+        quadratic_training(),
+        Some("Tune"),
+        None,
+        Some(vec![("lr", "best_lr"), ("loss", "best_loss"), ("epochs", "epochs")]),
+    );
+
+    let context = graph.build().execute(false, None);
+    assert_eq!(context.get_f64("best_lr"), Some(0.3));
+    assert_eq!(context["epochs"].as_int(), Some(8));
+    assert_eq!(context.get_f64("best_loss"), Some(1.0 / 8.0));
+    assert_eq!(runs.load(Ordering::SeqCst), 8 + 4 + 2 + 1);
+}
+
+#[test]
+fn test_adaptive_sweep_refines_range() {
+    let sweep = AdaptiveSweep::new("lr", vec![0.0, 1.0])
+        .score(|ctx| -ctx["loss"].as_float().unwrap())
+        .prune(|trials| match trials[0].round {
+            0 => SweepStep::Refine(Linspace::new(0.2, 0.4, 3).into_variant_values()),
+            _ => SweepStep::Stop,
+        });
+
+    let mut graph = Graph::new();
+    graph.adaptive_sweep(sweep, quadratic_training(), Some("Tune"), None, Some(vec![("lr", "best_lr")]));
+
+    let context = graph.build().execute(false, None);
+    assert_eq!(context.get_f64("best_lr"), Some(0.3));
+}
+
+#[test]
+fn test_adaptive_sweep_bounds_threads_and_never_picks_nan() {
+    static RUNNING: AtomicUsize = AtomicUsize::new(0);
+    static PEAK: AtomicUsize = AtomicUsize::new(0);
+    let sweep = AdaptiveSweep::new("lr", Linspace::new(0.0, 1.0, 201)).score(|ctx| {
+        let running = RUNNING.fetch_add(1, Ordering::SeqCst) + 1;
+        PEAK.fetch_max(running, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(1));
+        RUNNING.fetch_sub(1, Ordering::SeqCst);
+        // Large rates diverge
+        match ctx["lr"].as_float().unwrap() {
+            lr if lr > 0.75 => f64::NAN,
+            _ => -ctx["loss"].as_float().unwrap(),
+        }
+    });
+
+    let mut graph = Graph::new();
+    graph.adaptive_sweep(sweep, quadratic_training(), Some("Tune"), None, Some(vec![("lr", "best_lr")]));
+
+    let context = graph.build().execute(false, None);
+    assert_eq!(context.get_f64("best_lr"), Some(0.3));
+    let cores = std::thread::available_parallelism().map_or(1, usize::from);
+    assert!(PEAK.load(Ordering::SeqCst) <= cores);
+}
+
+// ─── Graph interface ──────────────────────────────────────────────────────────
+
+#[test]
+fn test_declared_inputs_and_outputs() {
+    let mut graph = Graph::new();
+    graph
+        .input("gain", 2.0)
+        .push(
+            |inputs: &HashMap<String, Arc<GraphData>>| {
+                let gain = inputs["g"].as_float().unwrap();
+                let mut out = HashMap::new();
+                out.insert("raw".to_string(), GraphData::float(gain * 10.0));
+                out.insert("scaled".to_string(), GraphData::float(gain * 100.0));
+                out
+            },
+            Some("Amplify"),
+            Some(vec![("gain", "g")]),
+            Some(vec![("raw", "intermediate"), ("scaled", "result")]),
+        )
+        .output("result");
+    let dag = graph.build();
+    assert!(dag.build_warnings().is_empty());
+
+    let context = dag.execute(false, None);
+    assert_eq!(context.len(), 1);
+    assert_eq!(context.get_f64("result"), Some(200.0));
+    assert!(dag.execute_detailed(false, None).context.contains_key("intermediate"));
+
+    let mut inputs = HashMap::new();
+    inputs.insert("gain".to_string(), GraphData::float(3.0));
+    let context = dag.try_execute_with_inputs(inputs, false, None).unwrap();
+    assert_eq!(context.get_f64("result"), Some(300.0));
+
+    let mut inputs = HashMap::new();
+    inputs.insert("gian".to_string(), GraphData::float(3.0));
+    let err = dag.try_execute_with_inputs(inputs, false, None).unwrap_err();
+    assert_eq!(
+        err,
+        InputError::Undeclared {
+            name: "gian".to_string(),
+            declared: vec!["gain".to_string()],
+        }
+    );
+    assert_eq!(err.to_string(), "'gian' is not an input of this graph (declared inputs: gain)");
+}
+
+#[test]
+fn test_input_default_used_only_when_not_supplied() {
+    let mut graph = Graph::new();
+    graph
+        .push(
+            |inputs: &HashMap<String, Arc<GraphData>>| {
+                let mut out = HashMap::new();
+                out.insert("r".to_string(), GraphData::float(inputs["bw"].as_float().unwrap() / 1e6));
+                out
+            },
+            Some("Resolution"),
+            Some(vec![("bandwidth", "bw")]),
+            Some(vec![("r", "mhz")]),
+        )
+        .with_default("bw", GraphData::float(100e6));
+    let dag = graph.build();
+    assert!(dag.build_warnings().is_empty());
+    assert_eq!(dag.execute(false, None).get_f64("mhz"), Some(100.0));
+
+    let mut inputs = HashMap::new();
+    inputs.insert("bandwidth".to_string(), GraphData::float(20e6));
+    assert_eq!(dag.execute_with_inputs(inputs, false, None).get_f64("mhz"), Some(20.0));
+}
+
+#[test]
+fn test_required_input_reports_missing_variable() {
+    let forward = |inputs: &HashMap<String, Arc<GraphData>>| {
+        let mut out = HashMap::new();
+        if let Some(x) = inputs.get("x") {
+            out.insert("y".to_string(), GraphData::clone(x));
+        }
+        out
+    };
+
+    let mut graph = Graph::new();
+    graph.add(
+        |_: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("s".to_string(), GraphData::float(1.0));
+            out
+        },
+        Some("FFT"),
+        None,
+        Some(vec![("s", "spectrum")]),
+    );
+    let detect = graph.add(forward, Some("Detect"), Some(vec![("spectra", "x")]), Some(vec![("y", "hits")]));
+    graph.require_input("x");
+    let report = graph.add(forward, Some("Report"), Some(vec![("hits", "x")]), Some(vec![("y", "report")]));
+
+    let dag = graph.build();
+    for parallel in [false, true] {
+        let result = dag.execute_detailed(parallel, None);
+        assert!(result.skipped_nodes.contains(&detect.id()));
+        assert!(result.skipped_nodes.contains(&report.id()));
+        assert_eq!(result.errors.len(), 1);
+
+        let err = dag.try_execute(parallel, None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "node {} (Detect) requires 'spectra', which is missing; did you mean 'spectrum', produced by node 0 (FFT)?",
+                detect.id()
+            )
+        );
+    }
+}
+
+#[test]
+fn test_validators_reject_bad_port_values() {
+    let positive = |d: &GraphData| match d.as_int() {
+        Some(v) if v > 0 => Ok(()),
+        _ => Err(format!("expected a positive int, got {}", d.summary())),
+    };
+    let build = |offset: i64| {
+        let mut graph = Graph::new();
+        graph.constant("offset", offset);
+        let add = graph.add(adder, Some("Add"), Some(vec![("offset", "input")]), Some(vec![("sum", "total")]));
+        graph.validate("input", positive);
+        let out = Some(vec![("processed_value", "y")]);
+        graph.add(processor, Some("Scale"), Some(vec![("total", "input_data")]), out);
+        graph.validate("processed_value", |d: &GraphData| match d.as_int() {
+            Some(v) if v < 100 => Ok(()),
+            _ => Err("too large"),
+        });
+        (graph.build(), add)
+    };
+
+    // Valid values pass through
+    let (dag, _) = build(5);
+    assert_eq!(dag.try_execute(false, None).unwrap().get_int("y"), Some(30));
+
+    // A rejected input stops the node and its dependents
+    let (dag, add) = build(-1);
+    for parallel in [false, true] {
+        let result = dag.execute_detailed(parallel, None);
+        assert!(result.skipped_nodes.contains(&add.id()));
+        assert!(result.context.get("y").is_none());
+        assert_eq!(
+            result.errors,
+            vec![ExecutionError::InvalidInput {
+                node_id: add.id(),
+                label: "Add".to_string(),
+                port: "input".to_string(),
+                message: "expected a positive int, got Int(-1)".to_string(),
+            }]
+        );
+    }
+
+    // A rejected output is not stored
+    let (dag, _) = build(50);
+    let err = dag.try_execute(true, None).unwrap_err();
+    assert_eq!(err.to_string(), "node 2 (Scale) produced an invalid 'processed_value': too large");
+    assert!(dag.execute(false, None).get("y").is_none());
+}
+
+#[test]
+fn test_validators_apply_inside_branches() {
+    let build = |x: f64| {
+        let mut graph = Graph::new();
+        graph.constant("x", x);
+        let mut sub = Graph::new();
+        sub.add(
+            |inputs: &HashMap<String, Arc<GraphData>>| {
+                HashMap::from([("y".to_string(), GraphData::float(inputs["x"].as_float().unwrap().sqrt()))])
+            },
+            Some("Root"),
+            Some(vec![("x", "x")]),
+            Some(vec![("y", "root")]),
+        );
+        sub.validate("x", |d: &GraphData| match d.as_float() {
+            Some(x) if x >= 0.0 => Ok(()),
+            _ => Err("expected a non-negative number"),
+        });
+        let branch = graph.branch(sub);
+        (graph.build(), branch)
+    };
+
+    let (dag, branch) = build(4.0);
+    let result = dag.execute_detailed(false, None);
+    assert_eq!(result.get_from_branch(branch, "root").and_then(|d| d.as_float()), Some(2.0));
+
+    let (dag, branch) = build(-1.0);
+    let result = dag.execute_detailed(false, None);
+    assert!(result.get_from_branch(branch, "root").is_none());
+    assert!(!result.errors.is_empty());
+    for error in &result.errors {
+        assert!(matches!(error, ExecutionError::InvalidInput { label, port, .. } if label == "Root" && port == "x"));
+    }
+}
+
+#[test]
+fn test_constant_node() {
+    let mut graph = Graph::new();
+    let data = graph.constant("data", GraphData::int(100));
+    graph.constant("offset", 5i64);
+    graph.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("y".to_string(), GraphData::int(inputs["x"].as_int().unwrap() + inputs["o"].as_int().unwrap()));
+            out
+        },
+        Some("Add"),
+        Some(vec![("data", "x"), ("offset", "o")]),
+        Some(vec![("y", "result")]),
+    );
+
+    let dag = graph.build();
+    assert_eq!(dag.nodes()[0].display_name(), "data");
+    let result = dag.execute_detailed(false, None);
+    assert_eq!(result.context.get_int("result"), Some(105));
+    assert_eq!(result.get_from_node(data, "data").and_then(|v| v.as_int()), Some(100));
+}
+
+#[test]
+fn test_node_metadata_query_and_stats() {
+    let mut graph = Graph::new();
+    let load = graph.constant("raw", GraphData::int(1));
+    graph.with_metadata("tag", "io").with_metadata("owner", "team-x");
+    graph
+        .push(
+            |inputs: &HashMap<String, Arc<GraphData>>| {
+                inputs.iter().map(|(k, v)| (k.clone(), GraphData::clone(v))).collect()
+            },
+            Some("Process"),
+            Some(vec![("raw", "raw")]),
+            Some(vec![("raw", "processed")]),
+        )
+        .with_metadata("owner", "team-x")
+        .with_description("Passes the samples through");
+    let save = graph.add(
+        |_: &HashMap<String, Arc<GraphData>>| HashMap::new(),
+        Some("Save"),
+        Some(vec![("processed", "x")]),
+        None,
+    );
+    graph.with_metadata("tag", "io");
+
+    let dag = graph.build();
+    assert_eq!(dag.nodes_with_metadata("tag", "io"), vec![load.id(), save.id()]);
+    assert_eq!(dag.nodes_with_metadata("owner", "team-x").len(), 2);
+    assert!(dag.nodes_with_metadata("tag", "compute").is_empty());
+    assert_eq!(dag.nodes()[1].description.as_deref(), Some("Passes the samples through"));
+
+    let stats = dag.stats();
+    assert_eq!(stats.metadata_counts["tag=io"], 2);
+    assert!(stats.summary().contains("Metadata owner=team-x: 2 nodes"));
+}
+
+// ─── Lazy values ──────────────────────────────────────────────────────────────
+
+#[test]
+fn test_lazy_outputs_only_computed_when_read() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    let computed = Arc::new(AtomicUsize::new(0));
+
+    let mut graph = Graph::new();
+    let counter = Arc::clone(&computed);
+    graph.add(
+        move |_: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            for (name, value) in [("small", 1), ("large", 2)] {
+                let counter = Arc::clone(&counter);
+                out.insert(
+                    name.to_string(),
+                    GraphData::lazy(move || {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        GraphData::int(value)
+                    }),
+                );
+            }
+            out
+        },
+        Some("Source"),
+        None,
+        Some(vec![("small", "small"), ("large", "large")]),
+    );
+    graph.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("y".to_string(), GraphData::int(inputs["x"].as_int().unwrap() * 10));
+            out
+        },
+        Some("Consumer"),
+        Some(vec![("small", "x")]),
+        Some(vec![("y", "result")]),
+    );
+
+    let context = graph.build().execute(false, None);
+    assert_eq!(context["result"].as_int(), Some(10));
+    assert_eq!(computed.load(Ordering::SeqCst), 1);
+}
+
+// ─── Build warnings ───────────────────────────────────────────────────────────
+
+#[test]
+fn test_build_warnings_report_unproduced_inputs() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.add(processor, Some("Process"), Some(vec![("dta", "input_data")]), Some(vec![("processed_value", "result")]));
+    let dag = graph.build();
+
+    let warnings = dag.build_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0],
+        BuildWarning::UnsatisfiedInput {
+            node_id: 1,
+            label: "Process".to_string(),
+            broadcast_var: "dta".to_string(),
+            suggestion: Some("data".to_string()),
+        }
+    );
+    assert_eq!(
+        warnings[0].to_string(),
+        "node 1 (Process) reads 'dta', which no node produces (did you mean 'data'?)"
+    );
+}
+
+#[test]
+fn test_build_warnings_empty_for_wired_branches_and_merge() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    let b1 = graph.branch(scaled_branch("A", 2, "scaled"));
+    let b2 = graph.branch(scaled_branch("B", 3, "scaled"));
+    graph.merge(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let a = inputs.get("a").and_then(|d| d.as_int()).unwrap_or(0);
+            let b = inputs.get("b").and_then(|d| d.as_int()).unwrap_or(0);
+            let mut out = HashMap::new();
+            out.insert("sum".to_string(), GraphData::int(a + b));
+            out
+        },
+        Some("Merge"),
+        vec![(b1, "scaled", "a"), (b2, "scaled", "b")],
+        Some(vec![("sum", "total")]),
+    );
+    assert!(graph.build().build_warnings().is_empty());
+}
+
+// ─── Per-node config ──────────────────────────────────────────────────────────
+
+fn scale_by_config(inputs: &HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
+    let x = inputs.get("x").and_then(|d| d.as_int()).unwrap_or(0);
+    let factor = inputs.get("factor").and_then(|d| d.as_int()).unwrap_or(1);
+    let mut out = HashMap::new();
+    out.insert("y".to_string(), GraphData::int(x * factor));
+    out
+}
+
+#[test]
+fn test_with_config_instantiates_function_with_different_settings() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph
+        .push(scale_by_config, Some("Double"), Some(vec![("data", "x")]), Some(vec![("y", "doubled")]))
+        .with_config("factor", GraphData::int(2));
+    graph
+        .push(scale_by_config, Some("Triple"), Some(vec![("data", "x")]), Some(vec![("y", "tripled")]))
+        .with_config("factor", GraphData::int(3));
+    let dag = graph.build();
+
+    let context = dag.execute(false, None);
+    assert_eq!(context.get("doubled").and_then(|d| d.as_int()), Some(200));
+    assert_eq!(context.get("tripled").and_then(|d| d.as_int()), Some(300));
+
+    // Configuration is part of a node's identity
+    let mut other = Graph::new();
+    other.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    other
+        .push(scale_by_config, Some("Double"), Some(vec![("data", "x")]), Some(vec![("y", "doubled")]))
+        .with_config("factor", GraphData::int(4));
+    other
+        .push(scale_by_config, Some("Triple"), Some(vec![("data", "x")]), Some(vec![("y", "tripled")]))
+        .with_config("factor", GraphData::int(3));
+    let diff = dag.diff(&other.build());
+    assert_eq!(diff.changed_nodes.len(), 1);
+    assert_eq!(diff.changed_nodes[0].label, "Double");
+}
+
+#[test]
+fn test_mapped_input_takes_precedence_over_config() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.add(
+        |_: &HashMap<String, Arc<GraphData>>| {
+            let mut out = HashMap::new();
+            out.insert("k".to_string(), GraphData::int(5));
+            out
+        },
+        Some("Factor"),
+        None,
+        Some(vec![("k", "k")]),
+    );
+    graph
+        .push(scale_by_config, Some("Scale"), Some(vec![("data", "x"), ("k", "factor")]), Some(vec![("y", "out")]))
+        .with_config("factor", GraphData::int(3));
+    let context = graph.build().execute(false, None);
+    assert_eq!(context.get("out").and_then(|d| d.as_int()), Some(500));
+}
+
+// ─── poll_execute ─────────────────────────────────────────────────────────────
+
+#[test]
+fn test_poll_execute_advances_one_slice_at_a_time() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.add(processor, Some("Process"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "result")]));
+    graph.add(adder, Some("Add"), Some(vec![("result", "input")]), Some(vec![("sum", "final")]));
+    let dag = graph.build();
+
+    let mut run = dag.start_execution();
+    assert_eq!(run.total_nodes(), 3);
+
+    // A zero budget still makes progress: exactly one node per poll.
+    assert_eq!(dag.poll_execute(&mut run, Duration::ZERO), ExecutionPoll::Pending);
+    assert_eq!(run.completed_nodes(), 1);
+    assert_eq!(run.result().get("data").and_then(|d| d.as_int()), Some(100));
+    assert_eq!(dag.poll_execute(&mut run, Duration::ZERO), ExecutionPoll::Pending);
+    assert_eq!(dag.poll_execute(&mut run, Duration::ZERO), ExecutionPoll::Complete);
+    assert!(run.is_complete());
+    assert_eq!(run.into_result().get("final").and_then(|d| d.as_int()), Some(210));
+}
+
+#[test]
+fn test_poll_execute_large_budget_completes_in_one_call() {
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.add(processor, Some("Process"), Some(vec![("data", "input_data")]), Some(vec![("processed_value", "result")]));
+    let dag = graph.build();
+
+    let mut run = dag.start_execution();
+    assert_eq!(dag.poll_execute(&mut run, Duration::from_secs(60)), ExecutionPoll::Complete);
+    assert_eq!(run.result().get("result").and_then(|d| d.as_int()), Some(200));
+}
+
+// ─── Execution plan cache ─────────────────────────────────────────────────────
+
+/// Two independent sources and their sum
+fn two_sources() -> Graph {
+    sources_with_a(1)
+}
+
+#[test]
+fn test_build_cached_reuses_saved_plan() {
+    let path = std::env::temp_dir().join(format!("dagex_plan_{}.plan", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let dag = two_sources().build_cached(&path).unwrap();
+    assert_eq!(dag.execution_order(), [0, 1, 2]);
+    assert_eq!(ExecutionPlan::load(&path).unwrap(), dag.execution_plan());
+
+    // A plan for the same topology is taken as is, without sorting the graph again
+    let text = std::fs::read_to_string(&path).unwrap().replace("order 0 1 2", "order 1 0 2");
+    std::fs::write(&path, text).unwrap();
+    let dag = two_sources().build_cached(&path).unwrap();
+    assert_eq!(dag.execution_order(), [1, 0, 2]);
+    assert_eq!(dag.execute(false, None).get_int("sum"), Some(3));
+
+    // A changed graph is analyzed again and its plan replaces the stale one
+    let mut graph = two_sources();
+    graph.add(processor, Some("Double"), Some(vec![("sum", "input_data")]), Some(vec![("processed_value", "out")]));
+    let dag = graph.build_cached(&path).unwrap();
+    assert_eq!(dag.execution_order(), [0, 1, 2, 3]);
+    assert_eq!(ExecutionPlan::load(&path).unwrap().topology_hash(), dag.topology_hash());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_plans_of_other_topologies_are_ignored() {
+    let plan = two_sources().build().execution_plan();
+    assert_eq!(plan.execution_levels(), [vec![0, 1], vec![2]]);
+    assert_eq!(two_sources().build().topology_hash(), plan.topology_hash());
+
+    let mut graph = two_sources();
+    graph.add(processor, Some("Double"), Some(vec![("sum", "input_data")]), Some(vec![("processed_value", "out")]));
+    let dag = graph.build_with_plan(&plan);
+    assert_ne!(dag.topology_hash(), plan.topology_hash());
+    assert_eq!(dag.execution_levels(), [vec![0, 1], vec![2], vec![3]]);
+
+    let path = std::env::temp_dir().join(format!("dagex_not_a_plan_{}.plan", std::process::id()));
+    std::fs::write(&path, "order 0 1 2\n").unwrap();
+    assert_eq!(ExecutionPlan::load(&path).unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    std::fs::remove_file(&path).unwrap();
+}
+
+// ─── Storage backends ─────────────────────────────────────────────────────────
+
+#[test]
+fn test_file_storage_stores_nested_keys() {
+    let root = std::env::temp_dir().join(format!("dagex_storage_{}", std::process::id()));
+    let storage = FileStorage::new(&root);
+    assert_eq!(storage.list("").unwrap(), Vec::<String>::new());
+    assert_eq!(storage.get("missing").unwrap(), None);
+
+    storage.put("sweep/a.json", b"1").unwrap();
+    storage.put("sweep/b.json", b"2").unwrap();
+    storage.put("plan", b"3").unwrap();
+    storage.put("sweep/a.json", b"4").unwrap();
+    assert_eq!(storage.get("sweep/a.json").unwrap(), Some(b"4".to_vec()));
+    assert_eq!(storage.list("sweep/").unwrap(), ["sweep/a.json", "sweep/b.json"]);
+    assert_eq!(storage.list("").unwrap(), ["plan", "sweep/a.json", "sweep/b.json"]);
+
+    // Keys cannot reach outside the root
+    for key in ["", "../escape", "/etc/passwd", "sweep/../../escape"] {
+        assert_eq!(storage.put(key, b"x").unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+    }
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_build_cached_in_memory_storage() {
+    let storage = MemoryStorage::new();
+    let dag = two_sources().build_cached_in(&storage, "plans/two_sources").unwrap();
+    assert_eq!(storage.list("plans/").unwrap(), ["plans/two_sources"]);
+    assert_eq!(ExecutionPlan::load_from(&storage, "plans/two_sources").unwrap(), dag.execution_plan());
+
+    let text = String::from_utf8(storage.get("plans/two_sources").unwrap().unwrap()).unwrap();
+    storage.put("plans/two_sources", text.replace("order 0 1 2", "order 1 0 2").as_bytes()).unwrap();
+    assert_eq!(two_sources().build_cached_in(&storage, "plans/two_sources").unwrap().execution_order(), [1, 0, 2]);
+
+    assert_eq!(ExecutionPlan::load_from(&storage, "plans/other").unwrap_err().kind(), std::io::ErrorKind::NotFound);
+    storage.put("plans/other", b"order 0 1 2\n").unwrap();
+    assert_eq!(ExecutionPlan::load_from(&storage, "plans/other").unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+}
+
+// ─── File I/O nodes ───────────────────────────────────────────────────────────
+
+/// Path of a scratch file unique to this process
+fn scratch_file(name: &str) -> String {
+    std::env::temp_dir().join(format!("dagex_{}_{}", std::process::id(), name)).to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_csv_nodes_round_trip_columns() {
+    let path = scratch_file("columns.csv");
+    let mut graph = Graph::new();
+    graph.constant("file", GraphData::string(path.clone()));
+    graph.constant("t", GraphData::float_vec(vec![0.0, 0.5, f64::NAN]));
+    graph.constant("n", GraphData::int_vec(vec![1, 2, 3]));
+    graph.constant(
+        "tag",
+        GraphData::list(vec![GraphData::string("a, b"), GraphData::string("say \"hi\""), GraphData::string("c\r\nd")]),
+    );
+    graph.add(
+        write_csv(&["time", "count", "tag"]),
+        Some("Save"),
+        Some(vec![("file", "path"), ("t", "time"), ("n", "count"), ("tag", "tag")]),
+        Some(vec![("path", "saved")]),
+    );
+    graph.add(
+        read_csv(),
+        Some("Load"),
+        Some(vec![("saved", "path")]),
+        Some(vec![("time", "time"), ("count", "count"), ("tag", "tags")]),
+    );
+    let context = graph.build().execute(false, None);
+
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "time,count,tag\n0,1,\"a, b\"\n0.5,2,\"say \"\"hi\"\"\"\n,3,\"c\r\nd\"\n"
+    );
+    let time = context.get("time").unwrap().as_float_slice().unwrap();
+    assert_eq!(&time[..2], [0.0, 0.5]);
+    assert!(time[2].is_nan());
+    assert_eq!(context.get("count").unwrap().as_float_slice(), Some(&[1.0, 2.0, 3.0][..]));
+    let tags: Vec<_> = context.get("tags").unwrap().as_list().unwrap().iter().map(|t| t.as_string().unwrap()).collect();
+    assert_eq!(tags, ["a, b", "say \"hi\"", "c\r\nd"]);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_float_file_nodes_round_trip_and_report_errors() {
+    let path = scratch_file("samples.f64");
+    let mut graph = Graph::new();
+    graph.constant("file", GraphData::string(path.clone()));
+    graph.constant("missing", GraphData::string(scratch_file("missing.f64")));
+    graph.constant("samples", GraphData::float_vec(vec![1.5, -2.0, 1e300]));
+    graph.add(write_floats(), Some("Save"), Some(vec![("file", "path"), ("samples", "data")]), Some(vec![("path", "saved")]));
+    graph.add(read_floats(), Some("Load"), Some(vec![("saved", "path")]), Some(vec![("data", "loaded")]));
+    graph.add(
+        read_floats(),
+        Some("Load missing"),
+        Some(vec![("missing", "path")]),
+        Some(vec![("data", "nothing"), ("error", "load_error")]),
+    );
+    let context = graph.build().execute(false, None);
+
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 24);
+    assert_eq!(context.get("loaded").unwrap().as_float_slice(), Some(&[1.5, -2.0, 1e300][..]));
+    assert!(!context.contains_key("nothing"));
+    assert!(context.get_str("load_error").unwrap().starts_with("cannot read"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_file_nodes_round_trip_documents() {
+    let path = scratch_file("config.json");
+    let mut graph = Graph::new();
+    graph.constant("file", GraphData::string(path.clone()));
+    graph.constant("config", GraphData::json(serde_json::json!({"gain": 2.5, "channels": [1, 2]})));
+    graph.add(write_json(), Some("Save"), Some(vec![("file", "path"), ("config", "data")]), Some(vec![("path", "saved")]));
+    graph.add(read_json(), Some("Load"), Some(vec![("saved", "path")]), Some(vec![("data", "loaded")]));
+    let context = graph.build().execute(false, None);
+
+    assert_eq!(context.get("loaded").unwrap().as_json().unwrap()["channels"][1], 2);
+    std::fs::remove_file(&path).unwrap();
+}
+
+// ─── std_nodes ────────────────────────────────────────────────────────────────
+
+#[test]
+fn test_std_arithmetic_and_formatting_nodes() {
+    let mut graph = Graph::new();
+    graph.constant("n", GraphData::int(7));
+    graph.constant("m", GraphData::int(7));
+    graph.constant("gain", GraphData::float(0.5));
+    graph.constant("signal", GraphData::float_vec(vec![2.0, -4.0, 10.0]));
+    graph.add_shared(
+        std_nodes::add(),
+        Some("Sum"),
+        Some(vec![("n", "a"), ("m", "b")]),
+        Some(vec![("result", "twice")]),
+    );
+    graph.add_shared(
+        std_nodes::divide(),
+        Some("Half"),
+        Some(vec![("n", "a"), ("twice", "b")]),
+        Some(vec![("result", "half")]),
+    );
+    graph.add_shared(
+        std_nodes::multiply(),
+        Some("Gain"),
+        Some(vec![("signal", "a"), ("gain", "b")]),
+        Some(vec![("result", "scaled")]),
+    );
+    graph.add_shared(
+        std_nodes::clamp(-1.0, 3.0),
+        Some("Clamp"),
+        Some(vec![("scaled", "x")]),
+        Some(vec![("result", "clamped")]),
+    );
+    graph.add_shared(
+        std_nodes::threshold(0.0),
+        Some("Sign"),
+        Some(vec![("clamped", "x")]),
+        Some(vec![("result", "positive")]),
+    );
+    graph.add_shared(
+        std_nodes::format("{n} doubled is {twice}, {{ok}}"),
+        Some("Describe"),
+        Some(vec![("n", "n"), ("twice", "twice")]),
+        Some(vec![("result", "text")]),
+    );
+    graph.add_shared(
+        std_nodes::subtract(),
+        Some("Bad"),
+        Some(vec![("text", "a"), ("n", "b")]),
+        Some(vec![("result", "nonsense"), ("error", "error")]),
+    );
+    let context = graph.build().execute(false, None);
+
+    assert_eq!(context.get_int("twice"), Some(14));
+    assert_eq!(context.get_f64("half"), Some(0.5));
+    assert_eq!(context.get("scaled").unwrap().as_float_slice(), Some(&[1.0, -2.0, 5.0][..]));
+    assert_eq!(context.get("clamped").unwrap().as_float_slice(), Some(&[1.0, -1.0, 3.0][..]));
+    assert_eq!(context.get("positive").unwrap().as_int_slice(), Some(&[1, 0, 1][..]));
+    assert_eq!(context.get_str("text"), Some("7 doubled is 14, {ok}"));
+    assert!(!context.contains_key("nonsense"));
+    assert!(context.get_str("error").unwrap().starts_with("cannot subtract"));
+}
+
+#[test]
+fn test_std_stateful_nodes_keep_state_across_executions() {
+    let mut graph = Graph::new();
+    graph.add_shared(
+        std_nodes::random_uniform(1.0, 2.0, 7),
+        Some("Draw"),
+        None,
+        Some(vec![("result", "x")]),
+    );
+    graph.add_shared(
+        std_nodes::running_sum(),
+        Some("Total"),
+        Some(vec![("x", "x")]),
+        Some(vec![("result", "total"), ("count", "count")]),
+    );
+    graph.add_shared(
+        std_nodes::history(2),
+        Some("Recent"),
+        Some(vec![("x", "x")]),
+        Some(vec![("result", "recent")]),
+    );
+    let dag = graph.build();
+
+    let runs: Vec<_> = (0..3).map(|_| dag.execute(false, None)).collect();
+    let draws: Vec<f64> = runs.iter().map(|c| c.get_f64("x").unwrap()).collect();
+    assert!(draws.iter().all(|x| (1.0..2.0).contains(x)));
+    assert_ne!(draws[0], draws[1]);
+    assert_eq!(runs[2].get_int("count"), Some(3));
+    assert!((runs[2].get_f64("total").unwrap() - draws.iter().sum::<f64>()).abs() < 1e-12);
+    assert_eq!(runs[2].get("recent").unwrap().as_float_slice(), Some(&draws[1..]));
+
+    // The same seed gives the same sequence
+    let again = std_nodes::random_normal(0.0, 1.0, 3);
+    let other = std_nodes::random_normal(0.0, 1.0, 3);
+    let n = HashMap::from([("n".to_string(), Arc::new(GraphData::int(4)))]);
+    assert_eq!(again(&n)["result"].as_float_slice(), other(&n)["result"].as_float_slice());
+    assert_eq!(again(&n)["result"].as_float_slice().unwrap().len(), 4);
+}
+
+#[test]
+#[should_panic(expected = "random_uniform range must be finite with low < high")]
+fn test_random_uniform_rejects_an_empty_range() {
+    std_nodes::random_uniform(2.0, 2.0, 7);
+}
+
+// ─── Output hashes ────────────────────────────────────────────────────────────
+
+fn sources_with_a(a: i64) -> Graph {
+    let mut graph = Graph::new();
+    graph.constant("a", GraphData::int(a));
+    graph.constant("b", GraphData::int(2));
+    graph.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let sum = inputs["a"].as_int().unwrap() + inputs["b"].as_int().unwrap();
+            HashMap::from([("sum".to_string(), GraphData::int(sum))])
+        },
+        Some("Sum"),
+        Some(vec![("a", "a"), ("b", "b")]),
+        Some(vec![("sum", "sum")]),
+    );
+    graph
+}
+
+#[test]
+fn test_output_hashes_match_between_identical_runs() {
+    let dag = sources_with_a(1).build();
+    let first = dag.execute_hashed(false, None);
+    let second = dag.execute_hashed(true, None);
+    assert_eq!(first.output_hashes.len(), 3);
+    assert_eq!(first.output_hashes, second.output_hashes);
+    assert!(first.differing_outputs(&second).is_empty());
+
+    // Plain timed runs record no hashes
+    assert!(dag.execute_timed(false, None).output_hashes.is_empty());
+}
+
+#[test]
+fn test_differing_outputs_names_nodes_downstream_of_a_change() {
+    let before = sources_with_a(1).build().execute_hashed(false, None);
+    let after = sources_with_a(5).build().execute_hashed(false, None);
+    // Node 0 produces "a", node 1 "b", node 2 the sum
+    assert_eq!(before.differing_outputs(&after), vec![0, 2]);
+    assert_eq!(before.output_hash(1), after.output_hash(1));
+}
+
+#[test]
+fn test_content_hash_of_graph_data() {
+    let map = |pairs: &[(&str, i64)]| {
+        GraphData::map(pairs.iter().map(|(k, v)| (k.to_string(), GraphData::int(*v))).collect())
+    };
+    assert_eq!(map(&[("x", 1), ("y", 2)]).content_hash(), map(&[("y", 2), ("x", 1)]).content_hash());
+    assert_ne!(GraphData::int(1).content_hash(), GraphData::float(1.0).content_hash());
+    assert_ne!(
+        GraphData::float_vec(vec![1.0, 2.0]).content_hash(),
+        GraphData::float_vec(vec![2.0, 1.0]).content_hash()
+    );
+    assert_eq!(GraphData::custom(42u32).content_hash(), None);
+    assert_eq!(GraphData::list(vec![GraphData::int(1), GraphData::custom(42u32)]).content_hash(), None);
+
+    let mut graph = Graph::new();
+    graph.add(
+        |_: &HashMap<String, Arc<GraphData>>| HashMap::from([("v".to_string(), GraphData::custom(7u8))]),
+        Some("Opaque"),
+        None,
+        Some(vec![("v", "v")]),
+    );
+    assert_eq!(graph.build().execute_hashed(false, None).output_hash(0), None);
+}
+
+// ─── Fingerprint ──────────────────────────────────────────────────────────────
+
+#[test]
+fn test_fingerprint_tracks_wiring_not_functions() {
+    type NodeFn = fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData>;
+    let pipeline = |output: &str, label: &str, node: NodeFn| {
+        let mut graph = Graph::new();
+        graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+        graph.add(node, Some(label), Some(vec![("data", "input_data")]), Some(vec![("processed_value", output)]));
+        graph.build()
+    };
+    let doubled = |inputs: &HashMap<String, Arc<GraphData>>| processor(inputs);
+
+    let base = pipeline("result", "Process", processor).fingerprint();
+    assert_eq!(base, pipeline("result", "Process", processor).fingerprint());
+    // Other functions and labels keep the structure
+    assert_eq!(base, pipeline("result", "Renamed", doubled).fingerprint());
+    // Other mappings do not
+    assert_ne!(base, pipeline("other", "Process", processor).fingerprint());
+
+    // Declared interface and variant parameters are part of the structure
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.add(
+        processor,
+        Some("Process"),
+        Some(vec![("data", "input_data")]),
+        Some(vec![("processed_value", "result")]),
+    );
+    graph.output("result");
+    assert_ne!(base, graph.build().fingerprint());
+
+    let sweep = |factors: Vec<i64>| {
+        let mut graph = Graph::new();
+        graph.variant("factor", factors, adder, Some("Sweep"), None, Some(vec![("sum", "y")]));
+        graph.build().fingerprint()
+    };
+    assert_eq!(sweep(vec![1, 2]), sweep(vec![1, 2]));
+    assert_ne!(sweep(vec![1, 2]), sweep(vec![1, 3]));
+}
+
+// ─── Run manifest ─────────────────────────────────────────────────────────────
+
+#[test]
+fn test_manifest_records_inputs_variants_timings_and_hashes() {
+    let mut graph = Graph::new();
+    graph.input("offset", 3);
+    graph.variant(
+        "factor",
+        vec![2, 5],
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let y = inputs["x"].as_int().unwrap() * inputs["factor"].as_int().unwrap();
+            HashMap::from([("y".to_string(), GraphData::int(y))])
+        },
+        Some("Scale"),
+        Some(vec![("offset", "x")]),
+        Some(vec![("y", "y")]),
+    );
+    let dag = graph.build();
+    let report = dag.execute_hashed(false, None);
+
+    let manifest: serde_json::Value = serde_json::from_str(&report.manifest()).unwrap();
+    assert_eq!(manifest["graph_fingerprint"], format!("{:016x}", dag.fingerprint()));
+    assert_eq!(manifest["inputs"]["offset"], "Int(3)");
+    assert_eq!(manifest["variant_grids"]["factor"], serde_json::json!(["Int(2)", "Int(5)"]));
+
+    let variants = manifest["variants"].as_array().unwrap();
+    assert_eq!(variants.len(), 2);
+    assert_eq!(variants[1]["params"]["factor"], "Int(5)");
+    assert_eq!(variants[1]["variant_path"], serde_json::json!([1]));
+
+    let nodes = manifest["nodes"].as_array().unwrap();
+    assert_eq!(nodes.len(), report.timings.len());
+    for node in nodes {
+        let id = node["node_id"].as_u64().unwrap() as usize;
+        assert_eq!(node["output_hash"], format!("{:016x}", report.output_hash(id).unwrap()));
+        assert!(node["end_us"].as_u64() >= node["start_us"].as_u64());
+    }
+
+    // Without hashes the entries are null
+    let manifest: serde_json::Value = serde_json::from_str(&dag.execute_timed(false, None).manifest()).unwrap();
+    assert!(manifest["nodes"][0]["output_hash"].is_null());
+}
+
+// ─── Middleware ───────────────────────────────────────────────────────────────
+
+#[test]
+fn test_middleware_wraps_every_node_function() {
+    #[derive(Default)]
+    struct Record(std::sync::Mutex<Vec<String>>);
+
+    impl Middleware for Record {
+        fn before(&self, node: &Node, _: &mut HashMap<String, Arc<GraphData>>) {
+            self.0.lock().unwrap().push(format!("before {}", node.display_name()));
+        }
+        fn after(&self, node: &Node, _: &mut HashMap<String, Arc<GraphData>>) {
+            self.0.lock().unwrap().push(format!("after {}", node.display_name()));
+        }
+    }
+
+    // Converts inputs given in kHz to Hz, and tags every output it sees
+    struct Convert;
+
+    impl Middleware for Convert {
+        fn before(&self, _: &Node, inputs: &mut HashMap<String, Arc<GraphData>>) {
+            if let Some(khz) = inputs.get("input_data").and_then(|d| d.as_int()) {
+                inputs.insert("input_data".to_string(), Arc::new(GraphData::int(khz * 1000)));
+            }
+        }
+        fn after(&self, _: &Node, outputs: &mut HashMap<String, Arc<GraphData>>) {
+            outputs.insert("tagged".to_string(), Arc::new(GraphData::bool(true)));
+        }
+    }
+
+    let mut graph = Graph::new();
+    graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
+    graph.add(
+        processor,
+        Some("Process"),
+        Some(vec![("data", "input_data")]),
+        Some(vec![("processed_value", "result"), ("tagged", "tagged")]),
+    );
+    let mut dag = graph.build();
+    let record = Arc::new(Record::default());
+    dag.add_middleware(Arc::clone(&record));
+    dag.add_middleware(Convert);
+
+    for parallel in [false, true] {
+        record.0.lock().unwrap().clear();
+        let context = dag.execute(parallel, None);
+        assert_eq!(context.get("result").and_then(|d| d.as_int()), Some(200_000));
+        assert_eq!(context.get("tagged").and_then(|d| d.as_bool()), Some(true));
+        assert_eq!(
+            *record.0.lock().unwrap(),
+            ["before Source", "after Source", "before Process", "after Process"]
+        );
+    }
+}
+
+// ─── Watchdog ─────────────────────────────────────────────────────────────────
+
+#[test]
+fn test_watchdog_reports_nodes_much_slower_than_usual() {
+    let stall = Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let mut graph = Graph::new();
+    let slow = Arc::clone(&stall);
+    graph.add(
+        move |_: &HashMap<String, Arc<GraphData>>| {
+            let stalled = slow.load(std::sync::atomic::Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(if stalled { 400 } else { 5 }));
+            HashMap::from([("x".to_string(), GraphData::int(1))])
+        },
+        Some("Fetch"),
+        None,
+        Some(vec![("x", "x")]),
+    );
+    let mut dag = graph.build();
+
+    let hung = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let reports = Arc::clone(&hung);
+    let watchdog = Arc::new(Watchdog::new(5.0, Duration::from_millis(10), move |node: &HungNode| {
+        reports.lock().unwrap().push(node.clone());
+    }));
+    dag.add_middleware(Arc::clone(&watchdog));
+
+    // The first runs build the history and are never reported
+    for _ in 0..3 {
+        dag.execute(false, None);
+    }
+    assert!(hung.lock().unwrap().is_empty());
+    assert!(watchdog.average(0).unwrap() < Duration::from_millis(100));
+
+    stall.store(true, std::sync::atomic::Ordering::SeqCst);
+    dag.execute(true, None);
+    let hung = hung.lock().unwrap();
+    assert_eq!(hung.len(), 1);
+    assert_eq!((hung[0].node_id, hung[0].label.as_str()), (0, "Fetch"));
+    assert!(hung[0].running_for > hung[0].average * 5);
+}
+
+#[test]
+fn test_watchdog_forgets_nodes_that_panicked() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let mut graph = Graph::new();
+    let counter = Arc::clone(&runs);
+    graph.add(
+        move |_: &HashMap<String, Arc<GraphData>>| {
+            // The second run panics, like a job caught by an `ExecutorPool`
+            assert_ne!(counter.fetch_add(1, Ordering::SeqCst), 1, "flaky node");
+            std::thread::sleep(Duration::from_millis(20));
+            HashMap::from([("x".to_string(), GraphData::int(1))])
+        },
+        Some("Flaky"),
+        None,
+        Some(vec![("x", "x")]),
+    );
+    let mut dag = graph.build();
+
+    let hung = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let reports = Arc::clone(&hung);
+    let watchdog = Arc::new(Watchdog::new(5.0, Duration::from_millis(5), move |node: &HungNode| {
+        reports.lock().unwrap().push(node.clone());
+    }));
+    dag.add_middleware(Arc::clone(&watchdog));
+
+    dag.execute(false, None);
+    let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| dag.execute(false, None)));
+    assert!(panicked.is_err());
+    std::thread::sleep(Duration::from_millis(300));
+    assert!(hung.lock().unwrap().is_empty());
+
+    // Later runs on the same thread are timed on their own
+    dag.execute(false, None);
+    assert!(watchdog.average(0).unwrap() < Duration::from_millis(100));
+    assert!(hung.lock().unwrap().is_empty());
+}
+
+// ─── Concurrent executions ────────────────────────────────────────────────────
+
+#[test]
+fn test_dag_executes_concurrently_from_several_threads() {
+    fn shared_between_threads<D: Send + Sync>(_: &D) {}
+
+    let mut graph = Graph::new();
+    graph.input("x", GraphData::int(0));
+    graph.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            std::thread::sleep(Duration::from_millis(5));
+            HashMap::from([("y".to_string(), GraphData::int(inputs["x"].as_int().unwrap() * 2))])
+        },
+        Some("Double"),
+        Some(vec![("x", "x")]),
+        Some(vec![("y", "doubled")]),
+    );
+    for offset in [1, 2] {
+        graph.add(
+            move |inputs: &HashMap<String, Arc<GraphData>>| {
+                HashMap::from([("z".to_string(), GraphData::int(inputs["y"].as_int().unwrap() + offset))])
+            },
+            Some("Offset"),
+            Some(vec![("doubled", "y")]),
+            Some(vec![("z", &*format!("plus{}", offset))]),
+        );
+    }
+    let dag = graph.build();
+    shared_between_threads(&dag);
+
+    let start = std::sync::Barrier::new(8);
+    std::thread::scope(|s| {
+        let runs: Vec<_> = (0..8i64)
+            .map(|x| {
+                let (dag, start) = (&dag, &start);
+                s.spawn(move || {
+                    start.wait();
+                    let inputs = HashMap::from([("x".to_string(), GraphData::int(x))]);
+                    (x, dag.execute_with_inputs(inputs, x % 2 == 0, None))
+                })
+            })
+            .collect();
+        for run in runs {
+            let (x, context) = run.join().unwrap();
+            assert_eq!(context.get_int("x"), Some(x));
+            assert_eq!(context.get_int("plus1"), Some(2 * x + 1));
+            assert_eq!(context.get_int("plus2"), Some(2 * x + 2));
+        }
+    });
+}
+
+// ─── ExecutorPool ─────────────────────────────────────────────────────────────
+
+#[test]
+fn test_executor_pool_reuses_workers_across_executions() {
+    let seen = Arc::new(std::sync::Mutex::new(std::collections::HashSet::new()));
+    let record = Arc::clone(&seen);
+    let mut graph = Graph::new();
+    graph.input("x", GraphData::int(0));
+    graph.add(
+        move |inputs: &HashMap<String, Arc<GraphData>>| {
+            record.lock().unwrap().insert(std::thread::current().id());
+            HashMap::from([("y".to_string(), GraphData::int(inputs["x"].as_int().unwrap() * 2))])
+        },
+        Some("Double"),
+        Some(vec![("x", "x")]),
+        Some(vec![("y", "y")]),
+    );
+    let dag = Arc::new(graph.build());
+
+    let pool = ExecutorPool::new(2);
+    assert_eq!(pool.threads(), 2);
+    let jobs: Vec<_> = (0..20)
+        .map(|i| pool.execute(&dag, HashMap::from([("x".to_string(), GraphData::int(i))])))
+        .collect();
+    assert!(jobs.windows(2).all(|pair| pair[0].id() < pair[1].id()));
+    for (i, job) in jobs.into_iter().enumerate() {
+        assert_eq!(job.wait().get_int("y"), Some(2 * i as i64));
+    }
+    assert!(seen.lock().unwrap().len() <= 2);
+    assert!(!seen.lock().unwrap().contains(&std::thread::current().id()));
+}
+
+#[test]
+fn test_executor_pool_reports_job_status() {
+    let pool = ExecutorPool::new(1);
+    let (release, gate) = std::sync::mpsc::channel::<()>();
+    let blocker = pool.spawn(move || gate.recv().unwrap());
+    let queued = pool.spawn(|| 42);
+
+    assert!(!blocker.wait_timeout(Duration::from_millis(20)));
+    assert_eq!(blocker.status(), JobStatus::Running);
+    assert_eq!(queued.status(), JobStatus::Queued);
+    assert_eq!((pool.running(), pool.queued()), (1, 1));
+
+    release.send(()).unwrap();
+    assert!(blocker.wait_timeout(Duration::from_secs(5)));
+    assert_eq!(queued.wait(), 42);
+    assert!(blocker.is_finished());
+}
+
+#[test]
+fn test_executor_pool_survives_panicking_jobs() {
+    let pool = ExecutorPool::new(1);
+    let failing = pool.spawn(|| panic!("job failed"));
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| failing.wait())).unwrap_err();
+    assert_eq!(panic.downcast_ref::<&str>(), Some(&"job failed"));
+    assert_eq!(pool.spawn(|| "still running").wait(), "still running");
+}
+
 // ─── DagStats::summary ────────────────────────────────────────────────────────
 
 #[test]
@@ -461,7 +3940,7 @@ fn build_linear_dag() -> Dag {
     // y = x * 2.0,  x ~ Normal(3, 1)
     let mut graph = Graph::new();
     graph.add(
-        |inputs: &HashMap<String, GraphData>| {
+        |inputs: &HashMap<String, Arc<GraphData>>| {
             let mut out = HashMap::new();
             if let Some(v) = inputs.get("x").and_then(|d| d.as_float()) {
                 out.insert("y".to_string(), GraphData::float(v * 2.0));
@@ -507,11 +3986,11 @@ fn test_predict_at_early_stop() {
     // Three-node chain: A -> B -> C
     let mut graph = Graph::new();
     graph.add(
-        |_: &HashMap<String, GraphData>| {
+        |_: &HashMap<String, Arc<GraphData>>| {
             let mut o = HashMap::new(); o.insert("a".to_string(), GraphData::float(1.0)); o
         }, Some("A"), None, Some(vec![("a", "a")]));
     graph.add(
-        |inputs: &HashMap<String, GraphData>| {
+        |inputs: &HashMap<String, Arc<GraphData>>| {
             let mut o = HashMap::new();
             if let Some(v) = inputs.get("a").and_then(|d| d.as_float()) {
                 o.insert("b".to_string(), GraphData::float(v + 10.0));
@@ -519,7 +3998,7 @@ fn test_predict_at_early_stop() {
             o
         }, Some("B"), Some(vec![("a", "a")]), Some(vec![("b", "b")]));
     graph.add(
-        |inputs: &HashMap<String, GraphData>| {
+        |inputs: &HashMap<String, Arc<GraphData>>| {
             let mut o = HashMap::new();
             if let Some(v) = inputs.get("b").and_then(|d| d.as_float()) {
                 o.insert("c".to_string(), GraphData::float(v + 100.0));
@@ -541,7 +4020,7 @@ fn test_predict_branch_id_target() {
     let mut graph = Graph::new();
     graph.add(data_source, Some("Source"), None, Some(vec![("raw_data", "data")]));
     let mut b = Graph::new();
-    b.add(|inputs: &HashMap<String, GraphData>| {
+    b.add(|inputs: &HashMap<String, Arc<GraphData>>| {
         let mut o = HashMap::new();
         if let Some(v) = inputs.get("x").and_then(|d| d.as_float()) {
             o.insert("br_out".to_string(), GraphData::float(v));
@@ -564,7 +4043,7 @@ fn test_predict_aligned() {
     // y = x + noise,  x ~ N(0,1), noise ~ N(0,0.1)
     let mut graph = Graph::new();
     graph.add(
-        |inputs: &HashMap<String, GraphData>| {
+        |inputs: &HashMap<String, Arc<GraphData>>| {
             let mut o = HashMap::new();
             if let Some(v) = inputs.get("x").and_then(|d| d.as_float()) {
                 o.insert("y".to_string(), GraphData::float(v * 2.0));
@@ -603,7 +4082,7 @@ fn test_predict_aligned() {
 #[test]
 fn test_stat_result_iter_and_accessors() {
     let mut graph = Graph::new();
-    graph.add(|_: &HashMap<String, GraphData>| {
+    graph.add(|_: &HashMap<String, Arc<GraphData>>| {
         let mut o = HashMap::new();
         o.insert("v".to_string(), GraphData::float(3.0));
         o
@@ -0,0 +1,45 @@
+#![cfg(feature = "log")]
+
+use dagex::{Graph, GraphData};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Three variants that each log their factor, and one node that fails loudly
+#[test]
+fn test_log_records_are_attached_to_the_node_that_emitted_them() {
+    dagex::install_log_capture(None, log::LevelFilter::Info).unwrap();
+
+    let mut graph = Graph::new();
+    graph.constant("x", GraphData::int(2));
+    graph.variant(
+        "factor",
+        vec![1, 2, 3],
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let factor = inputs["factor"].as_int().unwrap();
+            log::info!("scaling by {}", factor);
+            log::debug!("not enabled");
+            HashMap::from([("y".to_string(), GraphData::int(inputs["x"].as_int().unwrap() * factor))])
+        },
+        Some("Scale"),
+        Some(vec![("x", "x")]),
+        Some(vec![("y", "y")]),
+    );
+    let dag = graph.build();
+
+    for parallel in [false, true] {
+        let report = dag.execute_timed(parallel, None);
+        assert!(report.logs(0).is_empty());
+        for (i, variant) in report.variants.iter().enumerate() {
+            let logs = report.logs(variant.node_id);
+            assert_eq!(logs.len(), 1);
+            assert_eq!(logs[0].level, "INFO");
+            assert_eq!(logs[0].message, format!("scaling by {}", i + 1));
+            assert_eq!(logs[0].target, module_path!());
+        }
+    }
+
+    // Records outside node executions are not captured anywhere
+    log::warn!("between runs");
+    let report = dag.execute_timed(false, None);
+    assert_eq!(report.timings.iter().map(|t| t.logs.len()).sum::<usize>(), 3);
+}
@@ -1,5 +1,6 @@
 use dagex::{Graph, GraphData};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[test]
 fn test_minimal_pipeline_mermaid() {
@@ -7,7 +8,7 @@ fn test_minimal_pipeline_mermaid() {
 
     // Source node
     g.add(
-        |_: &HashMap<String, GraphData>| {
+        |_: &HashMap<String, Arc<GraphData>>| {
             let mut o = HashMap::new();
             o.insert("n".to_string(), GraphData::int(10));
             o
@@ -19,7 +20,7 @@ fn test_minimal_pipeline_mermaid() {
 
     // Double node
     g.add(
-        |inputs: &HashMap<String, GraphData>| {
+        |inputs: &HashMap<String, Arc<GraphData>>| {
             let mut o = HashMap::new();
             if let Some(v) = inputs.get("x").and_then(|d| d.as_int()) {
                 o.insert("y".to_string(), GraphData::int(v * 2));
@@ -51,7 +52,7 @@ fn test_processor_formatter_mermaid() {
 
     // Simple pipeline: Source -> Processor -> Formatter
     g.add(
-        |_: &HashMap<String, GraphData>| {
+        |_: &HashMap<String, Arc<GraphData>>| {
             let mut o = HashMap::new();
             o.insert("data".to_string(), GraphData::string("100".to_string()));
             o
@@ -62,7 +63,7 @@ fn test_processor_formatter_mermaid() {
     );
 
     g.add(
-        |inputs: &HashMap<String, GraphData>| {
+        |inputs: &HashMap<String, Arc<GraphData>>| {
             let mut o = HashMap::new();
             if let Some(v) = inputs.get("input").and_then(|d| d.as_string()) {
                 o.insert("result".to_string(), GraphData::string((v.parse::<i64>().unwrap_or(0) * 2).to_string()));
@@ -75,7 +76,7 @@ fn test_processor_formatter_mermaid() {
     );
 
     g.add(
-        |inputs: &HashMap<String, GraphData>| {
+        |inputs: &HashMap<String, Arc<GraphData>>| {
             let mut o = HashMap::new();
             if let Some(v) = inputs.get("value").and_then(|d| d.as_string()) {
                 o.insert("formatted".to_string(), GraphData::string(format!("Result: {}", v)));
@@ -94,3 +95,54 @@ fn test_processor_formatter_mermaid() {
     assert!(mermaid.contains("Formatter"), "mermaid missing 'Formatter': {}", mermaid);
     assert!(mermaid.contains("data → input") || mermaid.contains("data \u{2192} input"), "mermaid missing 'data → input': {}", mermaid);
 }
+
+#[test]
+fn test_ascii_lists_levels_and_edges() {
+    let mut g = Graph::new();
+
+    g.add(
+        |_: &HashMap<String, Arc<GraphData>>| {
+            let mut o = HashMap::new();
+            o.insert("n".to_string(), GraphData::int(10));
+            o
+        },
+        Some("Source"),
+        None,
+        Some(vec![("n", "x")]),
+    );
+    g.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let mut o = HashMap::new();
+            if let Some(v) = inputs.get("x").and_then(|d| d.as_int()) {
+                o.insert("y".to_string(), GraphData::int(v * 2));
+            }
+            o
+        },
+        Some("Double"),
+        Some(vec![("x", "x")]),
+        Some(vec![("y", "out")]),
+    );
+
+    let ascii = g.build().to_ascii();
+
+    assert_eq!(
+        ascii,
+        "Level 0\n└── [0] Source\nLevel 1\n└── [1] Double\n      ◄ [0] x → x\n",
+        "unexpected ascii rendering:\n{}",
+        ascii
+    );
+}
+
+#[test]
+fn test_mermaid_tooltips_show_description_and_metadata() {
+    let mut g = Graph::new();
+    g.constant("x", GraphData::int(1));
+    g.with_description("Reads \"raw\" samples").with_metadata("tag", "io");
+
+    let mermaid = g.build().to_mermaid();
+    assert!(
+        mermaid.contains("click 0 callback \"Reads #quot;raw#quot; samples; tag=io\""),
+        "mermaid missing tooltip: {}",
+        mermaid
+    );
+}
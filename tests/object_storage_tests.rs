@@ -0,0 +1,48 @@
+#![cfg(feature = "object_store")]
+
+use dagex::{ExecutionPlan, Graph, GraphData, ObjectStorage, StorageBackend};
+use object_store::memory::InMemory;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[test]
+fn test_object_storage_keeps_runs_apart_by_prefix() {
+    let store = Arc::new(InMemory::new());
+    let run1 = ObjectStorage::new(store.clone()).with_prefix("sweeps/run1");
+    let run2 = ObjectStorage::new(store).with_prefix("/sweeps/run2/");
+
+    run1.put("checkpoint.json", b"{}").unwrap();
+    run1.put("plans/a", b"1").unwrap();
+    run1.put("plans/ab", b"2").unwrap();
+    run2.put("plans/a", b"3").unwrap();
+
+    assert_eq!(run1.get("plans/a").unwrap(), Some(b"1".to_vec()));
+    assert_eq!(run2.get("plans/a").unwrap(), Some(b"3".to_vec()));
+    assert_eq!(run2.get("checkpoint.json").unwrap(), None);
+    assert_eq!(run1.list("").unwrap(), ["checkpoint.json", "plans/a", "plans/ab"]);
+    assert_eq!(run1.list("plans/a").unwrap(), ["plans/a", "plans/ab"]);
+    assert_eq!(run1.list("check").unwrap(), ["checkpoint.json"]);
+    assert_eq!(run2.list("missing/").unwrap(), Vec::<String>::new());
+    assert_eq!(run1.put("", b"x").unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_build_cached_in_object_storage() {
+    let graph = || {
+        let mut graph = Graph::new();
+        graph.constant("x", GraphData::int(2));
+        graph.add(
+            |inputs: &HashMap<String, Arc<GraphData>>| {
+                HashMap::from([("y".to_string(), GraphData::int(inputs["x"].as_int().unwrap() * 3))])
+            },
+            Some("Triple"),
+            Some(vec![("x", "x")]),
+            Some(vec![("y", "y")]),
+        );
+        graph
+    };
+    let storage = ObjectStorage::new(Arc::new(InMemory::new()));
+    let dag = graph().build_cached_in(&storage, "plan").unwrap();
+    assert_eq!(ExecutionPlan::load_from(&storage, "plan").unwrap(), dag.execution_plan());
+    assert_eq!(graph().build_cached_in(&storage, "plan").unwrap().execute(false, None).get_int("y"), Some(6));
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "queue")]
+
+use dagex::{CancelToken, ChannelQueue, Dag, Graph, GraphData, TaskQueue};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// `y = x * factor`, panicking for a negative `x`
+fn scale_graph(factor: i64) -> Dag {
+    let mut graph = Graph::new();
+    graph.input("x", GraphData::int(0));
+    graph.add(
+        move |inputs: &HashMap<String, Arc<GraphData>>| {
+            let x = inputs["x"].as_int().unwrap();
+            assert!(x >= 0, "negative x");
+            HashMap::from([("y".to_string(), GraphData::int(x * factor))])
+        },
+        Some("Scale"),
+        Some(vec![("x", "x")]),
+        Some(vec![("y", "y")]),
+    );
+    graph.output("y");
+    graph.build()
+}
+
+#[test]
+fn test_sweep_runs_on_queue_workers() {
+    let queue = ChannelQueue::new();
+    let dag = Arc::new(scale_graph(3));
+    let stop = CancelToken::new();
+    let workers: Vec<_> = (0..3)
+        .map(|_| {
+            let (dag, queue, stop) = (Arc::clone(&dag), queue.clone(), stop.clone());
+            std::thread::spawn(move || dag.serve_tasks(&queue, false, None, &stop).unwrap())
+        })
+        .collect();
+
+    let inputs = (0..10).map(|x| HashMap::from([("x".to_string(), GraphData::int(x))])).collect();
+    let results = dag.execute_on_queue(&queue, inputs, Duration::from_secs(10)).unwrap();
+    let ys: Vec<_> = results.iter().map(|r| r.as_ref().unwrap().get_int("y")).collect();
+    assert_eq!(ys, (0..10).map(|x| Some(x * 3)).collect::<Vec<_>>());
+    assert!(results.iter().all(|r| r.as_ref().unwrap().len() == 1));
+
+    // Failures come back as the task's error
+    let inputs = vec![
+        HashMap::from([("x".to_string(), GraphData::int(-1))]),
+        HashMap::from([("x".to_string(), GraphData::int(1))]),
+    ];
+    let results = dag.execute_on_queue(&queue, inputs, Duration::from_secs(10)).unwrap();
+    assert!(results[0].as_ref().unwrap_err().contains("negative x"));
+    assert_eq!(results[1].as_ref().unwrap().get_int("y"), Some(3));
+
+    // Workers refuse tasks for another graph
+    let mut other = Graph::new();
+    other.input("x", GraphData::int(0));
+    other.output("x");
+    let results = other.build().execute_on_queue(&queue, vec![HashMap::new()], Duration::from_secs(10)).unwrap();
+    assert!(results[0].as_ref().unwrap_err().contains("fingerprint mismatch"));
+
+    stop.cancel();
+    let served: usize = workers.into_iter().map(|w| w.join().unwrap()).sum();
+    assert_eq!(served, 13);
+}
+
+#[test]
+fn test_sweep_without_workers_times_out() {
+    let queue = ChannelQueue::new();
+    let error = scale_graph(2)
+        .execute_on_queue(&queue, vec![HashMap::new()], Duration::from_millis(50))
+        .unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::TimedOut);
+    assert!(queue.pop_task(Duration::ZERO).unwrap().is_some());
+}
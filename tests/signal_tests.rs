@@ -0,0 +1,49 @@
+#![cfg(feature = "signals")]
+
+use dagex::{Graph, GraphData, MemoryStorage, StorageBackend};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Node adding one to `input`, pressing Ctrl-C when it sees 2
+fn step(input: &str, output: &str) -> impl Fn(&HashMap<String, Arc<GraphData>>) -> HashMap<String, GraphData> {
+    let (input, output) = (input.to_string(), output.to_string());
+    move |inputs| {
+        let value = inputs[&input].as_int().unwrap();
+        if value == 2 {
+            signal_hook::low_level::raise(signal_hook::consts::SIGINT).unwrap();
+        }
+        HashMap::from([(output.clone(), GraphData::int(value + 1))])
+    }
+}
+
+/// Ctrl-C in the middle of a chain stops it after the running node
+#[test]
+fn test_interrupt_stops_the_run_and_writes_a_checkpoint() {
+    let mut graph = Graph::new();
+    graph.constant("a", GraphData::int(1));
+    graph.add(step("a", "b"), Some("First"), Some(vec![("a", "a")]), Some(vec![("b", "b")]));
+    graph.add(step("b", "c"), Some("Second"), Some(vec![("b", "b")]), Some(vec![("c", "c")]));
+    graph.add(step("c", "d"), Some("Third"), Some(vec![("c", "c")]), Some(vec![("d", "d")]));
+    let dag = graph.build();
+    let storage = MemoryStorage::new();
+
+    let report = dag.execute_interruptible(false, None, &storage, "run1/checkpoint.json").unwrap();
+    assert!(report.result.cancelled);
+    assert_eq!(report.result.get("c").and_then(GraphData::as_int), Some(3));
+    assert!(!report.result.contains_key("d"));
+
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&storage.get("run1/checkpoint.json").unwrap().unwrap()).unwrap();
+    assert_eq!(manifest["cancelled"], true);
+    assert_eq!(manifest["skipped_nodes"], serde_json::json!([3]));
+    assert_eq!(manifest["nodes"].as_array().unwrap().len(), 3);
+
+    // The next run starts afresh and, completing, writes no checkpoint
+    let mut graph = Graph::new();
+    graph.constant("a", GraphData::int(5));
+    graph.add(step("a", "b"), Some("First"), Some(vec![("a", "a")]), Some(vec![("b", "b")]));
+    let report = graph.build().execute_interruptible(true, None, &storage, "run2/checkpoint.json").unwrap();
+    assert!(!report.result.cancelled);
+    assert_eq!(report.result.get("b").and_then(GraphData::as_int), Some(6));
+    assert_eq!(storage.list("run").unwrap(), ["run1/checkpoint.json"]);
+}
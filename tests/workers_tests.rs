@@ -0,0 +1,81 @@
+#![cfg(any(feature = "rayon", feature = "tokio"))]
+
+use dagex::{Dag, Graph, GraphData};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Four independent sources feeding a sum; every source records the thread it ran on
+fn fan_in(threads: &Arc<Mutex<HashSet<String>>>) -> Dag {
+    let mut graph = Graph::new();
+    for i in 0..4 {
+        let threads = Arc::clone(threads);
+        graph.add(
+            move |_: &HashMap<String, Arc<GraphData>>| {
+                let name = std::thread::current().name().unwrap_or_default().to_string();
+                threads.lock().unwrap().insert(name);
+                HashMap::from([("value".to_string(), GraphData::int(i + 1))])
+            },
+            Some("Source"),
+            None,
+            Some(vec![("value", &*format!("v{}", i))]),
+        );
+    }
+    graph.add(
+        |inputs: &HashMap<String, Arc<GraphData>>| {
+            let sum = inputs.values().filter_map(|v| v.as_int()).sum();
+            HashMap::from([("sum".to_string(), GraphData::int(sum))])
+        },
+        Some("Sum"),
+        Some(vec![("v0", "a"), ("v1", "b"), ("v2", "c"), ("v3", "d")]),
+        Some(vec![("sum", "sum")]),
+    );
+    graph.build()
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_parallel_stages_run_on_injected_rayon_pool() {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .thread_name(|i| format!("app-pool-{}", i))
+        .build()
+        .unwrap();
+    let threads = Arc::new(Mutex::new(HashSet::new()));
+    let mut dag = fan_in(&threads);
+    dag.set_workers(dagex::Workers::Rayon(Arc::new(pool)));
+
+    let context = dag.execute(true, None);
+    assert_eq!(context.get_int("sum"), Some(10));
+    let threads = threads.lock().unwrap();
+    assert!(!threads.is_empty());
+    assert!(threads.iter().all(|name| name.starts_with("app-pool-")), "{:?}", threads);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_rayon_pool_runs_executions_started_inside_it() {
+    let pool = Arc::new(rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap());
+    let threads = Arc::new(Mutex::new(HashSet::new()));
+    let mut dag = fan_in(&threads);
+    dag.set_workers(dagex::Workers::Rayon(Arc::clone(&pool)));
+
+    // A single worker is busy running the execution itself; its chains must still run
+    let context = pool.install(|| dag.execute(true, None));
+    assert_eq!(context.get_int("sum"), Some(10));
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn test_spawn_on_runs_on_tokio_blocking_pool() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .thread_name("app-runtime")
+        .build()
+        .unwrap();
+    let threads = Arc::new(Mutex::new(HashSet::new()));
+    let dag = Arc::new(fan_in(&threads));
+
+    let job = dag.spawn_on(runtime.handle(), HashMap::new(), false, None);
+    let context = runtime.block_on(job).unwrap();
+    assert_eq!(context.get_int("sum"), Some(10));
+    assert_eq!(*threads.lock().unwrap(), HashSet::from(["app-runtime".to_string()]));
+}